@@ -14,7 +14,9 @@ use mana_tui::{key, mana_tui_beheaded::focus::FocusExt};
 use mana_tui::{
     mana_tui_beheaded::{
         self,
+        drag::{Draggable, OnDrop},
         focus::{FocusPolicy, Keybind, OnClick},
+        menu::{ContextMenu, ContextMenuItem, render_context_menu},
         setup_interactions,
     },
     prelude::*,
@@ -55,29 +57,32 @@ async fn main() -> anyhow::Result<()> {
 async fn app(terminal: &mut DefaultTerminal) -> Result<()> {
     let mut ctx = ElementCtx::new();
     mana_tui_beheaded::init(&mut ctx);
-    let r = init(&mut ctx, terminal.get_frame().area());
-    ctx.despawn_ui(r);
+    // quit application, whichever element is focused
+    mana_tui_beheaded::register_global_keybind(&mut ctx, Keybind::char('q'), |world| {
+        world.spawn((Quit,));
+    });
     loop {
-        let root = init(&mut ctx, terminal.get_frame().area());
+        // `read` gives control back on resize, lay the ui out at the new size.
+        terminal.autoresize()?;
+        let root = init(&mut ctx, terminal.get_frame().area())?;
         terminal
             .draw(|frame| {
                 ctx.render(root, frame.area(), frame.buffer_mut());
+                render_context_menu(&mut ctx, frame.buffer_mut());
             })
             .unwrap();
         // returning Some from the read handler gives control back to the application
         //
         // note that mana tui will give back control occasionally on certain crossterm
         // or ui events. in those cases, read returns None.
-        let quit = mana_tui_beheaded::read(&mut ctx, |world, event| match event {
-            // quit application
-            Event::Key(key!(Char('q'), Press)) => Some(true),
+        mana_tui_beheaded::read(&mut ctx, |_, event| match event {
             // redraw screen (debug purposes)
-            Event::Key(key!(Char('r'), Press)) => Some(false),
+            Event::Key(key!(Char('r'), Press)) => Some(()),
             _ => None,
         })
         .await;
 
-        if quit == Some(true) {
+        if ctx.query::<&Quit>().iter().next().is_some() {
             return Ok(());
         }
 
@@ -85,18 +90,22 @@ async fn app(terminal: &mut DefaultTerminal) -> Result<()> {
     }
 }
 
+/// spawned by the global `q` keybind.
+struct Quit;
+
 #[derive(Debug, Clone)]
 struct Todo {
     done: bool,
     description: String,
 }
 
-fn init(ctx: &mut ElementCtx, area: Rect) -> Element {
+/// spawns the ui and lays it out, it is ready to be drawn on the first frame.
+fn init(ctx: &mut ElementCtx, area: Rect) -> Result<Element> {
     let root = todo_app().ctx(ctx).into_view();
     let root = ctx.spawn_ui(root);
-    ctx.calculate_layout(root, area).unwrap();
+    ctx.calculate_layout(root, area)?;
     setup_interactions(ctx, root);
-    root
+    Ok(root)
 }
 
 fn handle_events(ctx: &mut ElementCtx, event: Event) -> bool {
@@ -112,10 +121,10 @@ fn handle_events(ctx: &mut ElementCtx, event: Event) -> bool {
 #[subview]
 fn todo_app(ctx: &mut ElementCtx) -> View {
     struct AddTodoButton;
-    ctx.use_focus::<AddTodoButton>();
+    ctx.use_pressed_style::<AddTodoButton>(Style::new().bg(tw::SKY.c400));
 
-    let mut todos = ctx.query::<&Todo>();
-    let todo_count = todos.iter().count();
+    let todos = ctx.query_ordered::<&Todo>();
+    let todo_count = todos.len();
     ui! {
         <Block Center Width::grow() Height::grow()>
             <Block .borders={Borders::all().difference(Borders::TOP)} .border_type={BorderType::QuadrantOutside} Width::fixed(48) Height::fixed(28) Padding::ZERO>
@@ -128,7 +137,7 @@ fn todo_app(ctx: &mut ElementCtx) -> View {
                         Keybind::new(KeyCode::Char('a'))
                         OnClick::new(move |world| {
                             // we throw our state into the ECS world :)
-                            world.spawn((Todo {
+                            world.spawn_ordered((Todo {
                                 done: false,
                                 description: format!("{} i crave productivity", todo_count + 1),
                             },));
@@ -136,11 +145,11 @@ fn todo_app(ctx: &mut ElementCtx) -> View {
                     >
                         "(a) add todo"
                     </Text>
-                    <Block .borders={Borders::TOP} .border_type={BorderType::LightDoubleDashed} Width::grow() Height::fixed(1)/>
+                    <Separator />
                     <Block Gap(1) ScrollView::default() ScrollViewState::new() Width::grow()>
                     {
-                        todos.iter().map(|todo| ui! {
-                            <TodoItem .todo={todo}/>
+                        todos.into_iter().enumerate().map(|(index, todo)| ui! {
+                            <TodoItem .todo={todo} .index={index}/>
                         })
                     }
                     </Block>
@@ -174,7 +183,7 @@ fn titlebar() -> View {
 }
 
 #[subview]
-fn todo_item(todo: &Todo) -> View {
+fn todo_item(todo: &Todo, index: usize) -> View {
     let Todo { done, description } = todo;
 
     #[subview]
@@ -190,9 +199,111 @@ fn todo_item(todo: &Todo) -> View {
         }
     }
     ui! {
-        <Block Direction::Horizontal Gap(1) Width::grow()>
+        <Block
+            Direction::Horizontal
+            Gap(1)
+            Width::grow()
+            Role::ListItem
+            FocusPolicy::Block
+            ContextMenu::new([
+                ContextMenuItem::new(if *done { "mark as todo" } else { "mark as done" }, move |world| {
+                    if let Some(todo) = world.query_ordered::<&mut Todo>().into_iter().nth(index) {
+                        todo.done = !todo.done;
+                    }
+                }),
+                ContextMenuItem::new("delete", move |world| {
+                    let todo = world
+                        .query_ordered::<(Element, &Todo)>()
+                        .into_iter()
+                        .nth(index)
+                        .map(|(todo, _)| todo);
+                    if let Some(todo) = todo {
+                        _ = world.despawn(todo);
+                    }
+                })
+                .keybind(KeyCode::Char('d')),
+            ])
+            Draggable
+            OnDrop::new(move |world, new_index| {
+                // todos are iterated in spawn order, so reordering them is just a matter of
+                // moving the values around
+                let mut todos = world
+                    .query_ordered::<&Todo>()
+                    .into_iter()
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if index >= todos.len() {
+                    return;
+                }
+                let todo = todos.remove(index);
+                todos.insert(new_index.min(todos.len()), todo);
+                for (slot, todo) in world.query_ordered::<&mut Todo>().into_iter().zip(todos) {
+                    *slot = todo;
+                }
+            })
+        >
             <Checkbox .done={*done}/>
             "{description}"
         </Block>
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reading_order() {
+        let mut ctx = ElementCtx::new();
+        mana_tui_beheaded::init(&mut ctx);
+        for (done, description) in [(false, "buy milk"), (true, "water plants")] {
+            ctx.spawn_ordered((Todo {
+                done,
+                description: description.to_string(),
+            },));
+        }
+        let root = init(&mut ctx, Rect::new(0, 0, 60, 32)).unwrap();
+
+        let icon = BRAILLE[255];
+        let expected = format!(
+            "\
+container
+  container
+    container
+      text: {icon} +++ Mana To Do +++
+      text: q {icon}
+    container
+      button: (a) add todo
+      container
+      container
+        list_item
+          text: [ ]
+          text: buy milk
+        list_item
+          text: [x]
+          text: water plants"
+        );
+        assert_eq!(ctx.reading_order(root).to_plain_text(), expected);
+    }
+
+    #[test]
+    fn test_first_frame() {
+        use ratatui::buffer::Buffer;
+
+        let mut ctx = ElementCtx::new();
+        mana_tui_beheaded::init(&mut ctx);
+        let area = Rect::new(0, 0, 60, 32);
+        // one pass of the loop in `app`.
+        let mut frame = || {
+            let root = init(&mut ctx, area).unwrap();
+            let mut buf = Buffer::empty(area);
+            ctx.render(root, area, &mut buf);
+            ctx.despawn_ui(root);
+            buf
+        };
+
+        let first = frame();
+        assert_ne!(first, Buffer::empty(area));
+        assert_eq!(first, frame());
+    }
+}
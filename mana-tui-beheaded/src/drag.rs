@@ -0,0 +1,448 @@
+use std::sync::Arc;
+
+use glam::{I16Vec2, U16Vec2, u16vec2};
+use hecs::{Entity, World};
+use mana_tui_elemental::layout::{Children, Props};
+use mana_tui_utils::resource::Resources;
+use ratatui::crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::{Position, Rect};
+
+use crate::focus::stacks_at;
+use crate::slider::Slider;
+
+/// marks an element that can be reordered among its siblings by dragging it with the mouse.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Draggable;
+
+/// added to a [`Draggable`] or [`OnDrag`] element while it is being dragged.
+#[derive(Debug, Clone, Copy)]
+pub struct Dragging {
+    /// offset of the cursor from the top left corner of the element.
+    pub offset: U16Vec2,
+    /// position of the element when the drag started.
+    pub origin: U16Vec2,
+    /// position of the cursor at the previous mouse event.
+    pub last: U16Vec2,
+}
+
+/// called when a [`Draggable`] element is dropped, with its new index among its draggable
+/// siblings.
+#[derive(derive_more::Deref, derive_more::DerefMut, Clone)]
+pub struct OnDrop(Arc<dyn Fn(&mut World, usize) + Send + Sync + 'static>);
+
+impl OnDrop {
+    pub fn new(func: impl Fn(&mut World, usize) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(func) as Arc<_>)
+    }
+}
+
+/// called while the element is dragged, with how far the cursor moved since the previous mouse
+/// event. use it for pane resizers and sliders.
+#[derive(derive_more::Deref, derive_more::DerefMut, Clone)]
+pub struct OnDrag(Arc<dyn Fn(&mut World, I16Vec2) + Send + Sync + 'static>);
+
+impl OnDrag {
+    pub fn new(func: impl Fn(&mut World, I16Vec2) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(func) as Arc<_>)
+    }
+}
+
+/// resource with the element that receives every mouse event until the left button is
+/// released, even when the cursor leaves it. set when a [`Draggable`], [`OnDrag`] or [`Slider`]
+/// element or the divider of a [`SplitPane`](mana_tui_elemental::layout::SplitPane) is pressed.
+/// while it is set, no other element is hovered or clicked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PointerCapture(pub Entity);
+
+/// how far the pointer has to move from where it pressed a [`Draggable`] before the element is
+/// dragged, in cells on either axis. a press that moves less is a click.
+pub const DRAG_THRESHOLD: u16 = 2;
+
+/// resource with the [`Draggable`] element that was pressed, until the pointer moves past the
+/// [`DRAG_THRESHOLD`] or is released.
+#[derive(Debug, Clone, Copy)]
+struct PendingDrag {
+    entity: Entity,
+    dragging: Dragging,
+}
+
+/// ends the current drag early, for example from an [`OnDrag`] handler. mouse events are routed
+/// to the element under the cursor again.
+pub fn release_capture(world: &mut World) {
+    if let Ok(PointerCapture(entity)) = world.remove_resource::<PointerCapture>() {
+        _ = world.remove_one::<Dragging>(entity);
+    }
+}
+
+pub(crate) fn handle_drag_event(world: &mut World, event: MouseEvent) -> bool {
+    let position = u16vec2(event.column, event.row);
+    match event.kind {
+        MouseEventKind::Down(MouseButton::Left) => {
+            crate::split::capture_divider(world, position) || start_drag(world, position)
+        }
+        MouseEventKind::Drag(MouseButton::Left) => {
+            start_pending_drag(world, position);
+            move_dragged(world, position)
+        }
+        MouseEventKind::Moved => move_dragged(world, position),
+        MouseEventKind::Up(MouseButton::Left) => {
+            _ = world.remove_resource::<PendingDrag>();
+            drop_dragged(world)
+        }
+        _ => false,
+    }
+}
+
+/// captures the pointer for the pressed [`Draggable`] once the pointer moved past the
+/// [`DRAG_THRESHOLD`].
+fn start_pending_drag(world: &mut World, position: U16Vec2) {
+    let Ok(pending) = world.get_resource::<&PendingDrag>().map(|pending| *pending) else {
+        return;
+    };
+    let moved = position.as_i16vec2() - pending.dragging.last.as_i16vec2();
+    if moved.abs().max_element() < DRAG_THRESHOLD as i16 {
+        return;
+    }
+    _ = world.remove_resource::<PendingDrag>();
+    if world.insert_one(pending.entity, pending.dragging).is_ok() {
+        world.insert_or_update_resource(PointerCapture(pending.entity));
+    }
+}
+
+fn start_drag(world: &mut World, position: U16Vec2) -> bool {
+    let cursor = Position {
+        x: position.x,
+        y: position.y,
+    };
+    let uistack: Vec<Entity> = stacks_at(world, cursor)
+        .iter()
+        .flat_map(|uistack| uistack.stack.iter().copied())
+        .collect();
+
+    let target = uistack.into_iter().rev().find_map(|entity| {
+        let mut query = world
+            .query_one::<(&Props, Option<&Draggable>, Option<&OnDrag>, Option<&Slider>)>(entity);
+        let (props, draggable, on_drag, slider) = query.get().ok()?;
+        if draggable.is_none() && on_drag.is_none() && slider.is_none() {
+            return None;
+        }
+        let area = Rect {
+            x: props.position.x,
+            y: props.position.y,
+            width: props.size.x,
+            height: props.size.y,
+        };
+        let origin = props.position;
+        let immediate = on_drag.is_some() || slider.is_some();
+        area.contains(cursor).then_some((entity, origin, immediate))
+    });
+    let Some((entity, origin, immediate)) = target else {
+        return false;
+    };
+
+    let dragging = Dragging {
+        offset: position.saturating_sub(origin),
+        origin,
+        last: position,
+    };
+    // a draggable is only dragged once the pointer moves, the press goes on to its children
+    // so they can still be clicked.
+    if !immediate {
+        world.insert_or_update_resource(PendingDrag { entity, dragging });
+        return false;
+    }
+    _ = world.insert_one(entity, dragging);
+    world.insert_or_update_resource(PointerCapture(entity));
+    true
+}
+
+fn move_dragged(world: &mut World, position: U16Vec2) -> bool {
+    let Ok(PointerCapture(entity)) = world
+        .get_resource::<&PointerCapture>()
+        .map(|capture| *capture)
+    else {
+        return false;
+    };
+    let dragged = world
+        .query_one_mut::<(&Props, &mut Dragging, Option<&Draggable>)>(entity)
+        .ok()
+        .map(|(props, dragging, draggable)| {
+            let last = std::mem::replace(&mut dragging.last, position);
+            (props.position, dragging.offset, last, draggable.is_some())
+        });
+    let Some((from, offset, last, draggable)) = dragged else {
+        // the captured element is gone.
+        release_capture(world);
+        return false;
+    };
+    if draggable {
+        move_subtree(world, entity, from, position.saturating_sub(offset));
+    }
+    // an event on the cell the pointer is already on moved nothing.
+    let delta = position.as_i16vec2() - last.as_i16vec2();
+    if delta == I16Vec2::ZERO {
+        return true;
+    }
+    let handler = world
+        .get::<&OnDrag>(entity)
+        .map(|handler| handler.0.clone());
+    if let Ok(handler) = handler {
+        handler(world, delta);
+    }
+    true
+}
+
+/// moves the element and its children so they keep their place relative to each other.
+fn move_subtree(world: &mut World, entity: Entity, from: U16Vec2, to: U16Vec2) {
+    if let Ok(mut props) = world.get::<&mut Props>(entity) {
+        props.position = props.position.saturating_sub(from).saturating_add(to);
+    }
+    let Ok(children) = world
+        .get::<&Children>(entity)
+        .map(|children| children.clone())
+    else {
+        return;
+    };
+    for child in children.iter() {
+        move_subtree(world, child, from, to);
+    }
+}
+
+fn drop_dragged(world: &mut World) -> bool {
+    let Ok(PointerCapture(entity)) = world
+        .get_resource::<&PointerCapture>()
+        .map(|capture| *capture)
+    else {
+        return false;
+    };
+    let dragging = world.get::<&Dragging>(entity).map(|dragging| *dragging);
+    release_capture(world);
+    let Ok(dragging) = dragging else {
+        return true;
+    };
+
+    let handler = world
+        .get::<&OnDrop>(entity)
+        .map(|handler| handler.0.clone());
+    if let (Some(index), Ok(handler)) = (drop_index(world, entity, dragging.origin), handler) {
+        handler(world, index);
+    }
+    true
+}
+
+/// index of the draggable sibling whose position is closest to where `entity` was dropped.
+/// the dragged element itself is measured from where the drag started.
+fn drop_index(world: &World, entity: Entity, origin: U16Vec2) -> Option<usize> {
+    let dropped = world.get::<&Props>(entity).ok()?.position;
+    let siblings = world
+        .query::<&Children>()
+        .iter()
+        .find(|children| children.contains(&entity))
+        .map(|children| children.clone())?;
+
+    siblings
+        .iter()
+        .copied()
+        .filter(|sibling| world.get::<&Draggable>(*sibling).is_ok())
+        .enumerate()
+        .filter_map(|(index, sibling)| {
+            let position = if sibling == entity {
+                origin
+            } else {
+                world.get::<&Props>(sibling).ok()?.position
+            };
+            Some((index, position))
+        })
+        .min_by_key(|(_, position)| position.as_ivec2().distance_squared(dropped.as_ivec2()))
+        .map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use glam::I16Vec2;
+    use mana_tui_elemental::layout::Props;
+    use mana_tui_elemental::prelude::*;
+    use mana_tui_utils::resource::Resources;
+    use ratatui::crossterm::event::{Event, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+    use ratatui::layout::Rect;
+
+    use crate::drag::{Draggable, Dragging, OnDrag, OnDrop, PointerCapture};
+    use crate::focus::{FocusExt, FocusPolicy, Focused, Hovered, OnClick};
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> Event {
+        Event::Mouse(MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::empty(),
+        })
+    }
+
+    #[test]
+    fn test_drag_drop() {
+        let dropped = Arc::new(Mutex::new(None));
+        let item = |dropped: &Arc<Mutex<Option<usize>>>| {
+            let dropped = dropped.clone();
+            ui(Block::new()).with((
+                Width::grow(),
+                Height::fixed(2),
+                Draggable,
+                OnDrop::new(move |_, index| {
+                    *dropped.lock().unwrap() = Some(index);
+                }),
+            ))
+        };
+
+        let mut ctx = ElementCtx::new();
+        crate::init(&mut ctx);
+        let root = ui(Block::new())
+            .with((Width::fixed(10), Height::fixed(6)))
+            .children((item(&dropped), item(&dropped), item(&dropped)));
+        let root = ctx.spawn_ui(root);
+        ctx.calculate_layout(root, Rect::new(0, 0, 10, 6)).unwrap();
+        crate::setup_interactions(&mut ctx, root);
+        let first = ctx.get::<&Children>(root).unwrap()[0];
+
+        crate::handle_event(
+            &mut ctx,
+            mouse(MouseEventKind::Down(MouseButton::Left), 1, 0),
+        );
+        // the press alone doesn't drag.
+        assert!(ctx.get::<&Dragging>(first).is_err());
+
+        crate::handle_event(
+            &mut ctx,
+            mouse(MouseEventKind::Drag(MouseButton::Left), 1, 5),
+        );
+        assert!(ctx.get::<&Dragging>(first).is_ok());
+        assert_eq!(ctx.get::<&Props>(first).unwrap().position.y, 5);
+
+        let consumed =
+            crate::handle_event(&mut ctx, mouse(MouseEventKind::Up(MouseButton::Left), 1, 5));
+        assert!(consumed);
+        assert!(ctx.get::<&Dragging>(first).is_err());
+        assert_eq!(*dropped.lock().unwrap(), Some(2));
+    }
+
+    #[tokio::test]
+    async fn test_click_inside_draggable() {
+        struct Button;
+
+        let clicks = Arc::new(Mutex::new(0));
+        let dropped = Arc::new(Mutex::new(false));
+        let mut ctx = ElementCtx::new();
+        crate::init(&mut ctx);
+        ctx.use_focus::<Button>();
+        let button = {
+            let clicks = clicks.clone();
+            ui(Block::new()).with((
+                Button,
+                Width::fixed(4),
+                Height::fixed(1),
+                FocusPolicy::Block,
+                OnClick::new(move |_| *clicks.lock().unwrap() += 1),
+            ))
+        };
+        let item = {
+            let dropped = dropped.clone();
+            ui(Block::new())
+                .with((
+                    Width::grow(),
+                    Height::fixed(2),
+                    Draggable,
+                    OnDrop::new(move |_, _| *dropped.lock().unwrap() = true),
+                ))
+                .child(button)
+        };
+        let root = ui(Block::new())
+            .with((Width::fixed(10), Height::fixed(4)))
+            .children((item, ui(Block::new()).with((Height::fixed(2), Draggable))));
+        let root = ctx.spawn_ui(root);
+        ctx.calculate_layout(root, Rect::new(0, 0, 10, 4)).unwrap();
+        crate::setup_interactions(&mut ctx, root);
+        let item = ctx.get::<&Children>(root).unwrap()[0];
+
+        // a press that moves less than the threshold is a click on the button.
+        for (column, row) in [(1, 0), (2, 0)] {
+            crate::handle_event(
+                &mut ctx,
+                mouse(MouseEventKind::Down(MouseButton::Left), 1, 0),
+            );
+            crate::handle_event(
+                &mut ctx,
+                mouse(MouseEventKind::Drag(MouseButton::Left), column, row),
+            );
+            crate::handle_event(
+                &mut ctx,
+                mouse(MouseEventKind::Up(MouseButton::Left), column, row),
+            );
+            assert!(ctx.get::<&Dragging>(item).is_err());
+            assert!(ctx.get_resource::<&PointerCapture>().is_err());
+        }
+        assert_eq!(*clicks.lock().unwrap(), 2);
+        assert!(
+            ctx.get::<&Focused>(ctx.get::<&Children>(item).unwrap()[0])
+                .is_ok()
+        );
+        assert!(!*dropped.lock().unwrap());
+    }
+
+    #[test]
+    fn test_pointer_capture() {
+        struct B;
+
+        let deltas = Arc::new(Mutex::new(Vec::new()));
+        let on_drag = {
+            let deltas = deltas.clone();
+            OnDrag::new(move |_, delta| deltas.lock().unwrap().push(delta))
+        };
+
+        let mut ctx = ElementCtx::new();
+        crate::init(&mut ctx);
+        ctx.use_focus::<B>();
+        let root = ui(Block::new())
+            .with((Width::fixed(10), Height::fixed(2), Direction::Horizontal))
+            .children((
+                ui(Block::new()).with((Width::fixed(5), Height::fixed(2), on_drag)),
+                ui(Block::new()).with((B, Width::fixed(5), Height::fixed(2), FocusPolicy::Block)),
+            ));
+        let root = ctx.spawn_ui(root);
+        ctx.calculate_layout(root, Rect::new(0, 0, 10, 2)).unwrap();
+        crate::setup_interactions(&mut ctx, root);
+        let children = ctx.get::<&Children>(root).unwrap().clone();
+        let (a, b) = (children[0], children[1]);
+
+        crate::handle_event(
+            &mut ctx,
+            mouse(MouseEventKind::Down(MouseButton::Left), 1, 0),
+        );
+        assert_eq!(
+            *ctx.get_resource::<&PointerCapture>().unwrap(),
+            PointerCapture(a)
+        );
+
+        for column in [4, 6, 8] {
+            crate::handle_event(
+                &mut ctx,
+                mouse(MouseEventKind::Drag(MouseButton::Left), column, 1),
+            );
+            crate::handle_event(&mut ctx, mouse(MouseEventKind::Moved, column, 1));
+            assert!(ctx.get::<&Hovered>(b).is_err());
+        }
+        assert_eq!(
+            *deltas.lock().unwrap(),
+            [I16Vec2::new(3, 1), I16Vec2::new(2, 0), I16Vec2::new(2, 0)]
+        );
+
+        crate::handle_event(&mut ctx, mouse(MouseEventKind::Up(MouseButton::Left), 8, 1));
+        assert!(ctx.get_resource::<&PointerCapture>().is_err());
+        assert!(ctx.get::<&Dragging>(a).is_err());
+
+        // without a capture, the element under the cursor is hovered again.
+        crate::handle_event(&mut ctx, mouse(MouseEventKind::Moved, 8, 1));
+        assert!(ctx.get::<&Hovered>(b).is_ok());
+        assert_eq!(deltas.lock().unwrap().len(), 3);
+    }
+}
@@ -0,0 +1,451 @@
+//! dropdowns: [`Text`] elements with a [`Select`] show the chosen option and open a list of the
+//! others.
+//!
+//! `Enter`, `Space` or a click opens the list of a focused dropdown below the element, or above
+//! it when there's no room below. while it is open the list takes every key and mouse event: the
+//! arrow keys (or `j` and `k`) highlight an option, `Enter` or a click chooses it, and `Esc` or a
+//! click outside closes the list. draw it over the frame with [`render_dropdown`].
+
+use std::sync::Arc;
+
+use hecs::{Entity, World};
+use mana_tui_elemental::layout::Props;
+use mana_tui_utils::resource::Resources;
+use ratatui::buffer::Buffer;
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Margin, Position, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Text};
+use ratatui::widgets::{Block, Clear, Widget};
+use unicode_width::UnicodeWidthStr;
+
+use crate::focus::Focused;
+use crate::menu::topmost_blocking;
+
+/// the options of a dropdown. the chosen one is kept in a [`SelectState`] next to it, and
+/// choosing one calls the element's [`OnSelect`].
+///
+/// the element is a [`Text`] with a single line showing the chosen option, so it is one cell
+/// high. it needs [`FocusPolicy::Block`](crate::focus::FocusPolicy::Block) to be clicked.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// # use mana_tui_beheaded::dropdown::{OnSelect, Select, SelectState};
+/// # use mana_tui_beheaded::focus::FocusPolicy;
+/// # use ratatui::text::Line;
+/// ui(Text::from(Line::default())).with((
+///     Select::new(["small", "medium", "large"]),
+///     SelectState::default(),
+///     OnSelect::new(|_, size| println!("picked {size}")),
+///     FocusPolicy::Block,
+///     Width::fixed(12),
+/// ));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Select {
+    /// the text of the options.
+    pub options: Vec<String>,
+}
+
+impl Select {
+    pub fn new(options: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            options: options.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// the line of the element for the option at `selected`, `width` cells wide.
+    #[must_use]
+    pub fn line(&self, selected: usize, width: u16) -> Line<'static> {
+        let label = self.options.get(selected).map_or("", String::as_str);
+        let gap = usize::from(width).saturating_sub(label.width() + 1);
+        Line::raw(format!("{label}{}▾", " ".repeat(gap)))
+    }
+}
+
+/// the chosen option of a [`Select`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SelectState {
+    /// the index of the chosen option.
+    pub selected: usize,
+    /// whether the list of options is open.
+    pub open: bool,
+}
+
+/// called with the index of the option chosen in a [`Select`], when it changes.
+#[derive(derive_more::Deref, derive_more::DerefMut, Clone)]
+pub struct OnSelect(Arc<dyn Fn(&mut World, usize) + Send + Sync + 'static>);
+
+impl OnSelect {
+    pub fn new(func: impl Fn(&mut World, usize) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(func) as Arc<_>)
+    }
+}
+
+/// resource with the open list of a [`Select`].
+#[derive(Debug, Clone)]
+pub struct OpenDropdown {
+    /// the element of the dropdown.
+    pub element: Entity,
+    /// the options of the dropdown.
+    pub options: Vec<String>,
+    /// the index of the highlighted option.
+    pub highlighted: usize,
+    /// the area of the element when the list was opened.
+    pub anchor: Rect,
+    /// where [`render_dropdown`] last drew the list. clicks are matched against it.
+    pub drawn: Option<Rect>,
+}
+
+impl OpenDropdown {
+    /// the area of the list, below the anchor or above it if it only fits there, moved left if
+    /// needed to fit in `bounds`.
+    #[must_use]
+    pub fn area(&self, bounds: Rect) -> Rect {
+        let inner = self
+            .options
+            .iter()
+            .map(|option| option.width())
+            .max()
+            .unwrap_or_default();
+        let width = u16::try_from(inner + 4)
+            .unwrap_or(u16::MAX)
+            .max(self.anchor.width)
+            .min(bounds.width);
+        let height = u16::try_from(self.options.len() + 2)
+            .unwrap_or(u16::MAX)
+            .min(bounds.height);
+        let below = bounds.bottom().saturating_sub(self.anchor.bottom());
+        let above = self.anchor.y.saturating_sub(bounds.y);
+        let y = if below >= height || below >= above {
+            self.anchor
+                .bottom()
+                .min(bounds.bottom().saturating_sub(height))
+        } else {
+            self.anchor.y.saturating_sub(height).max(bounds.y)
+        };
+        Rect {
+            x: self.anchor.x.min(bounds.right().saturating_sub(width)),
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// the index of the option at `position`.
+    fn option_at(&self, position: Position) -> Option<usize> {
+        let area = self
+            .drawn
+            .unwrap_or_else(|| self.area(Rect::new(0, 0, u16::MAX, u16::MAX)));
+        let inner = area.inner(Margin::new(1, 1));
+        inner
+            .contains(position)
+            .then(|| usize::from(position.y - inner.y))
+            .filter(|idx| *idx < self.options.len())
+    }
+}
+
+/// opens the list of the dropdown `entity`. returns false if it isn't a [`Select`] with options.
+fn open_dropdown(world: &mut World, entity: Entity) -> bool {
+    let Ok((select, state, props)) = world
+        .query_one_mut::<(&Select, &mut SelectState, &Props)>(entity)
+        .map(|(select, state, props)| {
+            state.open = !select.options.is_empty();
+            (select.clone(), *state, props.clone())
+        })
+    else {
+        return false;
+    };
+    if !state.open {
+        return false;
+    }
+
+    world.insert_or_update_resource(OpenDropdown {
+        element: entity,
+        options: select.options,
+        highlighted: state.selected,
+        anchor: Rect {
+            x: props.position.x,
+            y: props.position.y,
+            width: props.size.x,
+            height: props.size.y,
+        },
+        drawn: None,
+    });
+    true
+}
+
+/// closes the open list. chooses the option at `choice` if there is one.
+fn close_dropdown(world: &mut World, choice: Option<usize>) {
+    let Ok(dropdown) = world.remove_resource::<OpenDropdown>() else {
+        return;
+    };
+    let entity = dropdown.element;
+    let Ok(mut state) = world.get::<&mut SelectState>(entity) else {
+        return;
+    };
+    state.open = false;
+    let Some(choice) = choice.filter(|choice| *choice != state.selected) else {
+        return;
+    };
+    state.selected = choice;
+    drop(state);
+    sync_dropdowns(world);
+
+    let handler = world
+        .get::<&OnSelect>(entity)
+        .map(|handler| handler.0.clone());
+    if let Ok(handler) = handler {
+        handler(world, choice);
+    }
+}
+
+/// opens the focused dropdown, or moves through the open list. every key is used while a list
+/// is open.
+pub(crate) fn handle_dropdown_key(world: &mut World, event: KeyEvent) -> bool {
+    let Ok(dropdown) = world
+        .get_resource::<&OpenDropdown>()
+        .map(|dropdown| dropdown.clone())
+    else {
+        if event.kind == KeyEventKind::Release
+            || !matches!(event.code, KeyCode::Enter | KeyCode::Char(' '))
+        {
+            return false;
+        }
+        let focused = world
+            .query_mut::<(Entity, &Select, &Focused)>()
+            .into_iter()
+            .next()
+            .map(|(entity, ..)| entity);
+        return focused.is_some_and(|entity| open_dropdown(world, entity));
+    };
+    if event.kind == KeyEventKind::Release {
+        return true;
+    }
+
+    let last = dropdown.options.len().saturating_sub(1);
+    let highlighted = match event.code {
+        KeyCode::Esc => {
+            close_dropdown(world, None);
+            return true;
+        }
+        KeyCode::Enter => {
+            close_dropdown(world, Some(dropdown.highlighted));
+            return true;
+        }
+        KeyCode::Up | KeyCode::Char('k') => dropdown.highlighted.saturating_sub(1),
+        KeyCode::Down | KeyCode::Char('j') => (dropdown.highlighted + 1).min(last),
+        KeyCode::Home => 0,
+        KeyCode::End => last,
+        _ => return true,
+    };
+    if let Ok(mut dropdown) = world.get_resource::<&mut OpenDropdown>() {
+        dropdown.highlighted = highlighted;
+    }
+    true
+}
+
+/// opens a clicked dropdown, or chooses from the open list. returns true if the event was used.
+pub(crate) fn handle_dropdown_mouse(world: &mut World, event: MouseEvent) -> bool {
+    let cursor = Position {
+        x: event.column,
+        y: event.row,
+    };
+    let Ok(dropdown) = world
+        .get_resource::<&OpenDropdown>()
+        .map(|dropdown| dropdown.clone())
+    else {
+        if event.kind != MouseEventKind::Down(MouseButton::Left) {
+            return false;
+        }
+        let Some(target) = topmost_blocking(world, cursor)
+            .filter(|target| world.satisfies::<&Select>(*target).unwrap_or(false))
+        else {
+            return false;
+        };
+        _ = world.insert_one(target, Focused);
+        return open_dropdown(world, target);
+    };
+
+    match (event.kind, dropdown.option_at(cursor)) {
+        (MouseEventKind::Down(MouseButton::Left), Some(idx)) => close_dropdown(world, Some(idx)),
+        (MouseEventKind::Down(_), None) => close_dropdown(world, None),
+        (MouseEventKind::Moved, Some(idx)) => {
+            if let Ok(mut dropdown) = world.get_resource::<&mut OpenDropdown>() {
+                dropdown.highlighted = idx;
+            }
+        }
+        _ => {}
+    }
+    true
+}
+
+/// draws the chosen option of every [`Select`] text at its width.
+pub(crate) fn sync_dropdowns(world: &mut World) {
+    for (select, state, props, text) in
+        world.query_mut::<(&Select, &SelectState, &Props, &mut Text<'static>)>()
+    {
+        text.lines = vec![select.line(state.selected, props.size.x)];
+    }
+}
+
+/// draws the open list of a [`Select`] over `buf`, moved to fit in it. call it after rendering
+/// the tree.
+pub fn render_dropdown(world: &mut World, buf: &mut Buffer) {
+    let Ok(mut dropdown) = world.get_resource::<&mut OpenDropdown>() else {
+        return;
+    };
+    let area = dropdown.area(buf.area);
+    dropdown.drawn = Some(area);
+    Clear.render(area, buf);
+    let block = Block::bordered();
+    let inner = block.inner(area);
+    block.render(area, buf);
+
+    for (idx, (option, row)) in dropdown.options.iter().zip(inner.rows()).enumerate() {
+        if idx == dropdown.highlighted {
+            buf.set_style(row, Style::new().add_modifier(Modifier::REVERSED));
+        }
+        Line::raw(format!(" {option}")).render(row, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mana_tui_elemental::prelude::*;
+    use mana_tui_utils::resource::Resources;
+    use ratatui::buffer::Buffer;
+    use ratatui::crossterm::event::{
+        Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    };
+    use ratatui::layout::Rect;
+    use ratatui::text::Line;
+
+    use crate::dropdown::{OnSelect, OpenDropdown, Select, SelectState, render_dropdown};
+    use crate::focus::{FocusPolicy, Focused};
+
+    struct Chosen(Vec<usize>);
+
+    /// a dropdown at the top, or at the bottom of a 12 row terminal.
+    fn spawn_dropdown(ctx: &mut ElementCtx, bottom: bool) -> Element {
+        crate::init(ctx);
+        ctx.insert_resource(Chosen(Vec::new()));
+        let select = ui(Text::from(Line::default())).with((
+            Select::new(["red", "green", "blue", "yellow"]),
+            SelectState::default(),
+            OnSelect::new(|world, idx| {
+                world.get_resource::<&mut Chosen>().unwrap().0.push(idx);
+            }),
+            FocusPolicy::Block,
+            Width::fixed(10),
+        ));
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((
+                    Width::fixed(20),
+                    Height::fixed(12),
+                    if bottom {
+                        MainJustify::End
+                    } else {
+                        MainJustify::Start
+                    },
+                ))
+                .child(select),
+        );
+        ctx.calculate_layout(root, Rect::new(0, 0, 20, 12)).unwrap();
+        crate::setup_interactions(ctx, root);
+        ctx.get::<&Children>(root).unwrap()[0]
+    }
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_dropdown_keys() {
+        let mut ctx = ElementCtx::new();
+        let select = spawn_dropdown(&mut ctx, false);
+        ctx.insert_one(select, Focused).unwrap();
+        assert_eq!(
+            ctx.get::<&Text>(select).unwrap().lines[0].to_string(),
+            "red      ▾"
+        );
+
+        for code in [KeyCode::Enter, KeyCode::Down, KeyCode::Down] {
+            assert!(crate::handle_event(&mut ctx, key(code)));
+        }
+        assert!(ctx.get::<&SelectState>(select).unwrap().open);
+        // other keys don't leak out of the open list.
+        assert!(crate::handle_event(&mut ctx, key(KeyCode::Char('x'))));
+        assert!(crate::handle_event(&mut ctx, key(KeyCode::Enter)));
+
+        assert!(ctx.get_resource::<&OpenDropdown>().is_err());
+        assert_eq!(
+            *ctx.get::<&SelectState>(select).unwrap(),
+            SelectState {
+                selected: 2,
+                open: false
+            }
+        );
+        assert_eq!(
+            ctx.get::<&Text>(select).unwrap().lines[0].to_string(),
+            "blue     ▾"
+        );
+        assert_eq!(ctx.get_resource::<&Chosen>().unwrap().0, [2]);
+
+        // dismissing keeps the choice.
+        crate::handle_event(&mut ctx, key(KeyCode::Char(' ')));
+        crate::handle_event(&mut ctx, key(KeyCode::Up));
+        crate::handle_event(&mut ctx, key(KeyCode::Esc));
+        assert_eq!(ctx.get::<&SelectState>(select).unwrap().selected, 2);
+        assert_eq!(ctx.get_resource::<&Chosen>().unwrap().0, [2]);
+    }
+
+    #[test]
+    fn test_dropdown_flips_up() {
+        let area = Rect::new(0, 0, 20, 12);
+        let mut ctx = ElementCtx::new();
+        let select = spawn_dropdown(&mut ctx, true);
+
+        assert!(crate::handle_event(
+            &mut ctx,
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column: 2,
+                row: 11,
+                modifiers: KeyModifiers::empty(),
+            })
+        ));
+        assert!(ctx.get::<&Focused>(select).is_ok());
+
+        let mut buf = Buffer::empty(area);
+        render_dropdown(&mut ctx, &mut buf);
+        let drawn = ctx.get_resource::<&OpenDropdown>().unwrap().drawn;
+        assert_eq!(drawn, Some(Rect::new(0, 5, 10, 6)));
+        let row = |y: u16| {
+            (0..10)
+                .map(|x| buf[(x, y)].symbol().to_string())
+                .collect::<String>()
+        };
+        assert_eq!(row(5), "┌────────┐");
+        assert_eq!(row(6), "│ red    │");
+        assert_eq!(row(10), "└────────┘");
+
+        // a click outside closes the list without choosing.
+        assert!(crate::handle_event(
+            &mut ctx,
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column: 15,
+                row: 0,
+                modifiers: KeyModifiers::empty(),
+            })
+        ));
+        assert!(!ctx.get::<&SelectState>(select).unwrap().open);
+        assert!(ctx.get_resource::<&Chosen>().unwrap().0.is_empty());
+    }
+}
@@ -0,0 +1,164 @@
+//! in-place text editing for [`Text`] elements with a [`ContentEditable`].
+
+use std::sync::Arc;
+
+use hecs::{Entity, World};
+use mana_tui_elemental::layout::ContentEditable;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::text::Text;
+
+use crate::focus::Focused;
+
+/// called with the new value every time the user edits the element: the text of a
+/// [`ContentEditable`], or the number of a [`Slider`](crate::slider::Slider) as an
+/// `OnChange<f64>`.
+#[derive(derive_more::Deref, derive_more::DerefMut, Clone)]
+pub struct OnChange<T = String>(Arc<dyn Fn(&mut World, T) + Send + Sync + 'static>);
+
+impl<T> OnChange<T> {
+    pub fn new(func: impl Fn(&mut World, T) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(func) as Arc<_>)
+    }
+}
+
+/// edits the focused [`ContentEditable`] with `event`. returns true if the key was used, so
+/// typing doesn't trigger keybinds.
+pub(crate) fn handle_edit_key(world: &mut World, event: KeyEvent) -> bool {
+    if event.kind == KeyEventKind::Release {
+        return false;
+    }
+    let Some((entity, mut editable)) = world
+        .query_mut::<(Entity, &ContentEditable, &Focused)>()
+        .into_iter()
+        .next()
+        .map(|(entity, editable, _)| (entity, editable.clone()))
+    else {
+        return false;
+    };
+
+    let changed = match event.code {
+        KeyCode::Char(c)
+            if !event
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+        {
+            editable.insert(c);
+            true
+        }
+        KeyCode::Backspace => editable.backspace(),
+        KeyCode::Delete => editable.delete(),
+        KeyCode::Left => {
+            editable.move_left();
+            false
+        }
+        KeyCode::Right => {
+            editable.move_right();
+            false
+        }
+        KeyCode::Home => {
+            editable.move_home();
+            false
+        }
+        KeyCode::End => {
+            editable.move_end();
+            false
+        }
+        _ => return false,
+    };
+
+    let value = editable.value.clone();
+    _ = world.insert_one(entity, editable);
+    sync_content_editable(world);
+    if changed {
+        let handler = world
+            .get::<&OnChange>(entity)
+            .map(|handler| handler.0.clone());
+        if let Ok(handler) = handler {
+            handler(world, value);
+        }
+    }
+    true
+}
+
+/// replaces the content of every [`ContentEditable`] text with its value, with a cursor if the
+/// element is focused.
+pub(crate) fn sync_content_editable(world: &mut World) {
+    for (editable, text, focused) in
+        world.query_mut::<(&ContentEditable, &mut Text<'static>, Option<&Focused>)>()
+    {
+        text.lines = vec![editable.line(focused.is_some())];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mana_tui_elemental::prelude::*;
+    use mana_tui_utils::resource::Resources;
+    use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::layout::Rect;
+    use ratatui::style::Modifier;
+
+    use crate::edit::OnChange;
+    use crate::focus::{Focused, Keybind};
+
+    #[test]
+    fn test_content_editable() {
+        struct Changes(Vec<String>);
+        struct Quit;
+
+        let mut ctx = ElementCtx::new();
+        crate::init(&mut ctx);
+        ctx.insert_resource(Changes(Vec::new()));
+        crate::register_global_keybind(&mut ctx, Keybind::char('q'), |world| {
+            world.spawn((Quit,));
+        });
+        let root = ctx.spawn_ui(ui(Text::default()).with((
+            ContentEditable::new("hi"),
+            OnChange::new(|world, value| {
+                world.get_resource::<&mut Changes>().unwrap().0.push(value);
+            }),
+            Width::fixed(10),
+            Height::fixed(1),
+        )));
+        ctx.calculate_layout(root, Rect::new(0, 0, 10, 1)).unwrap();
+        crate::setup_interactions(&mut ctx, root);
+
+        let shown = |ctx: &ElementCtx| ctx.get::<&Text<'static>>(root).unwrap().to_string();
+        assert_eq!(shown(&ctx), "hi");
+
+        ctx.insert_one(root, Focused).unwrap();
+        for code in [
+            KeyCode::Char('a'),
+            KeyCode::Char('q'),
+            KeyCode::Left,
+            KeyCode::Char('c'),
+            KeyCode::Backspace,
+        ] {
+            let event = Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+            assert!(crate::handle_event(&mut ctx, event));
+        }
+
+        let editable = ctx.get::<&ContentEditable>(root).unwrap().clone();
+        assert_eq!(
+            editable,
+            ContentEditable {
+                value: "hiaq".into(),
+                cursor: 3
+            }
+        );
+        // the cursor is drawn over the `q`.
+        assert_eq!(shown(&ctx), "hiaq");
+        let text = ctx.get::<&Text<'static>>(root).unwrap();
+        let cursor = &text.lines[0].spans[1];
+        assert_eq!(cursor.content, "q");
+        assert!(cursor.style.add_modifier.contains(Modifier::REVERSED));
+        drop(text);
+
+        assert_eq!(
+            ctx.get_resource::<&Changes>().unwrap().0,
+            ["hia", "hiaq", "hiacq", "hiaq"]
+        );
+        // typing `q` doesn't quit.
+        assert!(ctx.query::<&Quit>().iter().next().is_none());
+    }
+}
@@ -1,11 +1,14 @@
 use std::any::TypeId;
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::time::Duration;
 
 use hecs::{CommandBuffer, Component, Or};
 use hecs::{Entity, World};
+use mana_tui_elemental::inspect::Role;
 use mana_tui_elemental::layout::Children;
 use mana_tui_elemental::layout::Props;
+use mana_tui_elemental::layout::Viewport;
 use mana_tui_utils::resource::Resources;
 use mana_tui_utils::systems::SystemsExt;
 use mana_tui_utils::{Ecs, EcsMut};
@@ -14,10 +17,10 @@ use ratatui::crossterm::event::{
 };
 use ratatui::crossterm::event::{KeyEvent, MouseEvent};
 use ratatui::layout::Position;
-use ratatui::layout::Rect;
 use ratatui::style::{Modifier, Style};
 use smallvec::SmallVec;
 
+use crate::hit::{self, HitGrid};
 use crate::schedule::{PostRenderSchedule, PreRenderSchedule};
 use crate::{EventQueue, Marker, Store};
 
@@ -31,28 +34,81 @@ pub struct Focused;
 pub struct Hovered;
 pub struct Clicked;
 
+#[derive(Clone)]
 pub struct UiStack {
-    stack: Arc<[Entity]>,
+    pub(crate) stack: Arc<[Entity]>,
+    /// the interactive elements of the stack by position, for large trees.
+    pub(crate) hits: Option<Arc<HitGrid>>,
+    /// when the root was set up for its frame, roots set up later are drawn on top.
+    pub(crate) order: u64,
 }
 
+/// resource with the next [`UiStack::order`].
+#[derive(Default)]
+struct StackCounter(u64);
+
+/// the stack is stored on the root element, so every root rendered into its own
+/// [`Viewport`] keeps an independent stack.
 pub(crate) fn generate_ui_stack(world: &mut World, root: Entity) {
     let mut stack = vec![];
     generate_ui_stack_impl(world, root, &mut stack);
-    world.insert_or_update_resource(UiStack {
-        stack: stack.into(),
-    });
+    let hits = HitGrid::build(world, &stack).map(Arc::new);
+    let order = {
+        let mut counter =
+            world.get_or_insert_resource_with::<&mut StackCounter>(|_| StackCounter::default());
+        counter.0 += 1;
+        counter.0
+    };
+    _ = world.insert_one(
+        root,
+        UiStack {
+            stack: stack.into(),
+            hits,
+            order,
+        },
+    );
+}
+
+/// the stacks of the roots whose [`Viewport`] contains `position`, the topmost one last.
+pub(crate) fn stacks_at(world: &World, position: Position) -> Vec<UiStack> {
+    let mut stacks = world
+        .query::<(&UiStack, Option<&Viewport>)>()
+        .iter()
+        .filter(|(_, viewport)| viewport.is_none_or(|viewport| viewport.contains(position)))
+        .map(|(uistack, _)| uistack.clone())
+        .collect::<Vec<_>>();
+    // hecs iterates by archetype, not in the order the roots are drawn.
+    stacks.sort_by_key(|uistack| uistack.order);
+    stacks
 }
 
 pub(crate) fn generate_ui_stack_impl(world: &World, root: Entity, stack: &mut Vec<Entity>) {
     stack.push(root);
-    let children = world.get::<&Children>(root);
+    let children = world
+        .get::<&Children>(root)
+        .map(|children| children.visible(world, root));
     if let Ok(children) = children {
         for child in children.iter() {
-            generate_ui_stack_impl(world, *child, stack);
+            generate_ui_stack_impl(world, child, stack);
         }
     }
 }
 
+/// marks elements with an [`OnClick`] or a [`Keybind`] as [`Role::Button`] for
+/// [`ElementCtx::reading_order`](mana_tui_elemental::layout::ElementCtx::reading_order), unless
+/// they already have a role.
+pub(crate) fn infer_button_roles(world: &mut World) {
+    let buttons = world
+        .query::<(Entity, Option<&OnClick>, Option<&Keybind>, Option<&Role>)>()
+        .iter()
+        .filter(|(_, click, key, role)| role.is_none() && (click.is_some() || key.is_some()))
+        .map(|(entity, ..)| entity)
+        .collect::<Vec<_>>();
+    for entity in buttons {
+        _ = world.insert_one(entity, Role::Button);
+    }
+}
+
 pub(crate) fn clear_old_hovers(world: &mut World) {
     let mut cmd = CommandBuffer::new();
     for (entity, _) in world.query_mut::<(Entity, &Hovered)>() {
@@ -67,67 +123,40 @@ pub(crate) fn handle_mouse_event(
 ) -> Result<bool, hecs::ComponentError> {
     let queue = world.get_resource::<&EventQueue>().unwrap().0.clone();
     tracing::info!(?event);
-    let uistack = {
-        let Ok(uistack) = world.get_resource::<&UiStack>() else {
-            return Ok(false);
-        };
-
-        uistack.stack.clone()
+    let position = Position {
+        x: event.column,
+        y: event.row,
     };
-
-    let mut consumed = false;
-
-    for entity in uistack.iter().rev().copied() {
-        let mut query_one = world.query_one::<(&Props, Option<&FocusPolicy>, &Marker)>(entity);
-        let Ok((props, focus_policy, &marker)) = query_one.get() else {
-            continue;
+    let target = stacks_at(world, position).iter().rev().find_map(|uistack| {
+        let index = match &uistack.hits {
+            Some(hits) => hits.hit(position),
+            None => hit::scan(world, &uistack.stack, position),
         };
-        let props = *props;
-        let focus_policy = *focus_policy.unwrap_or(&FocusPolicy::Pass);
-        let area = Rect {
-            x: props.position.x,
-            y: props.position.y,
-            width: props.size.x,
-            height: props.size.y,
-        };
-        drop(query_one);
+        index.map(|index| uistack.stack[index])
+    });
+    let Some(entity) = target else {
+        return Ok(false);
+    };
 
-        if !area.contains(Position {
-            x: event.column,
-            y: event.row,
-        }) {
-            continue;
+    match event.kind {
+        MouseEventKind::Down(_) => {
+            let marker = *world.get::<&Marker>(entity)?;
+            world.insert_one(entity, Clicked)?;
+            tokio::task::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                _ = queue
+                    .0
+                    .send_async(crate::UiEvent::ClickedStyleFinished(marker.0))
+                    .await;
+            });
+            Ok(true)
         }
-
-        match event.kind {
-            MouseEventKind::Down(_) => match focus_policy {
-                FocusPolicy::Block => {
-                    world.insert_one(entity, Clicked)?;
-                    consumed = true;
-                    tokio::task::spawn(async move {
-                        tokio::time::sleep(Duration::from_millis(100)).await;
-                        _ = queue
-                            .0
-                            .send_async(crate::UiEvent::ClickedStyleFinished(marker.0))
-                            .await;
-                    });
-                    break;
-                }
-                FocusPolicy::Pass => {}
-            },
-            MouseEventKind::Moved => match focus_policy {
-                FocusPolicy::Block => {
-                    consumed = true;
-                    world.insert_one(entity, Hovered)?;
-                    break;
-                }
-                FocusPolicy::Pass => {}
-            },
-            _ => {}
+        MouseEventKind::Moved => {
+            world.insert_one(entity, Hovered)?;
+            Ok(true)
         }
+        _ => Ok(false),
     }
-
-    Ok(consumed)
 }
 
 pub(crate) fn click_post_update_system(world: &mut World) {
@@ -216,12 +245,81 @@ impl Keybind {
 
 pub struct Pressed;
 
+/// an application wide key handler, like `q` to quit. unlike a [`Keybind`], it fires no matter
+/// which element is focused, and before the keybinds of elements. see
+/// [`register_global_keybind`].
+#[derive(Clone)]
+pub struct GlobalKeybind {
+    pub key: KeyCode,
+    pub modifiers: KeyModifiers,
+    pub handler: Arc<dyn Fn(&mut World) + Send + Sync + 'static>,
+}
+
+/// resource holding every [`GlobalKeybind`], in the order they were registered.
+#[derive(Clone, Default, derive_more::Deref, derive_more::DerefMut)]
+pub struct GlobalKeybinds(pub Vec<GlobalKeybind>);
+
+/// registers `handler` to run whenever `key` is pressed, see [`GlobalKeybind`].
+///
+/// # Example
+///
+/// ```
+/// # use hecs::World;
+/// # use mana_tui_beheaded::{focus::Keybind, register_global_keybind};
+/// struct Quit;
+///
+/// let mut world = World::new();
+/// register_global_keybind(&mut world, Keybind::char('q'), |world| {
+///     world.spawn((Quit,));
+/// });
+/// ```
+pub fn register_global_keybind(
+    world: &mut World,
+    key: Keybind,
+    handler: impl Fn(&mut World) + Send + Sync + 'static,
+) {
+    world
+        .get_or_insert_resource_with::<&mut GlobalKeybinds>(|_| GlobalKeybinds::default())
+        .push(GlobalKeybind {
+            key: key.0.code,
+            modifiers: key.0.modifiers,
+            handler: Arc::new(handler),
+        });
+}
+
+/// runs the global keybinds matching `event`. returns true if any did.
+pub(crate) fn global_keybind_system(world: &mut World, event: KeyEvent) -> bool {
+    if event.kind != KeyEventKind::Press {
+        return false;
+    }
+    let handlers: SmallVec<[_; 4]> = world
+        .get_resource::<&GlobalKeybinds>()
+        .map(|keybinds| {
+            keybinds
+                .iter()
+                .filter(|keybind| keybind.key == event.code && keybind.modifiers == event.modifiers)
+                .map(|keybind| keybind.handler.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    for handler in &handlers {
+        handler(world);
+    }
+    !handlers.is_empty()
+}
+
 pub(crate) fn keybind_clicked_system(world: &mut World, event: KeyEvent) -> bool {
     let queue = world.get_resource::<&EventQueue>().unwrap().0.clone();
     let mut cmd = CommandBuffer::new();
     let mut consumed = false;
+    // children hidden by their parent's `MaxChildren` or `ChildrenOffset` aren't on the stack.
+    let shown = world
+        .query_mut::<&UiStack>()
+        .into_iter()
+        .flat_map(|uistack| uistack.stack.iter().copied())
+        .collect::<HashSet<_>>();
     for (entity, keybind, &marker) in world.query_mut::<(Entity, &Keybind, &Marker)>() {
-        if keybind.event_eq(event, KeyEventKind::Press) {
+        if keybind.event_eq(event, KeyEventKind::Press) && shown.contains(&entity) {
             consumed = true;
             cmd.insert_one(entity, Pressed);
             let tx = queue.0.clone();
@@ -261,21 +359,22 @@ pub(crate) fn handle_pressed(world: &mut World) {
 pub(crate) struct FocusState {
     pub(crate) normal_style: Option<Style>,
     pub(crate) current_style: Option<Style>,
+    pub(crate) pressed_style: Option<Style>,
     pub(crate) entity: Option<Entity>,
 }
 
 pub trait Transient: Default + Clone + Component {
     fn hook_transient<T: Component, W: EcsMut + SystemsExt + ?Sized>(ecs: &mut W) {
-        let is_new = ecs.get_resource::<&Store<Self>>().is_ok();
         let mut store = ecs.get_or_insert_resource_with::<&mut Store<Self>>(|_| Store::new());
-        let original = store.get(&TypeId::of::<T>()).cloned();
+        let is_new = !store.contains_key(&TypeId::of::<T>());
 
-        if original.is_none() {
+        if is_new {
             store.insert(TypeId::of::<T>(), Self::default());
         }
 
         drop(store);
 
+        // the systems are per `T`, so they are only added the first time `T` is hooked.
         if is_new {
             ecs.add_system::<PostRenderSchedule>(|world| {
                 Self::update::<T>(world);
@@ -311,11 +410,19 @@ impl Transient for FocusState {
     }
 
     fn update<T: Component>(world: &mut World) {
-        let Some((entity, &props, _, _)) = world
+        let pressed = world
             .query_mut::<(Entity, &Props, Or<&Clicked, &Pressed>, &T)>()
             .into_iter()
             .next()
-        else {
+            .map(|(entity, &props, _, _)| (entity, props));
+        let Some((entity, props)) = pressed else {
+            // the pressed style only lasts while the element is pressed.
+            let mut store = world.get_resource::<&mut Store<FocusState>>().unwrap();
+            if let Some(focus) = store.get_mut(&TypeId::of::<T>())
+                && focus.pressed_style.is_some()
+            {
+                focus.current_style = focus.normal_style;
+            }
             return;
         };
         _ = world.insert_one(entity, Marker(TypeId::of::<T>()));
@@ -326,7 +433,10 @@ impl Transient for FocusState {
             if focus.normal_style.is_none() {
                 focus.normal_style = Some(style);
             }
-            focus.current_style = Some(style.add_modifier(Modifier::REVERSED));
+            focus.current_style = Some(match focus.pressed_style {
+                Some(pressed) => style.patch(pressed),
+                None => style.add_modifier(Modifier::REVERSED),
+            });
             focus.entity = Some(entity);
         }
     }
@@ -336,6 +446,226 @@ pub trait FocusExt: EcsMut + SystemsExt {
     fn use_focus<T: Component>(&mut self) {
         FocusState::hook_transient::<T, Self>(self);
     }
+
+    /// like [`FocusExt::use_focus`], but the element marked with `T` is patched with `style`
+    /// while it is [`Pressed`] or [`Clicked`] instead of being reversed.
+    fn use_pressed_style<T: Component>(&mut self, style: Style) {
+        FocusState::hook_transient::<T, Self>(self);
+        let mut store =
+            self.get_or_insert_resource_with::<&mut Store<FocusState>>(|_| Store::new());
+        if let Some(focus) = store.get_mut(&TypeId::of::<T>()) {
+            focus.pressed_style = Some(style);
+        }
+    }
 }
 
 impl<T: EcsMut> FocusExt for T {}
+
+#[cfg(test)]
+mod tests {
+    use mana_tui_elemental::layout::Props;
+    use mana_tui_elemental::prelude::*;
+    use mana_tui_utils::systems::SystemsExt;
+    use ratatui::layout::Rect;
+    use ratatui::style::{Color, Style};
+
+    use crate::focus::{FocusExt, Focused, Keybind, OnClick, Pressed};
+    use crate::schedule::{PostRenderSchedule, PreRenderSchedule};
+
+    #[test]
+    fn test_pressed_style() {
+        struct Button;
+
+        let mut ctx = ElementCtx::new();
+        crate::init(&mut ctx);
+        ctx.use_pressed_style::<Button>(Style::new().bg(Color::Red));
+        let root = ctx.spawn_ui(ui(Block::new()).with((Button, Width::fixed(4), Height::fixed(1))));
+        ctx.calculate_layout(root, Rect::new(0, 0, 4, 1)).unwrap();
+
+        let style = |ctx: &ElementCtx| {
+            let props = *ctx.get::<&Props>(root).unwrap();
+            (props.get_style)(ctx, root).unwrap_or_default()
+        };
+
+        ctx.insert_one(root, Pressed).unwrap();
+        ctx.run_systems::<PostRenderSchedule>();
+        ctx.run_systems::<PreRenderSchedule>();
+        assert_eq!(style(&ctx).bg, Some(Color::Red));
+
+        ctx.remove_one::<Pressed>(root).unwrap();
+        ctx.run_systems::<PostRenderSchedule>();
+        ctx.run_systems::<PreRenderSchedule>();
+        assert_eq!(style(&ctx).bg, None);
+    }
+
+    #[test]
+    fn test_global_keybind() {
+        use mana_tui_utils::resource::Resources;
+        use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+        #[derive(Debug, Default, PartialEq, Eq)]
+        struct Presses {
+            global: u32,
+            element: u32,
+        }
+
+        let mut ctx = ElementCtx::new();
+        crate::init(&mut ctx);
+        ctx.insert_resource(Presses::default());
+        crate::register_global_keybind(&mut ctx, Keybind::char('g'), |world| {
+            world.get_resource::<&mut Presses>().unwrap().global += 1;
+        });
+        let button = || {
+            ui(Block::new()).with((
+                Width::fixed(4),
+                Height::fixed(1),
+                Keybind::char('g'),
+                OnClick::new(|world| {
+                    world.get_resource::<&mut Presses>().unwrap().element += 1;
+                }),
+            ))
+        };
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Width::fixed(8), Height::fixed(1), Direction::Horizontal))
+                .children((button(), button())),
+        );
+        ctx.calculate_layout(root, Rect::new(0, 0, 8, 1)).unwrap();
+        crate::setup_interactions(&mut ctx, root);
+
+        let press =
+            |code, kind| Event::Key(KeyEvent::new_with_kind(code, KeyModifiers::NONE, kind));
+        let children = ctx.get::<&Children>(root).unwrap().to_vec();
+        for &child in &children {
+            for &other in &children {
+                _ = ctx.remove_one::<Focused>(other);
+            }
+            ctx.insert_one(child, Focused).unwrap();
+            assert!(crate::handle_event(
+                &mut ctx,
+                press(KeyCode::Char('g'), KeyEventKind::Press)
+            ));
+        }
+        // releases and other keys don't fire it.
+        crate::handle_event(&mut ctx, press(KeyCode::Char('g'), KeyEventKind::Release));
+        crate::handle_event(&mut ctx, press(KeyCode::Char('h'), KeyEventKind::Press));
+
+        assert_eq!(
+            *ctx.get_resource::<&Presses>().unwrap(),
+            Presses {
+                global: 2,
+                element: 0
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_hidden_keybind() {
+        use std::any::TypeId;
+
+        use mana_tui_utils::resource::Resources;
+        use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+        #[derive(Debug, Default)]
+        struct Presses(Vec<usize>);
+
+        let mut ctx = ElementCtx::new();
+        crate::init(&mut ctx);
+        ctx.insert_resource(Presses::default());
+        let tab = |idx: usize| {
+            ui(Block::new()).with((
+                Width::fixed(4),
+                Height::fixed(1),
+                Keybind::char('k'),
+                OnClick::new(move |world| {
+                    world.get_resource::<&mut Presses>().unwrap().0.push(idx);
+                }),
+            ))
+        };
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Width::fixed(4), Height::fixed(1), MaxChildren(1)))
+                .children((tab(0), tab(1), tab(2))),
+        );
+        for child in ctx.get::<&Children>(root).unwrap().to_vec() {
+            ctx.insert_one(child, crate::Marker(TypeId::of::<()>()))
+                .unwrap();
+        }
+        let press = |ctx: &mut ElementCtx| {
+            for kind in [KeyEventKind::Press, KeyEventKind::Release] {
+                let event = KeyEvent::new_with_kind(KeyCode::Char('k'), KeyModifiers::NONE, kind);
+                crate::handle_event(ctx, Event::Key(event));
+            }
+        };
+
+        ctx.calculate_layout(root, Rect::new(0, 0, 4, 1)).unwrap();
+        crate::setup_interactions(&mut ctx, root);
+        press(&mut ctx);
+        assert_eq!(ctx.get_resource::<&Presses>().unwrap().0, [0]);
+
+        ctx.insert_one(root, ChildrenOffset(2)).unwrap();
+        ctx.calculate_layout(root, Rect::new(0, 0, 4, 1)).unwrap();
+        crate::setup_interactions(&mut ctx, root);
+        press(&mut ctx);
+        assert_eq!(ctx.get_resource::<&Presses>().unwrap().0, [0, 2]);
+    }
+
+    #[test]
+    fn test_overlapping_roots() {
+        use std::any::TypeId;
+
+        use ratatui::crossterm::event::{KeyModifiers, MouseEvent, MouseEventKind};
+
+        use crate::focus::{FocusPolicy, Hovered};
+
+        let mut ctx = ElementCtx::new();
+        crate::init(&mut ctx);
+        let area = Rect::new(0, 0, 8, 4);
+        let root = |ctx: &mut ElementCtx, view: View| {
+            let root = ctx.spawn_ui(view);
+            ctx.insert(
+                root,
+                (FocusPolicy::Block, crate::Marker(TypeId::of::<()>())),
+            )
+            .unwrap();
+            root
+        };
+        // the popup is spawned first, and in another archetype than the main root.
+        let popup = root(
+            &mut ctx,
+            ui(Block::new())
+                .with((Width::grow(), Height::grow(), ClearBackground))
+                .done(),
+        );
+        let main = root(
+            &mut ctx,
+            ui(Block::new())
+                .with((Width::grow(), Height::grow()))
+                .done(),
+        );
+        let hovered = |ctx: &mut ElementCtx| {
+            crate::focus::clear_old_hovers(ctx);
+            let event = MouseEvent {
+                kind: MouseEventKind::Moved,
+                column: 2,
+                row: 2,
+                modifiers: KeyModifiers::NONE,
+            };
+            crate::focus::handle_mouse_event(ctx, event).unwrap();
+            [main, popup].map(|root| ctx.get::<&Hovered>(root).is_ok())
+        };
+
+        // the root set up last is drawn on top.
+        for root in [main, popup] {
+            ctx.calculate_layout(root, area).unwrap();
+            crate::setup_interactions(&mut ctx, root);
+        }
+        assert_eq!(hovered(&mut ctx), [false, true]);
+
+        for root in [popup, main] {
+            ctx.calculate_layout(root, area).unwrap();
+            crate::setup_interactions(&mut ctx, root);
+        }
+        assert_eq!(hovered(&mut ctx), [true, false]);
+    }
+}
@@ -0,0 +1,412 @@
+//! forms: the [`FormField`]s inside a [`Form`] are validated when they lose focus and when the
+//! form is submitted, and the [`OnSubmit`] handler of the form gets their values once all of
+//! them are valid.
+//!
+//! the value of a field is the value of its [`ContentEditable`]. enter submits the form of the
+//! focused field, tab and shift + tab move the focus between the fields of a form in the order
+//! they appear in the tree.
+
+use std::sync::Arc;
+
+use hecs::{Entity, World};
+use mana_tui_elemental::layout::{Children, ContentEditable, ElementCtx, Width};
+use mana_tui_elemental::ui::ui;
+use mana_tui_utils::resource::Resources;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind};
+use ratatui::style::{Color, Style};
+use ratatui::text::Text;
+
+use crate::focus::Focused;
+
+/// marks the element holding the fields of a form.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Form;
+
+/// a named input of a [`Form`], added next to the [`ContentEditable`] of the element.
+#[derive(Clone)]
+pub struct FormField {
+    /// the key of the value in the [`FormValues`].
+    pub name: &'static str,
+    /// checks the value of the field, the error is shown under the field.
+    pub validate: Arc<dyn Fn(&str) -> Result<(), String> + Send + Sync + 'static>,
+}
+
+impl FormField {
+    /// a field accepting any value.
+    #[must_use]
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            validate: Arc::new(|_| Ok(())),
+        }
+    }
+
+    /// sets the validation of the field.
+    #[must_use]
+    pub fn validate(
+        self,
+        validate: impl Fn(&str) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            validate: Arc::new(validate),
+            ..self
+        }
+    }
+}
+
+/// the values of the fields of a form, in the order the fields appear in the tree.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormValues(pub Vec<(&'static str, String)>);
+
+impl FormValues {
+    /// the value of the field called `name`.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(field, _)| *field == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// called with the values of the form when it is submitted and every field is valid.
+#[derive(derive_more::Deref, derive_more::DerefMut, Clone)]
+pub struct OnSubmit(Arc<dyn Fn(&mut World, FormValues) + Send + Sync + 'static>);
+
+impl OnSubmit {
+    pub fn new(func: impl Fn(&mut World, FormValues) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(func) as Arc<_>)
+    }
+}
+
+/// the error of a field that failed its validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError(pub String);
+
+/// resource with the style of the error messages. red text by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FormErrorStyle(pub Style);
+
+impl Default for FormErrorStyle {
+    fn default() -> Self {
+        Self(Style::new().fg(Color::Red))
+    }
+}
+
+/// the text element showing the [`FieldError`] of a field, inserted after it.
+struct ErrorElement(Entity);
+
+/// marks a field that was focused, so it is validated once it loses focus.
+struct WasFocused;
+
+/// submits the form with enter and moves the focus between its fields with tab.
+pub(crate) fn handle_form_key(world: &mut World, event: KeyEvent) -> bool {
+    if event.kind == KeyEventKind::Release {
+        return false;
+    }
+    let Some(field) = world
+        .query_mut::<(Entity, &FormField, &Focused)>()
+        .into_iter()
+        .next()
+        .map(|(entity, ..)| entity)
+    else {
+        return false;
+    };
+    let Some(form) = ancestors(world, field).find(|entity| world.get::<&Form>(*entity).is_ok())
+    else {
+        return false;
+    };
+
+    match event.code {
+        KeyCode::Enter => {
+            submit_form(world, form);
+        }
+        KeyCode::Tab | KeyCode::BackTab => {
+            let fields = fields(world, form);
+            let Some(idx) = fields.iter().position(|entity| *entity == field) else {
+                return false;
+            };
+            let next = if event.code == KeyCode::Tab {
+                (idx + 1) % fields.len()
+            } else {
+                (idx + fields.len() - 1) % fields.len()
+            };
+            _ = world.remove_one::<Focused>(field);
+            _ = world.insert_one(fields[next], Focused);
+            validate_blurred(world);
+        }
+        _ => return false,
+    }
+    true
+}
+
+/// validates every field of `form` and calls its [`OnSubmit`] handler if they are all valid.
+/// returns true if the form was submitted.
+///
+/// call it from the [`OnClick`](crate::focus::OnClick) of a submit button.
+pub fn submit_form(world: &mut World, form: Entity) -> bool {
+    let fields = fields(world, form);
+    let mut valid = true;
+    for field in &fields {
+        valid &= validate_field(world, *field);
+    }
+    if !valid {
+        return false;
+    }
+
+    let values = fields
+        .iter()
+        .filter_map(|field| {
+            let name = world.get::<&FormField>(*field).ok()?.name;
+            Some((name, field_value(world, *field)))
+        })
+        .collect();
+    let handler = world
+        .get::<&OnSubmit>(form)
+        .map(|handler| handler.0.clone());
+    if let Ok(handler) = handler {
+        handler(world, FormValues(values));
+    }
+    true
+}
+
+/// validates the fields that lost focus since the last call.
+pub(crate) fn validate_blurred(world: &mut World) {
+    let blurred = world
+        .query_mut::<(Entity, &FormField, &WasFocused, Option<&Focused>)>()
+        .into_iter()
+        .filter(|(.., focused)| focused.is_none())
+        .map(|(entity, ..)| entity)
+        .collect::<Vec<_>>();
+    for field in blurred {
+        _ = world.remove_one::<WasFocused>(field);
+        validate_field(world, field);
+    }
+
+    let focused = world
+        .query_mut::<(Entity, &FormField, &Focused)>()
+        .into_iter()
+        .map(|(entity, ..)| entity)
+        .collect::<Vec<_>>();
+    for field in focused {
+        _ = world.insert_one(field, WasFocused);
+    }
+}
+
+/// runs the validation of `field` and shows or clears its error. returns true if it is valid.
+fn validate_field(world: &mut World, field: Entity) -> bool {
+    let Ok(validate) = world
+        .get::<&FormField>(field)
+        .map(|field| field.validate.clone())
+    else {
+        return true;
+    };
+    match validate(&field_value(world, field)) {
+        Ok(()) => {
+            clear_error(world, field);
+            true
+        }
+        Err(message) => {
+            show_error(world, field, message);
+            false
+        }
+    }
+}
+
+fn field_value(world: &World, field: Entity) -> String {
+    world
+        .get::<&ContentEditable>(field)
+        .map(|editable| editable.value.clone())
+        .unwrap_or_default()
+}
+
+/// sets the [`FieldError`] of `field` and the text of its error element, spawning the element
+/// after the field the first time.
+fn show_error(world: &mut World, field: Entity, message: String) {
+    let style = world
+        .get_resource::<&FormErrorStyle>()
+        .map(|style| style.0)
+        .unwrap_or_else(|_| FormErrorStyle::default().0);
+    let text = Text::styled(message.clone(), style);
+    _ = world.insert_one(field, FieldError(message));
+
+    if let Ok(element) = world.get::<&ErrorElement>(field).map(|element| element.0)
+        && world.contains(element)
+    {
+        let width = Width::fixed(text.width() as u16);
+        _ = world.insert(element, (text, width));
+        return;
+    }
+    let Some(parent) = parent(world, field) else {
+        return;
+    };
+    let element = ElementCtx::with_world(world, |ctx| ctx.spawn_ui(ui(text)));
+    _ = world.insert_one(field, ErrorElement(element));
+    update_children(world, parent, |children| {
+        let idx = children.iter().position(|child| *child == field);
+        children.insert(idx.map_or(children.len(), |idx| idx + 1), element);
+    });
+}
+
+/// removes the [`FieldError`] of `field` and despawns its error element.
+fn clear_error(world: &mut World, field: Entity) {
+    _ = world.remove_one::<FieldError>(field);
+    let Ok(ErrorElement(element)) = world.remove_one::<ErrorElement>(field) else {
+        return;
+    };
+    if let Some(parent) = parent(world, element) {
+        update_children(world, parent, |children| {
+            children.retain(|child| *child != element);
+        });
+    }
+    _ = world.despawn(element);
+}
+
+fn update_children(world: &mut World, parent: Entity, update: impl FnOnce(&mut Vec<Entity>)) {
+    let Ok(mut children) = world.get::<&mut Children>(parent) else {
+        return;
+    };
+    let mut list = children.to_vec();
+    update(&mut list);
+    *children = Children::Some(Arc::new(list));
+}
+
+/// the fields inside `form`, in the order they appear in the tree.
+fn fields(world: &World, form: Entity) -> Vec<Entity> {
+    let mut fields = Vec::new();
+    let mut stack = vec![form];
+    while let Some(entity) = stack.pop() {
+        if world.get::<&FormField>(entity).is_ok() {
+            fields.push(entity);
+        }
+        if let Ok(children) = world.get::<&Children>(entity) {
+            stack.extend(children.iter().rev());
+        }
+    }
+    fields
+}
+
+fn parent(world: &World, entity: Entity) -> Option<Entity> {
+    world
+        .query::<(Entity, &Children)>()
+        .iter()
+        .find(|(_, children)| children.contains(&entity))
+        .map(|(parent, _)| parent)
+}
+
+fn ancestors(world: &World, entity: Entity) -> impl Iterator<Item = Entity> + '_ {
+    std::iter::successors(parent(world, entity), |entity| parent(world, *entity))
+}
+
+#[cfg(test)]
+mod tests {
+    use mana_tui_elemental::prelude::*;
+    use mana_tui_utils::resource::Resources;
+    use ratatui::buffer::Buffer;
+    use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+    use ratatui::layout::Rect;
+
+    use crate::focus::Focused;
+    use crate::form::{FieldError, Form, FormField, FormValues, OnSubmit};
+
+    struct Submitted(Vec<FormValues>);
+
+    fn press(ctx: &mut ElementCtx, code: KeyCode) -> bool {
+        crate::handle_event(ctx, Event::Key(KeyEvent::new(code, KeyModifiers::NONE)))
+    }
+
+    fn input(value: &str, field: FormField) -> View {
+        ui(Text::default())
+            .with((
+                ContentEditable::new(value),
+                field,
+                Width::fixed(10),
+                Height::fixed(1),
+            ))
+            .done()
+    }
+
+    #[test]
+    fn test_form() {
+        let area = Rect::new(0, 0, 20, 4);
+        let mut ctx = ElementCtx::new();
+        crate::init(&mut ctx);
+        ctx.insert_resource(Submitted(Vec::new()));
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((
+                    Form,
+                    Width::fixed(20),
+                    Height::fixed(4),
+                    OnSubmit::new(|world, values| {
+                        world
+                            .get_resource::<&mut Submitted>()
+                            .unwrap()
+                            .0
+                            .push(values);
+                    }),
+                ))
+                .children((
+                    input("ana", FormField::new("name")),
+                    input(
+                        "x",
+                        FormField::new("age").validate(|value| {
+                            value
+                                .parse::<u8>()
+                                .map(|_| ())
+                                .map_err(|_| "not a number".to_string())
+                        }),
+                    ),
+                )),
+        );
+        let [name, age] = ctx.get::<&Children>(root).unwrap()[..] else {
+            unreachable!()
+        };
+        let render = |ctx: &mut ElementCtx| {
+            ctx.calculate_layout(root, area).unwrap();
+            crate::setup_interactions(ctx, root);
+            let mut buf = Buffer::empty(area);
+            ctx.render(root, area, &mut buf);
+            (0..area.height)
+                .map(|y| {
+                    (0..area.width)
+                        .map(|x| buf[(x, y)].symbol())
+                        .collect::<String>()
+                        .trim_end()
+                        .to_string()
+                })
+                .collect::<Vec<_>>()
+        };
+        render(&mut ctx);
+
+        // tab moves to the next field, in the order of the tree.
+        ctx.insert_one(name, Focused).unwrap();
+        render(&mut ctx);
+        assert!(press(&mut ctx, KeyCode::Tab));
+        assert!(ctx.get::<&Focused>(name).is_err());
+        assert!(ctx.get::<&Focused>(age).is_ok());
+
+        // the invalid field shows its error and the form isn't submitted.
+        assert!(press(&mut ctx, KeyCode::Enter));
+        assert_eq!(render(&mut ctx), ["ana", "x", "not a number", ""]);
+        assert_eq!(
+            *ctx.get::<&FieldError>(age).unwrap(),
+            FieldError("not a number".into())
+        );
+        assert!(ctx.get_resource::<&Submitted>().unwrap().0.is_empty());
+
+        for code in [KeyCode::Backspace, KeyCode::Char('4'), KeyCode::Char('2')] {
+            assert!(press(&mut ctx, code));
+        }
+        assert!(press(&mut ctx, KeyCode::Enter));
+        assert_eq!(render(&mut ctx), ["ana", "42", "", ""]);
+        assert!(ctx.get::<&FieldError>(age).is_err());
+        assert_eq!(
+            ctx.get_resource::<&Submitted>().unwrap().0,
+            [FormValues(vec![
+                ("name", "ana".to_string()),
+                ("age", "42".to_string())
+            ])]
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! a grid of buckets over the interactive elements of a [`UiStack`](crate::focus::UiStack), so
+//! mouse events find the element under the cursor without walking the whole stack.
+
+use hecs::{Entity, World};
+use mana_tui_elemental::layout::Props;
+use ratatui::layout::{Position, Rect};
+
+use crate::Marker;
+use crate::focus::FocusPolicy;
+
+/// trees with fewer interactive elements are scanned linearly, the grid isn't worth building.
+const MIN_ELEMENTS: usize = 64;
+const BUCKET_WIDTH: u16 = 8;
+const BUCKET_HEIGHT: u16 = 4;
+
+/// the elements that take mouse events: they have a [`Marker`] and block the events from the
+/// elements below them.
+fn interactive_area(world: &World, entity: Entity) -> Option<Rect> {
+    let mut query = world.query_one::<(&Props, Option<&FocusPolicy>, &Marker)>(entity);
+    let (props, policy, _) = query.get().ok()?;
+    matches!(policy, Some(FocusPolicy::Block)).then(|| {
+        Rect::new(
+            props.position.x,
+            props.position.y,
+            props.size.x,
+            props.size.y,
+        )
+    })
+}
+
+/// the index in `stack` of the topmost interactive element under `position`, walking the whole
+/// stack.
+pub(crate) fn scan(world: &World, stack: &[Entity], position: Position) -> Option<usize> {
+    stack.iter().rposition(|entity| {
+        interactive_area(world, *entity).is_some_and(|area| area.contains(position))
+    })
+}
+
+/// the interactive elements of a stack sorted into buckets of cells. built with the stack after
+/// every layout.
+#[derive(Debug)]
+pub(crate) struct HitGrid {
+    area: Rect,
+    columns: u16,
+    /// the stack index and area of every element overlapping each bucket, topmost first.
+    buckets: Vec<Vec<(usize, Rect)>>,
+}
+
+impl HitGrid {
+    /// the grid for `stack`, or `None` if it has too few interactive elements.
+    pub(crate) fn build(world: &World, stack: &[Entity]) -> Option<Self> {
+        let elements = stack
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entity)| {
+                let area = interactive_area(world, *entity)?;
+                (!area.is_empty()).then_some((index, area))
+            })
+            .collect::<Vec<_>>();
+        if elements.len() < MIN_ELEMENTS {
+            return None;
+        }
+
+        let area = elements
+            .iter()
+            .map(|(_, area)| *area)
+            .reduce(Rect::union)
+            .unwrap_or_default();
+        let columns = area.width.div_ceil(BUCKET_WIDTH);
+        let rows = area.height.div_ceil(BUCKET_HEIGHT);
+        let mut grid = Self {
+            area,
+            columns,
+            buckets: vec![Vec::new(); usize::from(columns) * usize::from(rows)],
+        };
+        for &(index, element) in elements.iter().rev() {
+            let (left, top) = grid.bucket_coords(element.x, element.y);
+            let (right, bottom) = grid.bucket_coords(element.right() - 1, element.bottom() - 1);
+            for row in top..=bottom {
+                for column in left..=right {
+                    grid.buckets[usize::from(row) * usize::from(columns) + usize::from(column)]
+                        .push((index, element));
+                }
+            }
+        }
+        Some(grid)
+    }
+
+    fn bucket_coords(&self, x: u16, y: u16) -> (u16, u16) {
+        (
+            (x - self.area.x) / BUCKET_WIDTH,
+            (y - self.area.y) / BUCKET_HEIGHT,
+        )
+    }
+
+    /// the index in the stack of the topmost interactive element under `position`.
+    pub(crate) fn hit(&self, position: Position) -> Option<usize> {
+        if !self.area.contains(position) {
+            return None;
+        }
+        let (column, row) = self.bucket_coords(position.x, position.y);
+        self.buckets[usize::from(row) * usize::from(self.columns) + usize::from(column)]
+            .iter()
+            .find(|(_, area)| area.contains(position))
+            .map(|(index, _)| *index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::TypeId;
+    use std::time::Instant;
+
+    use hecs::Entity;
+    use mana_tui_elemental::layout::Props;
+    use mana_tui_elemental::prelude::*;
+    use ratatui::layout::{Position, Rect};
+
+    use crate::Marker;
+    use crate::focus::{FocusPolicy, UiStack, generate_ui_stack};
+    use crate::hit::scan;
+
+    const AREA: Rect = Rect::new(0, 0, 120, 40);
+
+    /// xorshift, so the layouts are random but the same on every run.
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, max: u16) -> u16 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % u64::from(max)) as u16
+        }
+    }
+
+    /// a flat tree of `count` overlapping elements at random places, most of them interactive.
+    fn random_tree(rng: &mut Rng, count: usize) -> (ElementCtx, Entity) {
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Width::grow(), Height::grow()))
+                .children(
+                    (0..count)
+                        .map(|_| ui(Block::new()).done())
+                        .collect::<Vec<_>>(),
+                ),
+        );
+        ctx.calculate_layout(root, AREA).unwrap();
+        let children = ctx.get::<&Children>(root).unwrap().to_vec();
+        for child in children {
+            {
+                let mut props = ctx.get::<&mut Props>(child).unwrap();
+                props.position.x = rng.below(AREA.width);
+                props.position.y = rng.below(AREA.height);
+                props.size.x = rng.below(AREA.width - props.position.x) + 1;
+                props.size.y = rng.below(AREA.height - props.position.y) + 1;
+            }
+            let policy = match rng.below(4) {
+                0 => FocusPolicy::Pass,
+                _ => FocusPolicy::Block,
+            };
+            ctx.insert_one(child, (policy,)).unwrap();
+            if rng.below(8) != 0 {
+                ctx.insert_one(child, Marker(TypeId::of::<()>())).unwrap();
+            }
+        }
+        generate_ui_stack(&mut ctx, root);
+        (ctx, root)
+    }
+
+    #[test]
+    fn test_hit_grid_matches_scan() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for count in [16, 100, 400] {
+            let (ctx, root) = random_tree(&mut rng, count);
+            let uistack = ctx.get::<&UiStack>(root).unwrap();
+            // small trees are only scanned.
+            assert_eq!(uistack.hits.is_some(), count >= 64);
+            let Some(hits) = &uistack.hits else {
+                continue;
+            };
+            for position in AREA.positions() {
+                assert_eq!(
+                    hits.hit(position),
+                    scan(&ctx, &uistack.stack, position),
+                    "{count} elements at {position}"
+                );
+            }
+            assert_eq!(hits.hit(Position::new(500, 500)), None);
+        }
+    }
+
+    #[test]
+    #[ignore = "benchmark, run with `cargo test --release -- --ignored --nocapture`"]
+    fn bench_hit_test() {
+        let (ctx, root) = random_tree(&mut Rng(7), 5_000);
+        let uistack = ctx.get::<&UiStack>(root).unwrap();
+        let hits = uistack.hits.as_ref().unwrap();
+        let positions = AREA.positions().collect::<Vec<_>>();
+
+        let start = Instant::now();
+        for position in &positions {
+            std::hint::black_box(scan(&ctx, &uistack.stack, *position));
+        }
+        let scanned = start.elapsed();
+        let start = Instant::now();
+        for position in &positions {
+            std::hint::black_box(hits.hit(*position));
+        }
+        let indexed = start.elapsed();
+        println!(
+            "{} lookups: scan {scanned:?}, grid {indexed:?}",
+            positions.len()
+        );
+    }
+}
@@ -6,11 +6,12 @@ use flume::Receiver;
 use flume::Sender;
 use hecs::TypeIdMap;
 use hecs::{Entity, World};
-use mana_tui_elemental::layout::Props;
+use mana_tui_elemental::layout::{Props, TerminalSize};
 use mana_tui_utils::resource::Resources;
 use mana_tui_utils::systems::SystemsExt;
 use ratatui::crossterm::event::Event;
 use ratatui::crossterm::event::EventStream;
+use ratatui::crossterm::event::MouseEventKind;
 use ratatui::style::Style;
 use smallvec::SmallVec;
 use tokio_stream::StreamExt;
@@ -20,14 +21,52 @@ use crate::focus::FocusState;
 use crate::schedule::PostRenderSchedule;
 use crate::schedule::PreRenderSchedule;
 
+pub mod drag;
+pub mod dropdown;
+pub mod edit;
 pub mod focus;
+pub mod form;
+mod hit;
+pub mod menu;
 pub mod schedule;
+pub mod select;
+pub mod slider;
+pub mod split;
+
+pub use focus::register_global_keybind;
 
 pub fn handle_event(mut world: &mut World, event: Event) -> bool {
     match event {
         Event::FocusGained => {}
         Event::FocusLost => {}
         Event::Key(key_event) => {
+            // an open context menu traps every key.
+            if menu::handle_menu_key(world, key_event) {
+                return true;
+            }
+            // so does an open dropdown, and a focused one opens on enter.
+            if dropdown::handle_dropdown_key(world, key_event) {
+                return true;
+            }
+            if form::handle_form_key(world, key_event) {
+                return true;
+            }
+            // typing into a focused text field shouldn't trigger any keybind.
+            if edit::handle_edit_key(world, key_event) {
+                return true;
+            }
+            if split::handle_split_key(world, key_event) {
+                return true;
+            }
+            if slider::handle_slider_key(world, key_event) {
+                return true;
+            }
+            if focus::global_keybind_system(world, key_event) {
+                return true;
+            }
+            if select::handle_copy_key(world, key_event) {
+                return true;
+            }
             let consumed = focus::keybind_clicked_system(world, key_event);
             world.run_systems::<PostRenderSchedule>();
             focus::handle_pressed(world);
@@ -35,17 +74,42 @@ pub fn handle_event(mut world: &mut World, event: Event) -> bool {
             return consumed;
         }
         Event::Mouse(mouse_event) => {
+            if menu::handle_menu_mouse(world, mouse_event) {
+                return true;
+            }
+            if dropdown::handle_dropdown_mouse(world, mouse_event) {
+                return true;
+            }
+            if drag::handle_drag_event(world, mouse_event) {
+                slider::scrub_captured_slider(world, mouse_event);
+                split::drag_captured_divider(world, mouse_event);
+                // only the drop is reported as consumed: giving control back in the middle of
+                // a drag would let the app respawn the ui and lose the dragged element.
+                return matches!(mouse_event.kind, MouseEventKind::Up(_));
+            }
+            // while an element captures the pointer, no other element is hovered or clicked.
+            if world.get_resource::<&drag::PointerCapture>().is_ok() {
+                return false;
+            }
+            if select::handle_select_event(world, mouse_event) {
+                return true;
+            }
             focus::clear_old_hovers(world);
             let consumed = focus::handle_mouse_event(world, mouse_event);
             focus::on_click_system(world);
             world.run_systems::<PostRenderSchedule>();
             focus::click_post_update_system(world);
+            edit::sync_content_editable(world);
             if consumed == Ok(true) {
                 return true;
             }
         }
         Event::Paste(_) => {}
-        Event::Resize(_, _) => {}
+        Event::Resize(width, height) => {
+            // give control back so the app lays the ui out at the new size.
+            world.insert_or_update_resource(TerminalSize(glam::u16vec2(width, height)));
+            return true;
+        }
     }
     false
 }
@@ -53,10 +117,17 @@ pub fn handle_event(mut world: &mut World, event: Event) -> bool {
 pub fn setup_interactions(mut world: &mut World, root: Entity) {
     world.run_systems::<PreRenderSchedule>();
     focus::generate_ui_stack(world, root);
+    focus::infer_button_roles(world);
+    edit::sync_content_editable(world);
+    form::validate_blurred(world);
+    slider::sync_sliders(world);
+    dropdown::sync_dropdowns(world);
+    select::sync_selection(world);
 }
 
 enum UiEvent {
     ClickedStyleFinished(TypeId),
+    ChangeSettled { entity: Entity, generation: u64 },
 }
 
 #[derive(derive_more::Deref, derive_more::DerefMut, Clone)]
@@ -119,6 +190,9 @@ pub(crate) fn handle_ui_event(world: &mut World, event: UiEvent) -> bool {
                 return true;
             }
         }
+        UiEvent::ChangeSettled { entity, generation } => {
+            return slider::settle_change(world, entity, generation);
+        }
     }
     false
 }
@@ -0,0 +1,409 @@
+//! right click menus for elements with a [`ContextMenu`].
+//!
+//! right clicking the topmost [`FocusPolicy::Block`] element under the cursor opens its menu
+//! under the cursor. while it is open the menu takes every key and mouse event: the arrow keys
+//! (or `j` and `k`) select an item, `Enter` or a click runs it, and `Esc` or a click outside
+//! closes the menu. draw it over the frame with [`render_context_menu`].
+
+use std::sync::Arc;
+
+use glam::{U16Vec2, u16vec2};
+use hecs::{Entity, World};
+use mana_tui_elemental::layout::Props;
+use mana_tui_utils::resource::Resources;
+use ratatui::buffer::Buffer;
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Position, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Clear, Widget};
+use unicode_width::UnicodeWidthStr;
+
+use crate::focus::{FocusPolicy, stacks_at};
+
+/// an entry of a [`ContextMenu`].
+#[derive(Clone)]
+pub struct ContextMenuItem {
+    /// the text of the entry.
+    pub label: String,
+    /// runs the entry while the menu is open, shown next to the label.
+    pub keybind: Option<KeyCode>,
+    /// called after the menu closes.
+    pub handler: Arc<dyn Fn(&mut World) + Send + Sync + 'static>,
+}
+
+impl std::fmt::Debug for ContextMenuItem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ContextMenuItem")
+            .field("label", &self.label)
+            .field("keybind", &self.keybind)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ContextMenuItem {
+    pub fn new(
+        label: impl Into<String>,
+        handler: impl Fn(&mut World) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            label: label.into(),
+            keybind: None,
+            handler: Arc::new(handler),
+        }
+    }
+
+    #[must_use]
+    pub fn keybind(mut self, keybind: KeyCode) -> Self {
+        self.keybind = Some(keybind);
+        self
+    }
+
+    /// the width of the entry without the padding of the menu.
+    fn width(&self) -> usize {
+        self.label.width()
+            + self
+                .keybind
+                .map_or(0, |keybind| keybind.to_string().width() + 2)
+    }
+}
+
+/// the entries shown when the element is right clicked. the element needs
+/// [`FocusPolicy::Block`].
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// # use mana_tui_beheaded::focus::FocusPolicy;
+/// # use mana_tui_beheaded::menu::{ContextMenu, ContextMenuItem};
+/// # use ratatui::crossterm::event::KeyCode;
+/// ui(Text::raw("notes.txt")).with((
+///     FocusPolicy::Block,
+///     ContextMenu::new([
+///         ContextMenuItem::new("open", |_| {}),
+///         ContextMenuItem::new("delete", |_| {}).keybind(KeyCode::Char('d')),
+///     ]),
+/// ));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ContextMenu {
+    pub items: Vec<ContextMenuItem>,
+}
+
+impl ContextMenu {
+    pub fn new(items: impl IntoIterator<Item = ContextMenuItem>) -> Self {
+        Self {
+            items: items.into_iter().collect(),
+        }
+    }
+}
+
+/// resource with the open menu. it outlives the tree, so the menu stays open when the ui is
+/// spawned again.
+#[derive(Debug, Clone)]
+pub struct OpenContextMenu {
+    /// the entries of the menu.
+    pub items: Vec<ContextMenuItem>,
+    /// the top left corner of the menu, one row below the cursor that opened it.
+    pub position: U16Vec2,
+    /// the index of the selected entry.
+    pub selected: usize,
+    /// where [`render_context_menu`] last drew the menu. clicks are matched against it.
+    pub drawn: Option<Rect>,
+}
+
+impl OpenContextMenu {
+    /// the area of the menu, moved left and up if needed to fit in `bounds`.
+    #[must_use]
+    pub fn area(&self, bounds: Rect) -> Rect {
+        let inner = self
+            .items
+            .iter()
+            .map(ContextMenuItem::width)
+            .max()
+            .unwrap_or_default();
+        let width = u16::try_from(inner + 4)
+            .unwrap_or(u16::MAX)
+            .min(bounds.width);
+        let height = u16::try_from(self.items.len() + 2)
+            .unwrap_or(u16::MAX)
+            .min(bounds.height);
+        Rect {
+            x: self.position.x.min(bounds.right().saturating_sub(width)),
+            y: self.position.y.min(bounds.bottom().saturating_sub(height)),
+            width,
+            height,
+        }
+    }
+
+    /// the index of the entry at `position`.
+    fn item_at(&self, position: Position) -> Option<usize> {
+        let area = self
+            .drawn
+            .unwrap_or_else(|| self.area(Rect::new(0, 0, u16::MAX, u16::MAX)));
+        let inner = area.inner(ratatui::layout::Margin::new(1, 1));
+        inner
+            .contains(position)
+            .then(|| usize::from(position.y - inner.y))
+            .filter(|idx| *idx < self.items.len())
+    }
+}
+
+/// closes the open [`ContextMenu`] without running an entry.
+pub fn close_context_menu(world: &mut World) {
+    _ = world.remove_resource::<OpenContextMenu>();
+}
+
+/// closes the menu and runs the entry at `idx`.
+fn run_item(world: &mut World, idx: usize) {
+    let Ok(menu) = world.remove_resource::<OpenContextMenu>() else {
+        return;
+    };
+    if let Some(item) = menu.items.get(idx) {
+        (item.handler)(world);
+    }
+}
+
+/// opens, navigates and closes context menus. returns true if the event was used.
+pub(crate) fn handle_menu_mouse(world: &mut World, event: MouseEvent) -> bool {
+    let cursor = Position {
+        x: event.column,
+        y: event.row,
+    };
+    let menu = world
+        .get_resource::<&OpenContextMenu>()
+        .map(|menu| menu.clone());
+    let Ok(menu) = menu else {
+        return event.kind == MouseEventKind::Down(MouseButton::Right)
+            && open_context_menu(world, cursor);
+    };
+
+    match (event.kind, menu.item_at(cursor)) {
+        (MouseEventKind::Down(MouseButton::Left), Some(idx)) => run_item(world, idx),
+        (MouseEventKind::Down(_), None) => close_context_menu(world),
+        (MouseEventKind::Moved, Some(idx)) => {
+            if let Ok(mut menu) = world.get_resource::<&mut OpenContextMenu>() {
+                menu.selected = idx;
+            }
+        }
+        _ => {}
+    }
+    true
+}
+
+/// the topmost [`FocusPolicy::Block`] element under `cursor`.
+pub(crate) fn topmost_blocking(world: &World, cursor: Position) -> Option<Entity> {
+    let uistack: Vec<Entity> = stacks_at(world, cursor)
+        .iter()
+        .flat_map(|uistack| uistack.stack.iter().copied())
+        .collect();
+
+    uistack.into_iter().rev().find(|entity| {
+        let mut query = world.query_one::<(&Props, &FocusPolicy)>(*entity);
+        query.get().is_ok_and(|(props, policy)| {
+            let area = Rect {
+                x: props.position.x,
+                y: props.position.y,
+                width: props.size.x,
+                height: props.size.y,
+            };
+            matches!(policy, FocusPolicy::Block) && area.contains(cursor)
+        })
+    })
+}
+
+fn open_context_menu(world: &mut World, cursor: Position) -> bool {
+    // the topmost blocking element takes the click, even without a menu.
+    let target = topmost_blocking(world, cursor);
+    let Some(menu) = target.and_then(|target| {
+        world
+            .get::<&ContextMenu>(target)
+            .map(|menu| menu.clone())
+            .ok()
+    }) else {
+        return false;
+    };
+    if menu.items.is_empty() {
+        return false;
+    }
+
+    world.insert_or_update_resource(OpenContextMenu {
+        items: menu.items,
+        position: u16vec2(cursor.x, cursor.y.saturating_add(1)),
+        selected: 0,
+        drawn: None,
+    });
+    true
+}
+
+/// moves the selection of the open menu or runs an entry. every key is used while a menu is
+/// open.
+pub(crate) fn handle_menu_key(world: &mut World, event: KeyEvent) -> bool {
+    let Ok(menu) = world
+        .get_resource::<&OpenContextMenu>()
+        .map(|menu| menu.clone())
+    else {
+        return false;
+    };
+    if event.kind == KeyEventKind::Release {
+        return true;
+    }
+
+    let last = menu.items.len().saturating_sub(1);
+    let selected = match event.code {
+        KeyCode::Esc => {
+            close_context_menu(world);
+            return true;
+        }
+        KeyCode::Enter => {
+            run_item(world, menu.selected);
+            return true;
+        }
+        KeyCode::Up | KeyCode::Char('k') => menu.selected.saturating_sub(1),
+        KeyCode::Down | KeyCode::Char('j') => (menu.selected + 1).min(last),
+        code => {
+            if let Some(idx) = menu
+                .items
+                .iter()
+                .position(|item| item.keybind == Some(code))
+            {
+                run_item(world, idx);
+            }
+            return true;
+        }
+    };
+    if let Ok(mut menu) = world.get_resource::<&mut OpenContextMenu>() {
+        menu.selected = selected;
+    }
+    true
+}
+
+/// draws the open [`ContextMenu`] over `buf`, moved to fit in it. call it after rendering the
+/// tree.
+pub fn render_context_menu(world: &mut World, buf: &mut Buffer) {
+    let Ok(mut menu) = world.get_resource::<&mut OpenContextMenu>() else {
+        return;
+    };
+    let area = menu.area(buf.area);
+    menu.drawn = Some(area);
+    Clear.render(area, buf);
+    let block = Block::bordered();
+    let inner = block.inner(area);
+    block.render(area, buf);
+
+    for (idx, (item, row)) in menu.items.iter().zip(inner.rows()).enumerate() {
+        let style = if idx == menu.selected {
+            Style::new().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::new()
+        };
+        let mut spans = vec![Span::raw(format!(" {}", item.label))];
+        if let Some(keybind) = item.keybind {
+            let key = keybind.to_string();
+            let gap = usize::from(inner.width).saturating_sub(item.label.width() + key.width() + 2);
+            spans.push(Span::raw(" ".repeat(gap)));
+            spans.push(Span::styled(key, Style::new().add_modifier(Modifier::DIM)));
+        }
+        buf.set_style(row, style);
+        Line::from(spans).render(row, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::u16vec2;
+    use mana_tui_elemental::prelude::*;
+    use mana_tui_utils::resource::Resources;
+    use ratatui::buffer::Buffer;
+    use ratatui::crossterm::event::{
+        Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    };
+    use ratatui::layout::Rect;
+
+    use crate::focus::FocusPolicy;
+    use crate::menu::{ContextMenu, ContextMenuItem, OpenContextMenu, render_context_menu};
+
+    struct Ran(Vec<&'static str>);
+
+    fn item(label: &'static str) -> ContextMenuItem {
+        ContextMenuItem::new(label, move |world| {
+            world.get_resource::<&mut Ran>().unwrap().0.push(label);
+        })
+    }
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> Event {
+        Event::Mouse(MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::empty(),
+        })
+    }
+
+    fn key(code: KeyCode) -> Event {
+        Event::Key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_context_menu() {
+        let area = Rect::new(0, 0, 20, 8);
+        let mut ctx = ElementCtx::new();
+        crate::init(&mut ctx);
+        ctx.insert_resource(Ran(Vec::new()));
+        let root = ctx.spawn_ui(ui(Block::new()).with((
+            Width::fixed(20),
+            Height::fixed(8),
+            FocusPolicy::Block,
+            ContextMenu::new([
+                item("open"),
+                item("rename"),
+                item("delete").keybind(KeyCode::Char('d')),
+            ]),
+        )));
+        ctx.calculate_layout(root, area).unwrap();
+        crate::setup_interactions(&mut ctx, root);
+
+        assert!(crate::handle_event(
+            &mut ctx,
+            mouse(MouseEventKind::Down(MouseButton::Right), 3, 1)
+        ));
+        assert_eq!(
+            ctx.get_resource::<&OpenContextMenu>().unwrap().position,
+            u16vec2(3, 2)
+        );
+
+        let mut buf = Buffer::empty(area);
+        render_context_menu(&mut ctx, &mut buf);
+        assert_eq!(buf[(3, 2)].symbol(), "┌");
+        let row = |buf: &Buffer, y: u16| {
+            (3..16)
+                .map(|x| buf[(x, y)].symbol().to_string())
+                .collect::<String>()
+        };
+        assert_eq!(row(&buf, 3), "│ open      │");
+        assert_eq!(row(&buf, 5), "│ delete  d │");
+
+        // keys go to the menu while it's open.
+        for code in [KeyCode::Down, KeyCode::Down, KeyCode::Up, KeyCode::Enter] {
+            assert!(crate::handle_event(&mut ctx, key(code)));
+        }
+        assert!(ctx.get_resource::<&OpenContextMenu>().is_err());
+
+        crate::handle_event(
+            &mut ctx,
+            mouse(MouseEventKind::Down(MouseButton::Right), 3, 1),
+        );
+        crate::handle_event(&mut ctx, key(KeyCode::Char('d')));
+        crate::handle_event(
+            &mut ctx,
+            mouse(MouseEventKind::Down(MouseButton::Right), 3, 1),
+        );
+        crate::handle_event(&mut ctx, key(KeyCode::Esc));
+        assert!(ctx.get_resource::<&OpenContextMenu>().is_err());
+
+        assert_eq!(ctx.get_resource::<&Ran>().unwrap().0, ["rename", "delete"]);
+    }
+}
@@ -0,0 +1,467 @@
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::Arc;
+
+use glam::{U16Vec2, u16vec2};
+use hecs::{Entity, World};
+use mana_tui_elemental::layout::Props;
+use mana_tui_utils::resource::Resources;
+use ratatui::buffer::Buffer;
+use ratatui::crossterm::event::{
+    KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Position, Rect};
+use ratatui::style::{Style, Styled};
+use ratatui::text::Text;
+use ratatui::widgets::Widget;
+use unicode_width::UnicodeWidthStr;
+
+use crate::focus::{Keybind, stacks_at};
+
+/// read only text that can be selected by dragging over it with the mouse, like a log pane.
+/// shift + drag extends the current selection.
+///
+/// lines longer than the element are wrapped at the last cell that fits. the source text is
+/// kept so the selected cells can be mapped back to it, see [`selected_text`].
+#[derive(Debug, Clone)]
+pub struct SelectableText {
+    text: Text<'static>,
+    key: u64,
+    highlight_style: Style,
+    selection: Option<(U16Vec2, U16Vec2)>,
+}
+
+impl SelectableText {
+    pub fn new(text: impl Into<Text<'static>>) -> Self {
+        let text = text.into();
+        let mut hasher = DefaultHasher::new();
+        text.hash(&mut hasher);
+        Self {
+            text,
+            key: hasher.finish(),
+            highlight_style: Style::new().reversed(),
+            selection: None,
+        }
+    }
+
+    /// style patched onto the selected cells. defaults to reversed.
+    #[must_use]
+    pub fn highlight_style(self, highlight_style: Style) -> Self {
+        Self {
+            highlight_style,
+            ..self
+        }
+    }
+
+    /// the graphemes of every row when the text is wrapped at `width` cells, with the column
+    /// they start at and whether the row ends a line of the source text.
+    fn rows(&self, width: u16) -> Vec<Row<'_>> {
+        let mut rows = Vec::new();
+        for line in &self.text.lines {
+            let mut row = Row::default();
+            for grapheme in line.styled_graphemes(self.text.style) {
+                let grapheme_width = grapheme.symbol.width() as u16;
+                if row.width + grapheme_width > width && !row.graphemes.is_empty() {
+                    rows.push(std::mem::take(&mut row));
+                }
+                row.graphemes
+                    .push((row.width, grapheme.symbol, grapheme.style));
+                row.width += grapheme_width;
+            }
+            row.ends_line = true;
+            rows.push(row);
+        }
+        rows
+    }
+}
+
+#[derive(Debug, Default)]
+struct Row<'a> {
+    graphemes: Vec<(u16, &'a str, Style)>,
+    width: u16,
+    ends_line: bool,
+}
+
+impl Widget for SelectableText {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        buf.set_style(area, self.text.style);
+        let rows = self.rows(area.width);
+        for (y, row) in (area.y..area.bottom()).zip(&rows) {
+            for &(column, symbol, style) in &row.graphemes {
+                buf.set_stringn(area.x + column, y, symbol, usize::MAX, style);
+            }
+        }
+        let Some((start, end)) = self.selection.map(ordered) else {
+            return;
+        };
+        for row in start.y..=end.y.min(area.height.saturating_sub(1)) {
+            let from = if row == start.y { start.x } else { 0 };
+            let to = if row == end.y {
+                end.x
+            } else {
+                area.width.saturating_sub(1)
+            };
+            let width = (to + 1).saturating_sub(from);
+            let cells = Rect::new(area.x + from, area.y + row, width, 1).intersection(area);
+            buf.set_style(cells, self.highlight_style);
+        }
+    }
+}
+
+impl Styled for SelectableText {
+    type Item = Self;
+
+    fn style(&self) -> Style {
+        self.text.style
+    }
+
+    fn set_style<S: Into<Style>>(mut self, style: S) -> Self::Item {
+        self.text.style = style.into();
+        self
+    }
+}
+
+/// the current selection. stored as a resource. it is dropped once the [`SelectableText`] it
+/// was made in is no longer in the tree or its text changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextSelection {
+    /// hash of the selected text.
+    key: u64,
+    /// cell the selection started at, relative to the element.
+    pub anchor: U16Vec2,
+    /// cell the selection ends at, relative to the element. can be before the anchor.
+    pub head: U16Vec2,
+    dragging: bool,
+    /// the pointer was dragged or the selection extended. a press alone selects nothing, but a
+    /// drag that ends on the cell it started at selects that cell.
+    selected: bool,
+}
+
+/// called with the selected text when the [`CopyKey`] is pressed while the [`SelectableText`]
+/// on the same element has a selection.
+#[derive(derive_more::Deref, derive_more::DerefMut, Clone)]
+pub struct OnCopy(Arc<dyn Fn(&mut World, String) + Send + Sync + 'static>);
+
+impl OnCopy {
+    pub fn new(func: impl Fn(&mut World, String) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(func) as Arc<_>)
+    }
+}
+
+/// resource with the key that copies the selection. defaults to `ctrl+c`.
+#[derive(derive_more::Deref)]
+pub struct CopyKey(pub Keybind);
+
+impl Default for CopyKey {
+    fn default() -> Self {
+        Self(Keybind::char('c').modifiers(KeyModifiers::CONTROL))
+    }
+}
+
+/// the text covered by the current selection. cells on wrapped rows are joined without a
+/// line break.
+#[must_use]
+pub fn selected_text(world: &World) -> Option<String> {
+    let selection = *world.get_resource::<&TextSelection>().ok()?;
+    if !selection.selected {
+        return None;
+    }
+    let (start, end) = ordered((selection.anchor, selection.head));
+    let mut query = world.query::<(&SelectableText, &Props)>();
+    let (text, props) = query.iter().find(|(text, _)| text.key == selection.key)?;
+
+    let mut out = String::new();
+    let rows = text.rows(props.size.x);
+    for (y, row) in rows.iter().enumerate().take(end.y as usize + 1) {
+        let y = y as u16;
+        if y < start.y {
+            continue;
+        }
+        let from = if y == start.y { start.x } else { 0 };
+        let to = if y == end.y { end.x } else { u16::MAX };
+        out.extend(
+            row.graphemes
+                .iter()
+                .filter(|(column, ..)| (from..=to).contains(column))
+                .map(|(_, symbol, _)| *symbol),
+        );
+        if row.ends_line && y != end.y {
+            out.push('\n');
+        }
+    }
+    Some(out)
+}
+
+/// sorts the ends of a selection so the first one comes first in reading order.
+fn ordered((anchor, head): (U16Vec2, U16Vec2)) -> (U16Vec2, U16Vec2) {
+    if (anchor.y, anchor.x) <= (head.y, head.x) {
+        (anchor, head)
+    } else {
+        (head, anchor)
+    }
+}
+
+/// copies the selection into the [`SelectableText`] it was made in, so it is highlighted on
+/// the next render, and drops it if that text is gone.
+pub(crate) fn sync_selection(world: &mut World) {
+    let selection = world.get_resource::<&TextSelection>().ok().map(|s| *s);
+    let mut found = false;
+    for text in world.query_mut::<&mut SelectableText>() {
+        let selection = selection.filter(|selection| selection.key == text.key);
+        found |= selection.is_some();
+        text.selection = selection
+            .filter(|selection| selection.selected)
+            .map(|selection| (selection.anchor, selection.head));
+    }
+    if selection.is_some() && !found {
+        _ = world.remove_resource::<TextSelection>();
+    }
+}
+
+pub(crate) fn handle_select_event(world: &mut World, event: MouseEvent) -> bool {
+    let position = u16vec2(event.column, event.row);
+    match event.kind {
+        MouseEventKind::Down(MouseButton::Left) => start_selection(
+            world,
+            position,
+            event.modifiers.contains(KeyModifiers::SHIFT),
+        ),
+        MouseEventKind::Drag(MouseButton::Left) => move_selection(world, position),
+        MouseEventKind::Up(MouseButton::Left) => {
+            let Ok(mut selection) = world.get_resource::<&mut TextSelection>() else {
+                return false;
+            };
+            std::mem::replace(&mut selection.dragging, false)
+        }
+        _ => false,
+    }
+}
+
+fn start_selection(world: &mut World, position: U16Vec2, extend: bool) -> bool {
+    let cursor = Position {
+        x: position.x,
+        y: position.y,
+    };
+    let uistack: Vec<Entity> = stacks_at(world, cursor)
+        .iter()
+        .flat_map(|uistack| uistack.stack.iter().copied())
+        .collect();
+
+    let target = uistack.into_iter().rev().find_map(|entity| {
+        let mut query = world.query_one::<(&Props, &SelectableText)>(entity);
+        let (props, text) = query.get().ok()?;
+        let area = Rect {
+            x: props.position.x,
+            y: props.position.y,
+            width: props.size.x,
+            height: props.size.y,
+        };
+        area.contains(cursor)
+            .then_some((text.key, position.saturating_sub(props.position)))
+    });
+    let Some((key, cell)) = target else {
+        _ = world.remove_resource::<TextSelection>();
+        return false;
+    };
+
+    let mut selection =
+        world.get_or_insert_resource_with::<&mut TextSelection>(|_| TextSelection {
+            key,
+            anchor: cell,
+            head: cell,
+            dragging: false,
+            selected: false,
+        });
+    if extend && selection.key == key {
+        selection.selected = true;
+    } else {
+        *selection = TextSelection {
+            key,
+            anchor: cell,
+            head: cell,
+            dragging: false,
+            selected: false,
+        };
+    }
+    selection.head = cell;
+    selection.dragging = true;
+    true
+}
+
+fn move_selection(world: &mut World, position: U16Vec2) -> bool {
+    let Some(key) = world
+        .get_resource::<&TextSelection>()
+        .ok()
+        .filter(|selection| selection.dragging)
+        .map(|selection| selection.key)
+    else {
+        return false;
+    };
+    let area = world
+        .query_mut::<(&SelectableText, &Props)>()
+        .into_iter()
+        .find(|(text, _)| text.key == key)
+        .map(|(_, props)| (props.position, props.size));
+    let Some((origin, size)) = area else {
+        return false;
+    };
+    let cell = position
+        .saturating_sub(origin)
+        .min(size.saturating_sub(U16Vec2::ONE));
+    if let Ok(mut selection) = world.get_resource::<&mut TextSelection>() {
+        selection.head = cell;
+        selection.selected = true;
+    }
+    true
+}
+
+/// runs the [`OnCopy`] handler of the selected text if `event` is the [`CopyKey`].
+pub(crate) fn handle_copy_key(world: &mut World, event: KeyEvent) -> bool {
+    let is_copy = world
+        .get_or_insert_resource_with::<&CopyKey>(|_| CopyKey::default())
+        .event_eq(event, KeyEventKind::Press);
+    if !is_copy {
+        return false;
+    }
+    let Some(text) = selected_text(world) else {
+        return false;
+    };
+    let Some(key) = world
+        .get_resource::<&TextSelection>()
+        .ok()
+        .map(|selection| selection.key)
+    else {
+        return false;
+    };
+    let handler = world
+        .query_mut::<(&SelectableText, &OnCopy)>()
+        .into_iter()
+        .find(|(selectable, _)| selectable.key == key)
+        .map(|(_, handler)| handler.0.clone());
+    let Some(handler) = handler else {
+        return false;
+    };
+    handler(world, text);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use mana_tui_elemental::prelude::*;
+    use ratatui::buffer::Buffer;
+    use ratatui::crossterm::event::{
+        Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    };
+    use ratatui::layout::Rect;
+    use ratatui::style::Style;
+
+    use crate::select::{OnCopy, SelectableText, selected_text};
+
+    fn mouse(kind: MouseEventKind, column: u16, row: u16) -> Event {
+        Event::Mouse(MouseEvent {
+            kind,
+            column,
+            row,
+            modifiers: KeyModifiers::empty(),
+        })
+    }
+
+    #[test]
+    fn test_select_wrapped_lines() {
+        let copied = Arc::new(Mutex::new(None));
+        let on_copy = {
+            let copied = copied.clone();
+            OnCopy::new(move |_, text| *copied.lock().unwrap() = Some(text))
+        };
+
+        let mut ctx = ElementCtx::new();
+        crate::init(&mut ctx);
+        let spawn = |ctx: &mut ElementCtx| {
+            let root = ctx.spawn_ui(ui(SelectableText::new("hello world\nbye")).with((
+                Width::fixed(6),
+                Height::fixed(3),
+                on_copy.clone(),
+            )));
+            ctx.calculate_layout(root, Rect::new(0, 0, 6, 3)).unwrap();
+            crate::setup_interactions(ctx, root);
+            root
+        };
+        let root = spawn(&mut ctx);
+
+        // "hello " and "world" are one wrapped line, "bye" is the next one.
+        crate::handle_event(
+            &mut ctx,
+            mouse(MouseEventKind::Down(MouseButton::Left), 3, 0),
+        );
+        crate::handle_event(
+            &mut ctx,
+            mouse(MouseEventKind::Drag(MouseButton::Left), 1, 2),
+        );
+        crate::handle_event(&mut ctx, mouse(MouseEventKind::Up(MouseButton::Left), 1, 2));
+        assert_eq!(selected_text(&ctx).as_deref(), Some("lo world\nby"));
+
+        // the selection survives respawning the same text.
+        ctx.despawn_ui(root);
+        let root = spawn(&mut ctx);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 6, 3));
+        ctx.render(root, buf.area, &mut buf);
+        let mut expected = Buffer::with_lines(["hello ", "world ", "bye   "]);
+        let highlight = Style::new().reversed();
+        expected.set_style(Rect::new(3, 0, 3, 1), highlight);
+        expected.set_style(Rect::new(0, 1, 6, 1), highlight);
+        expected.set_style(Rect::new(0, 2, 2, 1), highlight);
+        assert_eq!(buf, expected);
+
+        let copy = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert!(crate::handle_event(&mut ctx, Event::Key(copy)));
+        assert_eq!(copied.lock().unwrap().as_deref(), Some("lo world\nby"));
+
+        // changing the text clears the selection.
+        ctx.despawn_ui(root);
+        let root = ctx
+            .spawn_ui(ui(SelectableText::new("other")).with((Width::fixed(6), Height::fixed(3))));
+        ctx.calculate_layout(root, Rect::new(0, 0, 6, 3)).unwrap();
+        crate::setup_interactions(&mut ctx, root);
+        assert_eq!(selected_text(&ctx), None);
+    }
+
+    #[test]
+    fn test_select_single_cell() {
+        let mut ctx = ElementCtx::new();
+        crate::init(&mut ctx);
+        let root = ctx
+            .spawn_ui(ui(SelectableText::new("hello")).with((Width::fixed(5), Height::fixed(1))));
+        ctx.calculate_layout(root, Rect::new(0, 0, 5, 1)).unwrap();
+        crate::setup_interactions(&mut ctx, root);
+
+        // a click selects nothing.
+        crate::handle_event(
+            &mut ctx,
+            mouse(MouseEventKind::Down(MouseButton::Left), 1, 0),
+        );
+        crate::handle_event(&mut ctx, mouse(MouseEventKind::Up(MouseButton::Left), 1, 0));
+        assert_eq!(selected_text(&ctx), None);
+
+        // dragging back to where the drag started selects that cell.
+        crate::handle_event(
+            &mut ctx,
+            mouse(MouseEventKind::Down(MouseButton::Left), 1, 0),
+        );
+        for column in [3, 1] {
+            crate::handle_event(
+                &mut ctx,
+                mouse(MouseEventKind::Drag(MouseButton::Left), column, 0),
+            );
+        }
+        crate::handle_event(&mut ctx, mouse(MouseEventKind::Up(MouseButton::Left), 1, 0));
+        assert_eq!(selected_text(&ctx).as_deref(), Some("e"));
+
+        crate::setup_interactions(&mut ctx, root);
+        let mut buf = Buffer::empty(Rect::new(0, 0, 5, 1));
+        ctx.render(root, buf.area, &mut buf);
+        let mut expected = Buffer::with_lines(["hello"]);
+        expected.set_style(Rect::new(1, 0, 1, 1), Style::new().reversed());
+        assert_eq!(buf, expected);
+    }
+}
@@ -0,0 +1,350 @@
+//! sliders: [`Text`] elements with a [`Slider`] pick a number between `min` and `max` with the
+//! arrow keys or the mouse.
+
+use std::time::Duration;
+
+use hecs::{Entity, World};
+use mana_tui_elemental::layout::Props;
+use mana_tui_utils::resource::Resources;
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyEventKind, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+
+use crate::EventQueue;
+use crate::drag::PointerCapture;
+use crate::edit::OnChange;
+use crate::focus::Focused;
+
+/// a number between `min` and `max` in increments of `step`, drawn as a track with a thumb
+/// over the width of the element. the value is kept in a [`SliderState`] next to it.
+///
+/// the left and right arrow keys (or `h` and `l`) move a focused slider by `step`, clicking
+/// jumps to the clicked column and dragging scrubs. every change calls the element's
+/// [`OnChange<f64>`](OnChange).
+///
+/// the element is a [`Text`] with a single line, so it is one cell high. give it a width.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// # use mana_tui_beheaded::slider::{Slider, SliderState};
+/// # use ratatui::text::Line;
+/// ui(Text::from(Line::default())).with((
+///     Slider::new(0.0, 100.0, 5.0),
+///     SliderState { value: 50.0 },
+///     Width::fixed(20),
+/// ));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Slider {
+    /// the smallest value.
+    pub min: f64,
+    /// the largest value.
+    pub max: f64,
+    /// how much a key press moves the value. values picked with the mouse are rounded to it.
+    /// zero doesn't round.
+    pub step: f64,
+}
+
+/// the current value of a [`Slider`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SliderState {
+    /// the value, between the `min` and `max` of the slider.
+    pub value: f64,
+}
+
+/// delays the [`OnChange`] of a [`Slider`] until its value stops changing for the duration, so
+/// scrubbing doesn't call an expensive handler for every column. the handler gets the last
+/// value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Debounce(pub Duration);
+
+/// counts the changes of a debounced slider, so only the last one calls the handler.
+struct PendingChange(u64);
+
+impl Slider {
+    pub fn new(min: f64, max: f64, step: f64) -> Self {
+        Self { min, max, step }
+    }
+
+    /// `value` rounded to the nearest step from `min` and kept between `min` and `max`.
+    #[must_use]
+    pub fn snap(&self, value: f64) -> f64 {
+        let value = if self.step > 0.0 {
+            self.min + ((value - self.min) / self.step).round() * self.step
+        } else {
+            value
+        };
+        value.clamp(self.min, self.max.max(self.min))
+    }
+
+    /// the value under `column` cells from the left of a track `width` cells wide.
+    #[must_use]
+    pub fn value_at(&self, column: u16, width: u16) -> f64 {
+        let fraction = f64::from(column) / f64::from(width.max(1));
+        self.snap(self.min + fraction * (self.max - self.min))
+    }
+
+    /// the column of the thumb for `value` on a track `width` cells wide.
+    #[must_use]
+    pub fn thumb_column(&self, value: f64, width: u16) -> u16 {
+        let range = self.max - self.min;
+        if range <= 0.0 {
+            return 0;
+        }
+        let fraction = ((value - self.min) / range).clamp(0.0, 1.0);
+        ((fraction * f64::from(width)) as u16).min(width.saturating_sub(1))
+    }
+
+    /// the track for `value`, `width` cells wide. the thumb is bold when `focused`.
+    #[must_use]
+    pub fn line(&self, value: f64, width: u16, focused: bool) -> Line<'static> {
+        if width == 0 {
+            return Line::default();
+        }
+        let thumb = self.thumb_column(value, width);
+        let thumb_style = if focused {
+            Style::new().add_modifier(Modifier::BOLD)
+        } else {
+            Style::new()
+        };
+        Line::from(vec![
+            Span::raw("━".repeat(thumb.into())),
+            Span::styled("●", thumb_style),
+            Span::raw("─".repeat((width - thumb - 1).into())),
+        ])
+    }
+}
+
+/// moves the focused [`Slider`] with the arrow keys. returns true if the key was used.
+pub(crate) fn handle_slider_key(world: &mut World, event: KeyEvent) -> bool {
+    if event.kind == KeyEventKind::Release {
+        return false;
+    }
+    let Some((entity, slider, state)) = world
+        .query_mut::<(Entity, &Slider, &SliderState, &Focused)>()
+        .into_iter()
+        .next()
+        .map(|(entity, slider, state, _)| (entity, *slider, *state))
+    else {
+        return false;
+    };
+
+    let value = match event.code {
+        KeyCode::Left | KeyCode::Char('h') => state.value - slider.step,
+        KeyCode::Right | KeyCode::Char('l') => state.value + slider.step,
+        KeyCode::Home => slider.min,
+        KeyCode::End => slider.max,
+        _ => return false,
+    };
+    set_value(
+        world,
+        entity,
+        value.clamp(slider.min, slider.max.max(slider.min)),
+    );
+    true
+}
+
+/// moves the [`Slider`] that captured the pointer to the cursor. the capture is started by the
+/// drag handling when a slider is pressed.
+pub(crate) fn scrub_captured_slider(world: &mut World, event: MouseEvent) {
+    if !matches!(
+        event.kind,
+        MouseEventKind::Down(MouseButton::Left) | MouseEventKind::Drag(MouseButton::Left)
+    ) {
+        return;
+    }
+    let Ok(PointerCapture(entity)) = world
+        .get_resource::<&PointerCapture>()
+        .map(|capture| *capture)
+    else {
+        return;
+    };
+    let Ok(value) = world
+        .query_one_mut::<(&Slider, &Props)>(entity)
+        .map(|(slider, props)| {
+            slider.value_at(event.column.saturating_sub(props.position.x), props.size.x)
+        })
+    else {
+        return;
+    };
+    if matches!(event.kind, MouseEventKind::Down(_)) {
+        _ = world.insert_one(entity, Focused);
+    }
+    set_value(world, entity, value);
+}
+
+/// stores `value` and calls the [`OnChange`] handler if it changed.
+fn set_value(world: &mut World, entity: Entity, value: f64) {
+    let Ok(mut state) = world.get::<&mut SliderState>(entity) else {
+        return;
+    };
+    if (state.value - value).abs() < f64::EPSILON {
+        return;
+    }
+    state.value = value;
+    drop(state);
+    sync_sliders(world);
+
+    if let Ok(Debounce(delay)) = world.get::<&Debounce>(entity).map(|debounce| *debounce) {
+        let generation = world
+            .get::<&PendingChange>(entity)
+            .map_or(0, |pending| pending.0 + 1);
+        _ = world.insert_one(entity, PendingChange(generation));
+        let queue = world.get_resource::<&EventQueue>().unwrap().0.clone();
+        tokio::task::spawn(async move {
+            tokio::time::sleep(delay).await;
+            _ = queue
+                .0
+                .send_async(crate::UiEvent::ChangeSettled { entity, generation })
+                .await;
+        });
+        return;
+    }
+    call_on_change(world, entity, value);
+}
+
+/// calls the handler of a debounced slider if it didn't change again since `generation`.
+pub(crate) fn settle_change(world: &mut World, entity: Entity, generation: u64) -> bool {
+    if world
+        .get::<&PendingChange>(entity)
+        .is_ok_and(|pending| pending.0 == generation)
+    {
+        _ = world.remove_one::<PendingChange>(entity);
+        if let Ok(state) = world.get::<&SliderState>(entity).map(|state| *state) {
+            call_on_change(world, entity, state.value);
+            return true;
+        }
+    }
+    false
+}
+
+fn call_on_change(world: &mut World, entity: Entity, value: f64) {
+    let handler = world
+        .get::<&OnChange<f64>>(entity)
+        .map(|handler| handler.0.clone());
+    if let Ok(handler) = handler {
+        handler(world, value);
+    }
+}
+
+/// redraws the track of every [`Slider`] text from its value and width.
+pub(crate) fn sync_sliders(world: &mut World) {
+    for (slider, state, props, text, focused) in world.query_mut::<(
+        &Slider,
+        &SliderState,
+        &Props,
+        &mut Text<'static>,
+        Option<&Focused>,
+    )>() {
+        text.lines = vec![slider.line(state.value, props.size.x, focused.is_some())];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mana_tui_elemental::prelude::*;
+    use mana_tui_utils::resource::Resources;
+    use ratatui::buffer::Buffer;
+    use ratatui::crossterm::event::{
+        Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    };
+    use ratatui::layout::Rect;
+    use ratatui::text::Line;
+
+    use crate::edit::OnChange;
+    use crate::focus::Focused;
+    use crate::slider::{Slider, SliderState};
+
+    struct Changes(Vec<f64>);
+
+    fn spawn_slider(ctx: &mut ElementCtx, slider: Slider, value: f64) -> Element {
+        crate::init(ctx);
+        ctx.insert_resource(Changes(Vec::new()));
+        let root = ctx.spawn_ui(ui(Text::from(Line::default())).with((
+            slider,
+            SliderState { value },
+            OnChange::new(|world, value| {
+                world.get_resource::<&mut Changes>().unwrap().0.push(value);
+            }),
+            Width::fixed(20),
+        )));
+        ctx.calculate_layout(root, Rect::new(0, 0, 20, 1)).unwrap();
+        crate::setup_interactions(ctx, root);
+        root
+    }
+
+    fn mouse(kind: MouseEventKind, column: u16) -> Event {
+        Event::Mouse(MouseEvent {
+            kind,
+            column,
+            row: 0,
+            modifiers: KeyModifiers::empty(),
+        })
+    }
+
+    #[test]
+    fn test_slider_keys() {
+        let mut ctx = ElementCtx::new();
+        let root = spawn_slider(&mut ctx, Slider::new(0.0, 1.0, 0.25), 0.5);
+        ctx.insert_one(root, Focused).unwrap();
+
+        for code in [
+            KeyCode::Right,
+            KeyCode::Char('l'),
+            KeyCode::Right,
+            KeyCode::Left,
+            KeyCode::Char('h'),
+            KeyCode::Left,
+            KeyCode::Left,
+            KeyCode::Left,
+        ] {
+            let event = Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+            assert!(crate::handle_event(&mut ctx, event));
+        }
+
+        // the value stops at `min` and `max`, and only changes call the handler.
+        assert_eq!(
+            ctx.get_resource::<&Changes>().unwrap().0,
+            [0.75, 1.0, 0.75, 0.5, 0.25, 0.0]
+        );
+        assert_eq!(
+            *ctx.get::<&SliderState>(root).unwrap(),
+            SliderState { value: 0.0 }
+        );
+    }
+
+    #[test]
+    fn test_slider_mouse() {
+        let mut ctx = ElementCtx::new();
+        let root = spawn_slider(&mut ctx, Slider::new(0.0, 100.0, 1.0), 0.0);
+
+        crate::handle_event(&mut ctx, mouse(MouseEventKind::Down(MouseButton::Left), 15));
+        assert_eq!(ctx.get::<&SliderState>(root).unwrap().value, 75.0);
+        assert!(ctx.get::<&Focused>(root).is_ok());
+
+        // scrubbing past the end of the track stops at `max`.
+        crate::handle_event(&mut ctx, mouse(MouseEventKind::Drag(MouseButton::Left), 5));
+        crate::handle_event(&mut ctx, mouse(MouseEventKind::Drag(MouseButton::Left), 30));
+        crate::handle_event(&mut ctx, mouse(MouseEventKind::Up(MouseButton::Left), 30));
+        assert_eq!(
+            ctx.get_resource::<&Changes>().unwrap().0,
+            [75.0, 25.0, 100.0]
+        );
+    }
+
+    #[test]
+    fn test_slider_render() {
+        let mut ctx = ElementCtx::new();
+        let root = spawn_slider(&mut ctx, Slider::new(0.0, 100.0, 1.0), 75.0);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 20, 1));
+        ctx.render(root, buf.area, &mut buf);
+        assert_eq!(buf[(15, 0)].symbol(), "●");
+        assert_eq!(buf[(14, 0)].symbol(), "━");
+        assert_eq!(buf[(16, 0)].symbol(), "─");
+    }
+}
@@ -0,0 +1,252 @@
+//! resizable split panes: the divider of a [`SplitPane`] follows the mouse while it is dragged,
+//! and ctrl + arrow keys move it by one cell while an element in one of the panes is focused.
+
+use std::sync::Arc;
+
+use glam::U16Vec2;
+use hecs::{Entity, World};
+use mana_tui_elemental::layout::{Children, Props, SplitPane};
+use mana_tui_utils::resource::Resources;
+use ratatui::crossterm::event::{
+    KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+};
+use ratatui::layout::{Direction, Position, Rect};
+use ratatui::widgets::Padding;
+
+use crate::drag::{Dragging, PointerCapture};
+use crate::focus::Focused;
+
+/// called with the new ratio when the divider of a [`SplitPane`] is moved. the ratio is stored
+/// on the element too, keep it in the app state if the ui is spawned again every frame.
+#[derive(derive_more::Deref, derive_more::DerefMut, Clone)]
+pub struct OnSplitChange(Arc<dyn Fn(&mut World, f32) + Send + Sync + 'static>);
+
+impl OnSplitChange {
+    pub fn new(func: impl Fn(&mut World, f32) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(func) as Arc<_>)
+    }
+}
+
+/// the split, the area inside its padding and its direction.
+fn split_layout(world: &World, entity: Entity) -> Option<(SplitPane, Rect, Direction)> {
+    let mut query = world.query_one::<(&SplitPane, &Props, &Padding, &Direction)>(entity);
+    let (split, props, padding, direction) = query.get().ok()?;
+    Some((*split, props.inner_area(padding), *direction))
+}
+
+/// captures the pointer for the split pane whose divider is under `position`.
+pub(crate) fn capture_divider(world: &mut World, position: U16Vec2) -> bool {
+    let cursor = Position::new(position.x, position.y);
+    let splits = world
+        .query::<(Entity, &SplitPane)>()
+        .iter()
+        .map(|(entity, _)| entity)
+        .collect::<Vec<_>>();
+    let Some(entity) = splits.into_iter().find(|entity| {
+        split_layout(world, *entity).is_some_and(|(split, inner, direction)| {
+            split.divider(inner, direction).contains(cursor)
+        })
+    }) else {
+        return false;
+    };
+
+    let origin = world
+        .get::<&Props>(entity)
+        .map(|props| props.position)
+        .unwrap_or_default();
+    _ = world.insert_one(
+        entity,
+        Dragging {
+            offset: position.saturating_sub(origin),
+            origin,
+            last: position,
+        },
+    );
+    world.insert_or_update_resource(PointerCapture(entity));
+    true
+}
+
+/// moves the divider of the [`SplitPane`] that captured the pointer to the cursor.
+pub(crate) fn drag_captured_divider(world: &mut World, event: MouseEvent) {
+    if !matches!(event.kind, MouseEventKind::Drag(MouseButton::Left)) {
+        return;
+    }
+    let Ok(PointerCapture(entity)) = world
+        .get_resource::<&PointerCapture>()
+        .map(|capture| *capture)
+    else {
+        return;
+    };
+    let Some((split, inner, direction)) = split_layout(world, entity) else {
+        return;
+    };
+    let (first, available) = match direction {
+        Direction::Horizontal => (event.column.saturating_sub(inner.x), inner.width),
+        Direction::Vertical => (event.row.saturating_sub(inner.y), inner.height),
+    };
+    let available = available.saturating_sub(1);
+    set_ratio(world, entity, split.ratio_at(first, available));
+}
+
+/// moves the divider of the innermost [`SplitPane`] around the focused element with ctrl +
+/// arrow keys. left and right move horizontal splits, up and down vertical ones.
+pub(crate) fn handle_split_key(world: &mut World, event: KeyEvent) -> bool {
+    if event.kind == KeyEventKind::Release || !event.modifiers.contains(KeyModifiers::CONTROL) {
+        return false;
+    }
+    let (axis, forward) = match event.code {
+        KeyCode::Left => (Direction::Horizontal, false),
+        KeyCode::Right => (Direction::Horizontal, true),
+        KeyCode::Up => (Direction::Vertical, false),
+        KeyCode::Down => (Direction::Vertical, true),
+        _ => return false,
+    };
+    let Some(focused) = world
+        .query_mut::<(Entity, &Focused)>()
+        .into_iter()
+        .next()
+        .map(|(entity, _)| entity)
+    else {
+        return false;
+    };
+
+    let mut ancestor = parent(world, focused);
+    while let Some(entity) = ancestor {
+        if let Some((split, inner, direction)) = split_layout(world, entity)
+            && direction == axis
+        {
+            let available = match direction {
+                Direction::Horizontal => inner.width,
+                Direction::Vertical => inner.height,
+            }
+            .saturating_sub(1);
+            let (first, _) = split.sizes(available);
+            let first = if forward {
+                first.saturating_add(1)
+            } else {
+                first.saturating_sub(1)
+            };
+            set_ratio(world, entity, split.ratio_at(first, available));
+            return true;
+        }
+        ancestor = parent(world, entity);
+    }
+    false
+}
+
+fn parent(world: &World, entity: Entity) -> Option<Entity> {
+    world
+        .query::<(Entity, &Children)>()
+        .iter()
+        .find(|(_, children)| children.contains(&entity))
+        .map(|(parent, _)| parent)
+}
+
+/// stores `ratio` and calls the [`OnSplitChange`] handler if it changed.
+fn set_ratio(world: &mut World, entity: Entity, ratio: f32) {
+    let Ok(mut split) = world.get::<&mut SplitPane>(entity) else {
+        return;
+    };
+    if (split.ratio - ratio).abs() < f32::EPSILON {
+        return;
+    }
+    split.ratio = ratio;
+    drop(split);
+
+    let handler = world
+        .get::<&OnSplitChange>(entity)
+        .map(|handler| handler.0.clone());
+    if let Ok(handler) = handler {
+        handler(world, ratio);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mana_tui_elemental::layout::Props;
+    use mana_tui_elemental::prelude::*;
+    use mana_tui_utils::resource::Resources;
+    use ratatui::buffer::Buffer;
+    use ratatui::crossterm::event::{
+        Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    };
+    use ratatui::layout::Rect;
+
+    use crate::focus::Focused;
+    use crate::split::OnSplitChange;
+
+    struct Ratios(Vec<f32>);
+
+    fn mouse(kind: MouseEventKind, column: u16) -> Event {
+        Event::Mouse(MouseEvent {
+            kind,
+            column,
+            row: 2,
+            modifiers: KeyModifiers::empty(),
+        })
+    }
+
+    fn widths(ctx: &ElementCtx, split: Element) -> Vec<u16> {
+        ctx.get::<&Children>(split)
+            .unwrap()
+            .iter()
+            .map(|child| ctx.get::<&Props>(child).unwrap().size.x)
+            .collect()
+    }
+
+    #[test]
+    fn test_split_pane() {
+        let area = Rect::new(0, 0, 80, 5);
+        let mut ctx = ElementCtx::new();
+        crate::init(&mut ctx);
+        ctx.insert_resource(Ratios(Vec::new()));
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Width::fixed(80), Height::fixed(5)))
+                .child(
+                    ui(Block::new())
+                        .with((
+                            Direction::Horizontal,
+                            SplitPane::new(0.25).min_second(40),
+                            OnSplitChange::new(|world, ratio| {
+                                world.get_resource::<&mut Ratios>().unwrap().0.push(ratio);
+                            }),
+                        ))
+                        .children((ui(Text::raw("left")), ui(Text::raw("right")))),
+                ),
+        );
+        let split = ctx.get::<&Children>(root).unwrap()[0];
+        let layout = |ctx: &mut ElementCtx| {
+            ctx.calculate_layout(root, area).unwrap();
+            crate::setup_interactions(ctx, root);
+        };
+        layout(&mut ctx);
+        assert_eq!(widths(&ctx, split), [20, 59]);
+        let mut buf = Buffer::empty(area);
+        ctx.render(root, area, &mut buf);
+        assert_eq!(buf[(20, 0)].symbol(), "│");
+        assert_eq!(buf[(20, 4)].symbol(), "│");
+
+        // the divider follows the cursor and stops at the minimum of the second pane.
+        crate::handle_event(&mut ctx, mouse(MouseEventKind::Down(MouseButton::Left), 20));
+        crate::handle_event(&mut ctx, mouse(MouseEventKind::Drag(MouseButton::Left), 30));
+        layout(&mut ctx);
+        assert_eq!(widths(&ctx, split), [30, 49]);
+        crate::handle_event(&mut ctx, mouse(MouseEventKind::Drag(MouseButton::Left), 60));
+        crate::handle_event(&mut ctx, mouse(MouseEventKind::Up(MouseButton::Left), 60));
+        layout(&mut ctx);
+        assert_eq!(widths(&ctx, split), [39, 40]);
+
+        let pane = ctx.get::<&Children>(split).unwrap()[0];
+        ctx.insert_one(pane, Focused).unwrap();
+        let event = Event::Key(KeyEvent::new(KeyCode::Left, KeyModifiers::CONTROL));
+        assert!(crate::handle_event(&mut ctx, event));
+        layout(&mut ctx);
+        assert_eq!(widths(&ctx, split), [38, 41]);
+
+        assert_eq!(
+            ctx.get_resource::<&Ratios>().unwrap().0,
+            [30.0 / 79.0, 39.0 / 79.0, 38.0 / 79.0]
+        );
+    }
+}
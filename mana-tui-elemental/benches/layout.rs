@@ -0,0 +1,52 @@
+use std::hint::black_box;
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use mana_tui_elemental::{prelude::*, testing};
+use ratatui::{buffer::Buffer, layout::Rect};
+
+const AREA: Rect = Rect {
+    x: 0,
+    y: 0,
+    width: 320,
+    height: 120,
+};
+
+fn scenarios() -> [(&'static str, fn() -> View); 3] {
+    [
+        ("deep_chain_1k", || testing::deep_chain(1_000)),
+        ("wide_fanout_10k", || testing::wide_fanout(10_000)),
+        ("dashboard_5k", || testing::dashboard(50, 50)),
+    ]
+}
+
+fn bench_layout(c: &mut Criterion) {
+    let mut group = c.benchmark_group("calculate_layout");
+    for (name, view) in scenarios() {
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(view());
+        group.bench_function(name, |b| {
+            b.iter(|| ctx.calculate_layout(black_box(root), AREA).unwrap());
+        });
+    }
+    group.finish();
+}
+
+fn bench_render(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render");
+    for (name, view) in scenarios() {
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(view());
+        ctx.calculate_layout(root, AREA).unwrap();
+        let mut buf = Buffer::empty(AREA);
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                buf.reset();
+                ctx.render(black_box(root), AREA, &mut buf);
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_layout, bench_render);
+criterion_main!(benches);
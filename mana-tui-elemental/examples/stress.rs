@@ -0,0 +1,44 @@
+//! renders the benchmark dashboard as fast as possible. press any key to quit and print
+//! frame time percentiles.
+
+use std::time::{Duration, Instant};
+
+use mana_tui_elemental::{prelude::*, testing};
+use ratatui::crossterm::event::{self, Event};
+
+fn main() -> std::io::Result<()> {
+    let mut terminal = ratatui::init();
+    let mut ctx = ElementCtx::new();
+    let mut frame_times = Vec::new();
+
+    loop {
+        if event::poll(Duration::ZERO)? && matches!(event::read()?, Event::Key(_)) {
+            break;
+        }
+        let start = Instant::now();
+        let root = ctx.spawn_ui(testing::dashboard(50, 50));
+        terminal.draw(|frame| {
+            if let Err(err) = ctx.calculate_layout(root, frame.area()) {
+                tracing::error!("failed to calculate layout: {err}");
+                return;
+            }
+            ctx.render(root, frame.area(), frame.buffer_mut());
+        })?;
+        ctx.despawn_ui(root);
+        frame_times.push(start.elapsed());
+    }
+
+    ratatui::restore();
+
+    if frame_times.is_empty() {
+        return Ok(());
+    }
+    frame_times.sort_unstable();
+    let percentile = |p: usize| frame_times[(frame_times.len() - 1) * p / 100];
+    println!("frames: {}", frame_times.len());
+    println!("p50: {:?}", percentile(50));
+    println!("p90: {:?}", percentile(90));
+    println!("p99: {:?}", percentile(99));
+    println!("max: {:?}", percentile(100));
+    Ok(())
+}
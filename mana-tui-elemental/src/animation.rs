@@ -0,0 +1,135 @@
+//! # Animation
+//!
+//! per-element animations driven by the runtime. attach one with
+//! [`UiBuilder::animate`][crate::ui::UiBuilder::animate] and advance all of them with
+//! [`ElementCtx::tick_animations`].
+
+use std::time::Duration;
+
+use ratatui::style::{Modifier, Style};
+
+use crate::layout::{Element, ElementCtx, Props};
+
+/// state returned by [`Animation::tick`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationState {
+    /// the animation needs more frames.
+    Running,
+    /// the animation finished. it is applied one last time and then removed from the element.
+    Complete,
+}
+
+/// a custom per-element animation.
+///
+/// every frame the runtime calls [`Animation::tick`] with the time since the last frame and then
+/// [`Animation::apply`] to update the element.
+pub trait Animation: Send + Sync + 'static {
+    /// advances the animation by `dt`.
+    fn tick(&mut self, dt: Duration) -> AnimationState;
+    /// applies the current state of the animation to the element.
+    fn apply(&self, element: Element, ctx: &mut ElementCtx);
+}
+
+/// holds the animation of an element. added by
+/// [`UiBuilder::animate`][crate::ui::UiBuilder::animate].
+pub struct AnimationComponent(pub Box<dyn Animation>);
+
+impl std::fmt::Debug for AnimationComponent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("AnimationComponent").finish_non_exhaustive()
+    }
+}
+
+/// fades the element in over `duration`.
+///
+/// terminals have no real opacity, so the element is rendered with [`Modifier::DIM`] until the
+/// animation completes, then it gets its own style back.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FadeIn {
+    /// total length of the animation.
+    pub duration: Duration,
+    /// time passed since the animation started.
+    pub elapsed: Duration,
+}
+
+impl FadeIn {
+    /// create a fade in animation that lasts `duration`.
+    pub const fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// opacity of the element, from `0.0` to `1.0`.
+    pub fn opacity(&self) -> f32 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+        (self.elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+    }
+}
+
+/// the style the element had before its [`FadeIn`] started, put back when it completes.
+struct FadeInStyle(Style);
+
+impl Animation for FadeIn {
+    fn tick(&mut self, dt: Duration) -> AnimationState {
+        self.elapsed = (self.elapsed + dt).min(self.duration);
+        if self.elapsed >= self.duration {
+            AnimationState::Complete
+        } else {
+            AnimationState::Running
+        }
+    }
+
+    fn apply(&self, element: Element, ctx: &mut ElementCtx) {
+        let Ok(props) = ctx.get::<&Props>(element).map(|props| *props) else {
+            return;
+        };
+        let style = if let Ok(saved) = ctx.get::<&FadeInStyle>(element) {
+            saved.0
+        } else {
+            let style = (props.get_style)(ctx, element).unwrap_or_default();
+            _ = ctx.insert_one(element, FadeInStyle(style));
+            style
+        };
+        if self.opacity() < 1.0 {
+            (props.set_style)(ctx, element, style.add_modifier(Modifier::DIM));
+        } else {
+            (props.set_style)(ctx, element, style);
+            _ = ctx.remove_one::<FadeInStyle>(element);
+        }
+    }
+}
+
+impl ElementCtx {
+    /// advances every [`AnimationComponent`] by `dt` and applies it. completed animations are
+    /// removed from their element.
+    ///
+    /// returns `true` if any animation is still running.
+    pub fn tick_animations(&mut self, dt: Duration) -> bool {
+        let animated = self
+            .query_mut::<(Element, &AnimationComponent)>()
+            .into_iter()
+            .map(|(element, _)| element)
+            .collect::<Vec<_>>();
+
+        let mut running = false;
+        for element in animated {
+            let Ok(AnimationComponent(mut animation)) =
+                self.remove_one::<AnimationComponent>(element)
+            else {
+                continue;
+            };
+            let state = animation.tick(dt);
+            animation.apply(element, self);
+            if state == AnimationState::Running {
+                running = true;
+                _ = self.insert_one(element, AnimationComponent(animation));
+            }
+        }
+
+        running
+    }
+}
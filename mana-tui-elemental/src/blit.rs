@@ -0,0 +1,101 @@
+//! # Blit
+//!
+//! offscreen composition. render an expensive subtree once with
+//! [`ElementCtx::render_to_buffer`](crate::layout::ElementCtx::render_to_buffer) and draw the
+//! buffer with a [`Blit`] for as long as its data doesn't change.
+//!
+//! # Example
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use mana_tui_elemental::prelude::*;
+//! # use ratatui::layout::Rect;
+//! let mut ctx = ElementCtx::new();
+//! let chart = ctx.spawn_ui(ui(Block::bordered()).with((Width::fixed(8), Height::fixed(3))));
+//! ctx.calculate_layout(chart, Rect::new(0, 0, 8, 3)).unwrap();
+//! let chart = Arc::new(ctx.render_to_buffer(chart));
+//!
+//! // every frame, until the chart changes.
+//! ui(Block::new()).children((ui(Text::raw("cpu")), ui(Blit::new(chart.clone()))));
+//! ```
+
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    sync::Arc,
+};
+
+use ratatui::{
+    buffer::{Buffer, Cell},
+    layout::{Position, Rect},
+    style::Style,
+};
+
+use crate::layout::{ElWidget, UnstyledMarker};
+
+/// copies a stored buffer into its area, see the [module docs](self).
+///
+/// the buffer is drawn from its top left corner and cut to the area of the element. without a
+/// [`Width`](crate::layout::Width) or [`Height`](crate::layout::Height) the element is the
+/// size of the buffer.
+#[derive(Debug, Clone)]
+pub struct Blit {
+    /// the buffer to copy.
+    pub buffer: Arc<Buffer>,
+    /// leaves what is under the empty cells of the buffer, see [`Blit::skip_empty`].
+    pub skip_empty: bool,
+}
+
+impl Blit {
+    /// copies every cell of `buffer`.
+    #[must_use]
+    pub fn new(buffer: Arc<Buffer>) -> Self {
+        Self {
+            buffer,
+            skip_empty: false,
+        }
+    }
+
+    /// whether cells equal to [`Cell::EMPTY`] are skipped, so the buffer is drawn over what is
+    /// already there.
+    #[must_use]
+    pub fn skip_empty(mut self, skip_empty: bool) -> Self {
+        self.skip_empty = skip_empty;
+        self
+    }
+}
+
+impl ElWidget<UnstyledMarker> for Blit {
+    fn render_element(&self, area: Rect, buf: &mut Buffer) {
+        let source = self.buffer.area;
+        let width = area.width.min(source.width);
+        let height = area.height.min(source.height);
+        for y in 0..height {
+            for x in 0..width {
+                let to = Position::new(area.x + x, area.y + y);
+                let Some(cell) = self.buffer.cell((source.x + x, source.y + y)) else {
+                    continue;
+                };
+                if self.skip_empty && *cell == Cell::EMPTY {
+                    continue;
+                }
+                if let Some(target) = buf.cell_mut(to) {
+                    *target = cell.clone();
+                }
+            }
+        }
+    }
+
+    fn set_style(&mut self, _: Style) {}
+
+    fn get_style(&self) -> Style {
+        Style::default()
+    }
+
+    /// a stored buffer is only ever replaced, so the pointer says whether it changed.
+    fn fingerprint(&self) -> Option<u64> {
+        let mut hasher = DefaultHasher::new();
+        Arc::as_ptr(&self.buffer).hash(&mut hasher);
+        self.skip_empty.hash(&mut hasher);
+        Some(hasher.finish())
+    }
+}
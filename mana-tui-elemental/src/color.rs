@@ -0,0 +1,175 @@
+//! # Color
+//!
+//! the colors a terminal can show, and degrading RGB colors for terminals without truecolor.
+//!
+//! a runtime stores the [`ColorCaps`] of the terminal as a resource and calls
+//! [`ElementCtx::degrade_colors`] on the final buffer, so themes can use RGB colors everywhere.
+//! widgets can read the resource too, to pick a fallback when colors are scarce.
+
+use std::collections::HashMap;
+
+use mana_tui_utils::resource::Resources;
+use ratatui::{buffer::Buffer, style::Color};
+
+use crate::layout::ElementCtx;
+
+/// the colors a terminal can show.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ColorCaps {
+    /// any RGB color.
+    #[default]
+    TrueColor,
+    /// the 256 color palette: the 16 ansi colors, a 6x6x6 color cube and 24 grays.
+    Ansi256,
+    /// the 16 ansi colors.
+    Ansi16,
+}
+
+/// the RGB values of the 16 ansi colors, as xterm shows them.
+const ANSI16: [(Color, [u8; 3]); 16] = [
+    (Color::Black, [0, 0, 0]),
+    (Color::Red, [205, 0, 0]),
+    (Color::Green, [0, 205, 0]),
+    (Color::Yellow, [205, 205, 0]),
+    (Color::Blue, [0, 0, 238]),
+    (Color::Magenta, [205, 0, 205]),
+    (Color::Cyan, [0, 205, 205]),
+    (Color::Gray, [229, 229, 229]),
+    (Color::DarkGray, [127, 127, 127]),
+    (Color::LightRed, [255, 0, 0]),
+    (Color::LightGreen, [0, 255, 0]),
+    (Color::LightYellow, [255, 255, 0]),
+    (Color::LightBlue, [92, 92, 255]),
+    (Color::LightMagenta, [255, 0, 255]),
+    (Color::LightCyan, [0, 255, 255]),
+    (Color::White, [255, 255, 255]),
+];
+
+/// the levels of each channel in the 256 color cube.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl ColorCaps {
+    /// the capabilities of the terminal the process runs in, from the `COLORTERM` and `TERM`
+    /// environment variables.
+    #[must_use]
+    pub fn detect() -> Self {
+        Self::from_env(
+            std::env::var("COLORTERM").ok().as_deref(),
+            std::env::var("TERM").ok().as_deref(),
+        )
+    }
+
+    /// the capabilities for the values of `COLORTERM` and `TERM`.
+    ///
+    /// `COLORTERM=truecolor` or `24bit` means truecolor, a `TERM` containing `256color` means 256
+    /// colors and any other `TERM` means 16 colors. terminals that set neither, like the windows
+    /// console, are assumed to support truecolor.
+    #[must_use]
+    pub fn from_env(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        if colorterm.is_some_and(|colorterm| matches!(colorterm, "truecolor" | "24bit")) {
+            return Self::TrueColor;
+        }
+        match term {
+            Some(term) if term.contains("256color") => Self::Ansi256,
+            Some(_) => Self::Ansi16,
+            None => Self::TrueColor,
+        }
+    }
+
+    /// the supported color closest to `color`. named colors are always supported.
+    #[must_use]
+    pub fn degrade(self, color: Color) -> Color {
+        match (self, color) {
+            (Self::TrueColor, _) => color,
+            (Self::Ansi256, Color::Rgb(r, g, b)) => nearest_256([r, g, b]),
+            (Self::Ansi16, Color::Rgb(r, g, b)) => nearest_16([r, g, b]),
+            (Self::Ansi16, Color::Indexed(index)) => nearest_16(indexed_rgb(index)),
+            _ => color,
+        }
+    }
+}
+
+/// distance between two colors, weighted by how sensitive the eye is to each channel
+/// ("redmean").
+fn distance([r1, g1, b1]: [u8; 3], [r2, g2, b2]: [u8; 3]) -> u32 {
+    let mean = (u32::from(r1) + u32::from(r2)) / 2;
+    let [dr, dg, db] = [r1.abs_diff(r2), g1.abs_diff(g2), b1.abs_diff(b2)].map(u32::from);
+    (((512 + mean) * dr * dr) >> 8) + 4 * dg * dg + (((767 - mean) * db * db) >> 8)
+}
+
+fn nearest_16(rgb: [u8; 3]) -> Color {
+    ANSI16
+        .iter()
+        .min_by_key(|(_, ansi)| distance(rgb, *ansi))
+        .map_or(Color::Reset, |(color, _)| *color)
+}
+
+/// the closest color of the cube or the gray ramp. the first 16 colors are skipped since
+/// terminal themes change them.
+fn nearest_256(rgb: [u8; 3]) -> Color {
+    let cube = rgb.map(|channel| {
+        CUBE_LEVELS
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, level)| level.abs_diff(channel))
+            .map_or(0, |(index, _)| index as u8)
+    });
+    let cube_index = 16 + 36 * cube[0] + 6 * cube[1] + cube[2];
+    let gray = (u16::from(rgb[0]) + u16::from(rgb[1]) + u16::from(rgb[2])) / 3;
+    let gray_index = 232 + (gray.saturating_sub(3) / 10).min(23) as u8;
+    [cube_index, gray_index]
+        .into_iter()
+        .min_by_key(|index| distance(rgb, indexed_rgb(*index)))
+        .map_or(Color::Reset, Color::Indexed)
+}
+
+/// the RGB value of a color of the 256 color palette.
+fn indexed_rgb(index: u8) -> [u8; 3] {
+    match index {
+        0..16 => ANSI16[usize::from(index)].1,
+        16..232 => {
+            let index = index - 16;
+            [index / 36, index / 6 % 6, index % 6].map(|level| CUBE_LEVELS[usize::from(level)])
+        }
+        232.. => [8 + (index - 232) * 10; 3],
+    }
+}
+
+/// resource with the colors converted so far, for the [`ColorCaps`] they were converted for.
+#[derive(Debug, Default)]
+struct PaletteCache {
+    caps: ColorCaps,
+    colors: HashMap<Color, Color>,
+}
+
+impl ElementCtx {
+    /// replaces the colors of `buf` the terminal can't show with the closest ones it can, using
+    /// the [`ColorCaps`] resource. does nothing without the resource or with truecolor.
+    ///
+    /// conversions are cached, so calling it on every frame is cheap.
+    pub fn degrade_colors(&mut self, buf: &mut Buffer) {
+        let caps = self
+            .world
+            .get_resource::<&ColorCaps>()
+            .map(|caps| *caps)
+            .unwrap_or_default();
+        if caps == ColorCaps::TrueColor {
+            return;
+        }
+        let mut cache = self
+            .world
+            .get_or_insert_resource_with::<&mut PaletteCache>(|_| PaletteCache::default());
+        if cache.caps != caps {
+            cache.caps = caps;
+            cache.colors.clear();
+        }
+        for cell in &mut buf.content {
+            for color in [&mut cell.fg, &mut cell.bg] {
+                *color = *cache
+                    .colors
+                    .entry(*color)
+                    .or_insert_with(|| caps.degrade(*color));
+            }
+        }
+    }
+}
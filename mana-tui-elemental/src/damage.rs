@@ -0,0 +1,338 @@
+//! # Damage
+//!
+//! incremental rendering for apps that redraw often. [`ElementCtx::render_incremental`] copies
+//! the cells of subtrees that didn't change since the previous frame instead of rendering them
+//! again, and reports the cells that changed as [`Damage`].
+
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Offset, Rect},
+};
+use tui_scrollview::ScrollView;
+
+use crate::layout::{
+    Children, ClearBackground, ClipRegion, ContentVersion, Element, ElementCtx,
+    KeyboardShortcutHint, Props, Shadow,
+};
+
+/// the cells that changed between two frames, see [`ElementCtx::render_incremental`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Damage {
+    /// the changed regions. every run of changed cells in a row is a region, runs over the same
+    /// columns in consecutive rows are merged.
+    pub regions: Vec<Rect>,
+    /// the number of changed cells.
+    pub cells: usize,
+    /// the number of elements whose widget was rendered.
+    pub rendered: usize,
+    /// the number of subtrees copied from the previous frame.
+    pub reused: usize,
+}
+
+impl Damage {
+    /// true if no cell changed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.cells == 0
+    }
+
+    /// the smallest area containing every changed region.
+    #[must_use]
+    pub fn bounds(&self) -> Rect {
+        self.regions
+            .iter()
+            .copied()
+            .reduce(|acc, region| acc.union(region))
+            .unwrap_or_default()
+    }
+}
+
+/// the signature of an element and of its subtree. [`None`] if it can't be reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Signature {
+    own: Option<u64>,
+    subtree: Option<u64>,
+}
+
+/// the signatures of a tree and the areas drawn over other elements, like shadows and popups.
+/// both are keyed by the path of child indices from the root.
+#[derive(Debug, Default)]
+struct Frame {
+    signatures: HashMap<Vec<usize>, Signature>,
+    overlays: Vec<(Vec<usize>, Rect)>,
+}
+
+impl Frame {
+    /// true if an overlay that isn't part of the element at `path` or one of its ancestors
+    /// covers `area`.
+    fn covers(&self, path: &[usize], area: Rect) -> bool {
+        self.overlays.iter().any(|(overlay, rect)| {
+            !overlay.starts_with(path) && !path.starts_with(overlay) && rect.intersects(area)
+        })
+    }
+}
+
+/// the last tree drawn by [`ElementCtx::render_incremental`] in each area. stored as a
+/// resource.
+#[derive(Debug, Default)]
+struct RenderCache(HashMap<Rect, Frame>);
+
+impl ElementCtx {
+    /// renders the tree into `buf` like [`ElementCtx::render`], copying subtrees that are the
+    /// same as in the last call for this `area` from `previous` instead of rendering them.
+    ///
+    /// `previous` must hold what the last call drew, and `buf` should start out empty. an
+    /// element is the same if its widget [fingerprint](crate::layout::ElWidget::fingerprint) or
+    /// [`ContentVersion`], position, size and children are. elements with a [`Shadow`] or a
+    /// [`ScrollView`] are always rendered, and so are elements a shadow or a
+    /// [`ClearBackground`] element was drawn over in this frame or the last one.
+    ///
+    /// the tree can be despawned and spawned again between frames, elements are matched by
+    /// their position in the tree.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use mana_tui_elemental::prelude::*;
+    /// # use ratatui::{buffer::Buffer, layout::Rect};
+    /// let mut ctx = ElementCtx::new();
+    /// let area = Rect::new(0, 0, 10, 1);
+    /// let mut shadow = Buffer::empty(area);
+    /// let frame = |ctx: &mut ElementCtx, shadow: &mut Buffer| {
+    ///     let root = ctx.spawn_ui(ui(Text::raw("hi")));
+    ///     ctx.calculate_layout(root, area).unwrap();
+    ///     let mut next = Buffer::empty(area);
+    ///     let damage = ctx.render_incremental(root, area, &mut next, shadow);
+    ///     *shadow = next;
+    ///     ctx.despawn_ui(root);
+    ///     damage
+    /// };
+    /// assert_eq!(frame(&mut ctx, &mut shadow).cells, 2);
+    /// // nothing changed, so nothing is rendered either.
+    /// let damage = frame(&mut ctx, &mut shadow);
+    /// assert!(damage.is_empty());
+    /// assert_eq!(damage.rendered, 0);
+    /// ```
+    pub fn render_incremental(
+        &mut self,
+        root: Element,
+        area: Rect,
+        buf: &mut Buffer,
+        previous: &Buffer,
+    ) -> Damage {
+        let mut frame = Frame::default();
+        self.collect_signatures(root, &mut Vec::new(), &mut frame);
+
+        let mut cache = self.remove_resource::<RenderCache>().unwrap_or_default();
+        let previous_frame = cache.0.remove(&area).unwrap_or_default();
+        let mut damage = Damage::default();
+        let reusable = (previous.area == buf.area).then_some(previous);
+        self.render_cached(
+            root,
+            area,
+            buf,
+            reusable,
+            &mut Vec::new(),
+            (&previous_frame, &frame),
+            &mut damage,
+        );
+        cache.0.insert(area, frame);
+        self.insert_or_update_resource(cache);
+
+        (damage.regions, damage.cells) = diff_buffers(previous, buf);
+        damage
+    }
+
+    fn collect_signatures(
+        &self,
+        element: Element,
+        path: &mut Vec<usize>,
+        frame: &mut Frame,
+    ) -> Option<u64> {
+        let Ok(entity) = self.world.entity(element) else {
+            return None;
+        };
+        if let Some(props) = entity.get::<&Props>() {
+            let area = Rect::new(
+                props.position.x,
+                props.position.y,
+                props.size.x,
+                props.size.y,
+            );
+            if let Some(shadow) = entity.get::<&Shadow>() {
+                let offset = Offset {
+                    x: i32::from(shadow.offset.x),
+                    y: i32::from(shadow.offset.y),
+                };
+                frame
+                    .overlays
+                    .push((path.clone(), area.union(area.offset(offset))));
+            } else if entity.has::<ClearBackground>() {
+                frame.overlays.push((path.clone(), area));
+            }
+        }
+        let own = entity.get::<&Props>().and_then(|props| {
+            if entity.has::<Shadow>() || entity.has::<ScrollView>() {
+                return None;
+            }
+            let mut hasher = DefaultHasher::new();
+            match (props.fingerprint)(&self.world, element) {
+                Some(fingerprint) => fingerprint.hash(&mut hasher),
+                None => entity.get::<&ContentVersion>()?.hash(&mut hasher),
+            }
+            props.position.hash(&mut hasher);
+            props.size.hash(&mut hasher);
+            entity.has::<ClearBackground>().hash(&mut hasher);
+            entity
+                .get::<&ClipRegion>()
+                .map(|clip| *clip)
+                .hash(&mut hasher);
+            if let Some(hint) = entity.get::<&KeyboardShortcutHint>() {
+                hint.hash(&mut hasher);
+            }
+            Some(hasher.finish())
+        });
+
+        let children = entity
+            .get::<&Children>()
+            .map(|children| self.visible_children(element, &children))
+            .unwrap_or_default();
+        let mut subtree = own.map(|own| {
+            let mut hasher = DefaultHasher::new();
+            own.hash(&mut hasher);
+            hasher
+        });
+        for (idx, child) in children.into_iter().enumerate() {
+            path.push(idx);
+            let child = self.collect_signatures(child, path, frame);
+            path.pop();
+            match (child, subtree.as_mut()) {
+                (Some(child), Some(hasher)) => child.hash(hasher),
+                _ => subtree = None,
+            }
+        }
+
+        let subtree = subtree.map(|hasher| hasher.finish());
+        frame
+            .signatures
+            .insert(path.clone(), Signature { own, subtree });
+        subtree
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render_cached(
+        &mut self,
+        element: Element,
+        area: Rect,
+        buf: &mut Buffer,
+        previous: Option<&Buffer>,
+        path: &mut Vec<usize>,
+        (old, new): (&Frame, &Frame),
+        damage: &mut Damage,
+    ) {
+        let Ok(props) = self.world.get::<&Props>(element).map(|props| *props) else {
+            return;
+        };
+        let element_area = props.split_area(area, Offset { x: 0, y: 0 });
+        let old_signature = old.signatures.get(path.as_slice());
+        let new_signature = new.signatures.get(path.as_slice());
+
+        // cells an overlay was drawn over last frame would come back with the copy.
+        if let Some(previous) = previous
+            && let (Some(old_signature), Some(new_signature)) = (old_signature, new_signature)
+            && new_signature.subtree.is_some()
+            && old_signature.subtree == new_signature.subtree
+            && !old.covers(path, element_area)
+            && !new.covers(path, element_area)
+        {
+            for position in element_area.positions() {
+                buf[position] = previous[position].clone();
+            }
+            damage.reused += 1;
+            return;
+        }
+
+        damage.rendered += 1;
+        // cut elements are drawn whole and copied, see `render_impl`.
+        if self.world.get::<&ScrollView>(element).is_ok()
+            || (!element_area.is_empty() && element_area != props.rect(Offset { x: 0, y: 0 }))
+        {
+            self.render(element, area, buf);
+            return;
+        }
+        self.render_element(element, element_area, buf);
+
+        // children can only be copied from the previous frame if they were drawn over the same
+        // cells of this element.
+        let previous = previous.filter(|_| {
+            new_signature.is_some_and(|signature| signature.own.is_some())
+                && old_signature.map(|signature| signature.own)
+                    == new_signature.map(|signature| signature.own)
+        });
+        let children = self
+            .world
+            .get::<&Children>(element)
+            .map(|children| self.visible_children(element, &children))
+            .unwrap_or_default();
+        let children_area = self.clip_children(element, element_area, Offset { x: 0, y: 0 });
+        for (idx, child) in children.into_iter().enumerate() {
+            path.push(idx);
+            self.render_cached(
+                child,
+                children_area,
+                buf,
+                previous,
+                path,
+                (old, new),
+                damage,
+            );
+            path.pop();
+        }
+        self.render_shortcut_hint(element, element_area, buf);
+    }
+}
+
+/// the changed regions and the number of changed cells between two frames. every cell is
+/// changed if the areas differ.
+fn diff_buffers(previous: &Buffer, next: &Buffer) -> (Vec<Rect>, usize) {
+    if previous.area != next.area {
+        let area = next.area;
+        let regions = if area.is_empty() { vec![] } else { vec![area] };
+        return (regions, area.area() as usize);
+    }
+
+    let mut regions: Vec<Rect> = Vec::new();
+    let mut cells = 0;
+    let mut row_regions = Vec::new();
+    for y in next.area.rows().map(|row| row.y) {
+        let mut run: Option<Rect> = None;
+        for x in next.area.columns().map(|column| column.x) {
+            if previous[(x, y)] == next[(x, y)] {
+                row_regions.extend(run.take());
+                continue;
+            }
+            cells += 1;
+            match run.as_mut() {
+                Some(run) => run.width += 1,
+                None => run = Some(Rect::new(x, y, 1, 1)),
+            }
+        }
+        row_regions.extend(run);
+
+        for region in row_regions.drain(..) {
+            // grow the region of the row above if it covers the same columns.
+            match regions.iter_mut().find(|above| {
+                above.bottom() == y && above.x == region.x && above.width == region.width
+            }) {
+                Some(above) => above.height += 1,
+                None => regions.push(region),
+            }
+        }
+    }
+    (regions, cells)
+}
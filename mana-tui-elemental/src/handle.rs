@@ -0,0 +1,162 @@
+//! # Handles
+//!
+//! an [`Element`] is a plain [`hecs::Entity`], so one from another [`ElementCtx`] fails with a
+//! confusing [`ComponentError`], or worse, names an unrelated element of this context that got
+//! the same id.
+//!
+//! an [`ElRef`] remembers the context it came from. make one with [`ElementCtx::el_ref`] or
+//! [`ElementCtx::spawn_ui_checked`] and pass it to the `_checked` methods, which fail with
+//! [`ElRefError::WrongContext`] instead.
+//!
+//! # Example
+//!
+//! ```
+//! # use mana_tui_elemental::prelude::*;
+//! # use mana_tui_elemental::handle::ElRefError;
+//! # use ratatui::layout::Rect;
+//! let mut ctx = ElementCtx::new();
+//! let mut other = ElementCtx::new();
+//! let (_, root) = ctx.spawn_ui_checked(ui(Block::new()));
+//! assert!(ctx.calculate_layout_checked(root, Rect::new(0, 0, 10, 10)).is_ok());
+//! assert!(matches!(
+//!     other.calculate_layout_checked(root, Rect::new(0, 0, 10, 10)),
+//!     Err(ElRefError::WrongContext)
+//! ));
+//! ```
+
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use hecs::{ComponentError, EntityBuilder};
+use mana_tui_utils::resource::Resources;
+use ratatui::{buffer::Buffer, layout::Rect};
+
+use crate::layout::{Element, ElementCtx};
+
+static NEXT_CONTEXT_ID: AtomicU32 = AtomicU32::new(0);
+
+/// resource with the id of a context, inserted when its first [`ElRef`] is made. it lives in
+/// the world, so [`ElementCtx::with_world`] keeps it.
+struct ContextId(u32);
+
+/// an [`Element`] tied to the [`ElementCtx`] it was spawned in, see the [module docs](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ElRef {
+    entity: Element,
+    ctx_id: u32,
+}
+
+impl ElRef {
+    /// the raw element, for the methods without a `_checked` variant.
+    #[must_use]
+    pub fn element(self) -> Element {
+        self.entity
+    }
+}
+
+/// the error of the `_checked` methods of [`ElementCtx`].
+#[derive(Debug)]
+pub enum ElRefError {
+    /// the [`ElRef`] was made by another context.
+    WrongContext,
+    /// the element is missing a component, or was despawned.
+    Component(ComponentError),
+}
+
+impl std::fmt::Display for ElRefError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ElRefError::WrongContext => write!(f, "element belongs to another context"),
+            ElRefError::Component(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ElRefError {}
+
+impl From<ComponentError> for ElRefError {
+    fn from(err: ComponentError) -> Self {
+        ElRefError::Component(err)
+    }
+}
+
+impl ElementCtx {
+    /// the id of the context, given out on first use.
+    fn context_id(&mut self) -> u32 {
+        self.world
+            .get_or_insert_resource_with::<&ContextId>(|_| {
+                ContextId(NEXT_CONTEXT_ID.fetch_add(1, Ordering::Relaxed))
+            })
+            .0
+    }
+
+    /// ties `element` to this context.
+    pub fn el_ref(&mut self, element: Element) -> ElRef {
+        ElRef {
+            entity: element,
+            ctx_id: self.context_id(),
+        }
+    }
+
+    /// spawns `ui` like [`ElementCtx::spawn_ui`], returning the root both raw and as an
+    /// [`ElRef`].
+    pub fn spawn_ui_checked(&mut self, ui: impl Into<EntityBuilder>) -> (Element, ElRef) {
+        let root = self.spawn_ui(ui);
+        (root, self.el_ref(root))
+    }
+
+    /// the element of `el` if it belongs to this context and is still alive.
+    ///
+    /// # Errors
+    ///
+    /// [`ElRefError::WrongContext`] if `el` was made by another context.
+    pub fn check(&self, el: ElRef) -> Result<Element, ElRefError> {
+        let own = self
+            .world
+            .get_resource::<&ContextId>()
+            .is_ok_and(|id| id.0 == el.ctx_id);
+        if !own {
+            return Err(ElRefError::WrongContext);
+        }
+        if !self.world.contains(el.entity) {
+            return Err(ComponentError::NoSuchEntity.into());
+        }
+        Ok(el.entity)
+    }
+
+    /// [`ElementCtx::calculate_layout`] for an [`ElRef`].
+    ///
+    /// # Errors
+    ///
+    /// see [`ElementCtx::check`] and [`ElementCtx::calculate_layout`].
+    pub fn calculate_layout_checked(&mut self, el: ElRef, area: Rect) -> Result<(), ElRefError> {
+        let element = self.check(el)?;
+        Ok(self.calculate_layout(element, area)?)
+    }
+
+    /// [`ElementCtx::render`] for an [`ElRef`].
+    ///
+    /// # Errors
+    ///
+    /// see [`ElementCtx::check`].
+    pub fn render_checked(
+        &mut self,
+        el: ElRef,
+        area: Rect,
+        buf: &mut Buffer,
+    ) -> Result<(), ElRefError> {
+        let element = self.check(el)?;
+        self.render(element, area, buf);
+        Ok(())
+    }
+
+    /// [`ElementCtx::despawn_ui`] for an [`ElRef`].
+    ///
+    /// # Errors
+    ///
+    /// see [`ElementCtx::check`].
+    pub fn despawn_ui_checked(&mut self, el: ElRef) -> Result<(), ElRefError> {
+        let element = self.check(el)?;
+        self.despawn_ui(element);
+        Ok(())
+    }
+}
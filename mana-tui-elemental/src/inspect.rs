@@ -0,0 +1,517 @@
+//! # Inspect
+//!
+//! snapshots of the laid out tree for inspectors and layout diffs. take one with
+//! [`ElementCtx::dump_tree`] and compare two of them with [`TreeDump::diff`].
+//!
+//! enable the `serde` feature to serialize them.
+//!
+//! [`ElementCtx::reading_order`] linearizes the visible text of the tree for accessibility
+//! tooling and for tests that shouldn't depend on the layout.
+//!
+//! [`ElementCtx::layout_hints`] points out sizes that lay out but likely not as intended,
+//! [`ElementCtx::lint_layout`] logs them.
+
+use std::{any::TypeId, collections::HashMap};
+
+use hecs::EntityRef;
+use ratatui::{
+    buffer::Buffer,
+    layout::{Direction, Margin, Rect},
+    text::{Line, Span, Text},
+    widgets::{Block, Padding, Paragraph},
+};
+
+use crate::layout::{
+    AlignSelf, Children, CrossJustify, Element, ElementCtx, Gap, Height, MainJustify, Measure,
+    Props, Size, SplitPane, Width,
+};
+use crate::text_hit::SourceParagraph;
+
+/// a stable name for an element. keys the element in [`TreeDump::diff`] instead of its position
+/// in the tree.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, derive_more::Deref)]
+pub struct ElId(pub String);
+
+impl ElId {
+    /// creates a new id.
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+}
+
+/// the accessibility role of an element in [`ElementCtx::reading_order`]. insert it to override
+/// the inferred role: text widgets are [`Role::Text`] and everything else is a
+/// [`Role::Container`]. the runtimes mark elements with click or key handlers as
+/// [`Role::Button`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, strum::Display)]
+#[strum(serialize_all = "snake_case")]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Role {
+    /// an element that does something when clicked or when its key is pressed.
+    Button,
+    /// a piece of text.
+    Text,
+    /// an element that groups its children.
+    Container,
+    /// an item of a list.
+    ListItem,
+}
+
+/// an element of a [`ReadingOrder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadItem {
+    /// the element.
+    pub element: Element,
+    /// the role of the element, see [`Role`].
+    pub role: Role,
+    /// the text the element draws, without its children. rows are joined with spaces and
+    /// borders are left out.
+    pub text: String,
+    /// how deep the element is below the root.
+    pub depth: usize,
+}
+
+/// the visible elements of a tree in reading order, see [`ElementCtx::reading_order`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadingOrder {
+    /// the elements, in the order of the focus stack.
+    pub items: Vec<ReadItem>,
+}
+
+impl ReadingOrder {
+    /// one line per element like `button: save`, indented by two spaces per level.
+    #[must_use]
+    pub fn to_plain_text(&self) -> String {
+        self.items
+            .iter()
+            .map(|item| {
+                let indent = "  ".repeat(item.depth);
+                if item.text.is_empty() {
+                    format!("{indent}{}", item.role)
+                } else {
+                    format!("{indent}{}: {}", item.role, item.text)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// a combination of sizes that lays out, but likely not the way it was meant to. see
+/// [`ElementCtx::layout_hints`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutHint {
+    /// the element grows along the main axis of a parent that fits its children on that axis.
+    /// the parent is exactly as large as its children, so there is no space to grow into.
+    GrowInFitParent {
+        /// the growing element.
+        element: Element,
+        /// the main axis of the parent.
+        axis: Direction,
+    },
+    /// the element fits the main axis of its parent, or an axis of the root, but has no
+    /// children, content or padding to fit. it takes no space and is never drawn.
+    EmptyFit {
+        /// the empty element.
+        element: Element,
+        /// the axis without a size.
+        axis: Direction,
+    },
+}
+
+/// a snapshot of a laid out tree, see [`ElementCtx::dump_tree`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TreeDump {
+    /// the root of the tree.
+    pub root: NodeDump,
+}
+
+/// a single element of a [`TreeDump`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeDump {
+    /// the bits of the element's [`Element`] id.
+    pub entity: u64,
+    /// the type name of the element's widget.
+    pub widget: String,
+    /// the area computed by the last layout.
+    pub rect: Rect,
+    /// the element's [`ElId`], if it has one.
+    pub id: Option<String>,
+    /// the element's [`Width`].
+    pub width: Option<String>,
+    /// the element's [`Height`].
+    pub height: Option<String>,
+    /// the element's [`Padding`].
+    pub padding: Option<String>,
+    /// the element's [`Gap`].
+    pub gap: Option<String>,
+    /// the element's [`Direction`].
+    pub direction: Option<String>,
+    /// the element's [`MainJustify`].
+    pub main_justify: Option<String>,
+    /// the element's [`CrossJustify`].
+    pub cross_justify: Option<String>,
+    /// the element's [`AlignSelf`].
+    pub align_self: Option<String>,
+    /// the children of the element, in order.
+    pub children: Vec<NodeDump>,
+}
+
+/// a difference between two [`TreeDump`]s, see [`TreeDump::diff`].
+///
+/// nodes are keyed by their [`ElId`] as `#id`, or by their path of child indices from the root
+/// like `/0/2`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TreeDiff {
+    /// the node is only in the new tree.
+    Added {
+        /// key of the node.
+        key: String,
+    },
+    /// the node is only in the old tree.
+    Removed {
+        /// key of the node.
+        key: String,
+    },
+    /// the node changed size.
+    Resized {
+        /// key of the node.
+        key: String,
+        /// the area of the node in the old tree.
+        from: Rect,
+        /// the area of the node in the new tree.
+        to: Rect,
+    },
+}
+
+impl TreeDump {
+    /// the nodes added, removed and resized from `self` to `other`.
+    #[must_use]
+    pub fn diff(&self, other: &TreeDump) -> Vec<TreeDiff> {
+        let old = self.root.keyed();
+        let new = other.root.keyed();
+        let new_nodes = new.iter().cloned().collect::<HashMap<_, _>>();
+        let old_nodes = old.iter().cloned().collect::<HashMap<_, _>>();
+
+        let mut diffs = Vec::new();
+        for (key, node) in &old {
+            match new_nodes.get(key) {
+                None => diffs.push(TreeDiff::Removed { key: key.clone() }),
+                Some(new_node) if new_node.rect.as_size() != node.rect.as_size() => {
+                    diffs.push(TreeDiff::Resized {
+                        key: key.clone(),
+                        from: node.rect,
+                        to: new_node.rect,
+                    });
+                }
+                Some(_) => {}
+            }
+        }
+        for (key, _) in &new {
+            if !old_nodes.contains_key(key) {
+                diffs.push(TreeDiff::Added { key: key.clone() });
+            }
+        }
+        diffs
+    }
+}
+
+impl NodeDump {
+    /// every node of the tree with its key, in depth first order.
+    fn keyed(&self) -> Vec<(String, &NodeDump)> {
+        fn visit<'a>(node: &'a NodeDump, path: String, out: &mut Vec<(String, &'a NodeDump)>) {
+            let key = node
+                .id
+                .as_ref()
+                .map_or_else(|| path.clone(), |id| format!("#{id}"));
+            out.push((key, node));
+            for (idx, child) in node.children.iter().enumerate() {
+                visit(child, format!("{}/{idx}", path.trim_end_matches('/')), out);
+            }
+        }
+        let mut out = Vec::new();
+        visit(self, "/".to_string(), &mut out);
+        out
+    }
+}
+
+impl ElementCtx {
+    /// a snapshot of the tree under `root` as it was last laid out. useful for external
+    /// inspectors and for diffing layouts in tests.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use mana_tui_elemental::prelude::*;
+    /// # use ratatui::layout::Rect;
+    /// let mut ctx = ElementCtx::new();
+    /// let root = ctx.spawn_ui(ui(Block::new()).child(ui(Text::raw("hi"))));
+    /// ctx.calculate_layout(root, Rect::new(0, 0, 10, 10)).unwrap();
+    /// let dump = ctx.dump_tree(root);
+    /// assert_eq!(dump.root.children.len(), 1);
+    /// ```
+    #[must_use]
+    pub fn dump_tree(&self, root: Element) -> TreeDump {
+        TreeDump {
+            root: self.dump_node(root),
+        }
+    }
+
+    /// the elements under `root` in the order of the focus stack, with their [`Role`] and
+    /// the text they draw. elements the last layout left without space are skipped along with
+    /// their children.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use mana_tui_elemental::prelude::*;
+    /// # use ratatui::layout::Rect;
+    /// let mut ctx = ElementCtx::new();
+    /// let root = ctx.spawn_ui(
+    ///     ui(Block::bordered().title("todos"))
+    ///         .with((Width::grow(), Height::grow()))
+    ///         .child(ui(Text::raw("milk"))),
+    /// );
+    /// ctx.calculate_layout(root, Rect::new(0, 0, 10, 3)).unwrap();
+    /// let order = ctx.reading_order(root);
+    /// assert_eq!(order.to_plain_text(), "container: todos\n  text: milk");
+    /// ```
+    #[must_use]
+    pub fn reading_order(&self, root: Element) -> ReadingOrder {
+        let mut items = Vec::new();
+        self.read_element(root, 0, &mut items);
+        ReadingOrder { items }
+    }
+
+    /// the [`LayoutHint`]s of the tree under `root`. they don't stop the layout, see
+    /// [`ElementCtx::lint_layout`] to log them.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use mana_tui_elemental::prelude::*;
+    /// # use mana_tui_elemental::inspect::LayoutHint;
+    /// let mut ctx = ElementCtx::new();
+    /// let root = ctx.spawn_ui(ui(Block::new()).with((Width::fit(), Height::fixed(1))));
+    /// assert_eq!(
+    ///     ctx.layout_hints(root),
+    ///     [LayoutHint::EmptyFit { element: root, axis: Direction::Horizontal }]
+    /// );
+    /// ```
+    #[must_use]
+    pub fn layout_hints(&self, root: Element) -> Vec<LayoutHint> {
+        let mut hints = Vec::new();
+        self.hint_element(root, None, &mut hints);
+        hints
+    }
+
+    /// logs the [`LayoutHint`]s of the tree under `root` as warnings and returns them. the tree
+    /// is walked on every call, lint a tree once after spawning it rather than on every frame.
+    pub fn lint_layout(&self, root: Element) -> Vec<LayoutHint> {
+        let hints = self.layout_hints(root);
+        for hint in &hints {
+            tracing::warn!(?hint, "suspicious layout");
+        }
+        hints
+    }
+
+    fn hint_element(
+        &self,
+        element: Element,
+        parent_axis: Option<Direction>,
+        hints: &mut Vec<LayoutHint>,
+    ) {
+        let Ok(entity) = self.world.entity(element) else {
+            return;
+        };
+        let (Some(width), Some(height), Some(padding), Some(direction)) = (
+            entity.get::<&Width>().map(|width| **width),
+            entity.get::<&Height>().map(|height| **height),
+            entity.get::<&Padding>().map(|padding| *padding),
+            entity.get::<&Direction>().map(|direction| *direction),
+        ) else {
+            return;
+        };
+        let children = entity
+            .get::<&Children>()
+            .map(|children| self.visible_children(element, &children))
+            .unwrap_or_default();
+
+        if children.is_empty() && !entity.has::<Measure>() && self.content_size(element).is_none() {
+            let empty = [
+                (
+                    width,
+                    padding.left.saturating_add(padding.right),
+                    Direction::Horizontal,
+                ),
+                (
+                    height,
+                    padding.top.saturating_add(padding.bottom),
+                    Direction::Vertical,
+                ),
+            ];
+            for (size, padding, axis) in empty {
+                if matches!(size, Size::Fit)
+                    && padding == 0
+                    && parent_axis.is_none_or(|parent_axis| parent_axis == axis)
+                {
+                    hints.push(LayoutHint::EmptyFit { element, axis });
+                }
+            }
+        }
+
+        let main_fits = match direction {
+            Direction::Horizontal => matches!(width, Size::Fit),
+            Direction::Vertical => matches!(height, Size::Fit),
+        };
+        for child in &children {
+            let grows = match direction {
+                Direction::Horizontal => self
+                    .world
+                    .get::<&Width>(child)
+                    .is_ok_and(|width| matches!(**width, Size::Grow)),
+                Direction::Vertical => self
+                    .world
+                    .get::<&Height>(child)
+                    .is_ok_and(|height| matches!(**height, Size::Grow)),
+            };
+            if main_fits && grows && !entity.has::<SplitPane>() {
+                hints.push(LayoutHint::GrowInFitParent {
+                    element: child,
+                    axis: direction,
+                });
+            }
+            self.hint_element(child, Some(direction), hints);
+        }
+    }
+
+    fn read_element(&self, element: Element, depth: usize, items: &mut Vec<ReadItem>) {
+        let Ok(entity) = self.world.entity(element) else {
+            return;
+        };
+        let Some(props) = entity.get::<&Props>().map(|props| *props) else {
+            return;
+        };
+        if props.size.x == 0 || props.size.y == 0 {
+            return;
+        }
+
+        let text = self.visible_text(element, &props);
+        let is_text = [
+            TypeId::of::<Text<'static>>(),
+            TypeId::of::<Paragraph<'static>>(),
+            TypeId::of::<SourceParagraph>(),
+            TypeId::of::<Line<'static>>(),
+            TypeId::of::<Span<'static>>(),
+        ]
+        .contains(&props.typeid);
+        let role = entity
+            .get::<&Role>()
+            .map_or(if is_text { Role::Text } else { Role::Container }, |role| {
+                *role
+            });
+        items.push(ReadItem {
+            element,
+            role,
+            text,
+            depth,
+        });
+
+        let children = entity
+            .get::<&Children>()
+            .map(|children| children.clone())
+            .unwrap_or_default();
+        for child in &children {
+            self.read_element(child, depth + 1, items);
+        }
+    }
+
+    /// renders the widget of `element` on its own and reads the rows back, leaving out the box
+    /// drawing characters of its block's border so they don't end up in the text.
+    fn visible_text(&self, element: Element, props: &Props) -> String {
+        let area = Rect::new(0, 0, props.size.x, props.size.y);
+        let mut buf = Buffer::empty(area);
+        (props.render)(self, element, area, &mut buf);
+        let inner = if let Ok(block) = self.world.get::<&Block>(element) {
+            block.inner(area)
+        } else if let Ok(source) = self.world.get::<&SourceParagraph>(element) {
+            source.inner(area)
+        } else if [
+            TypeId::of::<Text<'static>>(),
+            TypeId::of::<Line<'static>>(),
+            TypeId::of::<Span<'static>>(),
+        ]
+        .contains(&props.typeid)
+        {
+            area
+        } else {
+            // the block of other widgets, like a `Paragraph`, can't be read back. its border
+            // would be the outermost cells.
+            area.inner(Margin::new(1, 1))
+        };
+        area.rows()
+            .map(|row| {
+                row.positions()
+                    .map(|position| {
+                        let symbol = buf[position].symbol();
+                        if !inner.contains(position) && symbol.chars().all(is_border) {
+                            " "
+                        } else {
+                            symbol
+                        }
+                    })
+                    .collect::<String>()
+            })
+            .filter_map(|row| {
+                let row = row.trim();
+                (!row.is_empty()).then(|| row.to_string())
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn dump_node(&self, element: Element) -> NodeDump {
+        let entity = self.world.entity(element).ok();
+        let props = entity.and_then(|entity| entity.get::<&Props>().map(|props| *props));
+        let children = entity
+            .and_then(|entity| entity.get::<&Children>().map(|children| children.clone()))
+            .unwrap_or_default();
+        NodeDump {
+            entity: element.to_bits().get(),
+            widget: props.map_or_else(String::new, |props| props.type_name.to_string()),
+            rect: props.map_or_else(Rect::default, |props| {
+                Rect::new(
+                    props.position.x,
+                    props.position.y,
+                    props.size.x,
+                    props.size.y,
+                )
+            }),
+            id: entity.and_then(|entity| entity.get::<&ElId>().map(|id| id.0.clone())),
+            width: debug::<Width>(entity),
+            height: debug::<Height>(entity),
+            padding: debug::<Padding>(entity),
+            gap: debug::<Gap>(entity),
+            direction: debug::<Direction>(entity),
+            main_justify: debug::<MainJustify>(entity),
+            cross_justify: debug::<CrossJustify>(entity),
+            align_self: debug::<AlignSelf>(entity),
+            children: children
+                .into_iter()
+                .map(|child| self.dump_node(child))
+                .collect(),
+        }
+    }
+}
+
+fn debug<T: hecs::Component + std::fmt::Debug>(entity: Option<EntityRef<'_>>) -> Option<String> {
+    entity?.get::<&T>().map(|value| format!("{:?}", *value))
+}
+
+/// box drawing and block element characters, used for borders.
+fn is_border(c: char) -> bool {
+    ('\u{2500}'..='\u{259f}').contains(&c)
+}
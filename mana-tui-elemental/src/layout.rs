@@ -2,22 +2,41 @@
 //!
 //! implements the layout algorithm.
 use std::{
-    any::TypeId,
+    any::{Any, TypeId},
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+    marker::PhantomData,
     ops::{Deref, Div},
-    sync::Arc,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
 };
 
 use derive_more as d;
 use glam::{U16Vec2, u16vec2};
-use hecs::{CommandBuffer, Component, ComponentError, Entity, Query, World};
-use mana_tui_utils::{Ecs, EcsMut};
+use hecs::{
+    CommandBuffer, Component, ComponentError, ComponentRef, Entity, EntityRef, Query, QueryBorrow,
+    QueryOne, World,
+};
+use mana_tui_utils::{Ecs, EcsMut, resource::Resources};
+
+use crate::inspect::ElId;
+use crate::profile::Phase;
+use crate::text_hit::SourceParagraph;
+use crate::theme::DefaultStyles;
+use crate::ui::View;
 use ratatui::{
-    buffer::Buffer,
-    layout::{Direction, Margin, Rect},
-    style::{Style, Styled},
-    widgets::{Padding, Widget},
+    buffer::{Buffer, Cell},
+    crossterm::event::KeyCode,
+    layout::{Direction, Position, Rect},
+    style::{Color, Modifier, Style, Styled},
+    text::{Line, Span, Text},
+    widgets::{Block, Clear, Padding, Paragraph, Widget, Wrap},
 };
 use ratatui::{layout::Offset, widgets::StatefulWidget};
+use tui_scrollview::ScrollbarVisibility;
 pub use tui_scrollview::{ScrollView, ScrollViewState};
 
 /// trait for rendering elements through a shared reference. this is automatically implemented
@@ -29,6 +48,23 @@ pub trait ElWidget<M>: std::fmt::Debug + Component {
     fn set_style(&mut self, style: Style);
     /// gets the style of a widget
     fn get_style(&self) -> Style;
+    /// a hash of everything the widget draws, used to skip rendering unchanged elements in
+    /// [`ElementCtx::render_incremental`].
+    ///
+    /// [`None`] by default, the element is then rendered every frame unless it has a
+    /// [`ContentVersion`]. ratatui's [`Text`], [`Line`], [`Span`], [`Paragraph`] and [`Block`]
+    /// are hashed.
+    fn fingerprint(&self) -> Option<u64> {
+        None
+    }
+    /// the width and height of the content, for elements spawned without a [`Width`] or
+    /// [`Height`].
+    ///
+    /// [`None`] by default, the element then fits its children. ratatui's [`Text`], [`Line`]
+    /// and [`Span`] are measured.
+    fn content_size(&self) -> Option<(usize, usize)> {
+        None
+    }
 }
 
 /// marker for [`ElWidget`] trait.
@@ -49,6 +85,771 @@ where
     fn get_style(&self) -> Style {
         self.style()
     }
+
+    fn fingerprint(&self) -> Option<u64> {
+        hash_widget(self)
+    }
+
+    fn content_size(&self) -> Option<(usize, usize)> {
+        measure_widget(self)
+    }
+}
+
+/// hashes the widgets whose content is known to implement [`Hash`].
+fn hash_widget(widget: &dyn Any) -> Option<u64> {
+    let mut hasher = DefaultHasher::new();
+    widget.type_id().hash(&mut hasher);
+    if let Some(text) = widget.downcast_ref::<Text<'static>>() {
+        text.hash(&mut hasher);
+    } else if let Some(line) = widget.downcast_ref::<Line<'static>>() {
+        line.hash(&mut hasher);
+    } else if let Some(span) = widget.downcast_ref::<Span<'static>>() {
+        span.hash(&mut hasher);
+    } else if let Some(paragraph) = widget.downcast_ref::<Paragraph<'static>>() {
+        paragraph.hash(&mut hasher);
+    } else if let Some(paragraph) = widget.downcast_ref::<SourceParagraph>() {
+        paragraph.hash(&mut hasher);
+    } else if let Some(block) = widget.downcast_ref::<Block<'static>>() {
+        block.hash(&mut hasher);
+    } else {
+        return None;
+    }
+    Some(hasher.finish())
+}
+
+/// measures the widgets that are sized after their text.
+fn measure_widget(widget: &dyn Any) -> Option<(usize, usize)> {
+    if let Some(text) = widget.downcast_ref::<Text<'static>>() {
+        Some((text.width(), text.height()))
+    } else if let Some(line) = widget.downcast_ref::<Line<'static>>() {
+        Some((line.width(), 1))
+    } else {
+        widget
+            .downcast_ref::<Span<'static>>()
+            .map(|span| (span.width(), 1))
+    }
+}
+
+/// marker for [`ElWidget`] implementations on widgets that have no style of their own.
+pub struct UnstyledMarker;
+
+impl ElWidget<UnstyledMarker> for Clear {
+    fn render_element(&self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+    }
+
+    fn set_style(&mut self, _: Style) {}
+
+    fn get_style(&self) -> Style {
+        Style::default()
+    }
+    fn fingerprint(&self) -> Option<u64> {
+        Some(0)
+    }
+}
+
+/// marker for the [`ElWidget`] implementation of [`DynWidget`].
+pub struct DynMarker;
+
+/// a widget whose type is only known at runtime, like a panel provided by a plugin. make one
+/// with [`dyn_widget`] and use it like any other widget, [`ElementCtx::swap_widget`] changes it
+/// later.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// # let bordered = true;
+/// let panel = if bordered {
+///     dyn_widget(Block::bordered())
+/// } else {
+///     dyn_widget(Paragraph::new("plain"))
+/// };
+/// ui(panel).with((Width::grow(), Height::grow()));
+/// ```
+pub type DynWidget = Box<dyn ElWidget<DynMarker>>;
+
+/// boxes `widget` as a [`DynWidget`].
+pub fn dyn_widget<M: 'static, W: ElWidget<M>>(widget: W) -> DynWidget {
+    Box::new(Erased(widget, PhantomData))
+}
+
+/// a widget with the marker of its [`ElWidget`] implementation erased, so it fits in a
+/// [`DynWidget`].
+struct Erased<M, W>(W, PhantomData<fn() -> M>);
+
+impl<M, W: std::fmt::Debug> std::fmt::Debug for Erased<M, W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<M: 'static, W: ElWidget<M>> ElWidget<DynMarker> for Erased<M, W> {
+    fn render_element(&self, area: Rect, buf: &mut Buffer) {
+        self.0.render_element(area, buf);
+    }
+
+    fn set_style(&mut self, style: Style) {
+        self.0.set_style(style);
+    }
+
+    fn get_style(&self) -> Style {
+        self.0.get_style()
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        self.0.fingerprint()
+    }
+
+    fn content_size(&self) -> Option<(usize, usize)> {
+        self.0.content_size()
+    }
+}
+
+impl ElWidget<DynMarker> for DynWidget {
+    fn render_element(&self, area: Rect, buf: &mut Buffer) {
+        (**self).render_element(area, buf);
+    }
+
+    fn set_style(&mut self, style: Style) {
+        (**self).set_style(style);
+    }
+
+    fn get_style(&self) -> Style {
+        (**self).get_style()
+    }
+
+    fn fingerprint(&self) -> Option<u64> {
+        (**self).fingerprint()
+    }
+
+    fn content_size(&self) -> Option<(usize, usize)> {
+        (**self).content_size()
+    }
+}
+
+/// a widget that draws nothing, for elements that only lay out their children.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// ui(Container)
+///     .with((Width::grow(), Height::grow(), Direction::Horizontal))
+///     .children((ui(Text::raw("left")), ui(Text::raw("right"))));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NopWidget;
+
+/// [`NopWidget`] under a name that says what it is used for.
+pub use NopWidget as Container;
+
+impl ElWidget<UnstyledMarker> for NopWidget {
+    fn render_element(&self, _: Rect, _: &mut Buffer) {}
+
+    fn set_style(&mut self, _: Style) {}
+
+    fn get_style(&self) -> Style {
+        Style::default()
+    }
+    fn fingerprint(&self) -> Option<u64> {
+        Some(0)
+    }
+}
+
+/// the size an element takes when it has no explicit [`Width`] or [`Height`]. useful for
+/// elements like [`raw`][crate::ui::raw] that can't measure their content.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, d::Deref)]
+pub struct PreferredSize(pub U16Vec2);
+
+/// measures the content of an element with a [`Size::Fit`] or [`Size::Grow`] size. called with
+/// the element and its fixed width, or [`u16::MAX`] if the width isn't fixed, and returns the
+/// size of the content without padding.
+///
+/// results are kept in a [`MeasureCache`] and reused until the width or the content changes,
+/// see [`ContentVersion`].
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// # use glam::u16vec2;
+/// ui(Block::new()).with((Width::fit(), Height::fit(), Measure::new(|_, _| u16vec2(4, 2))));
+/// ```
+#[derive(Clone, d::Deref)]
+pub struct Measure(pub Arc<dyn Fn(EntityRef<'_>, u16) -> U16Vec2 + Send + Sync + 'static>);
+
+impl Measure {
+    /// creates a new measure function.
+    pub fn new(f: impl Fn(EntityRef<'_>, u16) -> U16Vec2 + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+}
+
+/// the last result of an element's [`Measure`], maintained by the layout engine.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MeasureCache {
+    /// the width the element was measured with.
+    pub width_constraint: u16,
+    /// the measured size.
+    pub result: U16Vec2,
+    /// hash of the content the element was measured with.
+    pub content_hash: u64,
+}
+
+/// what the layout of an element depends on. the children are part of it through their hashes,
+/// so the inputs of a subtree only match if nothing below changed either.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct LayoutInputs {
+    width: Size,
+    height: Size,
+    padding: Padding,
+    margin: Margin,
+    gap: u16,
+    direction: Direction,
+    main_justify: MainJustify,
+    cross_justify: CrossJustify,
+    align_self: Option<CrossJustify>,
+    flex_basis: Option<u16>,
+    /// the ratio as bits, floats aren't [`Hash`].
+    split: Option<(u32, u16, u16)>,
+    flow_order: Option<FlowOrder>,
+    sticky_top: Option<u16>,
+    scroll_top: Option<u16>,
+    measured: Option<[u16; 2]>,
+    text_height: Option<usize>,
+    visible: std::ops::Range<usize>,
+    child_hashes: Vec<LayoutInputsHash>,
+}
+
+/// the hash of the [`LayoutInputs`] of an element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct LayoutInputsHash(u64);
+
+impl LayoutInputsHash {
+    fn new(inputs: &LayoutInputs) -> Self {
+        let mut hasher = DefaultHasher::new();
+        inputs.hash(&mut hasher);
+        Self(hasher.finish())
+    }
+}
+
+/// the layout of an element in the last [`ElementCtx::calculate_layout`], maintained by the
+/// layout engine. while the inputs of an element and the space it gets stay the same, the grow
+/// and position passes keep the sizes and positions of its subtree instead of descending. a tree
+/// spawned in place of a despawned one takes its memos over, see [`ElementCtx::retire_layout`].
+#[derive(Debug, Clone, Default)]
+struct LayoutMemo {
+    /// `None` for elements that are laid out again every time.
+    hash: Option<LayoutInputsHash>,
+    /// the inputs behind the hash, compared on a match so a collision only costs a layout.
+    #[cfg(debug_assertions)]
+    inputs: Option<LayoutInputs>,
+    /// the inputs match the last layout, the fit pass left the subtree alone.
+    hit: bool,
+    /// the size after the fit pass.
+    fit: U16Vec2,
+    /// the size the parent grows from, after its flex basis.
+    basis: U16Vec2,
+    /// the space of the last grow pass, the area for a root and the size for other elements.
+    constraint: Option<U16Vec2>,
+    /// the grow pass kept the sizes of the subtree.
+    reused: bool,
+    /// the position of the last position pass.
+    position: Option<U16Vec2>,
+}
+
+/// the layouts of despawned trees, see [`ElementCtx::retire_layout`].
+#[derive(Debug, Default)]
+struct RetiredLayouts {
+    /// the number of layouts since the first tree was retired.
+    layouts: u64,
+    /// by the [`ElId`] and the shape of the root, with the layout count they were retired at.
+    trees: HashMap<(Option<ElId>, u64), Vec<(u64, RetiredLayout)>>,
+}
+
+/// the memo and the layout of an element of a despawned tree, along with its children.
+#[derive(Debug)]
+struct RetiredLayout {
+    memo: LayoutMemo,
+    size: U16Vec2,
+    position: U16Vec2,
+    children: Vec<RetiredLayout>,
+}
+
+/// sets the height of the element from its width, see
+/// [`AspectRatioBox`][crate::ui::AspectRatioBox]. the height is known in the fit pass for fixed
+/// widths, and in the grow pass of the parent otherwise, before the parent shares its space out.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HeightFromWidth {
+    /// height divided by width.
+    pub ratio: f32,
+}
+
+impl HeightFromWidth {
+    fn height(self, width: u16) -> u16 {
+        (f32::from(width) * self.ratio) as u16
+    }
+}
+
+/// version of an element's content, bump it to measure the element again. without it the
+/// content is hashed from the element's [`Text`], [`Line`] or [`Span`], and elements with none of
+/// those are only measured again after [`ElementCtx::invalidate_measure`].
+///
+/// [`ElementCtx::render_incremental`] also uses it to reuse elements whose widget has no
+/// [fingerprint](ElWidget::fingerprint).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, d::Deref)]
+pub struct ContentVersion(pub u64);
+
+/// resets the element's area before its widget is rendered, like rendering a [`Clear`] widget
+/// underneath it. useful for popups drawn on top of other elements. a [`Clear`] element only
+/// clears its own area and is drawn after its parent, so it can't do this for the parent.
+///
+/// see [`UiBuilder::clear_background`][crate::ui::UiBuilder::clear_background].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ClearBackground;
+
+/// fills a rectangle of `style` the size of the element behind it, moved by `offset`. see
+/// [`UiBuilder::shadow`][crate::ui::UiBuilder::shadow].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shadow {
+    /// how far the shadow is moved from the element. defaults to one cell right and down.
+    pub offset: U16Vec2,
+    /// style of the shadow cells.
+    pub style: Style,
+}
+
+impl Shadow {
+    /// a shadow of `style` one cell right and down of the element.
+    #[must_use]
+    pub const fn new(style: Style) -> Self {
+        Self {
+            offset: U16Vec2::new(1, 1),
+            style,
+        }
+    }
+}
+
+impl Default for Shadow {
+    fn default() -> Self {
+        Self::new(Style::new().bg(Color::Black))
+    }
+}
+
+/// draws a badge like `[q]` over a corner of the element, after its widget and children. works
+/// on any element, unlike a block title. see
+/// [`UiBuilder::shortcut_hint`][crate::ui::UiBuilder::shortcut_hint].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyboardShortcutHint {
+    /// the key shown in the badge.
+    pub key: KeyCode,
+    /// the corner of the element the badge is drawn at.
+    pub position: HintPosition,
+    /// style of the badge. bold by default.
+    pub style: Style,
+}
+
+/// the corner of an element a [`KeyboardShortcutHint`] is drawn at.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, strum::EnumIter)]
+pub enum HintPosition {
+    /// the top left corner.
+    TopLeft,
+    /// the top right corner.
+    #[default]
+    TopRight,
+    /// the bottom left corner.
+    BottomLeft,
+    /// the bottom right corner.
+    BottomRight,
+}
+
+impl KeyboardShortcutHint {
+    /// a bold badge for `key` at `position`.
+    #[must_use]
+    pub const fn new(key: KeyCode, position: HintPosition) -> Self {
+        Self {
+            key,
+            position,
+            style: Style::new().add_modifier(Modifier::BOLD),
+        }
+    }
+
+    /// the text of the badge, like `[q]` or `[Enter]`.
+    #[must_use]
+    pub fn label(&self) -> String {
+        format!("[{}]", self.key)
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+        let label = Span::styled(self.label(), self.style);
+        let width = u16::try_from(label.width())
+            .unwrap_or(u16::MAX)
+            .min(area.width);
+        let x = match self.position {
+            HintPosition::TopLeft | HintPosition::BottomLeft => area.x,
+            HintPosition::TopRight | HintPosition::BottomRight => area.right() - width,
+        };
+        let y = match self.position {
+            HintPosition::TopLeft | HintPosition::TopRight => area.y,
+            HintPosition::BottomLeft | HintPosition::BottomRight => area.bottom() - 1,
+        };
+        label.render(Rect::new(x, y, width, 1), buf);
+    }
+}
+
+/// the area of the terminal a root element was last rendered into. inserted by
+/// [`ElementCtx::render_viewport`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, d::Deref)]
+pub struct Viewport(pub Rect);
+
+/// restricts the rendering of the children of an element to a rectangle, for example the
+/// visible part of a virtualized list. the rectangle is in the same coordinates as the layout.
+///
+/// the element itself is rendered unclipped. children are never rendered outside the area of
+/// their parent, so nested regions intersect. a child cut by the region is drawn as if it was
+/// whole, and wide characters cut by the edges of the region are replaced with spaces.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// # use ratatui::layout::Rect;
+/// ui(Block::bordered())
+///     .with((ClipRegion(Rect::new(0, 0, 20, 5)),))
+///     .children((ui(Text::raw("visible")), ui(Text::raw("maybe not"))));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, d::Deref)]
+pub struct ClipRegion(pub Rect);
+
+/// marks the edges of an element its children don't fit in, opt in on containers.
+///
+/// after the children are rendered, a symbol is drawn in the last cell of the element on the
+/// main axis when a child reaches past its [`Padding`]. a [`ScrollView`] element shows one on
+/// each side with content scrolled out of view. the style comes from
+/// [`DefaultStyles::overflow_style`].
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// // shows `aaaaaabbb▶` in 10 cells.
+/// ui(Block::new())
+///     .with((Width::fixed(10), Direction::Horizontal, OverflowIndicator::Arrows))
+///     .children((
+///         ui(Text::raw("aaaaaa")),
+///         ui(Text::raw("bbbbbb")),
+///         ui(Text::raw("cccccc")),
+///     ));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum OverflowIndicator {
+    /// `◀` and `▶` in rows, `▲` and `▼` in columns.
+    #[default]
+    Arrows,
+    /// `…` on every side.
+    Ellipsis,
+}
+
+impl OverflowIndicator {
+    /// the symbol for the start or the end of the main axis.
+    fn symbol(self, direction: Direction, end: bool) -> &'static str {
+        match (self, direction, end) {
+            (OverflowIndicator::Ellipsis, ..) => "…",
+            (OverflowIndicator::Arrows, Direction::Horizontal, false) => "◀",
+            (OverflowIndicator::Arrows, Direction::Horizontal, true) => "▶",
+            (OverflowIndicator::Arrows, Direction::Vertical, false) => "▲",
+            (OverflowIndicator::Arrows, Direction::Vertical, true) => "▼",
+        }
+    }
+}
+
+/// what an element does with children that don't fit in it.
+///
+/// the scrolling variants attach a [`ScrollView`] and a [`ScrollViewState`] to the element when
+/// it's spawned, scroll it by changing the offset of the state. the scroll view is as big as
+/// the children, scrollbars are shown when it's bigger than the element.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// ui(Block::new())
+///     .with((Height::fixed(5), Overflow::ScrollY))
+///     .children((0..20).map(|i| ui(Text::raw(format!("line {i}")))).collect::<Vec<_>>());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Overflow {
+    /// children are drawn up to the edges of the element, over its borders.
+    #[default]
+    Visible,
+    /// children are cut at the edges of the [`Padding`] of the element.
+    Clip,
+    /// scrollable through the [`ScrollViewState`], without scrollbars.
+    Hidden,
+    /// scrollable on both axes.
+    Scroll,
+    /// scrollable horizontally, the content is as wide as the children.
+    ScrollX,
+    /// scrollable vertically, the content is as tall as the children.
+    ScrollY,
+}
+
+impl Overflow {
+    /// whether the element gets a [`ScrollView`].
+    #[must_use]
+    pub fn scrolls(self) -> bool {
+        matches!(
+            self,
+            Overflow::Hidden | Overflow::Scroll | Overflow::ScrollX | Overflow::ScrollY
+        )
+    }
+
+    /// the visibility of the horizontal and vertical scrollbars.
+    fn scrollbars(self) -> (ScrollbarVisibility, ScrollbarVisibility) {
+        use ScrollbarVisibility::{Automatic, Never};
+        match self {
+            Overflow::Scroll => (Automatic, Automatic),
+            Overflow::ScrollX => (Automatic, Never),
+            Overflow::ScrollY => (Never, Automatic),
+            Overflow::Visible | Overflow::Clip | Overflow::Hidden => (Never, Never),
+        }
+    }
+}
+
+/// keeps a child of a [`ScrollView`] element visible once the content is scrolled past it, `n`
+/// rows below the top of the visible content. it scrolls normally before that, and never leaves
+/// its scroll view.
+///
+/// sticky children are drawn over their siblings.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// ui(Block::new())
+///     .with((ScrollView::default(), ScrollViewState::new()))
+///     .children((
+///         ui(Text::raw("header")).with((StickyTop(0),)),
+///         ui(Text::raw("a long list")),
+///     ));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, d::Deref)]
+pub struct StickyTop(pub u16);
+
+/// pins a child of a [`ScrollView`] element where it was laid out, the rest of the content
+/// scrolls beneath it. use it for header rows, unlike [`StickyTop`] it never scrolls.
+///
+/// the scrolled content is only drawn below the lowest sticky child, so use it for children at
+/// the top of the content. several sticky children stack in the order they were laid out.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// ui(Block::new())
+///     .with((ScrollView::default(), ScrollViewState::new()))
+///     .children((
+///         ui(Text::raw("time  level  message")).with((Sticky,)),
+///         ui(Text::raw("a long log")),
+///     ));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Sticky;
+
+/// size of the terminal, updated by [`ElementCtx::on_resize`]. stored as a resource, read it
+/// with [`Resources::get_resource`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, d::Deref)]
+pub struct TerminalSize(pub U16Vec2);
+
+impl TerminalSize {
+    /// the whole terminal, to lay out a tree that fills it.
+    #[must_use]
+    pub fn area(self) -> Rect {
+        Rect::new(0, 0, self.0.x, self.0.y)
+    }
+}
+
+/// timing of the last [`ElementCtx::calculate_layout`] call. stored as a resource, read it with
+/// [`Resources::get_resource`].
+///
+/// the resource only has the timings, use [`ElementCtx::layout_statistics`] to count the
+/// elements as well.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LayoutStats {
+    /// how long the last layout calculation took.
+    pub duration: Duration,
+    /// number of elements in the world.
+    pub elements: usize,
+    /// number of elements in the deepest path from a root to a leaf. a lone root has a depth of 1.
+    pub max_depth: usize,
+    /// number of elements with a [`Size::Grow`] width or height.
+    pub grow_elements: usize,
+    /// number of elements with a [`Padding`] other than [`Padding::ZERO`].
+    pub padded_elements: usize,
+    /// time spent measuring content and computing fit sizes in the last layout.
+    pub last_fit_pass_duration_ns: u64,
+    /// time spent growing elements and resolving wrapped heights in the last layout.
+    pub last_grow_pass_duration_ns: u64,
+    /// time spent positioning elements in the last layout.
+    pub last_position_pass_duration_ns: u64,
+    /// elements the grow pass visited in the last layout. a subtree laid out with the same
+    /// inputs and space as in the layout before keeps its sizes and is only visited at its root.
+    pub grow_visits: usize,
+    /// elements the position pass visited in the last layout, see [`LayoutStats::grow_visits`].
+    pub position_visits: usize,
+}
+
+impl std::fmt::Display for LayoutStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} elements, depth {}, {} grow, {} padded, layout {:?} (fit {:?}, grow {:?}, position {:?})",
+            self.elements,
+            self.max_depth,
+            self.grow_elements,
+            self.padded_elements,
+            self.duration,
+            Duration::from_nanos(self.last_fit_pass_duration_ns),
+            Duration::from_nanos(self.last_grow_pass_duration_ns),
+            Duration::from_nanos(self.last_position_pass_duration_ns),
+        )
+    }
+}
+
+/// places the terminal cursor inside the element.
+///
+/// `position` is relative to the element's top left corner. a cursor with no position is hidden,
+/// so toggling it is enough to show or hide the cursor when the element gains or loses focus.
+/// use [`ElementCtx::cursor`] after rendering to get the absolute cursor position.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cursor {
+    /// the shape of the cursor.
+    pub shape: CursorShape,
+    /// cursor position relative to the element. `None` hides the cursor.
+    pub position: Option<U16Vec2>,
+}
+
+/// shape of the terminal cursor. mirrors crossterm's `SetCursorStyle` so the layout engine does
+/// not depend on a specific backend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CursorShape {
+    /// the shape configured by the user's terminal.
+    #[default]
+    DefaultUserShape,
+    /// blinking block `█`
+    BlinkingBlock,
+    /// non blinking block `█`
+    SteadyBlock,
+    /// blinking underscore `_`
+    BlinkingUnderScore,
+    /// non blinking underscore `_`
+    SteadyUnderScore,
+    /// blinking bar `|`
+    BlinkingBar,
+    /// non blinking bar `|`
+    SteadyBar,
+}
+
+/// makes a [`Text`] element editable in place while it's focused. the runtime inserts typed
+/// characters into `value` and redraws the text from it, see [`ContentEditable::line`].
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// ui(Text::default()).with((ContentEditable::new("draft"), Width::fixed(20)));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentEditable {
+    /// the edited text.
+    pub value: String,
+    /// the cursor position, in characters from the start of `value`.
+    pub cursor: usize,
+}
+
+impl ContentEditable {
+    /// an editable `value` with the cursor at its end.
+    pub fn new(value: impl Into<String>) -> Self {
+        let value = value.into();
+        let cursor = value.chars().count();
+        Self { value, cursor }
+    }
+
+    /// inserts `c` before the cursor.
+    pub fn insert(&mut self, c: char) {
+        let idx = self.byte_index();
+        self.value.insert(idx, c);
+        self.cursor += 1;
+    }
+
+    /// removes the character before the cursor. returns true if there was one.
+    pub fn backspace(&mut self) -> bool {
+        if self.cursor == 0 {
+            return false;
+        }
+        self.cursor -= 1;
+        let idx = self.byte_index();
+        self.value.remove(idx);
+        true
+    }
+
+    /// removes the character under the cursor. returns true if there was one.
+    pub fn delete(&mut self) -> bool {
+        let idx = self.byte_index();
+        if idx == self.value.len() {
+            return false;
+        }
+        self.value.remove(idx);
+        true
+    }
+
+    /// moves the cursor one character left.
+    pub fn move_left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    /// moves the cursor one character right.
+    pub fn move_right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.value.chars().count());
+    }
+
+    /// moves the cursor to the start.
+    pub fn move_home(&mut self) {
+        self.cursor = 0;
+    }
+
+    /// moves the cursor to the end.
+    pub fn move_end(&mut self) {
+        self.cursor = self.value.chars().count();
+    }
+
+    /// the line shown by the element. `focused` draws the cursor as a reversed cell, a space
+    /// when it's past the last character.
+    #[must_use]
+    pub fn line(&self, focused: bool) -> Line<'static> {
+        if !focused {
+            return Line::raw(self.value.clone());
+        }
+        let idx = self.byte_index();
+        let (before, rest) = self.value.split_at(idx);
+        let mut rest = rest.chars();
+        let under = rest.next().map_or_else(|| " ".to_string(), String::from);
+        Line::from(vec![
+            Span::raw(before.to_string()),
+            Span::styled(under, Style::new().add_modifier(Modifier::REVERSED)),
+            Span::raw(rest.as_str().to_string()),
+        ])
+    }
+
+    fn byte_index(&self) -> usize {
+        self.value
+            .char_indices()
+            .nth(self.cursor)
+            .map_or(self.value.len(), |(idx, _)| idx)
+    }
 }
 
 /// Context struct that drives the layout engine.
@@ -84,6 +885,17 @@ pub struct ElementCtx {
     #[deref]
     #[deref_mut]
     pub(crate) world: World,
+    /// whether the [`Profiler`][crate::profile::Profiler] records, checked once per layout.
+    pub(crate) profiling: bool,
+    /// elements visited by the passes of the running layout.
+    pub(crate) layout_visits: LayoutVisits,
+}
+
+/// counts the elements the passes of a layout visit, see [`LayoutStats::grow_visits`].
+#[derive(Debug, Default)]
+pub(crate) struct LayoutVisits {
+    grow: AtomicUsize,
+    position: AtomicUsize,
 }
 
 impl Ecs for ElementCtx {}
@@ -94,11 +906,75 @@ impl ElementCtx {
     pub fn new() -> Self {
         Self::default()
     }
+    /// queries the elements of the context, see [`hecs::World::query`].
+    ///
+    /// useful inside subviews that read their data from the ecs.
+    pub fn query<Q: Query>(&self) -> QueryBorrow<'_, Q> {
+        self.world.query::<Q>()
+    }
+    /// queries a single element, see [`hecs::World::query_one`].
+    pub fn query_one<Q: Query>(&self, element: Element) -> QueryOne<'_, Q> {
+        self.world.query_one::<Q>(element)
+    }
+    /// borrows a single component of an element, see [`hecs::World::get`].
+    ///
+    /// # Error
+    ///
+    /// errors if the element doesn't exist or doesn't have the component.
+    pub fn get<'a, T: ComponentRef<'a>>(
+        &'a self,
+        element: Element,
+    ) -> Result<T::Ref, ComponentError> {
+        self.world.get::<T>(element)
+    }
+    /// stores `value` as a resource, replacing the previous resource of the same type.
+    /// see [`Resources`].
+    pub fn insert_or_update_resource<T: Component>(&mut self, value: T) {
+        self.world.insert_or_update_resource(value);
+    }
+    /// borrows a resource, like `ctx.get_resource::<&mut MyResource>()`.
+    ///
+    /// # Error
+    ///
+    /// errors if the resource doesn't exist.
+    pub fn get_resource<'a, T: ComponentRef<'a>>(&'a self) -> Result<T::Ref, ComponentError> {
+        self.world.get_resource::<T>()
+    }
+    /// removes a resource and returns it.
+    ///
+    /// # Error
+    ///
+    /// errors if the resource doesn't exist.
+    pub fn remove_resource<T: Component>(&mut self) -> Result<T, ComponentError> {
+        self.world.remove_resource::<T>()
+    }
     fn calculate_fit_sizes(&self, element: Element) -> Result<(), ComponentError> {
+        let _timer = self.profile(element, Phase::Layout);
         let mut query = self
             .world
             .query_one::<(&Width, &Height, &Padding, &Children, &Direction)>(element);
         let (width, height, padding, children, direction) = query.get().unwrap();
+
+        let visible = self.visible_range(element, children.len());
+        for (idx, child) in children.iter().enumerate() {
+            if !visible.contains(&idx) {
+                self.collapse(child);
+            }
+        }
+        let children = &children[visible];
+
+        children
+            .iter()
+            .try_for_each(|&child| -> Result<(), ComponentError> {
+                self.calculate_fit_sizes(child)?;
+                Ok(())
+            })?;
+
+        // the subtree keeps the sizes of the last layout, the grow pass decides if they still fit.
+        if self.memoize_inputs(element, children) {
+            return Ok(());
+        }
+
         let mut props_query = self.world.query_one::<&mut Props>(element);
         let props = props_query.get().unwrap();
 
@@ -113,14 +989,10 @@ impl ElementCtx {
 
         drop(props_query);
 
-        children
-            .iter()
-            .try_for_each(|child| -> Result<(), ComponentError> {
-                self.calculate_fit_sizes(child)?;
-                Ok(())
-            })?;
-
-        for child in children {
+        for &child in children {
+            let margin = self.margin(child);
+            self.restore_fit_size(child);
+            self.apply_flex_basis(child, *direction)?;
             let mut child_props = self.world.get::<&mut Props>(child)?;
             if width.should_clamp() {
                 child_props.size.x = child_props.size.x.clamp(0, inner_size.x);
@@ -128,7 +1000,11 @@ impl ElementCtx {
             if height.should_clamp() {
                 child_props.size.y = child_props.size.y.clamp(0, inner_size.x);
             }
-            space_used = space_used.increase(child_props.size, *direction);
+            if let Ok(mut memo) = self.world.get::<&mut LayoutMemo>(child) {
+                memo.basis = child_props.size;
+            }
+            space_used =
+                space_used.increase(child_props.size.saturating_add(margin.size()), *direction);
         }
 
         let mut query = self.world.query_one::<(
@@ -142,8 +1018,11 @@ impl ElementCtx {
         )>(element);
         let (props, width, height, padding, children, direction, gap) = query.get().unwrap();
 
+        let visible = self.visible_range(element, children.len()).len();
         space_used = space_used.pad(*padding, *direction);
-        space_used.main_axis += children.len().saturating_sub(1) as u16 * **gap;
+        space_used.main_axis = space_used
+            .main_axis
+            .saturating_add(gap_space(visible, *gap));
         let space_used = space_used.to_u16vec2(*direction);
         match **width {
             Size::Fit | Size::Grow => {
@@ -157,16 +1036,68 @@ impl ElementCtx {
             }
             _ => {}
         }
+        if let Ok(cache) = self.world.get::<&MeasureCache>(element) {
+            let measured = cache.result.saturating_add(padding_size(padding));
+            if let Size::Fit | Size::Grow = **width {
+                props.size.x = props.size.x.max(measured.x);
+            }
+            if let Size::Fit | Size::Grow = **height {
+                props.size.y = props.size.y.max(measured.y);
+            }
+        }
+        if let Size::Fixed(_) = **width
+            && let Ok(height_from_width) = self.world.get::<&HeightFromWidth>(element)
+        {
+            props.size.y = height_from_width.height(props.size.x);
+        }
+        if let Size::Fit = **height
+            && self.world.get::<&FitText>(element).is_ok()
+            && let Ok(text) = self.world.get::<&Text<'static>>(element)
+        {
+            props.size.y = props.size.y.max(text.height() as u16);
+        }
+        if let Ok(mut memo) = self.world.get::<&mut LayoutMemo>(element) {
+            memo.fit = props.size;
+        }
         tracing::trace!(target: "mana-tui::fit", ?element, ?props.size);
         Ok(())
     }
+    /// raises the main axis size of a growing child to its [`FlexBasis`], so a parent that fits
+    /// its children makes room for the basis. the grow pass starts the child at its basis again.
+    fn apply_flex_basis(&self, child: Element, direction: Direction) -> Result<(), ComponentError> {
+        let mut query = self
+            .world
+            .query_one::<(&mut Props, &Width, &Height, &FlexBasis)>(child);
+        let Ok((props, &width, &height, &FlexBasis(basis))) = query.get() else {
+            return Ok(());
+        };
+        if !main_size(direction, width, height).is_grow() {
+            return Ok(());
+        }
+        let mut size = axify(props.size, direction);
+        size.main_axis = size.main_axis.max(basis);
+        props.size = size.to_u16vec2(direction);
+        Ok(())
+    }
     fn sum_space_used(&self, elements: &[Element]) -> U16Vec2 {
         elements
             .iter()
             .copied()
-            .flat_map(|child| self.world.get::<&Props>(child))
-            .map(|props| props.size)
-            .sum::<U16Vec2>()
+            .map(|child| self.outer_size(child))
+            .fold(U16Vec2::ZERO, U16Vec2::saturating_add)
+    }
+    fn margin(&self, element: Element) -> Margin {
+        self.world
+            .get::<&Margin>(element)
+            .map(|margin| *margin)
+            .unwrap_or_default()
+    }
+    /// the size of the element including its [`Margin`].
+    fn outer_size(&self, element: Element) -> U16Vec2 {
+        self.world
+            .get::<&Props>(element)
+            .map(|props| props.size.saturating_add(self.margin(element).size()))
+            .unwrap_or_default()
     }
     fn calculate_grow_sizes(
         &self,
@@ -174,22 +1105,43 @@ impl ElementCtx {
         is_root: bool,
         area: Rect,
     ) -> Result<(), ComponentError> {
-        let mut query = self
-            .world
-            .query_one::<(&mut Props, &Padding, &Children, &Direction, &Gap)>(element);
-        let (props, &padding, children, &direction, &gap) = query.get().unwrap();
+        let _timer = self.profile(element, Phase::Layout);
+        self.layout_visits.grow.fetch_add(1, Ordering::Relaxed);
+        let constraint = if is_root {
+            u16vec2(area.width, area.height)
+        } else {
+            self.world.get::<&Props>(element)?.size
+        };
+        if self.reuse_sizes(element, constraint) {
+            return Ok(());
+        }
+        let mut query = self.world.query_one::<(
+            &mut Props,
+            &Padding,
+            &Children,
+            &Direction,
+            &Gap,
+            &CrossJustify,
+        )>(element);
+        let (props, &padding, children, &direction, &gap, &cross_justify) = query.get().unwrap();
 
         let children = children.clone();
         let inner_size = props.inner_size_from_padding(&padding);
 
         drop(query);
-
-        let space_used = self.sum_space_used(&children);
-        let remaining_size = inner_size.saturating_sub(space_used);
-        let mut remaining_size = axify(remaining_size, direction);
-        remaining_size.main_axis = remaining_size
-            .main_axis
-            .saturating_sub(children.len().saturating_sub(1) as u16 * *gap);
+        let children = self.visible_children(element, &children);
+        if self
+            .world
+            .get::<&LayoutMemo>(element)
+            .is_ok_and(|memo| memo.hit)
+        {
+            // the fit pass kept the grown sizes of the children, they grow from their basis again.
+            for child in children.iter() {
+                if let Ok(basis) = self.world.get::<&LayoutMemo>(child).map(|memo| memo.basis) {
+                    self.world.get::<&mut Props>(child)?.size = basis;
+                }
+            }
+        }
 
         if is_root {
             let mut query = self
@@ -204,23 +1156,50 @@ impl ElementCtx {
             if height.is_grow() {
                 props.size.y = area.height;
             }
+            drop(query);
+            self.apply_height_from_width(element);
         }
 
         // cross axis
         children
             .iter()
             .try_for_each(|child| -> Result<(), ComponentError> {
-                let mut child_query = self.world.query_one::<(&mut Props, &Width, &Height)>(child);
-                let (child_props, child_width, child_height) = child_query.get().unwrap();
-                if !cross_size(direction, *child_width, *child_height).is_grow() {
-                    return Ok(());
+                let mut child_query = self
+                    .world
+                    .query_one::<(&mut Props, &Width, &Height, Option<&AlignSelf>)>(child);
+                let (child_props, child_width, child_height, align_self) =
+                    child_query.get().unwrap();
+                let align = align_self.map_or(cross_justify, |align| align.0);
+                match cross_size(direction, *child_width, *child_height) {
+                    Size::Grow => {}
+                    Size::Fit if align == CrossJustify::Stretch => {}
+                    _ => return Ok(()),
                 }
+                let margin = axify(self.margin(child).size(), direction);
                 let mut size = AxisSizes::from_u16vec2(child_props.size, direction);
-                size.cross_axis = axify(inner_size, direction).cross_axis;
+                size.cross_axis = axify(inner_size, direction)
+                    .cross_axis
+                    .saturating_sub(margin.cross_axis);
                 child_props.size = size.to_u16vec2(direction);
                 Ok(())
             })?;
 
+        // in a column the cross axis pass settled the widths, so the heights that follow from
+        // them are known before the column shares its height out.
+        let vertical = direction == Direction::Vertical;
+        if vertical {
+            for child in children.iter() {
+                self.apply_height_from_width(child);
+            }
+        }
+
+        let space_used = self.sum_space_used(&children);
+        let remaining_size = inner_size.saturating_sub(space_used);
+        let mut remaining_size = axify(remaining_size, direction);
+        remaining_size.main_axis = remaining_size
+            .main_axis
+            .saturating_sub(gap_space(children.len(), gap));
+
         // main axis
         #[derive(Query, Debug)]
         struct GrowQuery<'a> {
@@ -231,6 +1210,10 @@ impl ElementCtx {
         #[derive(d::Debug)]
         struct GrowEntry {
             is_grow: bool,
+            /// the size the child starts at before the space is shared out.
+            basis: u16,
+            /// the size of the content, the child never ends up smaller.
+            floor: u16,
             #[debug("({}, {})", self.size.main_axis, self.size.cross_axis)]
             size: AxisSizes,
             entity: Element,
@@ -240,70 +1223,89 @@ impl ElementCtx {
             .map(|child| (self.query_one::<GrowQuery>(child), child))
             .map(|(mut grow_query, entity)| {
                 let grow_query = grow_query.get().unwrap();
-                let is_grow = main_size(direction, *grow_query.width, *grow_query.height).is_grow();
-                let size = axify(grow_query.props.size, direction);
+                let is_grow = main_size(direction, *grow_query.width, *grow_query.height).is_grow()
+                    && !(vertical && self.world.get::<&HeightFromWidth>(entity).is_ok());
+                let margin = self.margin(entity).size();
+                // margins take part in the distribution and are taken off again below.
+                let size = axify(grow_query.props.size.saturating_add(margin), direction);
+                let mut basis = 0;
+                let mut floor = size.main_axis;
+                if is_grow
+                    && let Ok(flex_basis) =
+                        self.world.get::<&FlexBasis>(entity).map(|basis| basis.0)
+                {
+                    // the fit pass raised the child to its basis, it can shrink back to its
+                    // content.
+                    basis = flex_basis.saturating_add(axify(margin, direction).main_axis);
+                    if let Ok(fit) = self.world.get::<&LayoutMemo>(entity).map(|memo| memo.fit) {
+                        floor = axify(fit.saturating_add(margin), direction).main_axis;
+                    }
+                }
                 GrowEntry {
                     is_grow,
+                    basis,
+                    floor,
                     size,
                     entity,
                 }
             })
             .collect::<Vec<_>>();
-        buffer.sort_by_key(|entry| entry.size.main_axis);
-        let mut remaining = remaining_size.main_axis;
-        while let Some([smallest, rest @ ..]) = buffer.get_mut(..) {
-            let second_smallest = rest
+        // every growing child starts at its basis and gets an even share of the space left
+        // after the bases, the first children get the remainder. a child whose share leaves it
+        // smaller than its content keeps its content size, and the others share what is left.
+        let mut available = buffer
+            .iter()
+            .filter(|entry| entry.is_grow)
+            .fold(remaining_size.main_axis, |available, entry| {
+                available.saturating_add(entry.size.main_axis)
+            });
+        let mut flexible = buffer
+            .iter_mut()
+            .filter(|entry| entry.is_grow)
+            .collect::<Vec<_>>();
+        while !flexible.is_empty() {
+            let count = flexible.len() as u16;
+            let bases = flexible
                 .iter()
-                .position(|entry| entry.size.main_axis != smallest.size.main_axis);
-            match second_smallest {
-                None => {
-                    // distribute remaining space evenly
-                    // +1 to include smallest element
-                    let grow_count = rest.iter().filter(|entry| entry.is_grow).count() + 1;
-                    if grow_count == 0 {
-                        break;
-                    }
-                    let growth = remaining as usize / grow_count;
-                    let growth = growth as u16;
-                    let remainder = remaining as usize % grow_count;
-                    let mut remainder = remainder as u16;
-                    for entry in buffer.iter_mut() {
-                        if !entry.is_grow {
-                            continue;
-                        }
-                        match remainder {
-                            0 => {
-                                entry.size.main_axis += growth;
-                            }
-                            _ => {
-                                entry.size.main_axis += growth + 1;
-                                remainder -= 1;
-                            }
-                        }
-                    }
-                    break;
-                }
-                Some(second_smallest) => {
-                    let end = second_smallest;
-                    let target_size = rest[second_smallest].size.main_axis;
-                    remaining = remaining
-                        .saturating_sub(target_size.saturating_sub(smallest.size.main_axis));
-                    for entry in buffer[..=end].iter_mut() {
-                        if entry.is_grow {
-                            entry.size.main_axis = target_size;
-                        }
-                    }
-                    if remaining == 0 {
-                        break;
-                    }
+                .fold(0u16, |bases, entry| bases.saturating_add(entry.basis));
+            let free = available.saturating_sub(bases);
+            let share = free / count;
+            let (frozen, rest): (Vec<_>, Vec<_>) = flexible
+                .into_iter()
+                .partition(|entry| entry.basis.saturating_add(share) < entry.floor);
+            if frozen.is_empty() {
+                let mut remainder = free % count;
+                for entry in rest {
+                    let extra = u16::from(remainder > 0);
+                    remainder -= extra;
+                    entry.size.main_axis = entry.basis.saturating_add(share + extra);
                 }
+                break;
             }
+            for entry in frozen {
+                entry.size.main_axis = entry.floor;
+                available = available.saturating_sub(entry.floor);
+            }
+            flexible = rest;
         }
 
         for entry in buffer {
+            let margin = self.margin(entry.entity);
             let mut query = self.query_one::<GrowQuery>(entry.entity);
             let query = query.get().unwrap();
-            query.props.size = entry.size.to_u16vec2(direction);
+            query.props.size = entry
+                .size
+                .to_u16vec2(direction)
+                .saturating_sub(margin.size());
+        }
+        if !vertical {
+            for child in children.iter() {
+                self.apply_height_from_width(child);
+            }
+        }
+
+        if let Ok(split) = self.world.get::<&SplitPane>(element).map(|split| *split) {
+            self.split_children(element, split, &children)?;
         }
 
         for child in children.iter() {
@@ -312,7 +1314,46 @@ impl ElementCtx {
 
         Ok(())
     }
+    /// sizes the two children of a [`SplitPane`], replacing the sizes they grew to.
+    fn split_children(
+        &self,
+        element: Element,
+        split: SplitPane,
+        children: &Children,
+    ) -> Result<(), ComponentError> {
+        let [first, second] = children[..] else {
+            tracing::warn!(
+                ?element,
+                children = children.len(),
+                "SplitPane needs exactly two children"
+            );
+            return Ok(());
+        };
+        let mut query = self
+            .world
+            .query_one::<(&Props, &Padding, &Direction)>(element);
+        let (props, padding, &direction) = query.get().unwrap();
+        let inner_size = axify(props.inner_size_from_padding(padding), direction);
+        drop(query);
+
+        let (first_size, second_size) = split.sizes(inner_size.main_axis.saturating_sub(1));
+        for (child, main_axis) in [(first, first_size), (second, second_size)] {
+            let margin = self.margin(child).size();
+            let size = AxisSizes {
+                main_axis,
+                cross_axis: inner_size.cross_axis,
+            };
+            self.world.get::<&mut Props>(child)?.size =
+                size.to_u16vec2(direction).saturating_sub(margin);
+        }
+        Ok(())
+    }
     fn calculate_positions(&self, root: Element) -> Result<(), ComponentError> {
+        let _timer = self.profile(root, Phase::Layout);
+        self.layout_visits.position.fetch_add(1, Ordering::Relaxed);
+        if self.reuse_positions(root) {
+            return Ok(());
+        }
         let mut query = self.world.query_one::<(
             &Props,
             &Padding,
@@ -326,16 +1367,71 @@ impl ElementCtx {
             query.get().unwrap();
         let children = children.clone();
         drop(query);
-        let space_used = self.sum_space_used(&children);
+        let children = self.visible_children(root, &children);
+        let inner_size = props.size.saturating_sub(padding_size(&padding));
+        let inner_main = axify(props.size, dir).shrink(padding, dir).main_axis;
+
+        let align_self = children
+            .iter()
+            .map(|child| {
+                self.world
+                    .get::<&AlignSelf>(child)
+                    .ok()
+                    .map(|align| align.0)
+            })
+            .collect::<Vec<_>>();
+        let main_size = |child: Element| axify(self.outer_size(child), dir).main_axis;
+        let group = |anchor: Option<CrossJustify>| {
+            children
+                .iter()
+                .zip(&align_self)
+                .enumerate()
+                .filter(move |(_, (_, align))| match anchor {
+                    Some(anchor) => **align == Some(anchor),
+                    None => !matches!(align, Some(CrossJustify::Start | CrossJustify::End)),
+                })
+                .map(|(idx, (child, _))| (idx, child))
+                .collect::<Vec<_>>()
+        };
+        let start_group = group(Some(CrossJustify::Start));
+        let end_group = group(Some(CrossJustify::End));
+        let flow = group(None);
+
+        // offset of every child on the main axis, relative to the inner area.
+        let mut offsets = vec![0u16; children.len()];
+
+        // children anchored with `AlignSelf` leave the flow and stack from the start or the end.
+        let mut flow_start = 0;
+        for &(idx, child) in &start_group {
+            offsets[idx] = flow_start;
+            flow_start = flow_start
+                .saturating_add(main_size(child))
+                .saturating_add(*gap);
+        }
+        let end_size = end_group
+            .iter()
+            .map(|&(_, child)| main_size(child))
+            .fold(0, u16::saturating_add)
+            .saturating_add(gap_space(end_group.len(), gap));
+        let mut end_start = inner_main.saturating_sub(end_size);
+        let flow_end = if end_group.is_empty() {
+            inner_main
+        } else {
+            end_start.saturating_sub(*gap)
+        };
+        for &(idx, child) in &end_group {
+            offsets[idx] = end_start;
+            end_start = end_start
+                .saturating_add(main_size(child))
+                .saturating_add(*gap);
+        }
+
+        let flow_children = flow.iter().map(|&(_, child)| child).collect::<Vec<_>>();
+        let space_used = self.sum_space_used(&flow_children);
         let space_used = axify(space_used, dir).main_axis;
-        let space_used = space_used + *gap * children.len().saturating_sub(1) as u16;
-        let inner_size = props.size.saturating_sub(u16vec2(
-            padding.left + padding.right,
-            padding.top + padding.bottom,
-        ));
-        let remaining_size = axify(props.size, dir)
-            .shrink(padding, dir)
-            .main_axis
+        let space_used = space_used.saturating_add(gap_space(flow.len(), gap));
+        let remaining_size = flow_end
+            .saturating_sub(flow_start)
             .saturating_sub(space_used);
 
         #[derive(Default)]
@@ -364,9 +1460,9 @@ impl ElementCtx {
                 inbetween: 0,
                 remainder: 0,
             },
-            MainJustify::SpaceBetween if children.is_empty() => AlignValues::default(),
+            MainJustify::SpaceBetween if flow.is_empty() => AlignValues::default(),
             MainJustify::SpaceBetween => {
-                let div_by = (children.len().saturating_sub(1)) as u16;
+                let div_by = (flow.len().saturating_sub(1)) as u16;
                 if div_by == 0 {
                     AlignValues::default()
                 } else {
@@ -379,9 +1475,9 @@ impl ElementCtx {
                     }
                 }
             }
-            MainJustify::SpaceAround if children.is_empty() => AlignValues::default(),
+            MainJustify::SpaceAround if flow.is_empty() => AlignValues::default(),
             MainJustify::SpaceAround => {
-                let div_by = (children.len() * 2) as u16;
+                let div_by = (flow.len() * 2) as u16;
                 if div_by == 0 {
                     AlignValues::default()
                 } else {
@@ -394,9 +1490,9 @@ impl ElementCtx {
                     }
                 }
             }
-            MainJustify::SpaceEvenly if children.is_empty() => AlignValues::default(),
+            MainJustify::SpaceEvenly if flow.is_empty() => AlignValues::default(),
             MainJustify::SpaceEvenly => {
-                let div_by = (children.len() * 2) as u16 + 2;
+                let div_by = (flow.len() * 2) as u16 + 2;
                 if div_by == 0 {
                     AlignValues::default()
                 } else {
@@ -414,162 +1510,1377 @@ impl ElementCtx {
                 remainder: 0,
             },
         };
+        align.start = align.start.saturating_add(flow_start);
+        for &(idx, child) in &flow {
+            offsets[idx] = align.start;
+            align.start = align
+                .start
+                .saturating_add(main_size(child))
+                .saturating_add(*gap)
+                .saturating_add(align.inbetween)
+                .saturating_add(align.tick_rem());
+        }
 
-        children
-            .iter()
-            .try_for_each(|child| -> Result<(), ComponentError> {
+        if self
+            .world
+            .get::<&FlowOrder>(root)
+            .is_ok_and(|order| *order == FlowOrder::Reversed)
+        {
+            // anchored and flowing children alike are mirrored inside the inner area.
+            for (offset, child) in offsets.iter_mut().zip(children.iter()) {
+                *offset = inner_main
+                    .saturating_sub(*offset)
+                    .saturating_sub(main_size(child));
+            }
+        }
+
+        // the row of the content at the top of a scroll view, sticky children don't go above it.
+        let scroll_top = self.world.get::<&ScrollView>(root).is_ok().then(|| {
+            self.world
+                .get::<&ScrollViewState>(root)
+                .map_or(0, |state| state.offset().y)
+        });
+        // the row the content of a scroll view ends at, sticky children don't go below it.
+        let content_bottom = scroll_top.map(|_| {
+            let content = children
+                .iter()
+                .zip(&offsets)
+                .map(|(child, &offset)| {
+                    let top = if dir == Direction::Vertical {
+                        offset
+                    } else {
+                        0
+                    };
+                    top.saturating_add(self.outer_size(child).y)
+                })
+                .max()
+                .unwrap_or(0);
+            props
+                .position
+                .y
+                .saturating_add(padding.top)
+                .saturating_add(content)
+        });
+
+        children.iter().zip(offsets).zip(align_self).try_for_each(
+            |((child, offset), align_self)| -> Result<(), ComponentError> {
                 {
+                    let margin = self.margin(child);
                     let mut child_props = self.world.get::<&mut Props>(child)?;
                     child_props.position = props.position;
-                    match dir {
-                        Direction::Horizontal => child_props.position.x += align.start,
-                        Direction::Vertical => child_props.position.y += align.start,
+                    let offset = match dir {
+                        Direction::Horizontal => u16vec2(offset, 0),
+                        Direction::Vertical => u16vec2(0, offset),
+                    };
+                    child_props.position = child_props
+                        .position
+                        .saturating_add(offset)
+                        .saturating_add(u16vec2(padding.left, padding.top))
+                        .saturating_add(u16vec2(margin.left, margin.top));
+                    let outer_size = child_props.size.saturating_add(margin.size());
+                    if align_self.is_some() && self.cross_grows(child, dir) {
+                        tracing::warn!(
+                            ?child,
+                            "AlignSelf has no effect on the cross axis of a growing element"
+                        );
                     }
-                    child_props.position += u16vec2(padding.left, padding.top);
-                    align.start = increase_axis(align.start, dir, child_props.size);
-                    match (cross_justify, dir) {
-                        (CrossJustify::Start, _) => {}
+                    match (align_self.unwrap_or(cross_justify), dir) {
+                        (CrossJustify::Start | CrossJustify::Stretch, _) => {}
                         (CrossJustify::Center, Direction::Horizontal) => {
-                            child_props.position.y +=
-                                inner_size.y.saturating_sub(child_props.size.y).div(2);
+                            child_props.position.y = child_props
+                                .position
+                                .y
+                                .saturating_add(inner_size.y.saturating_sub(outer_size.y).div(2));
                         }
                         (CrossJustify::Center, Direction::Vertical) => {
-                            child_props.position.x +=
-                                inner_size.x.saturating_sub(child_props.size.x).div(2);
+                            child_props.position.x = child_props
+                                .position
+                                .x
+                                .saturating_add(inner_size.x.saturating_sub(outer_size.x).div(2));
                         }
                         (CrossJustify::End, Direction::Horizontal) => {
-                            child_props.position.y +=
-                                inner_size.y.saturating_sub(child_props.size.y);
+                            child_props.position.y = child_props
+                                .position
+                                .y
+                                .saturating_add(inner_size.y.saturating_sub(outer_size.y));
                         }
                         (CrossJustify::End, Direction::Vertical) => {
-                            child_props.position.x +=
-                                inner_size.x.saturating_sub(child_props.size.x);
+                            child_props.position.x = child_props
+                                .position
+                                .x
+                                .saturating_add(inner_size.x.saturating_sub(outer_size.x));
                         }
                     }
-                    align.start += *gap + align.inbetween + align.tick_rem();
+                    if let Some(scroll_top) = scroll_top
+                        && let Some(content_bottom) = content_bottom
+                        && let Ok(sticky) = self.world.get::<&StickyTop>(child)
+                    {
+                        let top = props
+                            .position
+                            .y
+                            .saturating_add(scroll_top)
+                            .saturating_add(**sticky);
+                        let bottom = content_bottom.saturating_sub(outer_size.y);
+                        child_props.position.y = child_props.position.y.max(top.min(bottom));
+                    }
                 }
                 self.calculate_positions(child)?;
                 Ok(())
-            })?;
+            },
+        )?;
+
+        Ok(())
+    }
+    /// sets the height of `element` from its width, if it has a [`HeightFromWidth`].
+    fn apply_height_from_width(&self, element: Element) {
+        let mut query = self
+            .world
+            .query_one::<(&mut Props, &HeightFromWidth)>(element);
+        if let Ok((props, height_from_width)) = query.get() {
+            props.size.y = height_from_width.height(props.size.x);
+        }
+    }
+    fn cross_grows(&self, element: Element, dir: Direction) -> bool {
+        let mut query = self.world.query_one::<(&Width, &Height)>(element);
+        query
+            .get()
+            .is_ok_and(|(&width, &height)| cross_size(dir, width, height).is_grow())
+    }
+    /// runs the layout calculation step. takes the root element as the argument. elements that
+    /// were spawned without [`ElementCtx::spawn_ui`] get their default components first.
+    ///
+    /// # Error
+    ///
+    /// this will error if any element index is invalid. this can only happen if you manually despawn
+    /// entities using [`hecs::World::despawn`] or other such methods.
+    pub fn calculate_layout(&mut self, element: Element, area: Rect) -> Result<(), ComponentError> {
+        let start = Instant::now();
+        self.ensure_defaults();
+        self.age_retired_layouts();
+        self.insert_layout_memos();
+        self.layout_visits.grow.store(0, Ordering::Relaxed);
+        self.layout_visits.position.store(0, Ordering::Relaxed);
+        self.begin_profile(element);
+        self.update_measure_caches();
+        self.calculate_fit_sizes(element)?;
+        let fit_done = Instant::now();
+        if self
+            .world
+            .get::<&Props>(element)
+            .is_ok_and(|props| props.size.max_element() == u16::MAX)
+        {
+            tracing::warn!(
+                ?element,
+                "the content of the tree is too large, sizes are capped at u16::MAX"
+            );
+        }
+        let grow_start = Instant::now();
+        self.calculate_grow_sizes(element, true, area)?;
+        let grow_done = Instant::now();
+        // the root is placed at the origin of the area so the tree can be rendered anywhere in
+        // the buffer, not just at (0, 0).
+        self.world.get::<&mut Props>(element)?.position = u16vec2(area.x, area.y);
+        self.calculate_positions(element)?;
+        let position_done = Instant::now();
+        self.layout_postprocess();
+        let duration = start.elapsed();
+        let mut stats = self
+            .world
+            .get_or_insert_resource_with::<&mut LayoutStats>(|_| LayoutStats::default());
+        stats.duration = duration;
+        stats.last_fit_pass_duration_ns = (fit_done - start).as_nanos() as u64;
+        stats.last_grow_pass_duration_ns = (grow_done - grow_start).as_nanos() as u64;
+        stats.last_position_pass_duration_ns = (position_done - grow_done).as_nanos() as u64;
+        stats.grow_visits = self.layout_visits.grow.load(Ordering::Relaxed);
+        stats.position_visits = self.layout_visits.position.load(Ordering::Relaxed);
+        Ok(())
+    }
+    /// counts the elements of every tree in the world, along with the timings of the last
+    /// [`ElementCtx::calculate_layout`] call.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use mana_tui_elemental::prelude::*;
+    /// # use ratatui::layout::Rect;
+    /// let mut ctx = ElementCtx::new();
+    /// let root = ctx.spawn_ui(ui(Block::new()).child(ui(Text::raw("hi"))));
+    /// ctx.calculate_layout(root, Rect::new(0, 0, 10, 3)).unwrap();
+    ///
+    /// let stats = ctx.layout_statistics();
+    /// assert_eq!((stats.elements, stats.max_depth), (2, 2));
+    /// ```
+    #[must_use]
+    pub fn layout_statistics(&self) -> LayoutStats {
+        let mut stats = self
+            .world
+            .get_resource::<&LayoutStats>()
+            .map(|stats| *stats)
+            .unwrap_or_default();
+        stats.elements = 0;
+        stats.grow_elements = 0;
+        stats.padded_elements = 0;
+
+        let mut nested = std::collections::HashSet::new();
+        for children in self.world.query::<&Children>().iter() {
+            nested.extend(children.iter());
+        }
+        let mut stack = Vec::new();
+        for (element, _, width, height, padding) in self
+            .world
+            .query::<(
+                Entity,
+                &Props,
+                Option<&Width>,
+                Option<&Height>,
+                Option<&Padding>,
+            )>()
+            .iter()
+        {
+            stats.elements += 1;
+            if width.is_some_and(|width| width.is_grow()) || height.is_some_and(|h| h.is_grow()) {
+                stats.grow_elements += 1;
+            }
+            if padding.is_some_and(|padding| *padding != Padding::ZERO) {
+                stats.padded_elements += 1;
+            }
+            if !nested.contains(&element) {
+                stack.push((element, 1));
+            }
+        }
+
+        stats.max_depth = 0;
+        while let Some((element, depth)) = stack.pop() {
+            stats.max_depth = stats.max_depth.max(depth);
+            if let Ok(children) = self.world.get::<&Children>(element) {
+                stack.extend(children.iter().map(|child| (child, depth + 1)));
+            }
+        }
+        stats
+    }
+    /// switches a [`Size::Fixed`] [`Width`] or [`Height`] of `element` to [`Size::Fit`] so it
+    /// shrinks to its content, then lays out the tree it belongs to again. useful for popovers
+    /// and labels whose content changed after they were sized.
+    ///
+    /// the tree is laid out in its [`Viewport`] if it has one, otherwise in the area its root
+    /// took up in the last layout.
+    ///
+    /// # Error
+    ///
+    /// see [`ElementCtx::calculate_layout`].
+    pub fn resize_to_content(&mut self, element: Element) -> Result<(), ComponentError> {
+        {
+            let mut width = self.world.get::<&mut Width>(element)?;
+            if let Size::Fixed(_) = **width {
+                *width = Width::shrink_to_fit();
+            }
+        }
+        {
+            let mut height = self.world.get::<&mut Height>(element)?;
+            if let Size::Fixed(_) = **height {
+                *height = Height::shrink_to_fit();
+            }
+        }
+
+        let mut root = element;
+        while let Some(parent) = self.parent(root) {
+            root = parent;
+        }
+        let area = match self.world.get::<&Viewport>(root) {
+            Ok(viewport) => **viewport,
+            Err(_) => {
+                let props = self.world.get::<&Props>(root)?;
+                Rect::new(
+                    props.position.x,
+                    props.position.y,
+                    props.size.x,
+                    props.size.y,
+                )
+            }
+        };
+        self.calculate_layout(root, area)
+    }
+    /// the natural size of `view`, without spawning it into the tree. useful to place tooltips
+    /// and context menus before spawning them.
+    ///
+    /// growing elements are measured like [`Size::Fit`] ones, and text takes the size of its
+    /// content. a [`Paragraph`] doesn't expose its text, use a [`SourceParagraph`] to measure
+    /// one. the view is spawned into a temporary tree that is laid out and despawned again, it
+    /// is never rendered.
+    pub fn measure(&mut self, view: View) -> U16Vec2 {
+        let root = self.spawn_ui(view);
+        let mut tree = vec![root];
+        let mut idx = 0;
+        while let Some(&element) = tree.get(idx) {
+            if let Ok(children) = self.world.get::<&Children>(element) {
+                tree.extend(children.iter());
+            }
+            idx += 1;
+        }
+
+        for &element in &tree {
+            let content = self.content_size(element);
+            if let Ok(mut width) = self.world.get::<&mut Width>(element)
+                && width.is_grow()
+            {
+                *width = content.map_or(Width::fit(), |size| Width::fixed(size.x));
+            }
+            if let Ok(mut height) = self.world.get::<&mut Height>(element)
+                && height.is_grow()
+            {
+                *height = content.map_or(Height::fit(), |size| Height::fixed(size.y));
+            }
+        }
+
+        self.update_measure_caches();
+        if let Err(err) = self.calculate_fit_sizes(root) {
+            tracing::error!("failed to measure view: {err}");
+        }
+        let size = self
+            .world
+            .get::<&Props>(root)
+            .map(|props| props.size)
+            .unwrap_or_default();
+        self.despawn_ui(root);
+        size
+    }
+    /// drops the cached [`Measure`] result of `element`, so the next layout measures it again.
+    pub fn invalidate_measure(&mut self, element: Element) {
+        _ = self.world.remove_one::<MeasureCache>(element);
+    }
+    /// lays `element` out from scratch in the next layout, instead of reusing its last one.
+    pub(crate) fn invalidate_layout(&mut self, element: Element) {
+        self.invalidate_measure(element);
+        if let Ok(mut memo) = self.world.get::<&mut LayoutMemo>(element) {
+            memo.hash = None;
+        }
+    }
+    /// records the new size of the terminal in the [`TerminalSize`] resource. call it on
+    /// [`Event::Resize`][ratatui::crossterm::event::Event::Resize], then lay the roots out again
+    /// with [`TerminalSize::area`].
+    ///
+    /// returns whether the size changed.
+    pub fn on_resize(&mut self, width: u16, height: u16) -> bool {
+        let size = TerminalSize(u16vec2(width, height));
+        if self
+            .world
+            .get_resource::<&TerminalSize>()
+            .is_ok_and(|current| *current == size)
+        {
+            return false;
+        }
+        self.world.insert_or_update_resource(size);
+        true
+    }
+    /// runs the [`Measure`] functions whose width or content changed since the last layout.
+    fn update_measure_caches(&mut self) {
+        let measured = self
+            .world
+            .query::<(Entity, &Measure, &Width, Option<&MeasureCache>)>()
+            .iter()
+            .filter_map(|(element, measure, width, cache)| {
+                let entity = self.world.entity(element).ok()?;
+                let width_constraint = match **width {
+                    Size::Fixed(value) => value,
+                    _ => u16::MAX,
+                };
+                let content_hash = content_hash(entity);
+                if let Some(cache) = cache
+                    && cache.width_constraint == width_constraint
+                    && cache.content_hash == content_hash
+                {
+                    return None;
+                }
+                let result =
+                    guard(|| (measure.0)(entity, width_constraint)).unwrap_or_else(|message| {
+                        tracing::error!(?element, %message, "measure panicked, using a zero size");
+                        U16Vec2::ZERO
+                    });
+                let cache = MeasureCache {
+                    width_constraint,
+                    result,
+                    content_hash,
+                };
+                Some((element, cache))
+            })
+            .collect::<Vec<_>>();
+        for (element, cache) in measured {
+            _ = self.world.insert_one(element, cache);
+        }
+    }
+    /// the size of the text an element draws, if it draws text.
+    pub(crate) fn content_size(&self, element: Element) -> Option<U16Vec2> {
+        let entity = self.world.entity(element).ok()?;
+        let (width, height) = if let Some(text) = entity.get::<&Text<'static>>() {
+            (text.width(), text.height())
+        } else if let Some(source) = entity.get::<&SourceParagraph>() {
+            source.natural_size()
+        } else if let Some(line) = entity.get::<&Line<'static>>() {
+            (line.width(), 1)
+        } else if let Some(span) = entity.get::<&Span<'static>>() {
+            (span.width(), 1)
+        } else {
+            return None;
+        };
+        Some(u16vec2(width as u16, height as u16))
+    }
+    fn parent(&self, element: Element) -> Option<Element> {
+        self.world
+            .query::<(Entity, &Children)>()
+            .iter()
+            .find(|(_, children)| children.contains(&element))
+            .map(|(parent, _)| parent)
+    }
+    fn layout_postprocess(&mut self) {
+        let overflowing = self
+            .world
+            .query::<(Entity, &Overflow, &Props, Option<&Children>)>()
+            .iter()
+            .filter(|(_, overflow, ..)| overflow.scrolls())
+            .map(|(element, overflow, props, children)| {
+                let content = self.scroll_content_size(props, children, *overflow);
+                (element, (*overflow, content))
+            })
+            .collect::<HashMap<_, _>>();
+        for (element, props, scrollview, padding) in
+            self.query_mut::<(Entity, &mut Props, &mut ScrollView, Option<&Padding>)>()
+        {
+            if let Some((overflow, content)) = overflowing.get(&element) {
+                let (horizontal, vertical) = overflow.scrollbars();
+                *scrollview = ScrollView::new(ratatui::layout::Size {
+                    width: content.x,
+                    height: content.y,
+                })
+                .horizontal_scrollbar_visibility(horizontal)
+                .vertical_scrollbar_visibility(vertical);
+                continue;
+            }
+            let inner_size = props.inner_size_from_padding(padding.unwrap_or(&Padding::ZERO));
+            *scrollview = ScrollView::new(ratatui::layout::Size {
+                width: inner_size.x,
+                height: inner_size.y,
+            });
+        }
+    }
 
+    /// the size of the scroll view of an element with a scrolling [`Overflow`]: the element
+    /// grown to fit its children on the axes it scrolls on.
+    fn scroll_content_size(
+        &self,
+        props: &Props,
+        children: Option<&Children>,
+        overflow: Overflow,
+    ) -> U16Vec2 {
+        let extent = children
+            .into_iter()
+            .flat_map(Children::iter)
+            .filter_map(|child| self.world.get::<&Props>(child).ok())
+            .map(|child| (child.position.saturating_add(child.size)).saturating_sub(props.position))
+            .fold(props.size, U16Vec2::max);
+        match overflow {
+            Overflow::ScrollX => u16vec2(extent.x, props.size.y),
+            Overflow::ScrollY => u16vec2(props.size.x, extent.y),
+            _ => extent,
+        }
+    }
+    /// lays out and renders the tree scoped to `viewport`, for example a single pane of a
+    /// larger ratatui app. the viewport is stored on the root as a [`Viewport`] component so
+    /// event handling can tell which root a terminal position belongs to.
+    ///
+    /// multiple roots can be rendered into different viewports of the same context.
+    ///
+    /// # Error
+    ///
+    /// see [`ElementCtx::calculate_layout`].
+    pub fn render_viewport(
+        &mut self,
+        root: Element,
+        viewport: Rect,
+        buf: &mut Buffer,
+    ) -> Result<(), ComponentError> {
+        self.world
+            .insert_one(root, Viewport(viewport))
+            .map_err(|_| ComponentError::NoSuchEntity)?;
+        self.calculate_layout(root, viewport)?;
+        self.render(root, viewport, buf);
         Ok(())
     }
-    /// runs the layout calculation step. takes the root element as the argument.
-    ///
-    /// # Error
-    ///
-    /// this will error if any element index is invalid. this can only happen if you manually despawn
-    /// entities using [`hecs::World::despawn`] or other such methods.
-    pub fn calculate_layout(&mut self, element: Element, area: Rect) -> Result<(), ComponentError> {
-        self.calculate_fit_sizes(element)?;
-        self.calculate_grow_sizes(element, true, area)?;
-        self.calculate_positions(element)?;
-        self.layout_postprocess();
-        Ok(())
+    /// returns the absolute position and shape of the first visible [`Cursor`] in the context.
+    /// the position is only valid after [`ElementCtx::calculate_layout`].
+    ///
+    /// pass the position to [`ratatui::Frame::set_cursor_position`] after rendering.
+    pub fn cursor(&self) -> Option<(Position, CursorShape)> {
+        self.world
+            .query::<(&Cursor, &Props)>()
+            .iter()
+            .find_map(|(cursor, props)| {
+                let position = props.position + cursor.position?;
+                Some((Position::new(position.x, position.y), cursor.shape))
+            })
+    }
+    /// renders the tree.
+    ///
+    /// also see [`ratatui::prelude::Rect`], [`ratatui::prelude::Buffer`]
+    pub fn render(&mut self, root: Element, area: Rect, buf: &mut Buffer) {
+        // render self
+        self.render_impl(root, area, buf, Offset { x: 0, y: 0 });
+    }
+
+    /// renders the subtree of `root` into a new buffer the size of `root`, with `root` at the
+    /// origin. draw the buffer with a [`Blit`](crate::blit::Blit).
+    ///
+    /// the layout must be calculated first, an element without one gives an empty buffer.
+    pub fn render_to_buffer(&mut self, root: Element) -> Buffer {
+        let Ok(props) = self.world.get::<&Props>(root).map(|props| *props) else {
+            return Buffer::empty(Rect::ZERO);
+        };
+        let area = Rect::new(0, 0, props.size.x, props.size.y);
+        let mut buf = Buffer::empty(area);
+        let offset = Offset {
+            x: -i32::from(props.position.x),
+            y: -i32::from(props.position.y),
+        };
+        self.render_impl(root, area, &mut buf, offset);
+        buf
+    }
+
+    fn render_impl(&mut self, root: Element, area: Rect, buf: &mut Buffer, offset: Offset) {
+        let mut query = self.world.query_one::<(&Props, Option<&Children>)>(root);
+        let (props, children) = query.get().unwrap();
+        let full = props.rect(offset);
+        let area = props.split_area(area, offset);
+
+        // a cut element is drawn whole over what is behind it, and only the visible part is
+        // copied back, so its first rows and columns aren't drawn at the edge of the cut.
+        if !area.is_empty() && area != full {
+            drop(query);
+            let mut scratch = Buffer::empty(full);
+            for position in area.positions() {
+                scratch[position] = buf[position].clone();
+            }
+            self.render_impl(root, full, &mut scratch, offset);
+            for position in area.positions() {
+                buf[position] = scratch[position].clone();
+            }
+            return;
+        }
+
+        self.render_element(root, area, buf);
+
+        // render children
+
+        let Some(children) = children else {
+            drop(query);
+            self.render_shortcut_hint(root, area, buf);
+            return;
+        };
+
+        let children = children.clone();
+        drop(query);
+        let children = self.visible_children(root, &children);
+
+        if let Ok(mut scrollview) = self.remove_one::<ScrollView>(root) {
+            {
+                let offset = Offset {
+                    x: offset.x - i32::from(area.x),
+                    y: offset.y - i32::from(area.y),
+                };
+                let content = scrollview.area();
+                let children_area = self.clip_children(root, content, offset);
+                let (pinned, scrolled): (Vec<_>, Vec<_>) = children
+                    .iter()
+                    .partition(|child| self.world.get::<&Sticky>(*child).is_ok());
+
+                // pinned children are drawn into the content where the scroll view shows them
+                // unscrolled.
+                let scroll = self
+                    .world
+                    .get::<&ScrollViewState>(root)
+                    .map_or(0, |state| state.offset().y)
+                    .min(content.height.saturating_sub(area.height));
+                let pinned_offset = Offset {
+                    x: offset.x,
+                    y: offset.y + i32::from(scroll),
+                };
+                let pinned_rows = pinned
+                    .iter()
+                    .filter_map(|child| self.world.get::<&Props>(*child).ok())
+                    .map(|props| props.rect(pinned_offset))
+                    .reduce(|rows, rect| rows.union(rect));
+                // the scrolled content only shows below the pinned rows.
+                let body_area = pinned_rows.map_or(children_area, |rows| {
+                    let top = children_area.y.max(rows.bottom());
+                    Rect {
+                        y: top,
+                        height: children_area.bottom().saturating_sub(top),
+                        ..children_area
+                    }
+                });
+
+                // sticky children are drawn over the content they stay on top of.
+                let (sticky, flowing): (Vec<_>, Vec<_>) = scrolled
+                    .into_iter()
+                    .partition(|child| self.world.get::<&StickyTop>(*child).is_ok());
+                for child in flowing.into_iter().chain(sticky) {
+                    self.render_impl(child, body_area, scrollview.buf_mut(), offset);
+                }
+
+                let children_area = self.clip_children(root, content, pinned_offset);
+                if let Some(rows) = pinned_rows {
+                    // the content of the last frame is still in the buffer.
+                    Clear.render(
+                        Rect {
+                            x: content.x,
+                            width: content.width,
+                            ..rows.intersection(content)
+                        },
+                        scrollview.buf_mut(),
+                    );
+                }
+                for child in pinned {
+                    self.render_impl(child, children_area, scrollview.buf_mut(), pinned_offset);
+                }
+            };
+            {
+                let mut scroll_state = self.get::<&mut ScrollViewState>(root);
+                let mut default_scroll_state = ScrollViewState::default();
+                scrollview.clone().render(
+                    area,
+                    buf,
+                    scroll_state
+                        .as_deref_mut()
+                        .unwrap_or(&mut default_scroll_state),
+                );
+            }
+
+            _ = self.insert_one(root, scrollview);
+        } else {
+            let children_area = self.clip_children(root, area, offset);
+            for child in children.iter() {
+                self.render_impl(child, children_area, buf, offset);
+            }
+            if children_area != area {
+                blank_cut_wide_chars(children_area, buf);
+            }
+        }
+        self.render_overflow_indicator(root, &children, area, buf, offset);
+        self.render_shortcut_hint(root, area, buf);
+    }
+
+    /// draws the [`OverflowIndicator`] of `element` on the sides its content is cut at. the
+    /// sides come from the layout, or the offset of a scroll view.
+    fn render_overflow_indicator(
+        &self,
+        element: Element,
+        children: &Children,
+        area: Rect,
+        buf: &mut Buffer,
+        offset: Offset,
+    ) {
+        let Ok(indicator) = self
+            .world
+            .get::<&OverflowIndicator>(element)
+            .map(|indicator| *indicator)
+        else {
+            return;
+        };
+        let Ok(props) = self.world.get::<&Props>(element).map(|props| *props) else {
+            return;
+        };
+        let direction = self
+            .world
+            .get::<&Direction>(element)
+            .map_or(Direction::Vertical, |direction| *direction);
+        let main = |rect: Rect| match direction {
+            Direction::Horizontal => (rect.left(), rect.right()),
+            Direction::Vertical => (rect.top(), rect.bottom()),
+        };
+
+        let (edges, before, after) = if let Ok(scrollview) = self.world.get::<&ScrollView>(element)
+        {
+            let content = scrollview.area();
+            let scroll = self
+                .world
+                .get::<&ScrollViewState>(element)
+                .map_or(Position::ORIGIN, |state| state.offset());
+            let (scroll, viewport, content) = match direction {
+                Direction::Horizontal => (scroll.x, area.width, content.width),
+                Direction::Vertical => (scroll.y, area.height, content.height),
+            };
+            let after = scroll.saturating_add(viewport) < content;
+            (area, scroll > 0, after)
+        } else {
+            let padding = self
+                .world
+                .get::<&Padding>(element)
+                .map_or(Padding::ZERO, |padding| *padding);
+            let inner = props.inner_area(&padding);
+            let (_, inner_end) = main(inner);
+            let after = children
+                .iter()
+                .filter_map(|child| self.world.get::<&Props>(child).ok())
+                .any(|child| {
+                    let (_, end) = main(Rect::new(
+                        child.position.x,
+                        child.position.y,
+                        child.size.x,
+                        child.size.y,
+                    ));
+                    end > inner_end
+                });
+            (inner.offset(offset).intersection(area), false, after)
+        };
+        let edges = edges.intersection(buf.area);
+        if edges.is_empty() {
+            return;
+        }
+
+        let style = self
+            .world
+            .get_resource::<&DefaultStyles>()
+            .map_or(Style::new(), |styles| styles.overflow_style);
+        // both indicators sit on the last row or column across the main axis.
+        let (start, end) = match direction {
+            Direction::Horizontal => (
+                (edges.left(), edges.bottom() - 1),
+                (edges.right() - 1, edges.bottom() - 1),
+            ),
+            Direction::Vertical => (
+                (edges.right() - 1, edges.top()),
+                (edges.right() - 1, edges.bottom() - 1),
+            ),
+        };
+        for (shown, (x, y), is_end) in [(before, start, false), (after, end, true)] {
+            if shown {
+                buf.set_string(x, y, indicator.symbol(direction, is_end), style);
+            }
+        }
+    }
+
+    /// the area the children of `element` are rendered into: `area`, cut to the element's
+    /// [`ClipRegion`] moved by `offset`.
+    pub(crate) fn clip_children(&self, element: Element, area: Rect, offset: Offset) -> Rect {
+        let area = match self.world.get::<&ClipRegion>(element) {
+            Ok(clip) => area.intersection(clip.offset(offset)),
+            Err(_) => area,
+        };
+        if !matches!(
+            self.world.get::<&Overflow>(element).as_deref(),
+            Ok(Overflow::Clip)
+        ) {
+            return area;
+        }
+        let Ok(props) = self.world.get::<&Props>(element) else {
+            return area;
+        };
+        let padding = self.world.get::<&Padding>(element);
+        let inner = props.inner_area(padding.as_deref().unwrap_or(&Padding::ZERO));
+        area.intersection(inner.offset(offset))
+    }
+
+    /// the range of the children of `element` that take part in layout and rendering, see
+    /// [`MaxChildren`] and [`ChildrenOffset`].
+    fn visible_range(&self, element: Element, len: usize) -> std::ops::Range<usize> {
+        Children::visible_range(&self.world, element, len)
+    }
+
+    /// gives the elements spawned since the last layout an empty [`LayoutMemo`].
+    fn insert_layout_memos(&mut self) {
+        let spawned = self
+            .world
+            .query::<Entity>()
+            .with::<&Props>()
+            .without::<&LayoutMemo>()
+            .iter()
+            .collect::<Vec<_>>();
+        for element in spawned {
+            _ = self.world.insert_one(element, LayoutMemo::default());
+        }
+    }
+
+    /// keeps the layout of the tree of `root` when it's despawned, for the next tree spawned in
+    /// its place, see [`ElementCtx::adopt_layout`]. trees are told apart by the [`ElId`] of
+    /// their root and the widgets they are made of. a layout no tree takes before the second
+    /// [`ElementCtx::calculate_layout`] after it was retired is dropped.
+    pub(crate) fn retire_layout(&mut self, root: Element) {
+        let Some(layout) = self.retired_layout(root) else {
+            return;
+        };
+        let key = self.retired_key(root);
+        let mut retired = self
+            .world
+            .get_or_insert_resource_with::<&mut RetiredLayouts>(|_| RetiredLayouts::default());
+        let layouts = retired.layouts;
+        retired
+            .trees
+            .entry(key)
+            .or_default()
+            .push((layouts, layout));
+    }
+
+    /// the [`ElId`] of `root` and a hash of the widget types of its tree.
+    fn retired_key(&self, root: Element) -> (Option<ElId>, u64) {
+        fn shape(world: &World, element: Element, hasher: &mut DefaultHasher) {
+            if let Ok(props) = world.get::<&Props>(element) {
+                props.typeid.hash(hasher);
+            }
+            let children = world
+                .get::<&Children>(element)
+                .map(|children| children.clone())
+                .unwrap_or_default();
+            children.len().hash(hasher);
+            for child in children.iter() {
+                shape(world, child, hasher);
+            }
+        }
+        let mut hasher = DefaultHasher::new();
+        shape(&self.world, root, &mut hasher);
+        let id = self.world.get::<&ElId>(root).ok().map(|id| id.clone());
+        (id, hasher.finish())
+    }
+
+    /// counts a layout, and drops the retired layouts no tree took in the last two.
+    fn age_retired_layouts(&mut self) {
+        let Ok(mut retired) = self.world.get_resource::<&mut RetiredLayouts>() else {
+            return;
+        };
+        retired.layouts += 1;
+        let layouts = retired.layouts;
+        retired.trees.retain(|_, trees| {
+            trees.retain(|(retired_at, _)| retired_at + 2 > layouts);
+            !trees.is_empty()
+        });
+    }
+
+    fn retired_layout(&self, element: Element) -> Option<RetiredLayout> {
+        let memo = self
+            .world
+            .get::<&LayoutMemo>(element)
+            .map(|memo| memo.clone())
+            .ok()?;
+        let props = *self.world.get::<&Props>(element).ok()?;
+        let children = self
+            .world
+            .get::<&Children>(element)
+            .map(|children| children.clone())
+            .unwrap_or_default();
+        Some(RetiredLayout {
+            memo,
+            size: props.size,
+            position: props.position,
+            children: children
+                .iter()
+                .map_while(|child| self.retired_layout(child))
+                .collect(),
+        })
+    }
+
+    /// gives the freshly spawned tree of `root` the layout kept from the tree despawned in its
+    /// place. the memos are matched by position in the tree, and only hit where the new tree
+    /// has the inputs of the old one, so an identical tree isn't laid out again.
+    pub(crate) fn adopt_layout(&mut self, root: Element) {
+        // the key walks the whole tree, skip it while nothing is retired.
+        if !self
+            .world
+            .get_resource::<&RetiredLayouts>()
+            .is_ok_and(|retired| !retired.trees.is_empty())
+        {
+            return;
+        }
+        let key = self.retired_key(root);
+        let Some((_, layout)) = self
+            .world
+            .get_resource::<&mut RetiredLayouts>()
+            .ok()
+            .and_then(|mut retired| retired.trees.get_mut(&key)?.pop())
+        else {
+            return;
+        };
+        self.adopt(root, layout);
+    }
+
+    fn adopt(&mut self, element: Element, layout: RetiredLayout) {
+        if self.world.get::<&LayoutMemo>(element).is_ok() {
+            return;
+        }
+        let Ok(mut props) = self.world.get::<&mut Props>(element) else {
+            return;
+        };
+        props.size = layout.size;
+        props.position = layout.position;
+        drop(props);
+        _ = self.world.insert_one(element, layout.memo);
+        let children = self
+            .world
+            .get::<&Children>(element)
+            .map(|children| children.clone())
+            .unwrap_or_default();
+        for (child, layout) in children.iter().zip(layout.children) {
+            self.adopt(child, layout);
+        }
+    }
+
+    /// what the layout of `element` depends on, `None` if it's laid out again every time.
+    fn layout_inputs(&self, element: Element, visible: &[Element]) -> Option<LayoutInputs> {
+        let entity = self.world.entity(element).ok()?;
+        // the height follows the width the parent gives it, which the memo doesn't keep.
+        if entity.has::<HeightFromWidth>() {
+            return None;
+        }
+        let child_hashes = visible
+            .iter()
+            .map(|&child| self.world.get::<&LayoutMemo>(child).ok()?.hash)
+            .collect::<Option<Vec<_>>>()?;
+        let len = entity.get::<&Children>()?.len();
+        Some(LayoutInputs {
+            width: **entity.get::<&Width>()?,
+            height: **entity.get::<&Height>()?,
+            padding: *entity.get::<&Padding>()?,
+            margin: self.margin(element),
+            gap: **entity.get::<&Gap>()?,
+            direction: *entity.get::<&Direction>()?,
+            main_justify: *entity.get::<&MainJustify>()?,
+            cross_justify: *entity.get::<&CrossJustify>()?,
+            align_self: entity.get::<&AlignSelf>().map(|align| align.0),
+            flex_basis: entity.get::<&FlexBasis>().map(|basis| basis.0),
+            split: entity
+                .get::<&SplitPane>()
+                .map(|split| (split.ratio.to_bits(), split.min_first, split.min_second)),
+            flow_order: entity.get::<&FlowOrder>().map(|order| *order),
+            sticky_top: entity.get::<&StickyTop>().map(|sticky| sticky.0),
+            scroll_top: entity.has::<ScrollView>().then(|| {
+                entity
+                    .get::<&ScrollViewState>()
+                    .map_or(0, |state| state.offset().y)
+            }),
+            measured: entity
+                .get::<&MeasureCache>()
+                .map(|cache| cache.result.to_array()),
+            text_height: entity
+                .has::<FitText>()
+                .then(|| entity.get::<&Text<'static>>().map(|text| text.height()))
+                .flatten(),
+            visible: self.visible_range(element, len),
+            child_hashes,
+        })
     }
-    fn layout_postprocess(&mut self) {
-        for (props, scrollview, padding) in
-            self.query_mut::<(&mut Props, &mut ScrollView, Option<&Padding>)>()
+
+    /// hashes the [`LayoutInputs`] of `element` into its [`LayoutMemo`]. returns whether they
+    /// are the ones of the last layout, so the sizes of its subtree from then still hold.
+    fn memoize_inputs(&self, element: Element, visible: &[Element]) -> bool {
+        let inputs = self.layout_inputs(element, visible);
+        // the hashes only describe the structure, a new child with the same hash hasn't been
+        // grown or placed yet.
+        let children_hit = visible.iter().all(|&child| {
+            self.world
+                .get::<&LayoutMemo>(child)
+                .is_ok_and(|memo| memo.hit)
+        });
+        let Ok(mut memo) = self.world.get::<&mut LayoutMemo>(element) else {
+            return false;
+        };
+        let hash = inputs.as_ref().map(LayoutInputsHash::new);
+        let hit = children_hit && hash.is_some() && hash == memo.hash;
+        #[cfg(debug_assertions)]
+        let hit = hit && {
+            let same = inputs == memo.inputs;
+            if !same {
+                tracing::debug!(?element, "layout inputs hash collision");
+            }
+            same
+        };
+        #[cfg(debug_assertions)]
         {
-            let inner_size = props.inner_size_from_padding(padding.unwrap_or(&Padding::ZERO));
-            *scrollview = ScrollView::new(ratatui::layout::Size {
-                width: inner_size.x,
-                height: inner_size.y,
-            });
+            memo.inputs = inputs;
         }
+        memo.hash = hash;
+        memo.hit = hit;
+        hit
     }
-    /// renders the tree.
-    ///
-    /// also see [`ratatui::prelude::Rect`], [`ratatui::prelude::Buffer`]
-    pub fn render(&mut self, root: Element, area: Rect, buf: &mut Buffer) {
-        // render self
-        self.render_impl(root, area, buf, Offset { x: 0, y: 0 });
+
+    /// sets `element` back to the size of its fit pass, a reused subtree still has the grown one.
+    fn restore_fit_size(&self, element: Element) {
+        let Ok(fit) = self.world.get::<&LayoutMemo>(element).map(|memo| memo.fit) else {
+            return;
+        };
+        if let Ok(mut props) = self.world.get::<&mut Props>(element) {
+            props.size = fit;
+        }
     }
 
-    fn render_impl(&mut self, root: Element, area: Rect, buf: &mut Buffer, offset: Offset) {
-        let mut query = self
+    /// records the space the grow pass gives `element`. returns whether the subtree keeps the
+    /// sizes of the last layout.
+    fn reuse_sizes(&self, element: Element, constraint: U16Vec2) -> bool {
+        let Ok(mut memo) = self.world.get::<&mut LayoutMemo>(element) else {
+            return false;
+        };
+        memo.reused = memo.hit && memo.constraint == Some(constraint);
+        memo.constraint = Some(constraint);
+        memo.reused
+    }
+
+    /// records the position of `element`. returns whether the subtree keeps the positions of the
+    /// last layout.
+    fn reuse_positions(&self, element: Element) -> bool {
+        let Ok(position) = self
             .world
-            .query_one::<(&mut Props, Option<&Children>)>(root);
-        let (props, children) = query.get().unwrap();
-        let area = props.split_area(area, offset);
+            .get::<&Props>(element)
+            .map(|props| props.position)
+        else {
+            return false;
+        };
+        let Ok(mut memo) = self.world.get::<&mut LayoutMemo>(element) else {
+            return false;
+        };
+        let reused = memo.reused && memo.position == Some(position);
+        memo.position = Some(position);
+        reused
+    }
 
-        (props.render)(self, root, area, buf);
+    /// the children of `element` that take part in layout and rendering.
+    pub(crate) fn visible_children(&self, element: Element, children: &Children) -> Children {
+        children.visible(&self.world, element)
+    }
 
-        // render children
+    /// sets the size of a hidden element and its subtree to zero, so they can't be hovered or
+    /// clicked where they were last shown.
+    fn collapse(&self, element: Element) {
+        if let Ok(mut props) = self.world.get::<&mut Props>(element) {
+            props.size = U16Vec2::ZERO;
+        }
+        // the zero sizes aren't the ones the memo describes anymore.
+        if let Ok(mut memo) = self.world.get::<&mut LayoutMemo>(element) {
+            memo.hash = None;
+        }
+        let children = self
+            .world
+            .get::<&Children>(element)
+            .map(|children| children.clone())
+            .unwrap_or_default();
+        for child in children.iter() {
+            self.collapse(child);
+        }
+    }
+}
+
+impl ElementCtx {
+    /// draws the shadow, background and widget of `element` into `area`, without its children
+    /// or its [`KeyboardShortcutHint`].
+    pub(crate) fn render_element(&self, element: Element, area: Rect, buf: &mut Buffer) {
+        let _timer = self.profile(element, Phase::Render);
+        if let Ok(shadow) = self.world.get::<&Shadow>(element) {
+            let shadow_area = area
+                .offset(Offset {
+                    x: i32::from(shadow.offset.x),
+                    y: i32::from(shadow.offset.y),
+                })
+                .intersection(buf.area);
+            Clear.render(shadow_area, buf);
+            buf.set_style(shadow_area, shadow.style);
+        }
+        if self.world.get::<&ClearBackground>(element).is_ok() {
+            Clear.render(area, buf);
+        }
+        if let Ok(props) = self.world.get::<&Props>(element)
+            && let Err(message) = guard(|| (props.render)(self, element, area, buf))
+        {
+            tracing::error!(
+                ?element,
+                id = ?self.world.get::<&ElId>(element).ok().map(|id| id.0.clone()),
+                widget = props.type_name,
+                %message,
+                "widget panicked while rendering"
+            );
+            render_panic_placeholder(&message, area, buf);
+        }
+        if let Ok(block) = self.world.get::<&Block>(element)
+            && self.world.get::<&NoTruncateTitles>(element).is_err()
+        {
+            let fit = self.world.get::<&mut TitleFit>(element);
+            fit_block_titles(&block, fit.ok().as_deref_mut(), area, buf);
+        }
+        let mut query = self
+            .world
+            .query_one::<(&SplitPane, &Props, &Padding, &Direction)>(element);
+        if let Ok((split, props, padding, &direction)) = query.get() {
+            let inner = props.inner_area(padding);
+            let symbol = match direction {
+                Direction::Horizontal => "│",
+                Direction::Vertical => "─",
+            };
+            let divider = split
+                .divider(inner, direction)
+                .intersection(area)
+                .intersection(buf.area);
+            for position in divider.positions() {
+                buf[position].set_symbol(symbol);
+            }
+        }
+    }
 
-        let Some(children) = children else { return };
+    /// draws the [`KeyboardShortcutHint`] of `element`, after its children so they don't cover
+    /// it.
+    pub(crate) fn render_shortcut_hint(&self, element: Element, area: Rect, buf: &mut Buffer) {
+        if let Ok(hint) = self.world.get::<&KeyboardShortcutHint>(element) {
+            hint.render(area, buf);
+        }
+    }
+}
 
-        let children = children.clone();
-        drop(query);
+/// runs `f` and returns the message if it panics, so one broken widget doesn't take down the
+/// whole frame. panics are only caught with the `render-guard` feature.
+fn guard<R>(f: impl FnOnce() -> R) -> Result<R, String> {
+    #[cfg(feature = "render-guard")]
+    {
+        std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).map_err(|payload| {
+            payload
+                .downcast_ref::<&str>()
+                .map(ToString::to_string)
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_string())
+        })
+    }
+    #[cfg(not(feature = "render-guard"))]
+    {
+        Ok(f())
+    }
+}
 
-        if let Ok(mut scrollview) = self.remove_one::<ScrollView>(root) {
-            {
-                for child in children.iter() {
-                    self.render_impl(
-                        child,
-                        scrollview.area(),
-                        scrollview.buf_mut(),
-                        Offset {
-                            x: offset.x - i32::from(area.x),
-                            y: offset.y - i32::from(area.y),
-                        },
-                    );
-                }
-            };
-            {
-                let mut scroll_state = self.get::<&mut ScrollViewState>(root);
-                let mut default_scroll_state = ScrollViewState::default();
-                scrollview.clone().render(
-                    area,
-                    buf,
-                    scroll_state
-                        .as_deref_mut()
-                        .unwrap_or(&mut default_scroll_state),
-                );
+/// replaces the wide characters that straddle the left or right edge of `clip` with spaces,
+/// keeping their style. a head inside the clip would spill its glyph past it, and a head left
+/// of the clip would draw over the first cells of the clipped content.
+fn blank_cut_wide_chars(clip: Rect, buf: &mut Buffer) {
+    let clip = clip.intersection(buf.area);
+    if clip.is_empty() {
+        return;
+    }
+    let cut = |x: u16, edge: u16, buf: &Buffer, y: u16| {
+        let width = Span::raw(buf[(x, y)].symbol()).width();
+        width > 1 && usize::from(x) + width > usize::from(edge)
+    };
+    for y in clip.top()..clip.bottom() {
+        let inside = clip.right().saturating_sub(2).max(clip.left())..clip.right();
+        let left_of = clip.left().saturating_sub(2).max(buf.area.left())..clip.left();
+        let edges = inside
+            .map(|x| (x, clip.right()))
+            .chain(left_of.map(|x| (x, clip.left())));
+        for (x, edge) in edges {
+            if cut(x, edge, buf, y) {
+                buf[(x, y)].set_symbol(" ");
             }
+        }
+    }
+}
 
-            _ = self.insert_one(root, scrollview);
+/// fills `area` with a red block showing the panic message, cut to fit.
+fn render_panic_placeholder(message: &str, area: Rect, buf: &mut Buffer) {
+    let area = area.intersection(buf.area);
+    Clear.render(area, buf);
+    Paragraph::new(format!("panic: {message}"))
+        .wrap(Wrap { trim: true })
+        .style(Style::new().fg(Color::White).bg(Color::Red))
+        .render(area, buf);
+}
+
+/// keeps the corners of a block whose titles are wider than the element, and ends the cut
+/// title with an ellipsis. the edits are kept in `fit` until the block or its size changes.
+fn fit_block_titles(block: &Block, fit: Option<&mut TitleFit>, area: Rect, buf: &mut Buffer) {
+    let area = area.intersection(buf.area);
+    if area.width < 3 || area.height == 0 {
+        return;
+    }
+    // the border rows only depend on the width, two rows are enough for the top and the bottom.
+    let size = u16vec2(area.width, area.height.min(2));
+    let mut hasher = DefaultHasher::new();
+    block.hash(&mut hasher);
+    let key = (hasher.finish(), size);
+
+    let mut local = TitleFit::default();
+    let fit = fit.unwrap_or(&mut local);
+    if fit.0.as_ref().is_none_or(|(cached, _)| *cached != key) {
+        fit.0 = Some((key, title_edits(block, size)));
+    }
+    let Some((_, edits)) = &fit.0 else {
+        return;
+    };
+    for edit in edits {
+        let y = if edit.row == 0 {
+            area.top()
         } else {
-            for child in children.iter() {
-                self.render_impl(child, area, buf, offset);
+            area.bottom() - 1
+        };
+        let cell = &mut buf[(area.x + edit.x, y)];
+        match &edit.change {
+            TitleChange::Cell(new) => *cell = new.clone(),
+            TitleChange::Symbol(symbol) => {
+                cell.set_symbol(symbol);
+            }
+        }
+    }
+}
+
+/// finds the cells of the border rows of `block` to change at `size`, see [`fit_block_titles`].
+///
+/// ratatui doesn't expose the titles of a block, so a clone is rendered with room for every
+/// title and compared with a clone rendered at `size`: a border row that lost title cells was
+/// cut.
+fn title_edits(block: &Block, size: U16Vec2) -> Vec<TitleEdit> {
+    let render = |width: u16| {
+        let mut scratch = Buffer::empty(Rect::new(0, 0, width, size.y));
+        block.clone().render(scratch.area, &mut scratch);
+        scratch
+    };
+    let narrow = render(size.x);
+    let wide = render(size.x.saturating_add(TITLE_ROOM));
+    let last = size.x - 1;
+    let wide_last = wide.area.width - 1;
+
+    let mut edits = Vec::new();
+    for row in [0, size.y - 1] {
+        // the plain border symbol, the most common one of the wide row.
+        let mut counts = HashMap::<&str, usize>::new();
+        for x in 1..wide_last {
+            *counts.entry(wide[(x, row)].symbol()).or_default() += 1;
+        }
+        let Some(border) = counts
+            .into_iter()
+            .max_by_key(|(_, count)| *count)
+            .map(|(symbol, _)| symbol.to_string())
+        else {
+            continue;
+        };
+        let is_title = |buf: &Buffer, x: u16| buf[(x, row)].symbol() != border;
+        let shown = (1..last).filter(|x| is_title(&narrow, *x)).count();
+        let total = (1..wide_last).filter(|x| is_title(&wide, *x)).count();
+        if shown >= total {
+            continue;
+        }
+
+        for (x, wide_x) in [(0, 0), (last, wide_last)] {
+            if is_title(&wide, wide_x) && narrow[(x, row)] != wide[(wide_x, row)] {
+                let change = TitleChange::Cell(wide[(wide_x, row)].clone());
+                edits.push(TitleEdit { x, row, change });
             }
         }
+        let cut = if is_title(&narrow, last - 1) {
+            last - 1
+        } else if is_title(&narrow, 1) {
+            1
+        } else {
+            continue;
+        };
+        // replace the whole character when the cut lands on the second half of a wide one.
+        let halved = cut > 1 && Span::raw(narrow[(cut - 1, row)].symbol()).width() > 1;
+        if halved {
+            edits.push(TitleEdit {
+                x: cut,
+                row,
+                change: TitleChange::Symbol(" "),
+            });
+        }
+        edits.push(TitleEdit {
+            x: if halved { cut - 1 } else { cut },
+            row,
+            change: TitleChange::Symbol("…"),
+        });
     }
+    edits
+}
+
+/// the title edits of the last frame, keyed by the hash of the block and the size it was
+/// rendered at.
+#[derive(Debug, Default)]
+pub(crate) struct TitleFit(Option<((u64, U16Vec2), Vec<TitleEdit>)>);
+
+/// a cell of a border row changed by [`fit_block_titles`].
+#[derive(Debug)]
+struct TitleEdit {
+    x: u16,
+    /// the top row when zero, the bottom row otherwise.
+    row: u16,
+    change: TitleChange,
+}
+
+#[derive(Debug)]
+enum TitleChange {
+    Cell(Cell),
+    Symbol(&'static str),
+}
+
+/// the room [`fit_block_titles`] gives the titles to check if they were cut.
+const TITLE_ROOM: u16 = 512;
+
+/// the space taken by the gaps between `count` children, capped at [`u16::MAX`].
+fn gap_space(count: usize, gap: Gap) -> u16 {
+    u16::try_from(count.saturating_sub(1))
+        .unwrap_or(u16::MAX)
+        .saturating_mul(*gap)
+}
+
+/// the horizontal and vertical space taken by `padding`, capped at [`u16::MAX`].
+const fn padding_size(padding: &Padding) -> U16Vec2 {
+    u16vec2(
+        padding.left.saturating_add(padding.right),
+        padding.top.saturating_add(padding.bottom),
+    )
 }
 
 fn increase_axis(init: u16, dir: Direction, size: U16Vec2) -> u16 {
     match dir {
-        Direction::Horizontal => init + size.x,
-        Direction::Vertical => init + size.y,
+        Direction::Horizontal => init.saturating_add(size.x),
+        Direction::Vertical => init.saturating_add(size.y),
     }
 }
 
+/// a size split along a [`Direction`] instead of into width and height, the way the layout
+/// engine measures. useful for custom layout algorithms that work for both directions.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::layout::AxisSizes;
+/// # use ratatui::layout::{Direction, Rect};
+/// # use glam::u16vec2;
+/// let sizes = AxisSizes::from((Rect::new(2, 3, 40, 10), Direction::Vertical));
+/// assert_eq!((sizes.main_axis, sizes.cross_axis), (10, 40));
+/// assert_eq!(sizes.to_u16vec2(Direction::Vertical), u16vec2(40, 10));
+/// ```
 #[derive(
     Debug, Clone, Copy, Default, d::Sub, d::SubAssign, d::Add, d::AddAssign, d::Sum, PartialEq, Eq,
 )]
-struct AxisSizes {
-    main_axis: u16,
-    cross_axis: u16,
+pub struct AxisSizes {
+    /// the size along the direction.
+    pub main_axis: u16,
+    /// the size across the direction.
+    pub cross_axis: u16,
 }
 
-const fn axify(vec: U16Vec2, dir: Direction) -> AxisSizes {
+/// splits `vec` along `dir`, see [`AxisSizes::from_u16vec2`].
+#[must_use]
+pub const fn axify(vec: U16Vec2, dir: Direction) -> AxisSizes {
     AxisSizes::from_u16vec2(vec, dir)
 }
 
 impl AxisSizes {
+    /// the smaller size on each axis.
     #[inline(always)]
-    fn min(self, other: AxisSizes) -> AxisSizes {
+    #[must_use]
+    pub fn min(self, other: AxisSizes) -> AxisSizes {
         AxisSizes {
             main_axis: self.main_axis.min(other.main_axis),
             cross_axis: self.cross_axis.min(other.cross_axis),
         }
     }
-    const fn with_main(mut self, value: u16) -> Self {
+    /// the size with `value` on the main axis.
+    #[must_use]
+    pub const fn with_main(mut self, value: u16) -> Self {
         self.main_axis = value;
         self
     }
+    /// splits a width and height along `dir`.
     #[inline(always)]
-    const fn from_u16vec2(value: U16Vec2, dir: Direction) -> Self {
+    #[must_use]
+    pub const fn from_u16vec2(value: U16Vec2, dir: Direction) -> Self {
         match dir {
             Direction::Horizontal => Self {
                 main_axis: value.x,
@@ -581,47 +2892,46 @@ impl AxisSizes {
             },
         }
     }
+    /// the size grown by `padding` on both axes.
     #[inline(always)]
-    const fn pad(self, padding: Padding, dir: Direction) -> AxisSizes {
-        match dir {
-            Direction::Horizontal => AxisSizes {
-                main_axis: self.main_axis + padding.left + padding.right,
-                cross_axis: self.cross_axis + padding.top + padding.bottom,
-            },
-            Direction::Vertical => AxisSizes {
-                main_axis: self.main_axis + padding.top + padding.bottom,
-                cross_axis: self.cross_axis + padding.left + padding.right,
-            },
+    #[must_use]
+    pub const fn pad(self, padding: Padding, dir: Direction) -> AxisSizes {
+        let padding = axify(padding_size(&padding), dir);
+        AxisSizes {
+            main_axis: self.main_axis.saturating_add(padding.main_axis),
+            cross_axis: self.cross_axis.saturating_add(padding.cross_axis),
         }
     }
+    /// the size without `padding` on both axes.
     #[inline(always)]
-    const fn shrink(self, padding: Padding, dir: Direction) -> AxisSizes {
-        match dir {
-            Direction::Horizontal => AxisSizes {
-                main_axis: self.main_axis.saturating_sub(padding.left + padding.right),
-                cross_axis: self.cross_axis.saturating_sub(padding.top + padding.bottom),
-            },
-            Direction::Vertical => AxisSizes {
-                main_axis: self.main_axis.saturating_sub(padding.top + padding.bottom),
-                cross_axis: self.cross_axis.saturating_sub(padding.left + padding.right),
-            },
+    #[must_use]
+    pub const fn shrink(self, padding: Padding, dir: Direction) -> AxisSizes {
+        let padding = axify(padding_size(&padding), dir);
+        AxisSizes {
+            main_axis: self.main_axis.saturating_sub(padding.main_axis),
+            cross_axis: self.cross_axis.saturating_sub(padding.cross_axis),
         }
     }
+    /// the size after placing a child of size `by` next to it: the main axes add up, the cross
+    /// axis is the larger one.
     #[inline(always)]
-    fn increase(self, by: U16Vec2, dir: Direction) -> AxisSizes {
+    #[must_use]
+    pub fn increase(self, by: U16Vec2, dir: Direction) -> AxisSizes {
         match dir {
             Direction::Horizontal => AxisSizes {
-                main_axis: self.main_axis + by.x,
+                main_axis: self.main_axis.saturating_add(by.x),
                 cross_axis: self.cross_axis.max(by.y),
             },
             Direction::Vertical => AxisSizes {
-                main_axis: self.main_axis + by.y,
+                main_axis: self.main_axis.saturating_add(by.y),
                 cross_axis: self.cross_axis.max(by.x),
             },
         }
     }
+    /// the width and height of the size split along `dir`.
     #[inline(always)]
-    const fn to_u16vec2(self, dir: Direction) -> U16Vec2 {
+    #[must_use]
+    pub const fn to_u16vec2(self, dir: Direction) -> U16Vec2 {
         match dir {
             Direction::Horizontal => u16vec2(self.main_axis, self.cross_axis),
             Direction::Vertical => u16vec2(self.cross_axis, self.main_axis),
@@ -629,9 +2939,48 @@ impl AxisSizes {
     }
 }
 
+impl From<(Rect, Direction)> for AxisSizes {
+    /// splits the size of the rect, its position is dropped.
+    fn from((rect, dir): (Rect, Direction)) -> Self {
+        AxisSizes::from_u16vec2(u16vec2(rect.width, rect.height), dir)
+    }
+}
+
+impl From<(U16Vec2, Direction)> for AxisSizes {
+    fn from((vec, dir): (U16Vec2, Direction)) -> Self {
+        AxisSizes::from_u16vec2(vec, dir)
+    }
+}
+
+/// hash of the content of an element for its [`MeasureCache`].
+fn content_hash(entity: EntityRef<'_>) -> u64 {
+    if let Some(version) = entity.get::<&ContentVersion>() {
+        return **version;
+    }
+    let mut hasher = DefaultHasher::new();
+    if let Some(text) = entity.get::<&Text<'static>>() {
+        for line in &text.lines {
+            for span in &line.spans {
+                span.content.hash(&mut hasher);
+            }
+        }
+    } else if let Some(line) = entity.get::<&Line<'static>>() {
+        for span in &line.spans {
+            span.content.hash(&mut hasher);
+        }
+    } else if let Some(span) = entity.get::<&Span<'static>>() {
+        span.content.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
 /// type alias for [`hecs::Entity`]
 pub type Element = Entity;
 
+/// internal marker for text children created from strings. a [`Size::Fit`] height on these
+/// elements is measured from the number of lines in the text.
+pub(crate) struct FitText;
+
 /// marker for any element created via [`ui`][crate::ui::ui]. you can use this query elements in the tree.
 /// see [`hecs::World::query`] and [`hecs::World::query_mut`] for details.
 pub struct TuiElMarker;
@@ -649,32 +2998,55 @@ pub struct Props {
     pub set_style: fn(&mut World, Element, Style),
     ///
     pub get_style: fn(&World, Element) -> Option<Style>,
+    /// see [`ElWidget::fingerprint`].
+    pub fingerprint: fn(&World, Element) -> Option<u64>,
+    /// removes the widget, see [`ElementCtx::swap_widget`].
+    pub remove: fn(&mut World, Element),
     ///
     pub typeid: TypeId,
+    /// the type name of the widget, for debugging.
+    pub type_name: &'static str,
 }
 
 impl Props {
+    /// the area inside `padding`, in layout coordinates.
+    #[must_use]
+    pub fn inner_area(&self, padding: &Padding) -> Rect {
+        let inner = self.inner_size_from_padding(padding);
+        Rect::new(
+            self.position.x.saturating_add(padding.left),
+            self.position.y.saturating_add(padding.top),
+            inner.x,
+            inner.y,
+        )
+    }
     fn inner_size_from_padding(&self, padding: &Padding) -> U16Vec2 {
-        self.inner_size(Margin {
-            horizontal: padding.left + padding.right,
-            vertical: padding.top + padding.bottom,
+        let padding = padding_size(padding);
+        self.inner_size(ratatui::layout::Margin {
+            horizontal: padding.x,
+            vertical: padding.y,
         })
     }
-    fn inner_size(&self, margin: Margin) -> U16Vec2 {
+    fn inner_size(&self, margin: ratatui::layout::Margin) -> U16Vec2 {
         self.size
             .saturating_sub(u16vec2(margin.horizontal, margin.vertical))
     }
 }
 
 impl Props {
-    fn split_area(&self, area: Rect, offset: Offset) -> Rect {
-        area.intersection(Rect {
-            // DONE: implement position
-            x: self.position.x.saturating_add_signed(offset.x as i16),
-            y: self.position.y.saturating_add_signed(offset.y as i16),
-            width: self.size.x,
-            height: self.size.y,
-        })
+    pub(crate) fn split_area(&self, area: Rect, offset: Offset) -> Rect {
+        area.intersection(self.rect(offset))
+    }
+
+    /// the area of the element moved by `offset`, before it is cut to the area of its parent.
+    pub(crate) fn rect(&self, offset: Offset) -> Rect {
+        // DONE: implement position
+        Rect::new(
+            self.position.x.saturating_add_signed(offset.x as i16),
+            self.position.y.saturating_add_signed(offset.y as i16),
+            self.size.x,
+            self.size.y,
+        )
     }
 }
 
@@ -703,6 +3075,11 @@ macro_rules! impl_sizing_functions {
             pub const fn grow() -> Self {
                 Self(Size::Grow)
             }
+            /// create a [`Size::Fit`] value. alias of `fit`, reads better when replacing a fixed
+            /// size, see [`ElementCtx::resize_to_content`].
+            pub const fn shrink_to_fit() -> Self {
+                Self::fit()
+            }
         }
     };
 }
@@ -735,6 +3112,141 @@ impl_sizing_functions!(Height);
 #[derive(Debug, Clone, Copy, Default, d::Deref)]
 pub struct Gap(pub u16);
 
+/// the size a [`Size::Grow`] child starts at on its parent's main axis, before the remaining
+/// space is shared evenly between the growing children. without it a growing child starts at
+/// zero. a growing child never ends up smaller than its content.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// ui(Block::new())
+///     .with((Direction::Horizontal, Width::grow()))
+///     .children((
+///         ui(Block::bordered()).with((Width::grow(), FlexBasis(20))),
+///         ui(Block::bordered()).with((Width::grow(),)),
+///     ));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, d::Deref)]
+pub struct FlexBasis(pub u16);
+
+/// limits the children of an element that take part in layout and rendering to the first
+/// `n`, or the first `n` after [`ChildrenOffset`]. the other children stay spawned with a size
+/// of zero, so their state survives while they are hidden. useful for tabs and pages.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// // only the second tab is shown.
+/// ui(Block::bordered())
+///     .with((MaxChildren(1), ChildrenOffset(1)))
+///     .children((ui(Text::raw("first")), ui(Text::raw("second")), ui(Text::raw("third"))));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, d::Deref)]
+pub struct MaxChildren(pub usize);
+
+/// keeps the titles of a [`Block`] element as ratatui draws them when the element is narrower
+/// than they are. by default they are cut with an ellipsis between the corners.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoTruncateTitles;
+
+/// the order the children of an element are placed in on the main axis.
+///
+/// a [`FlowOrder::Reversed`] element mirrors the positions of its children: the first child is
+/// placed at the end of the main axis and the others follow it backwards, and [`MainJustify`] and
+/// [`AlignSelf`] are mirrored with them. the children keep their order everywhere else, like
+/// focus and rendering.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// // a status bar anchored to the right, overflowing to the left.
+/// ui(Block::new())
+///     .with((Direction::Horizontal, Width::grow(), FlowOrder::Reversed))
+///     .children((ui(Text::raw("12:00")), ui(Text::raw("main"))));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum FlowOrder {
+    /// from the start of the main axis to the end.
+    #[default]
+    Forward,
+    /// from the end of the main axis to the start.
+    Reversed,
+}
+
+/// the index of the first child that takes part in layout and rendering, the children before it
+/// are hidden like the ones past [`MaxChildren`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, d::Deref)]
+pub struct ChildrenOffset(pub usize);
+
+/// transparent space around the element, outside its border. unlike [`Padding`], which spaces
+/// the children from the border, the margin pushes the element's siblings away. the element is
+/// rendered inside its margin.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// ui(Block::new())
+///     .with((Direction::Horizontal,))
+///     .children((
+///         ui(Block::bordered()).with((Width::fixed(4), Margin::uniform(2))),
+///         ui(Block::bordered()).with((Width::fixed(4),)),
+///     ));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Margin {
+    /// space above the element.
+    pub top: u16,
+    /// space to the right of the element.
+    pub right: u16,
+    /// space below the element.
+    pub bottom: u16,
+    /// space to the left of the element.
+    pub left: u16,
+}
+
+impl Margin {
+    /// the same margin on every side.
+    #[must_use]
+    pub const fn uniform(value: u16) -> Self {
+        Self {
+            top: value,
+            right: value,
+            bottom: value,
+            left: value,
+        }
+    }
+    /// margin on the left and right.
+    #[must_use]
+    pub const fn horizontal(value: u16) -> Self {
+        Self {
+            top: 0,
+            right: value,
+            bottom: 0,
+            left: value,
+        }
+    }
+    /// margin above and below.
+    #[must_use]
+    pub const fn vertical(value: u16) -> Self {
+        Self {
+            top: value,
+            right: 0,
+            bottom: value,
+            left: 0,
+        }
+    }
+    const fn size(self) -> U16Vec2 {
+        U16Vec2::new(
+            self.left.saturating_add(self.right),
+            self.top.saturating_add(self.bottom),
+        )
+    }
+}
+
 /// holds a list of entity ids to the element's children. this component is added automatically.
 /// you can use this to iterate the children of an element like this
 ///
@@ -750,7 +3262,7 @@ pub struct Gap(pub u16);
 /// # let root = ctx.spawn_ui(root);
 /// let children = ctx.get::<&Children>(root).unwrap();
 /// for child in children.iter() {
-///     let width = ctx.get::<&Width>(*child);
+///     let width = ctx.get::<&Width>(child);
 /// }
 /// ```
 #[derive(Debug, Clone, Default)]
@@ -763,9 +3275,46 @@ pub enum Children {
 }
 
 impl Children {
-    fn iter<'a>(&'a self) -> ChildrenIter<'a> {
+    /// iterates over the children in order.
+    #[must_use]
+    pub fn iter(&self) -> ChildrenIter<'_> {
         self.into_iter()
     }
+    /// the number of children.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.deref().len()
+    }
+    /// whether there are no children, with either representation.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.deref().is_empty()
+    }
+    /// the child at `idx`.
+    #[must_use]
+    pub fn get(&self, idx: usize) -> Option<Element> {
+        self.deref().get(idx).copied()
+    }
+    /// the children of `element` that take part in layout and rendering, see [`MaxChildren`]
+    /// and [`ChildrenOffset`]. the hidden ones shouldn't be focused or react to keys either.
+    #[must_use]
+    pub fn visible(&self, world: &World, element: Element) -> Children {
+        let visible = Self::visible_range(world, element, self.len());
+        if visible.len() == self.len() {
+            return self.clone();
+        }
+        Children::Some(Arc::new(self[visible].to_vec()))
+    }
+    fn visible_range(world: &World, element: Element, len: usize) -> std::ops::Range<usize> {
+        let offset = world
+            .get::<&ChildrenOffset>(element)
+            .map_or(0, |offset| offset.0);
+        let max = world
+            .get::<&MaxChildren>(element)
+            .map_or(usize::MAX, |max| max.0);
+        let start = offset.min(len);
+        start..start.saturating_add(max).min(len)
+    }
 }
 
 impl Deref for Children {
@@ -790,8 +3339,20 @@ impl<'a> Iterator for ChildrenIter<'a> {
     fn next(&mut self) -> Option<Self::Item> {
         self.inner.next()
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for ChildrenIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.next_back()
+    }
 }
 
+impl ExactSizeIterator for ChildrenIter<'_> {}
+
 impl<'a> IntoIterator for &'a Children {
     type Item = Element;
 
@@ -819,7 +3380,7 @@ fn main_size(dir: Direction, x: Width, y: Height) -> Size {
 }
 
 /// defines the sizing strategy for an element.
-#[derive(Default, Clone, Copy, Debug)]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Size {
     /// the element will be exactly `x` cells in size, clamped to it's parent's size.
     Fixed(u16),
@@ -831,7 +3392,7 @@ pub enum Size {
 }
 
 /// defines the alignment strategy on the main axis.
-#[derive(Default, Clone, Copy, Debug, strum::EnumIter)]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash, strum::EnumIter)]
 pub enum MainJustify {
     /// aligns the items toward the start of the container.
     ///
@@ -907,7 +3468,7 @@ pub enum MainJustify {
     End,
 }
 
-#[derive(Default, Clone, Copy, Debug, strum::EnumIter)]
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash, strum::EnumIter)]
 /// defines the alignment strategy on the cross axis.
 pub enum CrossJustify {
     /// aligns the items toward the start of the container.
@@ -917,6 +3478,125 @@ pub enum CrossJustify {
     Center,
     /// aligns the items toward the end of the container.
     End,
+    /// makes the items with a [`Size::Fit`] cross size fill the cross axis of the container.
+    /// items with a [`Size::Fixed`] cross size keep it and are aligned to the start.
+    Stretch,
+}
+
+/// overrides the alignment of a single child, for example a right aligned button in an otherwise
+/// left aligned toolbar.
+///
+/// on the cross axis it replaces the parent's [`CrossJustify`] for this child. on the main axis,
+/// [`CrossJustify::Start`] and [`CrossJustify::End`] take the child out of the parent's
+/// [`MainJustify`] distribution and anchor it to the start or the end of the parent. multiple
+/// anchored children stack in order. [`CrossJustify::Center`] and [`CrossJustify::Stretch`] only
+/// affect the cross axis.
+///
+/// has no effect on the cross axis of a child that grows on it.
+///
+/// # Example
+///
+/// ```plaintext
+/// ╭toolbar───────────────╮
+/// │╭──╮╭──╮          ╭──╮│
+/// ││#0││#1│          ││#2││
+/// │╰──╯╰──╯          ╰──╯│
+/// ╰──────────────────────╯
+/// ```
+///
+/// `#2` has `AlignSelf(CrossJustify::End)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, d::Deref)]
+pub struct AlignSelf(pub CrossJustify);
+
+/// lays out exactly two children side by side along the element's [`Direction`], splitting the
+/// space at `ratio` with a one cell divider between them. the [`Width`] and [`Height`] of the
+/// children are ignored, and the divider replaces the element's [`Gap`].
+///
+/// the element grows to fill its parent by default. `mana-tui-beheaded` moves the divider with
+/// the mouse and with ctrl + arrow keys.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// ui(Block::new())
+///     .with((Direction::Horizontal, SplitPane::new(0.25).min_first(10)))
+///     .children((
+///         ui(Text::raw("sidebar")),
+///         ui(Text::raw("content")),
+///     ));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SplitPane {
+    /// the share of the space, without the divider, taken by the first child.
+    pub ratio: f32,
+    /// the smallest size of the first child along the main axis.
+    pub min_first: u16,
+    /// the smallest size of the second child along the main axis.
+    pub min_second: u16,
+}
+
+impl SplitPane {
+    /// a split giving `ratio` of the space to the first child, without minimum sizes.
+    #[must_use]
+    pub const fn new(ratio: f32) -> Self {
+        Self {
+            ratio,
+            min_first: 0,
+            min_second: 0,
+        }
+    }
+    /// sets the smallest size of the first child.
+    #[must_use]
+    pub const fn min_first(mut self, min_first: u16) -> Self {
+        self.min_first = min_first;
+        self
+    }
+    /// sets the smallest size of the second child.
+    #[must_use]
+    pub const fn min_second(mut self, min_second: u16) -> Self {
+        self.min_second = min_second;
+        self
+    }
+    /// the sizes of the two children when they share `available` cells. the minimum of the
+    /// first child wins when both minimums don't fit.
+    #[must_use]
+    pub fn sizes(&self, available: u16) -> (u16, u16) {
+        let first = (f32::from(available) * self.ratio.clamp(0.0, 1.0)).round() as u16;
+        let first = first
+            .min(available.saturating_sub(self.min_second))
+            .max(self.min_first)
+            .min(available);
+        (first, available - first)
+    }
+    /// the ratio that gives the first child `first` cells out of `available`, clamped by the
+    /// minimum sizes.
+    #[must_use]
+    pub fn ratio_at(&self, first: u16, available: u16) -> f32 {
+        if available == 0 {
+            return self.ratio;
+        }
+        let first = first
+            .min(available.saturating_sub(self.min_second))
+            .max(self.min_first)
+            .min(available);
+        f32::from(first) / f32::from(available)
+    }
+    /// the cells of the divider, given the area inside the element's padding.
+    #[must_use]
+    pub fn divider(&self, inner: Rect, direction: Direction) -> Rect {
+        match direction {
+            Direction::Horizontal => {
+                let (first, _) = self.sizes(inner.width.saturating_sub(1));
+                Rect::new(inner.x.saturating_add(first), inner.y, 1, inner.height)
+            }
+            Direction::Vertical => {
+                let (first, _) = self.sizes(inner.height.saturating_sub(1));
+                Rect::new(inner.x, inner.y.saturating_add(first), inner.width, 1)
+            }
+        }
+        .intersection(inner)
+    }
 }
 
 pub(crate) trait ManaComponent {
@@ -4,15 +4,44 @@
 
 extern crate self as mana_tui_elemental;
 
+pub mod animation;
+pub mod blit;
+pub mod color;
+pub mod damage;
+pub mod handle;
+pub mod inspect;
 pub mod layout;
+pub mod order;
 pub mod prelude;
+pub mod profile;
+pub mod progress;
+pub mod schedule;
+pub mod separator;
+pub mod spacer;
+pub mod spinner;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod text_hit;
+pub mod theme;
 pub mod ui;
 
 #[cfg(test)]
 mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use glam::U16Vec2;
     use hecs::World;
     use mana_tui::prelude::*;
-    use ratatui::{buffer::Buffer, layout::Rect, widgets::Wrap};
+    use ratatui::{
+        Terminal,
+        backend::TestBackend,
+        buffer::Buffer,
+        layout::{Position, Rect},
+        widgets::Wrap,
+    };
     use strum::IntoEnumIterator;
 
     fn buffer_to_string(buf: &Buffer) -> String {
@@ -22,6 +51,23 @@ mod tests {
             .collect()
     }
 
+    /// the position and size of every element of the tree of `root`, depth first.
+    fn snapshot(ctx: &ElementCtx, root: Element) -> Vec<(U16Vec2, U16Vec2)> {
+        use mana_tui::mana_tui_elemental::layout::Props;
+
+        fn walk(ctx: &ElementCtx, element: Element, out: &mut Vec<(U16Vec2, U16Vec2)>) {
+            let props = *ctx.get::<&Props>(element).unwrap();
+            out.push((props.position, props.size));
+            let children = ctx.get::<&Children>(element).unwrap().clone();
+            for child in children.iter() {
+                walk(ctx, child, out);
+            }
+        }
+        let mut out = Vec::new();
+        walk(ctx, root, &mut out);
+        out
+    }
+
     #[test]
     fn test_grow_2() {
         _ = tracing_subscriber::fmt::try_init();
@@ -245,6 +291,2537 @@ mod tests {
         tracing::info!("\ntest_list_justify\n{}", buffer_to_string(&buf));
     }
 
+    #[test]
+    fn test_align_self() {
+        _ = tracing_subscriber::fmt::try_init();
+        _ = color_eyre::install();
+
+        fn render(root: View) -> Buffer {
+            let mut ctx = ElementCtx::new();
+            let root = ctx.spawn_ui(root);
+            let mut buf = Buffer::empty(Rect::new(0, 0, 24, 5));
+            ctx.calculate_layout(root, buf.area).unwrap();
+            ctx.render(root, buf.area, &mut buf);
+            buf
+        }
+        #[subview]
+        fn numbered_box(idx: i32) -> View {
+            ui! {
+                <Block .rounded Width::fixed(4) Height::fixed(3)>
+                    "{idx:02}"
+                </Block>
+            }
+        }
+
+        for gap in [0, 1] {
+            let aligned = render(ui! {
+                <Block .rounded Width::fixed(24) Height::fixed(5) Direction::Horizontal Gap(gap)>
+                    <NumberedBox .idx={0} />
+                    <NumberedBox .idx={1} />
+                    <Block .rounded Width::fixed(4) Height::fixed(3) AlignSelf(CrossJustify::End)>
+                        "02"
+                    </Block>
+                </Block>
+            });
+            let spacer = render(ui! {
+                <Block .rounded Width::fixed(24) Height::fixed(5) Direction::Horizontal Gap(gap)>
+                    <NumberedBox .idx={0} />
+                    <NumberedBox .idx={1} />
+                    <Block Width::grow() Height::fixed(3) />
+                    <NumberedBox .idx={2} />
+                </Block>
+            });
+            tracing::info!(
+                "\ntest_align_self gap {gap}\n{}",
+                buffer_to_string(&aligned)
+            );
+            assert_eq!(aligned, spacer);
+        }
+
+        // on the cross axis it overrides the parent's `CrossJustify`.
+        let buf = render(ui! {
+            <Block .rounded Width::fixed(24) Height::fixed(5) Direction::Horizontal>
+                <Block Width::fixed(1) Height::fixed(1)>"a"</Block>
+                <Block Width::fixed(1) Height::fixed(1) AlignSelf(CrossJustify::Center)>"b"</Block>
+                <Block Width::fixed(1) Height::fixed(1) AlignSelf(CrossJustify::End)>"c"</Block>
+            </Block>
+        });
+        let expected = Buffer::with_lines(vec![
+            "╭──────────────────────╮",
+            "│a                     │",
+            "│ b                    │",
+            "│                     c│",
+            "╰──────────────────────╯",
+        ]);
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn test_resize_to_content() {
+        let mut ctx = ElementCtx::new();
+        let label = ui(Block::bordered())
+            .with((Width::fixed(50), Height::fixed(50)))
+            .child(ui(Block::new()).with((Width::fixed(10), Height::fixed(1))));
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Width::grow(), Height::grow(), Center))
+                .child(label),
+        );
+        ctx.calculate_layout(root, Rect::new(0, 0, 60, 60)).unwrap();
+        let label = ctx.get::<&Children>(root).unwrap()[0];
+        assert_eq!(
+            ctx.get::<&mana_tui::mana_tui_elemental::layout::Props>(label)
+                .unwrap()
+                .size,
+            U16Vec2::new(50, 50)
+        );
+
+        ctx.resize_to_content(label).unwrap();
+        let props = *ctx
+            .get::<&mana_tui::mana_tui_elemental::layout::Props>(label)
+            .unwrap();
+        assert_eq!(props.size, U16Vec2::new(12, 3));
+        // the parent is laid out again, so the label is centered with its new size.
+        assert_eq!(props.position, U16Vec2::new(24, 28));
+    }
+
+    #[test]
+    fn test_conditional() {
+        let mut ctx = ElementCtx::new();
+        let tree = |show: bool| {
+            ui(Block::new()).children((
+                ui(Text::raw("always")),
+                ui(Text::raw("maybe")).conditional(move || show),
+                ui(Text::raw("last")),
+            ))
+        };
+
+        let root = ctx.spawn_ui(tree(false));
+        let children = ctx.get::<&Children>(root).unwrap().clone();
+        assert_eq!(children.len(), 2);
+        assert_eq!(
+            ctx.get::<&Text<'static>>(children[1]).unwrap().lines[0].spans[0].content,
+            "last"
+        );
+        ctx.despawn_ui(root);
+
+        let root = ctx.spawn_ui(tree(true));
+        let children = ctx.get::<&Children>(root).unwrap().clone();
+        assert_eq!(children.len(), 3);
+        assert_eq!(
+            ctx.get::<&Text<'static>>(children[1]).unwrap().lines[0].spans[0].content,
+            "maybe"
+        );
+
+        // a conditional element also works as a list of children on its own.
+        let root = ctx.spawn_ui(ui(Block::new()).children(ui(Block::new()).conditional(|| false)));
+        assert_eq!(ctx.get::<&Children>(root).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_measure() {
+        let mut ctx = ElementCtx::new();
+        let entities = ctx.len();
+        assert_eq!(
+            ctx.measure(ui(SourceParagraph::new("hello")).done()),
+            U16Vec2::new(5, 1)
+        );
+        assert_eq!(
+            ctx.measure(ui(SourceParagraph::new("hello").block(Block::bordered())).done()),
+            U16Vec2::new(7, 3)
+        );
+        assert_eq!(
+            ctx.measure(
+                ui(Block::bordered())
+                    .with((Width::grow(), Height::grow()))
+                    .children((
+                        ui(Text::raw("two\nlines")),
+                        ui(Block::new()).with((Width::fixed(8),)),
+                    ))
+                    .done()
+            ),
+            U16Vec2::new(10, 4)
+        );
+        // the temporary tree is gone.
+        assert_eq!(ctx.len(), entities);
+    }
+
+    #[test]
+    fn test_measure_cache() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let measure = Measure::new({
+            let count = count.clone();
+            move |element, _| {
+                count.fetch_add(1, Ordering::Relaxed);
+                let width = element
+                    .get::<&Text<'static>>()
+                    .map_or(0, |text| text.width());
+                U16Vec2::new(width as u16, 2)
+            }
+        });
+
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Width::grow(), Height::grow()))
+                .child(ui(Text::raw("hello")).with((Width::fit(), Height::fit(), measure))),
+        );
+        let label = ctx.get::<&Children>(root).unwrap()[0];
+        let area = Rect::new(0, 0, 20, 10);
+        let size = |ctx: &ElementCtx| {
+            ctx.get::<&mana_tui::mana_tui_elemental::layout::Props>(label)
+                .unwrap()
+                .size
+        };
+
+        for _ in 0..3 {
+            ctx.calculate_layout(root, area).unwrap();
+        }
+        assert_eq!(count.load(Ordering::Relaxed), 1);
+        assert_eq!(size(&ctx), U16Vec2::new(5, 2));
+
+        // new content is measured again.
+        ctx.insert_one(label, Text::raw("hello world")).unwrap();
+        ctx.calculate_layout(root, area).unwrap();
+        ctx.calculate_layout(root, area).unwrap();
+        assert_eq!(count.load(Ordering::Relaxed), 2);
+        assert_eq!(size(&ctx), U16Vec2::new(11, 2));
+
+        ctx.insert_one(label, ContentVersion(1)).unwrap();
+        ctx.calculate_layout(root, area).unwrap();
+        assert_eq!(count.load(Ordering::Relaxed), 3);
+
+        ctx.invalidate_measure(label);
+        ctx.calculate_layout(root, area).unwrap();
+        ctx.calculate_layout(root, area).unwrap();
+        assert_eq!(count.load(Ordering::Relaxed), 4);
+    }
+
+    #[test]
+    fn test_shadow() {
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(
+            ui(Block::bordered())
+                .with((Width::fixed(4), Height::fixed(3)))
+                .shadow(Style::new().bg(Color::DarkGray)),
+        );
+
+        let mut buf = Buffer::with_lines(vec!["xxxxxx"; 5]);
+        ctx.calculate_layout(root, buf.area).unwrap();
+        ctx.render(root, buf.area, &mut buf);
+
+        let mut expected =
+            Buffer::with_lines(vec!["┌──┐xx", "│  │ x", "└──┘ x", "x    x", "xxxxxx"]);
+        expected.set_style(Rect::new(1, 1, 4, 3), Style::new().bg(Color::DarkGray));
+        // the element is drawn on top of the shadow, keeping the shadow's background.
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn test_render_incremental_overlay() {
+        let area = Rect::new(0, 0, 4, 2);
+        let view = |shadow: bool| {
+            let top = ui(Text::raw("a")).with((Width::fixed(2), Height::fixed(1)));
+            let top = if shadow {
+                top.shadow(Style::new().bg(Color::Red))
+            } else {
+                top
+            };
+            ui(Container)
+                .with((Width::fixed(4), Height::fixed(2), Direction::Vertical))
+                .children((
+                    top,
+                    ui(Text::raw("b")).with((Width::fixed(4), Height::fixed(1))),
+                ))
+        };
+        let frame = |ctx: &mut ElementCtx, previous: &mut Buffer, shadow: bool| {
+            let root = ctx.spawn_ui(view(shadow));
+            ctx.calculate_layout(root, area).unwrap();
+            let mut next = Buffer::empty(area);
+            ctx.render_incremental(root, area, &mut next, previous);
+            *previous = next;
+            ctx.despawn_ui(root);
+        };
+
+        let mut ctx = ElementCtx::new();
+        let mut previous = Buffer::empty(area);
+        frame(&mut ctx, &mut previous, true);
+        assert_eq!(previous[(1, 1)].bg, Color::Red);
+        // the text below didn't change, but the shadow drawn over it is gone.
+        frame(&mut ctx, &mut previous, false);
+        assert_eq!(previous[(1, 1)].bg, Color::Reset);
+    }
+
+    #[test]
+    fn test_shortcut_hint() {
+        use ratatui::{crossterm::event::KeyCode, style::Modifier};
+
+        let expected = [
+            (
+                HintPosition::TopLeft,
+                ["[q]─────┐", "│        │", "└────────┘"],
+                Rect::new(0, 0, 3, 1),
+            ),
+            (
+                HintPosition::TopRight,
+                ["┌─────[q]", "│        │", "└────────┘"],
+                Rect::new(7, 0, 3, 1),
+            ),
+            (
+                HintPosition::BottomLeft,
+                ["┌────────┐", "│        │", "[q]─────┘"],
+                Rect::new(0, 2, 3, 1),
+            ),
+            (
+                HintPosition::BottomRight,
+                ["┌────────┐", "│        │", "└─────[q]"],
+                Rect::new(7, 2, 3, 1),
+            ),
+        ];
+        assert_eq!(expected.len(), HintPosition::iter().count());
+        for (position, lines, badge) in expected {
+            let mut ctx = ElementCtx::new();
+            let root = ctx.spawn_ui(
+                ui(Block::bordered())
+                    .with((Width::fixed(10), Height::fixed(3)))
+                    .shortcut_hint(KeyCode::Char('q'), position),
+            );
+            let mut buf = Buffer::empty(Rect::new(0, 0, 10, 3));
+            ctx.calculate_layout(root, buf.area).unwrap();
+            ctx.render(root, buf.area, &mut buf);
+
+            let mut expected = Buffer::with_lines(lines);
+            expected.set_style(badge, Style::new().add_modifier(Modifier::BOLD));
+            assert_eq!(buf, expected, "{position:?}");
+        }
+
+        // the badge stays on top of the children.
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(
+            ui(Container)
+                .with((Width::fixed(6), Height::fixed(1)))
+                .shortcut_hint(KeyCode::Char('q'), HintPosition::TopRight)
+                .child(ui(Text::raw("xxxxxx")).with((Width::fixed(6), Height::fixed(1)))),
+        );
+        let mut buf = Buffer::empty(Rect::new(0, 0, 6, 1));
+        ctx.calculate_layout(root, buf.area).unwrap();
+        ctx.render(root, buf.area, &mut buf);
+        assert_eq!(buffer_to_string(&buf), "xxx[q]\n");
+    }
+
+    #[test]
+    fn test_reading_order_borders() {
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(
+            ui(Block::bordered().title("menu"))
+                .with((Width::fixed(12), Height::fixed(5), Direction::Vertical))
+                .children((
+                    ui(Text::raw("a ─ b")),
+                    ui(Paragraph::new("x│y").block(Block::bordered()))
+                        .with((Width::fixed(5), Height::fixed(3))),
+                )),
+        );
+        ctx.calculate_layout(root, Rect::new(0, 0, 12, 5)).unwrap();
+        // only the borders are left out, box drawing characters in the text are kept.
+        assert_eq!(
+            ctx.reading_order(root).to_plain_text(),
+            "container: menu\n  text: a ─ b\n  text: x│y"
+        );
+    }
+
+    fn dump_fixture(ctx: &mut ElementCtx) -> (Element, Element) {
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Width::grow(), Height::grow(), Direction::Horizontal))
+                .children((
+                    ui(Block::bordered()).with((
+                        Width::fixed(4),
+                        Height::grow(),
+                        ElId::new("sidebar"),
+                    )),
+                    ui(Text::raw("main")),
+                )),
+        );
+        let sidebar = ctx.get::<&Children>(root).unwrap()[0];
+        ctx.calculate_layout(root, Rect::new(0, 0, 20, 5)).unwrap();
+        (root, sidebar)
+    }
+
+    #[test]
+    fn test_dump_tree_diff() {
+        use mana_tui::mana_tui_elemental::inspect::TreeDiff;
+
+        let mut ctx = ElementCtx::new();
+        let (root, sidebar) = dump_fixture(&mut ctx);
+        let before = ctx.dump_tree(root);
+        assert_eq!(before.root.children.len(), 2);
+        assert_eq!(before.root.children[0].id.as_deref(), Some("sidebar"));
+        assert_eq!(before.root.children[0].rect, Rect::new(0, 0, 4, 5));
+        assert_eq!(
+            before.root.children[0].width.as_deref(),
+            Some("Width(Fixed(4))")
+        );
+        assert!(before.root.children[1].widget.contains("Text"));
+
+        ctx.insert_one(sidebar, Width::fixed(6)).unwrap();
+        ctx.calculate_layout(root, Rect::new(0, 0, 20, 5)).unwrap();
+        let after = ctx.dump_tree(root);
+        assert_eq!(
+            before.diff(&after),
+            vec![TreeDiff::Resized {
+                key: "#sidebar".to_string(),
+                from: Rect::new(0, 0, 4, 5),
+                to: Rect::new(0, 0, 6, 5),
+            }]
+        );
+        assert!(after.diff(&after).is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_dump_tree_json() {
+        use mana_tui::mana_tui_elemental::inspect::TreeDump;
+
+        let mut ctx = ElementCtx::new();
+        let (root, _) = dump_fixture(&mut ctx);
+        let dump = ctx.dump_tree(root);
+        let json = serde_json::to_string(&dump).unwrap();
+        assert_eq!(serde_json::from_str::<TreeDump>(&json).unwrap(), dump);
+    }
+
+    #[test]
+    fn test_margin() {
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Width::grow(), Height::grow(), Direction::Horizontal))
+                .children((
+                    ui(Block::bordered()).with((
+                        Width::fixed(4),
+                        Height::fixed(3),
+                        Margin::uniform(2),
+                    )),
+                    ui(Block::bordered()).with((Width::fixed(4), Height::fixed(3))),
+                )),
+        );
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 14, 7));
+        ctx.calculate_layout(root, buf.area).unwrap();
+        ctx.render(root, buf.area, &mut buf);
+        tracing::info!("\ntest_margin\n{}", buffer_to_string(&buf));
+
+        let expected = Buffer::with_lines(vec![
+            "        ┌──┐  ",
+            "        │  │  ",
+            "  ┌──┐  └──┘  ",
+            "  │  │        ",
+            "  └──┘        ",
+            "              ",
+            "              ",
+        ]);
+        assert_eq!(buf, expected);
+    }
+
+    fn fit_row(count: usize, size: u16, gap: u16, padding: u16) -> View {
+        ui(Block::new())
+            .with((Direction::Horizontal, Gap(gap), Padding::uniform(padding)))
+            .children(
+                (0..count)
+                    .map(|_| {
+                        ui(Block::new())
+                            .with((Width::fixed(size), Height::fixed(size)))
+                            .done()
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .done()
+    }
+
+    #[test]
+    fn test_size_overflow() {
+        let mut ctx = ElementCtx::new();
+        let area = Rect::new(0, 0, 80, 24);
+        for view in [
+            fit_row(70, 1, 1000, 0),
+            fit_row(2, u16::MAX, 0, 0),
+            fit_row(1, 10, 0, u16::MAX),
+        ] {
+            let root = ctx.spawn_ui(view);
+            ctx.calculate_layout(root, area).unwrap();
+            let size = ctx
+                .get::<&mana_tui::mana_tui_elemental::layout::Props>(root)
+                .unwrap()
+                .size;
+            assert_eq!(size.x, u16::MAX);
+            ctx.despawn_ui(root);
+        }
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn test_size_overflow_props(
+            count in 0usize..100,
+            size in proptest::num::u16::ANY,
+            gap in proptest::num::u16::ANY,
+            padding in proptest::num::u16::ANY,
+        ) {
+            let mut ctx = ElementCtx::new();
+            let area = Rect::new(0, 0, 80, 24);
+            let mut fit_width = |gap| {
+                let root = ctx.spawn_ui(fit_row(count, size, gap, padding));
+                ctx.calculate_layout(root, area).unwrap();
+                let width = ctx
+                    .get::<&mana_tui::mana_tui_elemental::layout::Props>(root)
+                    .unwrap()
+                    .size
+                    .x;
+                ctx.despawn_ui(root);
+                width
+            };
+            // more space between the children never makes the row smaller.
+            let width = fit_width(gap);
+            proptest::prop_assert!(fit_width(gap.saturating_add(1)) >= width);
+        }
+    }
+
+    #[test]
+    fn test_aspect_ratio_box() {
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Width::grow(), Height::grow()))
+                .child(
+                    AspectRatioBox::new(16, 9)
+                        .child(ui(Block::bordered()).with((Width::grow(), Height::grow()))),
+                ),
+        );
+        ctx.calculate_layout(root, Rect::new(0, 0, 64, 50)).unwrap();
+
+        let video = ctx.get::<&Children>(root).unwrap()[0];
+        let frame = ctx.get::<&Children>(video).unwrap()[0];
+        let size = |element| {
+            ctx.get::<&mana_tui::mana_tui_elemental::layout::Props>(element)
+                .unwrap()
+                .size
+        };
+        assert_eq!(size(video), U16Vec2::new(64, 64 * 9 / 16));
+        // children grow into the resolved height.
+        assert_eq!(size(frame), U16Vec2::new(64, 36));
+
+        // a column shares out the height left after the box.
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Width::grow(), Height::grow()))
+                .children((
+                    AspectRatioBox::new(2, 1),
+                    ui(Block::new()).with((Height::grow(),)),
+                )),
+        );
+        ctx.calculate_layout(root, Rect::new(0, 0, 40, 30)).unwrap();
+        let children = ctx.get::<&Children>(root).unwrap().clone();
+        assert_eq!(size(children[0]), U16Vec2::new(40, 20));
+        assert_eq!(size(children[1]).y, 10);
+
+        // in a row the height follows the width the box grew to.
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Width::grow(), Height::grow(), Direction::Horizontal))
+                .children((
+                    AspectRatioBox::new(2, 1),
+                    ui(Block::new()).with((Width::fixed(20), Height::grow())),
+                )),
+        );
+        ctx.calculate_layout(root, Rect::new(0, 0, 40, 30)).unwrap();
+        let children = ctx.get::<&Children>(root).unwrap().clone();
+        assert_eq!(size(children[0]), U16Vec2::new(20, 10));
+
+        // a fixed width is resolved in the fit pass, so fitting parents make room for it.
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Width::fit(), Height::fit()))
+                .child(AspectRatioBox::new(2, 1).with((Width::fixed(10),))),
+        );
+        ctx.calculate_layout(root, Rect::new(0, 0, 40, 30)).unwrap();
+        assert_eq!(size(root), U16Vec2::new(10, 5));
+    }
+
+    #[test]
+    fn test_retain_keyed() {
+        #[derive(Debug, PartialEq)]
+        struct Expanded;
+
+        let mut ctx = ElementCtx::new();
+        ctx.retain_keyed::<Expanded>();
+        let list = |ctx: &mut ElementCtx, keys: &[u32]| {
+            let root = ctx.spawn_ui(keyed_list(keys.iter().map(|&key| (key, ())), |_, ()| {
+                ui(Block::new()).done()
+            }));
+            let children = ctx.get::<&Children>(root).unwrap().clone();
+            (root, children)
+        };
+        let expanded = |ctx: &ElementCtx, children: &Children| {
+            children
+                .iter()
+                .map(|child| ctx.get::<&Expanded>(child).is_ok())
+                .collect::<Vec<_>>()
+        };
+
+        let (root, children) = list(&mut ctx, &[1, 2, 3]);
+        ctx.insert_one(children[1], Expanded).unwrap();
+        ctx.despawn_ui(root);
+
+        // the state follows key 2 to the front.
+        let (root, children) = list(&mut ctx, &[2, 3, 1]);
+        assert_eq!(expanded(&ctx, &children), [true, false, false]);
+
+        // states whose key is gone are dropped.
+        ctx.despawn_ui(root);
+        list(&mut ctx, &[1, 3]);
+        let (_, children) = list(&mut ctx, &[2]);
+        assert_eq!(expanded(&ctx, &children), [false]);
+    }
+
+    #[test]
+    fn test_clear() {
+        _ = tracing_subscriber::fmt::try_init();
+        _ = color_eyre::install();
+
+        let mut ctx = ElementCtx::new();
+        let root = ui(Block::new())
+            .with((Width::grow(), Height::grow()))
+            .child(ui(Clear));
+        let root = ctx.spawn_ui(root);
+
+        let mut buf = Buffer::with_lines(vec!["xxxxxx", "xxxxxx"]);
+        ctx.calculate_layout(root, buf.area).unwrap();
+        ctx.render(root, buf.area, &mut buf);
+        tracing::info!("\ntest_clear\n{}", buffer_to_string(&buf));
+
+        assert_eq!(buf, Buffer::with_lines(vec!["      ", "      "]));
+    }
+
+    #[test]
+    fn test_clear_background() {
+        _ = tracing_subscriber::fmt::try_init();
+        _ = color_eyre::install();
+
+        let mut ctx = ElementCtx::new();
+        let root = ui(Block::new())
+            .with((Width::grow(), Height::grow(), Center))
+            .child(
+                ui(Block::bordered())
+                    .with((Width::fixed(4), Height::fixed(3)))
+                    .clear_background(),
+            );
+        let root = ctx.spawn_ui(root);
+
+        let mut buf = Buffer::with_lines(vec!["xxxxxx"; 5]);
+        ctx.calculate_layout(root, buf.area).unwrap();
+        ctx.render(root, buf.area, &mut buf);
+        tracing::info!("\ntest_clear_background\n{}", buffer_to_string(&buf));
+
+        let expected = Buffer::with_lines(vec!["xxxxxx", "x┌──┐x", "x│  │x", "x└──┘x", "xxxxxx"]);
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn test_fit_text_children() {
+        _ = tracing_subscriber::fmt::try_init();
+        _ = color_eyre::install();
+
+        let mut ctx = ElementCtx::new();
+        let root = ui(Block::bordered())
+            .with((Width::fixed(10), Height::fit()))
+            .children("line1\nline2\nline3");
+        let root = ctx.spawn_ui(root);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 6));
+        ctx.calculate_layout(root, buf.area).unwrap();
+        ctx.render(root, buf.area, &mut buf);
+        tracing::info!("\ntest_fit_text_children\n{}", buffer_to_string(&buf));
+
+        let size = ctx
+            .get::<&mana_tui::mana_tui_elemental::layout::Props>(root)
+            .unwrap()
+            .size;
+        assert_eq!(size.y, 5);
+        let expected = Buffer::with_lines(vec![
+            "┌────────┐",
+            "│line1   │",
+            "│line2   │",
+            "│line3   │",
+            "└────────┘",
+            "          ",
+        ]);
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn test_fit_text_explicit_height() {
+        _ = tracing_subscriber::fmt::try_init();
+        _ = color_eyre::install();
+
+        let mut ctx = ElementCtx::new();
+        let root = ui(Block::new())
+            .with((Width::fixed(10), Height::fixed(3)))
+            .child(ui(Text::raw("line1\nline2\nline3")).with((Width::grow(), Height::fixed(1))));
+        let root = ctx.spawn_ui(root);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 3));
+        ctx.calculate_layout(root, buf.area).unwrap();
+        ctx.render(root, buf.area, &mut buf);
+        tracing::info!(
+            "\ntest_fit_text_explicit_height\n{}",
+            buffer_to_string(&buf)
+        );
+
+        let expected = Buffer::with_lines(vec!["line1     ", "          ", "          "]);
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn test_cursor() {
+        _ = tracing_subscriber::fmt::try_init();
+        _ = color_eyre::install();
+
+        let mut ctx = ElementCtx::new();
+        let root = ui(Block::bordered())
+            .with((Width::fixed(10), Height::fixed(3)))
+            .children((
+                ui(Text::raw("hidden")).with((Cursor::default(),)),
+                ui(Text::raw("input")).with((Cursor {
+                    shape: CursorShape::SteadyBar,
+                    position: Some(U16Vec2::new(5, 0)),
+                },)),
+            ));
+        let root = ctx.spawn_ui(root);
+
+        let mut terminal = Terminal::new(TestBackend::new(10, 4)).unwrap();
+        terminal
+            .draw(|frame| {
+                ctx.calculate_layout(root, frame.area()).unwrap();
+                ctx.render(root, frame.area(), frame.buffer_mut());
+                if let Some((position, _)) = ctx.cursor() {
+                    frame.set_cursor_position(position);
+                }
+            })
+            .unwrap();
+
+        assert_eq!(
+            ctx.cursor(),
+            Some((Position::new(6, 2), CursorShape::SteadyBar))
+        );
+        terminal
+            .backend_mut()
+            .assert_cursor_position(Position::new(6, 2));
+    }
+
+    #[test]
+    fn test_fade_in() {
+        use ratatui::style::{Modifier, Stylize};
+
+        _ = tracing_subscriber::fmt::try_init();
+        _ = color_eyre::install();
+
+        let mut fade = FadeIn::new(Duration::from_millis(100));
+        assert_eq!(fade.opacity(), 0.0);
+        assert_eq!(
+            fade.tick(Duration::from_millis(50)),
+            AnimationState::Running
+        );
+        assert_eq!(fade.opacity(), 0.5);
+        assert_eq!(
+            fade.tick(Duration::from_millis(80)),
+            AnimationState::Complete
+        );
+        assert_eq!(fade.opacity(), 1.0);
+
+        let mut ctx = ElementCtx::new();
+        let root = ui(Text::raw("fade"))
+            .animate(FadeIn::new(Duration::from_millis(100)))
+            .done();
+        let root = ctx.spawn_ui(root);
+        let area = Rect::new(0, 0, 4, 1);
+
+        let frame = |ctx: &mut ElementCtx, dt| {
+            let running = ctx.tick_animations(Duration::from_millis(dt));
+            let mut buf = Buffer::empty(area);
+            ctx.calculate_layout(root, area).unwrap();
+            ctx.render(root, area, &mut buf);
+            (running, buf[(0, 0)].modifier.contains(Modifier::DIM))
+        };
+        assert_eq!(frame(&mut ctx, 0), (true, true));
+        assert_eq!(frame(&mut ctx, 50), (true, true));
+        assert_eq!(frame(&mut ctx, 50), (false, false));
+        // completed animations are removed from the element.
+        assert_eq!(frame(&mut ctx, 50), (false, false));
+
+        // an element that is dimmed on its own stays dimmed.
+        let dimmed = ui(Text::raw("fade").dim())
+            .animate(FadeIn::new(Duration::from_millis(100)))
+            .done();
+        let dimmed = ctx.spawn_ui(dimmed);
+        ctx.tick_animations(Duration::from_millis(100));
+        let mut buf = Buffer::empty(area);
+        ctx.calculate_layout(dimmed, area).unwrap();
+        ctx.render(dimmed, area, &mut buf);
+        assert!(buf[(0, 0)].modifier.contains(Modifier::DIM));
+    }
+
+    #[test]
+    fn test_ctx_query() {
+        _ = tracing_subscriber::fmt::try_init();
+        _ = color_eyre::install();
+
+        #[derive(Debug)]
+        struct Todo(&'static str);
+
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(ui(Block::new()).children((
+            ui(Text::raw("a")).with((Todo("a"),)),
+            ui(Text::raw("b")).with((Todo("b"),)),
+        )));
+
+        let mut todos = ctx
+            .query::<&Todo>()
+            .iter()
+            .map(|todo| todo.0)
+            .collect::<Vec<_>>();
+        todos.sort_unstable();
+        assert_eq!(todos, ["a", "b"]);
+
+        let children = ctx.get::<&Children>(root).unwrap().clone();
+        assert_eq!(children.len(), 2);
+        let mut query = ctx.query_one::<(&Todo, &Width)>(children[0]);
+        assert!(query.get().is_ok());
+    }
+
+    #[test]
+    fn test_ctx_resources() {
+        #[derive(Debug, PartialEq)]
+        struct Theme(&'static str);
+
+        let mut ctx = ElementCtx::new();
+        assert!(ctx.get_resource::<&Theme>().is_err());
+
+        ctx.insert_or_update_resource(Theme("dark"));
+        assert_eq!(*ctx.get_resource::<&Theme>().unwrap(), Theme("dark"));
+
+        ctx.insert_or_update_resource(Theme("light"));
+        assert_eq!(*ctx.get_resource::<&Theme>().unwrap(), Theme("light"));
+
+        ctx.get_resource::<&mut Theme>().unwrap().0 = "solarized";
+        assert_eq!(ctx.remove_resource::<Theme>().unwrap(), Theme("solarized"));
+        assert!(ctx.get_resource::<&Theme>().is_err());
+        assert!(ctx.remove_resource::<Theme>().is_err());
+    }
+
+    #[test]
+    fn test_raw() {
+        _ = tracing_subscriber::fmt::try_init();
+        _ = color_eyre::install();
+
+        let areas = Arc::new(Mutex::new(Vec::new()));
+        let mut ctx = ElementCtx::new();
+        let root = ui(Block::new())
+            .with((Width::fixed(8), Height::fixed(5), Padding::new(2, 1, 1, 1)))
+            .children((
+                raw({
+                    let areas = areas.clone();
+                    move |area, buf| {
+                        areas.lock().unwrap().push(area);
+                        for position in area.positions() {
+                            buf[position].set_symbol("x");
+                        }
+                    }
+                })
+                .with((mana_tui::mana_tui_elemental::layout::PreferredSize(
+                    U16Vec2::new(3, 2),
+                ),)),
+                // no size: draws nothing
+                raw(|area, buf| {
+                    for position in area.positions() {
+                        buf[position].set_symbol("!");
+                    }
+                }),
+            ));
+        let root = ctx.spawn_ui(root);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 8, 5));
+        ctx.calculate_layout(root, buf.area).unwrap();
+        ctx.render(root, buf.area, &mut buf);
+        ctx.render(root, buf.area, &mut buf);
+        tracing::info!("\ntest_raw\n{}", buffer_to_string(&buf));
+
+        assert_eq!(*areas.lock().unwrap(), [Rect::new(2, 1, 3, 2); 2]);
+        let expected = Buffer::with_lines(vec![
+            "        ", "  xxx   ", "  xxx   ", "        ", "        ",
+        ]);
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn test_testing_trees() {
+        _ = tracing_subscriber::fmt::try_init();
+        _ = color_eyre::install();
+
+        let area = Rect::new(0, 0, 120, 40);
+        let mut buf = Buffer::empty(area);
+        for view in [
+            mana_tui::mana_tui_elemental::testing::deep_chain(100),
+            mana_tui::mana_tui_elemental::testing::wide_fanout(100),
+            mana_tui::mana_tui_elemental::testing::dashboard(4, 4),
+        ] {
+            let mut ctx = ElementCtx::new();
+            let root = ctx.spawn_ui(view);
+            ctx.calculate_layout(root, area).unwrap();
+            ctx.render(root, area, &mut buf);
+        }
+
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(mana_tui::mana_tui_elemental::testing::wide_fanout(100));
+        assert_eq!(ctx.get::<&Children>(root).unwrap().len(), 100);
+
+        // the dashboard of the benchmarks.
+        let mut ctx = ElementCtx::new();
+        ctx.spawn_ui(mana_tui::mana_tui_elemental::testing::dashboard(50, 50));
+        let elements = ctx
+            .query::<&mana_tui::mana_tui_elemental::layout::Props>()
+            .iter()
+            .count();
+        assert_eq!(elements, 5_106);
+    }
+
+    #[test]
+    fn test_spinner() {
+        use mana_tui::mana_tui_elemental::spinner::{self, Spinner};
+        use std::time::Instant;
+
+        let area = Rect::new(0, 0, 4, 2);
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(ui(Block::new()).children((
+            ui(Spinner::new(spinner::CLASSIC, Duration::from_millis(100))),
+            ui(Spinner::new(spinner::DOTS, Duration::from_millis(200))),
+        )));
+        let start = Instant::now();
+        let frame = |ctx: &mut ElementCtx, elapsed: u64| {
+            assert!(ctx.tick_spinners(start + Duration::from_millis(elapsed)));
+            ctx.calculate_layout(root, area).unwrap();
+            let mut buf = Buffer::empty(area);
+            ctx.render(root, area, &mut buf);
+            buffer_to_string(&buf)
+        };
+
+        assert_eq!(frame(&mut ctx, 0), "⠋   \n    \n");
+        assert_eq!(frame(&mut ctx, 250), "⠹   \n.   \n");
+        assert_eq!(frame(&mut ctx, 450), "⠼   \n..  \n");
+        // both wrap around to their first frame.
+        assert_eq!(frame(&mut ctx, 4000), "⠋   \n    \n");
+
+        let mut ctx = ElementCtx::new();
+        assert!(!ctx.tick_spinners(start));
+    }
+
+    #[test]
+    fn test_schedule_spinners() {
+        use mana_tui::mana_tui_elemental::spinner::{self, Spinner};
+        use std::time::Instant;
+
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(ui(Block::new()).children((
+            ui(Spinner::new(spinner::CLASSIC, Duration::from_millis(100))),
+            ui(Spinner::new(spinner::DOTS, Duration::from_millis(200))),
+        )));
+        let start = Instant::now();
+        ctx.tick_spinners(start);
+        ctx.schedule_spinners(start);
+        // the fastest spinner sets the pace.
+        let deadline = ctx.next_animation_deadline().unwrap();
+        assert_eq!(deadline, start + Duration::from_millis(100));
+        assert!(!ctx.tick_scheduled(start + Duration::from_millis(50)));
+        assert!(ctx.tick_scheduled(deadline));
+        // the same spinners keep the same handle.
+        ctx.schedule_spinners(deadline);
+        assert_eq!(
+            ctx.next_animation_deadline(),
+            Some(start + Duration::from_millis(200))
+        );
+
+        ctx.despawn_ui(root);
+        ctx.schedule_spinners(deadline);
+        assert_eq!(ctx.next_animation_deadline(), None);
+    }
+
+    #[test]
+    fn test_query_ordered() {
+        use std::cmp::Reverse;
+
+        struct Todo(&'static str);
+        struct Done;
+
+        let mut ctx = ElementCtx::new();
+        let names = ["buy milk", "water plants", "call mom"];
+        let first = ctx.spawn_ordered((Todo(names[0]),));
+        for name in &names[1..] {
+            ctx.spawn_ordered((Todo(name),));
+        }
+        // moves the first todo to a new archetype, after the others.
+        ctx.insert_one(first, Done).unwrap();
+
+        let raw = ctx
+            .query_mut::<&Todo>()
+            .into_iter()
+            .map(|todo| todo.0)
+            .collect::<Vec<_>>();
+        assert_ne!(raw, names);
+        let ordered = ctx
+            .query_ordered::<&Todo>()
+            .into_iter()
+            .map(|todo| todo.0)
+            .collect::<Vec<_>>();
+        assert_eq!(ordered, names);
+
+        // or by a key of the app.
+        let priorities = ctx
+            .query_mut::<(Element, &SpawnOrder)>()
+            .into_iter()
+            .map(|(entity, order)| (entity, Reverse(*order)))
+            .collect::<Vec<_>>();
+        for (entity, priority) in priorities {
+            ctx.insert_one(entity, priority).unwrap();
+        }
+        let reversed = ctx
+            .query_ordered_by::<Reverse<SpawnOrder>, &Todo>()
+            .into_iter()
+            .map(|todo| todo.0)
+            .collect::<Vec<_>>();
+        assert_eq!(reversed, ["call mom", "water plants", "buy milk"]);
+    }
+
+    #[test]
+    fn test_progress_bar() {
+        let area = Rect::new(0, 0, 20, 1);
+        let render = |bar: ProgressBar| {
+            let mut ctx = ElementCtx::new();
+            let root = ctx.spawn_ui(ui(bar));
+            ctx.calculate_layout(root, area).unwrap();
+            let mut buf = Buffer::empty(area);
+            ctx.render(root, area, &mut buf);
+            buf
+        };
+        let filled = |buf: &Buffer| {
+            buf.content()
+                .iter()
+                .filter(|cell| cell.symbol() == "█")
+                .count()
+        };
+
+        for (value, max, cells) in [
+            (0.0, 1.0, 0),
+            (0.5, 1.0, 10),
+            (1.0, 3.0, 6),
+            (0.99, 1.0, 19),
+            (1.0, 1.0, 20),
+            (2.0, 1.0, 20),
+            (1.0, 0.0, 0),
+        ] {
+            let buf = render(ProgressBar::new(value, max));
+            assert_eq!(filled(&buf), cells, "{value}/{max}");
+        }
+
+        let buf = render(
+            ProgressBar::new(1.0, 4.0)
+                .filled_style(Style::new().fg(Color::Green))
+                .label("25%"),
+        );
+        assert_eq!(buffer_to_string(&buf), "█████░░░25%░░░░░░░░░\n");
+        assert_eq!(buf[(0, 0)].fg, Color::Green);
+        assert_eq!(buf[(5, 0)].fg, Color::Reset);
+    }
+
+    #[test]
+    fn test_render_panic() {
+        use mana_tui::mana_tui_elemental::layout::UnstyledMarker;
+
+        #[derive(Debug)]
+        struct Boom;
+
+        impl ElWidget<UnstyledMarker> for Boom {
+            fn render_element(&self, _: Rect, _: &mut Buffer) {
+                panic!("boom");
+            }
+
+            fn set_style(&mut self, _: Style) {}
+
+            fn get_style(&self) -> Style {
+                Style::default()
+            }
+        }
+
+        let area = Rect::new(0, 0, 16, 1);
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Direction::Horizontal, Width::grow()))
+                .children((
+                    ui(Boom).with((Width::fixed(12), Height::fixed(1))),
+                    ui(Text::raw("ok")),
+                )),
+        );
+        ctx.calculate_layout(root, area).unwrap();
+        let mut buf = Buffer::empty(area);
+        ctx.render(root, area, &mut buf);
+
+        // the sibling is still rendered.
+        assert_eq!(buffer_to_string(&buf), "panic: boom ok  \n");
+        assert_eq!(buf[(0, 0)].bg, Color::Red);
+        assert_eq!(buf[(11, 0)].bg, Color::Red);
+        assert_eq!(buf[(12, 0)].bg, Color::Reset);
+    }
+
+    #[test]
+    fn test_profiler() {
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        use mana_tui::mana_tui_elemental::{layout::UnstyledMarker, profile::Profiler};
+        use mana_tui_utils::resource::Resources;
+
+        // a fake clock that only moves while the slow widget renders.
+        static NOW: AtomicU64 = AtomicU64::new(0);
+        fn clock() -> Duration {
+            Duration::from_nanos(NOW.load(Ordering::SeqCst))
+        }
+
+        #[derive(Debug)]
+        struct Sleepy;
+
+        impl ElWidget<UnstyledMarker> for Sleepy {
+            fn render_element(&self, _: Rect, _: &mut Buffer) {
+                NOW.fetch_add(5_000_000, Ordering::SeqCst);
+            }
+
+            fn set_style(&mut self, _: Style) {}
+
+            fn get_style(&self) -> Style {
+                Style::default()
+            }
+        }
+
+        let area = Rect::new(0, 0, 16, 3);
+        let mut ctx = ElementCtx::new();
+        ctx.insert_resource(Profiler::with_clock(clock));
+        let root = ctx.spawn_ui(ui(Block::new()).children((
+            ui(Text::raw("fast")),
+            ui(Sleepy).with((Width::fixed(4), Height::fixed(1))),
+            ui(Text::raw("faster")),
+        )));
+        let children = ctx.get::<&Children>(root).unwrap().to_vec();
+
+        ctx.calculate_layout(root, area).unwrap();
+        ctx.render(root, area, &mut Buffer::empty(area));
+
+        let profiler = ctx.get_resource::<&Profiler>().unwrap();
+        let report = profiler.report(2);
+        let lines = report.lines().collect::<Vec<_>>();
+        assert_eq!(lines[1], "   5.00ms 100.0%    0.00ms  Block", "{report}");
+        assert_eq!(lines[2], "   5.00ms 100.0%    5.00ms    Sleepy", "{report}");
+        assert!(lines[4].ends_with("… 1 more"), "{report}");
+
+        let times = profiler.times(root).unwrap();
+        let sleepy = profiler.times(children[1]).unwrap();
+        assert_eq!(sleepy.exclusive, Duration::from_millis(5));
+        assert_eq!(profiler.total(), times.inclusive);
+        assert_eq!(times.inclusive, Duration::from_millis(5));
+        let children_total = children
+            .iter()
+            .map(|child| profiler.times(*child).unwrap().inclusive)
+            .sum::<Duration>();
+        assert_eq!(times.inclusive, children_total + times.exclusive);
+    }
+
+    #[test]
+    fn test_layout_statistics() {
+        use mana_tui::mana_tui_elemental::layout::LayoutStats;
+
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Width::grow(), Padding::uniform(1)))
+                .children((
+                    ui(Block::new())
+                        .with((Padding::ZERO,))
+                        .child(ui(Block::new()).child(ui(Text::raw("deep")))),
+                    ui(Text::raw("shallow")).with((Height::grow(),)),
+                )),
+        );
+        assert_eq!(ctx.layout_statistics().last_fit_pass_duration_ns, 0);
+        ctx.calculate_layout(root, Rect::new(0, 0, 20, 10)).unwrap();
+
+        let stats = ctx.layout_statistics();
+        assert_eq!(stats.elements, 5);
+        assert_eq!(stats.max_depth, 4);
+        assert_eq!(stats.grow_elements, 2);
+        assert_eq!(stats.padded_elements, 1);
+        assert_eq!(
+            stats.duration,
+            ctx.get_resource::<&LayoutStats>().unwrap().duration
+        );
+        assert!(
+            stats
+                .to_string()
+                .starts_with("5 elements, depth 4, 2 grow, 1 padded")
+        );
+    }
+
+    #[test]
+    fn test_layout_memo() {
+        fn tree(width: u16) -> View {
+            let column = |top, bottom| {
+                ui(Block::new())
+                    .with((Width::grow(), Height::grow(), Direction::Vertical))
+                    .children((ui(Text::raw(top)), ui(Text::raw(bottom))))
+            };
+            ui(Block::new())
+                .with((Width::grow(), Height::grow(), Direction::Horizontal))
+                .children((
+                    column("a", "b"),
+                    column("c", "d").with((Width::fixed(width),)),
+                ))
+                .done()
+        }
+        let visits = |ctx: &ElementCtx| {
+            let stats = ctx.layout_statistics();
+            (stats.grow_visits, stats.position_visits)
+        };
+
+        let area = Rect::new(0, 0, 20, 4);
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(tree(8));
+        ctx.calculate_layout(root, area).unwrap();
+        assert_eq!(visits(&ctx), (7, 7));
+        let first = snapshot(&ctx, root);
+
+        // nothing changed, only the root is visited.
+        ctx.calculate_layout(root, area).unwrap();
+        assert_eq!(visits(&ctx), (1, 1));
+        assert_eq!(snapshot(&ctx, root), first);
+
+        // a new area is laid out like a fresh tree, and back again.
+        let wide = Rect::new(0, 0, 30, 4);
+        ctx.calculate_layout(root, wide).unwrap();
+        let mut fresh = ElementCtx::new();
+        let fresh_root = fresh.spawn_ui(tree(8));
+        fresh.calculate_layout(fresh_root, wide).unwrap();
+        assert_eq!(snapshot(&ctx, root), snapshot(&fresh, fresh_root));
+        ctx.calculate_layout(root, area).unwrap();
+        assert_eq!(snapshot(&ctx, root), first);
+
+        // growing `c` runs the grow pass for the root, the right column and `c`. their siblings
+        // are visited, but keep their subtrees.
+        let right = ctx.get::<&Children>(root).unwrap().get(1).unwrap();
+        let c = ctx.get::<&Children>(right).unwrap().get(0).unwrap();
+        *ctx.get::<&mut Width>(c).unwrap() = Width::fixed(3);
+        ctx.calculate_layout(root, area).unwrap();
+        assert_eq!(visits(&ctx), (5, 5));
+
+        let mut fresh = ElementCtx::new();
+        let fresh_root = fresh.spawn_ui(tree(8));
+        let fresh_right = fresh.get::<&Children>(fresh_root).unwrap().get(1).unwrap();
+        let fresh_c = fresh.get::<&Children>(fresh_right).unwrap().get(0).unwrap();
+        *fresh.get::<&mut Width>(fresh_c).unwrap() = Width::fixed(3);
+        fresh.calculate_layout(fresh_root, area).unwrap();
+        assert_eq!(snapshot(&ctx, root), snapshot(&fresh, fresh_root));
+        assert_ne!(snapshot(&ctx, root), first);
+    }
+
+    #[test]
+    fn test_layout_memo_respawn() {
+        fn tree(width: u16) -> View {
+            ui(Block::new())
+                .with((Width::grow(), Height::grow(), Direction::Horizontal))
+                .children((
+                    ui(Text::raw("a")),
+                    ui(Block::new())
+                        .with((Width::fixed(width), Height::grow()))
+                        .child(ui(Text::raw("b"))),
+                ))
+                .done()
+        }
+
+        let area = Rect::new(0, 0, 20, 4);
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(tree(8));
+        ctx.calculate_layout(root, area).unwrap();
+        let first = snapshot(&ctx, root);
+
+        // an identical tree takes over the layout of the despawned one.
+        ctx.despawn_ui(root);
+        let root = ctx.spawn_ui(tree(8));
+        ctx.calculate_layout(root, area).unwrap();
+        assert_eq!(ctx.layout_statistics().grow_visits, 1);
+        assert_eq!(snapshot(&ctx, root), first);
+
+        // a different one is laid out again.
+        ctx.despawn_ui(root);
+        let root = ctx.spawn_ui(tree(6));
+        ctx.calculate_layout(root, area).unwrap();
+        assert!(ctx.layout_statistics().grow_visits > 1);
+        let mut fresh = ElementCtx::new();
+        let fresh_root = fresh.spawn_ui(tree(6));
+        fresh.calculate_layout(fresh_root, area).unwrap();
+        assert_eq!(snapshot(&ctx, root), snapshot(&fresh, fresh_root));
+
+        // roots without an id keep a layout each.
+        let other =
+            |ctx: &mut ElementCtx| ctx.spawn_ui(ui(Block::new()).child(ui(Text::raw("other"))));
+        let mut ctx = ElementCtx::new();
+        let roots = [ctx.spawn_ui(tree(8)), other(&mut ctx)];
+        for root in roots {
+            ctx.calculate_layout(root, area).unwrap();
+        }
+        for root in roots {
+            ctx.despawn_ui(root);
+        }
+        let roots = [ctx.spawn_ui(tree(8)), other(&mut ctx)];
+        for root in roots {
+            ctx.calculate_layout(root, area).unwrap();
+            assert_eq!(ctx.layout_statistics().grow_visits, 1);
+        }
+
+        // a layout that isn't taken within two layouts is dropped.
+        ctx.despawn_ui(roots[1]);
+        ctx.calculate_layout(roots[0], area).unwrap();
+        ctx.calculate_layout(roots[0], area).unwrap();
+        let root = other(&mut ctx);
+        ctx.calculate_layout(root, area).unwrap();
+        assert_eq!(ctx.layout_statistics().grow_visits, 2);
+    }
+
+    #[test]
+    fn test_text_hit() {
+        use mana_tui::mana_tui_elemental::text_hit::{SourceParagraph, TextHit, text_hit};
+        use ratatui::text::{Line, Span};
+
+        let text = Line::from(vec![Span::raw("open "), Span::raw("日本.rs now")]);
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(
+            ui(SourceParagraph::new(text)
+                .wrap(Wrap { trim: true })
+                .block(Block::new().padding(Padding::uniform(1))))
+            .with((Width::fixed(11), Height::fixed(4))),
+        );
+        // "open" on the first row of the block, "日本.rs" on the second.
+        ctx.calculate_layout(root, Rect::new(0, 0, 11, 4)).unwrap();
+
+        let hit = |x, y| text_hit(root, &ctx, Position::new(x, y));
+        let at = |column, span_index, byte_offset| {
+            Some(TextHit {
+                line: 0,
+                column,
+                span_index,
+                byte_offset,
+            })
+        };
+        assert_eq!(hit(1, 1), at(0, 0, 0));
+        assert_eq!(hit(4, 1), at(3, 0, 3));
+        // the space the line was wrapped at is trimmed.
+        assert_eq!(hit(5, 1), None);
+        assert_eq!(hit(1, 2), at(5, 1, 0));
+        // both cells of a wide character hit it.
+        assert_eq!(hit(2, 2), at(5, 1, 0));
+        assert_eq!(hit(4, 2), at(7, 1, 3));
+        assert_eq!(hit(7, 2), at(10, 1, 8));
+        // the padding of the block.
+        assert_eq!(hit(0, 1), None);
+        assert_eq!(hit(1, 0), None);
+        assert_eq!(hit(1, 3), None);
+    }
+
+    #[test]
+    fn test_clip_region() {
+        let area = Rect::new(0, 0, 10, 4);
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(
+            ui(Block::bordered())
+                .with((
+                    Width::fixed(10),
+                    Height::fixed(4),
+                    ClipRegion(Rect::new(1, 1, 4, 2)),
+                ))
+                .children((
+                    ui(Text::raw("abcdefgh")),
+                    ui(Block::new())
+                        .with((ClipRegion(Rect::new(0, 2, 3, 1)),))
+                        .child(ui(Text::raw("xyz12"))),
+                )),
+        );
+        ctx.calculate_layout(root, area).unwrap();
+        let mut buf = Buffer::empty(area);
+        ctx.render(root, area, &mut buf);
+
+        // the border of the clipping element is drawn, nested regions intersect.
+        assert_eq!(
+            buffer_to_string(&buf),
+            "┌────────┐\n│abcd    │\n│xy      │\n└────────┘\n"
+        );
+
+        // a child cut at the top and left loses its first rows and columns.
+        let area = Rect::new(0, 0, 6, 3);
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((
+                    Width::fixed(6),
+                    Height::fixed(3),
+                    ClipRegion(Rect::new(2, 1, 4, 2)),
+                ))
+                .child(ui(Text::raw("abcdef\nghijkl\nmnopqr"))),
+        );
+        ctx.calculate_layout(root, area).unwrap();
+        let mut buf = Buffer::empty(area);
+        ctx.render(root, area, &mut buf);
+        assert_eq!(buffer_to_string(&buf), "      \n  ijkl\n  opqr\n");
+    }
+
+    #[test]
+    fn test_max_children() {
+        use mana_tui::mana_tui_elemental::layout::Props;
+
+        let area = Rect::new(0, 0, 3, 5);
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Width::fixed(3), Height::fixed(5), MaxChildren(2)))
+                .children(["a", "b", "c", "d", "e"].map(|text| ui(Text::raw(text)).done())),
+        );
+        let children = ctx.get::<&Children>(root).unwrap().to_vec();
+        let render = |ctx: &mut ElementCtx| {
+            ctx.calculate_layout(root, area).unwrap();
+            let mut buf = Buffer::empty(area);
+            ctx.render(root, area, &mut buf);
+            buffer_to_string(&buf)
+        };
+
+        assert_eq!(render(&mut ctx), "a  \nb  \n   \n   \n   \n");
+        // the hidden children stay spawned, without a size.
+        assert!(
+            children[2..]
+                .iter()
+                .all(|child| ctx.get::<&Props>(*child).unwrap().size == U16Vec2::ZERO)
+        );
+
+        ctx.insert_one(root, ChildrenOffset(3)).unwrap();
+        assert_eq!(render(&mut ctx), "d  \ne  \n   \n   \n   \n");
+        assert_eq!(ctx.get::<&Props>(children[0]).unwrap().size, U16Vec2::ZERO);
+        assert_eq!(
+            ctx.get::<&Props>(children[3]).unwrap().position,
+            U16Vec2::ZERO
+        );
+    }
+
+    #[test]
+    fn test_mount_hooks() {
+        #[derive(Default)]
+        struct Counts {
+            mounted: Vec<&'static str>,
+            unmounted: Vec<&'static str>,
+        }
+
+        let counts = Arc::new(Mutex::new(Counts::default()));
+        let hooked = |name: &'static str| {
+            let (on_mount, on_unmount) = (counts.clone(), counts.clone());
+            ui(Block::new()).with((
+                OnMount::new(move |_, _| on_mount.lock().unwrap().mounted.push(name)),
+                OnUnmount::new(move |world, element| {
+                    // the element still exists while it is unmounted.
+                    assert!(world.contains(element));
+                    on_unmount.lock().unwrap().unmounted.push(name);
+                }),
+            ))
+        };
+
+        let mut ctx = ElementCtx::new();
+        let first = ctx.spawn_ui(hooked("root").children((
+            hooked("pane").child(hooked("log")),
+            ui(Text::raw("no hooks")),
+        )));
+        assert_eq!(counts.lock().unwrap().mounted, ["log", "pane", "root"]);
+
+        // children are unmounted first. moving a resolved suspense view between trees is
+        // tested through the potion runtime.
+        let pane = ctx.get::<&Children>(first).unwrap()[0];
+        ctx.despawn_ui(first);
+        assert_eq!(counts.lock().unwrap().unmounted, ["log", "pane", "root"]);
+        assert_eq!(counts.lock().unwrap().mounted, ["log", "pane", "root"]);
+        assert!(!ctx.contains(pane));
+    }
+
+    #[test]
+    fn test_grow_sizes() {
+        use mana_tui::mana_tui_elemental::layout::Props;
+
+        let widths = |width: u16, children: Vec<View>| {
+            let mut ctx = ElementCtx::new();
+            let root = ctx.spawn_ui(
+                ui(Block::new())
+                    .with((Direction::Horizontal, Width::fixed(width)))
+                    .children(children),
+            );
+            ctx.calculate_layout(root, Rect::new(0, 0, width, 1))
+                .unwrap();
+            ctx.get::<&Children>(root)
+                .unwrap()
+                .iter()
+                .map(|child| ctx.get::<&Props>(*child).unwrap().size.x)
+                .collect::<Vec<_>>()
+        };
+        let grow = || ui(Block::new()).with((Width::grow(), Height::fixed(1)));
+        let fixed = |width: u16| ui(Block::new()).with((Width::fixed(width), Height::fixed(1)));
+        // a growing child that starts out `width` cells wide.
+        let wide = |width: u16| grow().child(fixed(width)).done();
+
+        // the space is shared evenly, the first children get the remainder.
+        assert_eq!(
+            widths(30, vec![grow().done(), grow().done(), grow().done()]),
+            [10, 10, 10]
+        );
+        assert_eq!(
+            widths(31, vec![grow().done(), grow().done(), grow().done()]),
+            [11, 10, 10]
+        );
+        // children that don't grow keep their size and aren't counted.
+        assert_eq!(
+            widths(30, vec![fixed(6).done(), grow().done(), grow().done()]),
+            [6, 12, 12]
+        );
+        // smaller children catch up to bigger ones before everyone grows.
+        assert_eq!(widths(30, vec![wide(10), grow().done()]), [15, 15]);
+        // a child bigger than its share keeps its size, the others stay inside the parent.
+        assert_eq!(widths(14, vec![wide(10), grow().done()]), [10, 4]);
+        assert_eq!(
+            widths(20, vec![wide(10), wide(4), grow().done()]),
+            [10, 5, 5]
+        );
+    }
+
+    #[test]
+    fn test_flex_basis() {
+        use mana_tui::mana_tui_elemental::layout::Props;
+
+        let widths = |width: u16| {
+            let mut ctx = ElementCtx::new();
+            let grow = || ui(Block::new()).with((Width::grow(), Height::fixed(1)));
+            let root = ctx.spawn_ui(
+                ui(Block::new())
+                    .with((Direction::Horizontal, Width::fixed(width)))
+                    .children((grow().with((FlexBasis(20),)), grow(), grow())),
+            );
+            ctx.calculate_layout(root, Rect::new(0, 0, width, 1))
+                .unwrap();
+            ctx.get::<&Children>(root)
+                .unwrap()
+                .iter()
+                .map(|child| ctx.get::<&Props>(child).unwrap().size.x)
+                .collect::<Vec<_>>()
+        };
+
+        // the basis plus an even share of what is left.
+        assert_eq!(widths(100), [47, 27, 26]);
+        assert_eq!(widths(30), [24, 3, 3]);
+    }
+
+    #[test]
+    fn test_degrade_colors() {
+        use mana_tui_utils::resource::Resources;
+        use ratatui::style::{Color, Style};
+
+        assert_eq!(
+            ColorCaps::from_env(Some("truecolor"), Some("xterm")),
+            ColorCaps::TrueColor
+        );
+        assert_eq!(
+            ColorCaps::from_env(None, Some("xterm-256color")),
+            ColorCaps::Ansi256
+        );
+        assert_eq!(ColorCaps::from_env(None, Some("linux")), ColorCaps::Ansi16);
+        assert_eq!(ColorCaps::from_env(None, None), ColorCaps::TrueColor);
+
+        let degraded = |caps: ColorCaps| {
+            let mut ctx = ElementCtx::new();
+            ctx.insert_resource(caps);
+            let mut buf = Buffer::empty(Rect::new(0, 0, 1, 1));
+            buf[(0, 0)].set_style(Style::new().fg(Color::Rgb(255, 100, 0)).bg(Color::Blue));
+            ctx.degrade_colors(&mut buf);
+            (buf[(0, 0)].fg, buf[(0, 0)].bg)
+        };
+        assert_eq!(
+            degraded(ColorCaps::TrueColor),
+            (Color::Rgb(255, 100, 0), Color::Blue)
+        );
+        assert_eq!(
+            degraded(ColorCaps::Ansi256),
+            (Color::Indexed(202), Color::Blue)
+        );
+        assert_eq!(degraded(ColorCaps::Ansi16), (Color::LightRed, Color::Blue));
+    }
+
+    #[test]
+    fn test_with_if() {
+        let mut ctx = ElementCtx::new();
+        let spawn = |ctx: &mut ElementCtx, condition: bool, basis: Option<u16>| {
+            ctx.spawn_ui(
+                ui(Block::new())
+                    .with_if(condition, (Gap(2),))
+                    .with_option(basis.map(|basis| (FlexBasis(basis),))),
+            )
+        };
+
+        let with = spawn(&mut ctx, true, Some(4));
+        assert_eq!(ctx.get::<&Gap>(with).unwrap().0, 2);
+        assert_eq!(*ctx.get::<&FlexBasis>(with).unwrap(), FlexBasis(4));
+
+        // the defaults are added to the element instead.
+        let without = spawn(&mut ctx, false, None);
+        assert_eq!(ctx.get::<&Gap>(without).unwrap().0, 0);
+        assert!(ctx.get::<&FlexBasis>(without).is_err());
+    }
+
+    #[test]
+    fn test_blit() {
+        let area = Rect::new(0, 0, 12, 5);
+        let chart = || {
+            ui(Block::bordered().title_top("cpu"))
+                .with((Width::fixed(8), Height::fixed(3)))
+                .children((ui(Text::styled("42%", Style::new().fg(Color::Red))),))
+        };
+        let placed = || ui(Block::new()).with((Padding::new(2, 0, 1, 0),));
+        let mut ctx = ElementCtx::new();
+
+        let direct = ctx.spawn_ui(placed().children((chart(),)));
+        ctx.calculate_layout(direct, area).unwrap();
+        let mut expected = Buffer::empty(area);
+        ctx.render(direct, area, &mut expected);
+
+        // the chart is laid out away from the origin, the offscreen buffer starts at it.
+        let offscreen = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Padding::uniform(1),))
+                .children((chart(),)),
+        );
+        ctx.calculate_layout(offscreen, area).unwrap();
+        let chart = ctx.get::<&Children>(offscreen).unwrap().get(0).unwrap();
+        let cached = Arc::new(ctx.render_to_buffer(chart));
+        assert_eq!(cached.area, Rect::new(0, 0, 8, 3));
+        assert_eq!(cached[(1, 1)].fg, Color::Red);
+
+        let blitted = ctx.spawn_ui(placed().children((ui(Blit::new(cached.clone())),)));
+        ctx.calculate_layout(blitted, area).unwrap();
+        let mut buf = Buffer::empty(area);
+        ctx.render(blitted, area, &mut buf);
+        assert_eq!(buf, expected);
+
+        // skipping empty cells keeps what was drawn under the buffer.
+        let mut sparse = Buffer::empty(Rect::new(0, 0, 3, 1));
+        sparse.set_string(0, 0, "a", Style::new());
+        sparse.set_string(2, 0, "c", Style::new());
+        let overlay = ctx.spawn_ui(ui(Blit::new(Arc::new(sparse)).skip_empty(true)));
+        ctx.calculate_layout(overlay, area).unwrap();
+        let mut buf = Buffer::empty(Rect::new(0, 0, 3, 1));
+        buf.set_string(0, 0, "xyz", Style::new());
+        ctx.render(overlay, buf.area, &mut buf);
+        assert_eq!(buf, Buffer::with_lines(["ayc"]));
+    }
+
+    #[test]
+    fn test_clip_wide_chars() {
+        let area = Rect::new(0, 0, 8, 1);
+        let mut ctx = ElementCtx::new();
+        let mut text = Buffer::empty(Rect::new(0, 0, 6, 1));
+        text.set_string(0, 0, "日本語", Style::new().fg(Color::Red));
+        let root = ctx.spawn_ui(
+            ui(Container)
+                .with((
+                    Width::fixed(8),
+                    Height::fixed(1),
+                    ClipRegion(Rect::new(3, 0, 3, 1)),
+                ))
+                .children((ui(Blit::new(Arc::new(text.clone()))),)),
+        );
+        ctx.calculate_layout(root, area).unwrap();
+        let mut buf = Buffer::empty(area);
+        buf.set_string(0, 0, "日本語", Style::new());
+        ctx.render(root, area, &mut buf);
+
+        // `本` left of the clip reached into it, the clipped `本` reached out of it.
+        let symbols = (0..8).map(|x| buf[(x, 0)].symbol()).collect::<Vec<_>>();
+        assert_eq!(symbols, ["日", " ", " ", "日", " ", " ", " ", " "]);
+        assert_eq!(buf[(5, 0)].fg, Color::Red);
+    }
+
+    #[test]
+    fn test_spacer() {
+        let area = Rect::new(0, 0, 12, 7);
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(
+            ui(Container)
+                .with((Width::grow(), Height::grow()))
+                .children((
+                    ui(Container)
+                        .with((Width::grow(), Direction::Horizontal))
+                        .children((
+                            ui(Text::raw("left")),
+                            ui(Spacer::default()),
+                            ui(Text::raw("right")),
+                        )),
+                    ui(Text::raw("top")),
+                    ui(Spacer::fixed(1, Direction::Vertical)),
+                    ui(Text::raw("gap")),
+                    ui(Spacer::default()),
+                    ui(Text::raw("end")),
+                )),
+        );
+        ctx.calculate_layout(root, area).unwrap();
+        let mut buf = Buffer::empty(area);
+        ctx.render(root, area, &mut buf);
+
+        assert_eq!(
+            buffer_to_string(&buf),
+            "left   right\ntop         \n            \ngap         \n            \n            \nend         \n"
+        );
+    }
+
+    #[test]
+    fn test_spacer_sizes() {
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(ui(Spacer::default()));
+        assert!(matches!(ctx.get::<&Width>(root).unwrap().0, Size::Grow));
+        assert!(matches!(ctx.get::<&Height>(root).unwrap().0, Size::Grow));
+
+        let row = ctx.spawn_ui(ui(Container).with((Direction::Horizontal,)).children((
+            ui(Spacer::default()),
+            ui(Spacer::fixed(3, Direction::Horizontal)),
+        )));
+        let children = ctx.get::<&Children>(row).unwrap().clone();
+        let (grow, fixed) = (children.get(0).unwrap(), children.get(1).unwrap());
+        assert_eq!(
+            ctx.get::<&Spacer>(grow).unwrap().direction,
+            Some(Direction::Horizontal)
+        );
+        assert!(matches!(ctx.get::<&Width>(grow).unwrap().0, Size::Grow));
+        assert!(matches!(
+            ctx.get::<&Height>(grow).unwrap().0,
+            Size::Fixed(0)
+        ));
+        assert!(matches!(
+            ctx.get::<&Width>(fixed).unwrap().0,
+            Size::Fixed(3)
+        ));
+    }
+
+    #[test]
+    fn test_container() {
+        let area = Rect::new(0, 0, 8, 2);
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(
+            ui(Container)
+                .with((Width::grow(), Height::grow(), Padding::left(1)))
+                .children((ui(Text::raw("one")), ui(NopWidget), ui(Text::raw("two")))),
+        );
+        ctx.calculate_layout(root, area).unwrap();
+        let mut buf = Buffer::empty(area);
+        buf.set_string(0, 0, "xxxxxxxx", Style::new());
+        ctx.render(root, area, &mut buf);
+
+        // the container leaves what was under it, only the children draw.
+        assert_eq!(buffer_to_string(&buf), "xonexxxx\n two    \n");
+    }
+
+    #[test]
+    fn test_get_widget() {
+        let mut ctx = ElementCtx::new();
+        let block = Block::bordered().title_top("title");
+        let root = ctx.spawn_ui(ui(block.clone()).children((ui(Text::raw("text")),)));
+        assert_eq!(ctx.get_widget::<_, Block>(root).as_deref(), Some(&block));
+        // only the widget the element was created with.
+        assert!(ctx.get_widget::<_, Text>(root).is_none());
+        let text = ctx.get::<&Children>(root).unwrap()[0];
+        assert!(ctx.get_widget::<_, Block>(text).is_none());
+
+        *ctx.get_widget_mut::<_, Text>(text).unwrap() = Text::raw("changed");
+        assert_eq!(
+            ctx.get_widget::<_, Text>(text).as_deref(),
+            Some(&Text::raw("changed"))
+        );
+    }
+
+    #[test]
+    fn test_swap_widget() {
+        use mana_tui::mana_tui_elemental::layout::Props;
+
+        fn draw(ctx: &mut ElementCtx, root: Element) -> Buffer {
+            let area = Rect::new(0, 0, 7, 3);
+            ctx.calculate_layout(root, area).unwrap();
+            let mut buf = Buffer::empty(area);
+            ctx.render(root, area, &mut buf);
+            buf
+        }
+
+        let mut ctx = ElementCtx::new();
+        let size = (Width::fixed(7), Height::fixed(3));
+        let root = ctx.spawn_ui(ui(dyn_widget(Paragraph::new("hello"))).with(size));
+        assert_eq!(
+            draw(&mut ctx, root),
+            Buffer::with_lines(["hello  ", "       ", "       "])
+        );
+
+        assert!(ctx.swap_widget(root, dyn_widget(Block::bordered())));
+        assert_eq!(
+            draw(&mut ctx, root),
+            Buffer::with_lines(["┌─────┐", "│     │", "└─────┘"])
+        );
+        let widget = ctx.get_widget::<_, DynWidget>(root).unwrap();
+        assert!(format!("{widget:?}").starts_with("Block"));
+        drop(widget);
+
+        // an element created with a concrete widget loses it.
+        let text = ctx.spawn_ui(ui(Text::raw("text")).with(size));
+        assert!(ctx.swap_widget(text, dyn_widget(Paragraph::new("swapped"))));
+        assert!(ctx.get::<&Text<'static>>(text).is_err());
+        assert!(ctx.get_widget::<_, Text>(text).is_none());
+        assert_eq!(
+            draw(&mut ctx, text),
+            Buffer::with_lines(["swapped", "       ", "       "])
+        );
+
+        // the sizes an element got from its old widget are derived from the new one.
+        let text = ctx.spawn_ui(ui(Text::raw("text")));
+        draw(&mut ctx, text);
+        assert!(ctx.swap_widget(text, dyn_widget(Text::raw("new\ntext!"))));
+        draw(&mut ctx, text);
+        assert_eq!(ctx.get::<&Props>(text).unwrap().size, U16Vec2::new(5, 2));
+        let block = ctx.spawn_ui(ui(Block::bordered()).with(size).child(ui(Text::raw("hi"))));
+        assert!(ctx.swap_widget(block, dyn_widget(Clear)));
+        assert_eq!(
+            draw(&mut ctx, block),
+            Buffer::with_lines(["hi     ", "       ", "       "])
+        );
+    }
+
+    #[test]
+    fn test_spawn_ui_with_parent() {
+        let mut ctx = ElementCtx::new();
+        let root = ctx
+            .spawn_ui(ui(Block::new()).children((ui(Text::raw("first")), ui(Text::raw("second")))));
+        let shared = ctx.get::<&Children>(root).unwrap().clone();
+
+        let third = ctx.spawn_ui_with_parent(ui(Text::raw("third")), root);
+        let children = ctx.get::<&Children>(root).unwrap().clone();
+        assert_eq!(children.len(), 3);
+        assert_eq!(children.last(), Some(&third));
+        // a clone taken before still sees the old children.
+        assert_eq!(shared.len(), 2);
+
+        let leaf = children[0];
+        let nested = ctx.spawn_ui_with_parent(ui(Text::raw("nested")), leaf);
+        assert_eq!(&**ctx.get::<&Children>(leaf).unwrap(), [nested]);
+    }
+
+    #[test]
+    fn test_detect_block_padding() {
+        use mana_tui::mana_tui_elemental::ui::detect_block_padding;
+
+        for border_type in [
+            BorderType::Plain,
+            BorderType::Rounded,
+            BorderType::Double,
+            BorderType::Thick,
+            BorderType::LightDoubleDashed,
+            BorderType::HeavyDoubleDashed,
+            BorderType::LightTripleDashed,
+            BorderType::HeavyTripleDashed,
+            BorderType::LightQuadrupleDashed,
+            BorderType::HeavyQuadrupleDashed,
+            BorderType::QuadrantInside,
+            BorderType::QuadrantOutside,
+        ] {
+            let block = Block::bordered().border_type(border_type);
+            assert_eq!(detect_block_padding(&block), Padding::uniform(1));
+            assert_eq!(Padding::from_border(&block), Padding::uniform(1));
+        }
+        let block = Block::new()
+            .borders(Borders::TOP | Borders::LEFT)
+            .padding(Padding::uniform(1));
+        assert_eq!(detect_block_padding(&block), Padding::new(2, 1, 2, 1));
+        // a title takes the top row even without borders.
+        let block = Block::new().title("title");
+        assert_eq!(detect_block_padding(&block), Padding::new(0, 0, 1, 0));
+
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(ui(Block::bordered().border_type(BorderType::Thick)));
+        assert_eq!(*ctx.get::<&Padding>(root).unwrap(), Padding::uniform(1));
+    }
+
+    #[test]
+    fn test_truncate_titles() {
+        let area = Rect::new(0, 0, 10, 2);
+        let render = |block: Block<'static>, truncate: bool| {
+            let mut ctx = ElementCtx::new();
+            let root = ctx.spawn_ui(
+                ui(block)
+                    .with((Width::fixed(10), Height::fixed(2)))
+                    .with_if(!truncate, (NoTruncateTitles,)),
+            );
+            ctx.calculate_layout(root, area).unwrap();
+            let mut buf = Buffer::empty(area);
+            ctx.render(root, area, &mut buf);
+            buffer_to_string(&buf)
+        };
+
+        let block = || Block::bordered().title("a title that is long");
+        assert_eq!(render(block(), true), "┌a title…┐\n└────────┘\n");
+        assert_eq!(render(block(), false), "┌a title ┐\n└────────┘\n");
+        let block = Block::bordered().title_bottom("a bottom title");
+        assert_eq!(render(block, true), "┌────────┐\n└a botto…┘\n");
+        let block = Block::bordered().title("short");
+        assert_eq!(render(block, true), "┌short───┐\n└────────┘\n");
+        // several titles on one border, the one that runs out of room is cut.
+        let block = Block::bordered().title("first").title("second");
+        assert_eq!(render(block, true), "┌first─s…┐\n└────────┘\n");
+
+        // the titles are fitted again when the width changes.
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(
+            ui(Block::bordered().title("a title that is long"))
+                .with((Width::grow(), Height::fixed(2))),
+        );
+        for (width, expected) in [
+            (10, "┌a title…┐\n└────────┘\n"),
+            (24, "┌a title that is long──┐\n└──────────────────────┘\n"),
+            (10, "┌a title…┐\n└────────┘\n"),
+        ] {
+            let area = Rect::new(0, 0, width, 2);
+            ctx.calculate_layout(root, area).unwrap();
+            let mut buf = Buffer::empty(area);
+            ctx.render(root, area, &mut buf);
+            assert_eq!(buffer_to_string(&buf), expected);
+        }
+    }
+
+    #[test]
+    fn test_on_resize() {
+        use mana_tui::mana_tui_elemental::layout::Props;
+        use mana_tui_utils::resource::Resources;
+
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(ui(Block::new()).with((Width::grow(), Height::grow())));
+        let size_after = |ctx: &mut ElementCtx, width, height| {
+            let area = ctx.get_resource::<&TerminalSize>().unwrap().area();
+            assert_eq!(area, Rect::new(0, 0, width, height));
+            ctx.calculate_layout(root, area).unwrap();
+            ctx.get::<&Props>(root).unwrap().size
+        };
+
+        assert!(ctx.on_resize(30, 4));
+        assert_eq!(size_after(&mut ctx, 30, 4), U16Vec2::new(30, 4));
+        assert!(!ctx.on_resize(30, 4));
+        assert!(ctx.on_resize(20, 6));
+        assert_eq!(size_after(&mut ctx, 20, 6), U16Vec2::new(20, 6));
+    }
+
+    #[test]
+    fn test_flow_order() {
+        use mana_tui::mana_tui_elemental::layout::Props;
+
+        let row = |order: FlowOrder, justify: MainJustify, width: u16| {
+            let mut ctx = ElementCtx::new();
+            let root = ctx.spawn_ui(
+                ui(Block::new())
+                    .with((
+                        Direction::Horizontal,
+                        Width::fixed(width),
+                        Height::fixed(1),
+                        Gap(1),
+                        order,
+                        justify,
+                    ))
+                    .children((
+                        ui(Text::raw("ab")).with((Width::fixed(2),)),
+                        ui(Text::raw("cde")).with((Width::fixed(3),)),
+                        ui(Text::raw("fghi")).with((Width::fixed(4),)),
+                    )),
+            );
+            let area = Rect::new(0, 0, width, 1);
+            ctx.calculate_layout(root, area).unwrap();
+            let xs = ctx
+                .get::<&Children>(root)
+                .unwrap()
+                .iter()
+                .map(|child| ctx.get::<&Props>(child).unwrap().position.x)
+                .collect::<Vec<_>>();
+            let mut buf = Buffer::empty(area);
+            ctx.render(root, area, &mut buf);
+            (xs, buffer_to_string(&buf))
+        };
+
+        let (xs, text) = row(FlowOrder::Forward, MainJustify::Start, 20);
+        assert_eq!(xs, [0, 3, 7]);
+        assert_eq!(text, "ab cde fghi         \n");
+        let (xs, text) = row(FlowOrder::Reversed, MainJustify::Start, 20);
+        assert_eq!(xs, [18, 14, 9]);
+        assert_eq!(text, "         fghi cde ab\n");
+
+        // the space between the children is mirrored with them.
+        let (xs, _) = row(FlowOrder::Forward, MainJustify::SpaceBetween, 21);
+        let (reversed, _) = row(FlowOrder::Reversed, MainJustify::SpaceBetween, 21);
+        let widths = [2, 3, 4];
+        let mirrored = xs.iter().zip(widths).map(|(x, width)| 21 - x - width);
+        assert!(reversed.iter().copied().eq(mirrored));
+    }
+
+    #[test]
+    fn test_cross_justify_stretch() {
+        let mut ctx = ElementCtx::new();
+        let area = Rect::new(0, 0, 12, 4);
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((
+                    Direction::Horizontal,
+                    Width::grow(),
+                    Height::grow(),
+                    CrossJustify::Stretch,
+                ))
+                .children((
+                    ui(Block::bordered()).child(ui(Text::raw("a"))),
+                    ui(Block::bordered())
+                        .with((Height::fixed(3),))
+                        .child(ui(Text::raw("b"))),
+                    ui(Block::bordered())
+                        .with((AlignSelf(CrossJustify::End),))
+                        .child(ui(Text::raw("c"))),
+                )),
+        );
+        ctx.calculate_layout(root, area).unwrap();
+        let mut buf = Buffer::empty(area);
+        ctx.render(root, area, &mut buf);
+        assert_eq!(
+            buffer_to_string(&buf),
+            "┌─┐┌─┐      \n\
+             │a││b│   ┌─┐\n\
+             │ │└─┘   │c│\n\
+             └─┘      └─┘\n"
+        );
+    }
+
+    #[test]
+    fn test_first_frame_is_complete() {
+        let mut ctx = ElementCtx::new();
+        // a mount hook adds a child the spawn didn't give defaults to.
+        let view = ui(Block::bordered().title("todo"))
+            .with((
+                Width::fixed(12),
+                Height::fixed(3),
+                OnMount::new(|world, element| {
+                    let child = world.spawn(ui(Text::raw("mounted")).done().build());
+                    *world.get::<&mut Children>(element).unwrap() =
+                        Children::Some(Arc::new(vec![child]));
+                }),
+            ))
+            .done();
+        let root = ctx.spawn_ui(view);
+        let area = Rect::new(0, 0, 12, 3);
+        let mut frame = || {
+            ctx.calculate_layout(root, area).unwrap();
+            let mut buf = Buffer::empty(area);
+            ctx.render(root, area, &mut buf);
+            buffer_to_string(&buf)
+        };
+
+        let first = frame();
+        assert_eq!(first, "┌todo──────┐\n│mounted   │\n└──────────┘\n");
+        assert_eq!(first, frame());
+    }
+
+    #[test]
+    fn test_despawn_reclaims_elements() {
+        use mana_tui::mana_tui_elemental::layout::Props;
+
+        let mut ctx = ElementCtx::new();
+        let area = Rect::new(0, 0, 10, 2);
+        let tree = |i: usize| {
+            ui(Block::new()).children((ui(Text::raw("a")), ui(Paragraph::new(i.to_string()))))
+        };
+        // the first layout and despawn insert resources, which are entities as well.
+        let root = ctx.spawn_ui(tree(0));
+        ctx.calculate_layout(root, area).unwrap();
+        ctx.despawn_ui(root);
+        let baseline = ctx.len();
+        let mut stale = Vec::new();
+        for i in 0..10_000 {
+            let root = ctx.spawn_ui(tree(i));
+            ctx.calculate_layout(root, area).unwrap();
+            if i % 1000 == 0 {
+                stale.extend(ctx.get::<&Children>(root).unwrap().iter());
+                stale.push(root);
+            }
+            ctx.despawn_ui(root);
+            assert_eq!(ctx.len(), baseline);
+        }
+        // the slots are reused, old handles don't reach the new elements.
+        for element in stale {
+            assert!(!ctx.contains(element));
+            assert!(ctx.get::<&Props>(element).is_err());
+        }
+    }
+
+    #[test]
+    fn test_sticky_top() {
+        use mana_tui::mana_tui_elemental::layout::Props;
+
+        let mut ctx = ElementCtx::new();
+        let header = ui(Text::raw("header")).with((StickyTop(0),)).done();
+        let lines = (1..=10).map(|i| ui(Text::raw(format!("line {i}"))).done());
+        // the scroll view is as tall as the viewport, the content is taller.
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((
+                    Height::fixed(4),
+                    ScrollView::default(),
+                    ScrollViewState::new(),
+                ))
+                .children(std::iter::once(header).chain(lines).collect::<Vec<_>>()),
+        );
+        let header = ctx.get::<&Children>(root).unwrap()[0];
+        let header_y = |ctx: &mut ElementCtx, scroll: u16| {
+            ctx.get::<&mut ScrollViewState>(root)
+                .unwrap()
+                .set_offset(Position::new(0, scroll));
+            ctx.calculate_layout(root, Rect::new(0, 0, 10, 4)).unwrap();
+            ctx.get::<&Props>(header).unwrap().position.y
+        };
+
+        assert_eq!(header_y(&mut ctx, 0), 0);
+        // scrolled below the header, it stays at the top of the viewport.
+        assert_eq!(header_y(&mut ctx, 3), 3);
+        assert_eq!(header_y(&mut ctx, 7), 7);
+        assert_eq!(header_y(&mut ctx, 10), 10);
+        // it doesn't leave the content.
+        assert_eq!(header_y(&mut ctx, 20), 10);
+    }
+
+    #[test]
+    fn test_overflow_indicator() {
+        let render = |width: u16, scroll: Option<u16>| {
+            let area = Rect::new(0, 0, width, 1);
+            let mut ctx = ElementCtx::new();
+            let root = ctx.spawn_ui(
+                ui(Container)
+                    .with((
+                        Width::fixed(width),
+                        Height::fixed(1),
+                        Direction::Horizontal,
+                        OverflowIndicator::Arrows,
+                    ))
+                    .children((
+                        ui(Text::raw("aaaaaa")),
+                        ui(Text::raw("bbbbbb")),
+                        ui(Text::raw("cccccc")),
+                    )),
+            );
+            if let Some(scroll) = scroll {
+                ctx.insert(
+                    root,
+                    (
+                        Overflow::Hidden,
+                        ScrollView::default(),
+                        ScrollViewState::new(),
+                    ),
+                )
+                .unwrap();
+                ctx.get::<&mut ScrollViewState>(root)
+                    .unwrap()
+                    .set_offset(Position::new(scroll, 0));
+            }
+            ctx.calculate_layout(root, area).unwrap();
+            let mut buf = Buffer::empty(area);
+            ctx.render(root, area, &mut buf);
+            buffer_to_string(&buf)
+        };
+
+        assert_eq!(render(10, None), "aaaaaabbb▶\n");
+        assert_eq!(render(20, None), "aaaaaabbbbbbcccccc  \n");
+        // a scroll view shows the sides with hidden content.
+        assert_eq!(render(10, Some(0)), "aaaaaabbb▶\n");
+        assert_eq!(render(10, Some(4)), "◀abbbbbbc▶\n");
+        assert_eq!(render(10, Some(8)), "◀bbbcccccc\n");
+    }
+
+    #[test]
+    fn test_overflow_scroll() {
+        let area = Rect::new(0, 0, 10, 4);
+        let spawn = |ctx: &mut ElementCtx, rows: usize, overflow: Overflow| {
+            let rows = (0..rows).map(|i| ui(Text::raw(format!("row {i}"))).done());
+            ctx.spawn_ui(
+                ui(Block::new())
+                    .with((Width::fixed(10), Height::fixed(4), overflow))
+                    .children(rows.collect::<Vec<_>>()),
+            )
+        };
+        let render = |ctx: &mut ElementCtx, root: Element| {
+            ctx.calculate_layout(root, area).unwrap();
+            let mut buf = Buffer::empty(area);
+            ctx.render(root, area, &mut buf);
+            buf
+        };
+        let scrollbar = |buf: &Buffer| (0..4).any(|y| buf[(9, y)].symbol() == "█");
+
+        let mut ctx = ElementCtx::new();
+        let root = spawn(&mut ctx, 10, Overflow::ScrollY);
+        assert!(ctx.get::<&ScrollView>(root).is_ok());
+        let buf = render(&mut ctx, root);
+        assert!(buf[(0, 0)].symbol() == "r" && buf[(4, 0)].symbol() == "0");
+        assert!(scrollbar(&buf));
+
+        ctx.get::<&mut ScrollViewState>(root)
+            .unwrap()
+            .set_offset(Position::new(0, 3));
+        let buf = render(&mut ctx, root);
+        assert_eq!(buf[(4, 0)].symbol(), "3");
+
+        // the content fits, or scrolls without scrollbars.
+        let fits = spawn(&mut ctx, 2, Overflow::ScrollY);
+        assert!(!scrollbar(&render(&mut ctx, fits)));
+        let hidden = spawn(&mut ctx, 10, Overflow::Hidden);
+        assert!(!scrollbar(&render(&mut ctx, hidden)));
+        let visible = spawn(&mut ctx, 10, Overflow::Visible);
+        assert!(ctx.get::<&ScrollView>(visible).is_err());
+    }
+
+    #[test]
+    fn test_sticky_header() {
+        let area = Rect::new(0, 0, 12, 6);
+        let mut ctx = ElementCtx::new();
+        let header = ui(Text::raw("header")).with((Sticky,)).done();
+        let rows = (0..20).map(|i| ui(Text::raw(format!("row {i:02}"))).done());
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((
+                    Width::fixed(10),
+                    ScrollView::default(),
+                    ScrollViewState::new(),
+                ))
+                .children(std::iter::once(header).chain(rows).collect::<Vec<_>>()),
+        );
+        ctx.get::<&mut ScrollViewState>(root)
+            .unwrap()
+            .set_offset(Position::new(0, 5));
+        ctx.calculate_layout(root, area).unwrap();
+        let mut buf = Buffer::empty(area);
+        ctx.render(root, area, &mut buf);
+
+        let row = |y: u16| {
+            (0..8)
+                .map(|x| buf[(x, y)].symbol().to_string())
+                .collect::<String>()
+        };
+        assert_eq!(row(0), "header  ");
+        // the body scrolls below the header, row 04 isn't drawn under it.
+        for (y, i) in (1..5).zip(5..) {
+            assert_eq!(row(y), format!("row {i:02}  "));
+        }
+
+        // scrolled back up, the header is where it was laid out.
+        ctx.get::<&mut ScrollViewState>(root)
+            .unwrap()
+            .set_offset(Position::new(0, 0));
+        let mut buf = Buffer::empty(area);
+        ctx.render(root, area, &mut buf);
+        assert_eq!(buf[(0, 0)].symbol(), "h");
+        assert_eq!(buf[(4, 1)].symbol(), "0");
+    }
+
+    #[test]
+    fn test_sticky_headers() {
+        let area = Rect::new(0, 0, 10, 6);
+        let mut ctx = ElementCtx::new();
+        let headers =
+            ["title", "columns"].map(|header| ui(Text::raw(header)).with((Sticky,)).done());
+        let rows = (0..20).map(|i| ui(Text::raw(format!("row {i:02}"))).done());
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((
+                    Width::fixed(10),
+                    Height::fixed(6),
+                    ScrollView::default(),
+                    ScrollViewState::new(),
+                ))
+                .children(headers.into_iter().chain(rows).collect::<Vec<_>>()),
+        );
+        let render = |ctx: &mut ElementCtx, scroll: u16| {
+            ctx.get::<&mut ScrollViewState>(root)
+                .unwrap()
+                .set_offset(Position::new(0, scroll));
+            ctx.calculate_layout(root, area).unwrap();
+            let mut buf = Buffer::empty(area);
+            ctx.render(root, area, &mut buf);
+            (0..area.height)
+                .map(|y| {
+                    (0..7)
+                        .map(|x| buf[(x, y)].symbol().to_string())
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // the headers stack in order, the body shows below both of them.
+        assert_eq!(
+            render(&mut ctx, 5),
+            [
+                "title  ", "columns", "row 05 ", "row 06 ", "row 07 ", "row 08 "
+            ]
+        );
+        assert_eq!(
+            render(&mut ctx, 0),
+            [
+                "title  ", "columns", "row 00 ", "row 01 ", "row 02 ", "row 03 "
+            ]
+        );
+    }
+
+    #[test]
+    fn test_layout_hints() {
+        use mana_tui::mana_tui_elemental::inspect::LayoutHint;
+
+        let mut ctx = ElementCtx::new();
+        let area = Rect::new(0, 0, 10, 5);
+
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Direction::Horizontal, Width::fit()))
+                .children((
+                    ui(Text::raw("a")),
+                    ui(Block::new()).with((Width::grow(), Height::fixed(1))),
+                )),
+        );
+        let grow = ctx.get::<&Children>(root).unwrap()[1];
+        assert_eq!(
+            ctx.layout_hints(root),
+            [LayoutHint::GrowInFitParent {
+                element: grow,
+                axis: Direction::Horizontal
+            }]
+        );
+        ctx.calculate_layout(root, area).unwrap();
+        ctx.despawn_ui(root);
+
+        // an empty spacer has no height, which is only a problem in a column.
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Width::fixed(10), Height::fixed(5)))
+                .children((ui(Block::new()), ui(Text::raw("a")))),
+        );
+        let empty = ctx.get::<&Children>(root).unwrap()[0];
+        assert_eq!(
+            ctx.layout_hints(root),
+            [LayoutHint::EmptyFit {
+                element: empty,
+                axis: Direction::Vertical
+            }]
+        );
+        ctx.calculate_layout(root, area).unwrap();
+        ctx.despawn_ui(root);
+
+        let root = ctx.spawn_ui(
+            ui(Block::bordered())
+                .with((Direction::Horizontal, Width::grow()))
+                .children((
+                    ui(Text::raw("a")),
+                    ui(Block::new()).with((Width::grow(),)),
+                    ui(Text::raw("b")),
+                )),
+        );
+        assert!(ctx.layout_hints(root).is_empty());
+        assert!(ctx.lint_layout(root).is_empty());
+    }
+
+    #[test]
+    fn test_gap_without_siblings() {
+        use mana_tui::mana_tui_elemental::layout::Props;
+
+        let mut ctx = ElementCtx::new();
+        let area = Rect::new(0, 0, 20, 5);
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Direction::Horizontal, Gap(3)))
+                .children((
+                    ui(Block::new())
+                        .with((Direction::Horizontal, Gap(3)))
+                        .child(ui(Text::raw("ab"))),
+                    ui(Block::new()).with((Direction::Horizontal, Gap(3), Height::fixed(1))),
+                )),
+        );
+        ctx.calculate_layout(root, area).unwrap();
+
+        let children = ctx.get::<&Children>(root).unwrap().clone();
+        assert_eq!(children.len(), 2);
+        let (one, none) = (children.get(0).unwrap(), children.get(1).unwrap());
+        assert_eq!(children.get(2), None);
+        assert_eq!(children.iter().rev().collect::<Vec<_>>(), [none, one]);
+        assert!(ctx.get::<&Children>(none).unwrap().is_empty());
+        assert_eq!(ctx.get::<&Children>(one).unwrap().len(), 1);
+
+        // a single child or no child at all adds no gap.
+        let width = |element| ctx.get::<&Props>(element).unwrap().size.x;
+        assert_eq!(width(one), 2);
+        assert_eq!(width(none), 0);
+        assert_eq!(width(root), 2 + 3);
+    }
+
+    #[test]
+    fn test_default_styles() {
+        let mut ctx = ElementCtx::new().with_default_styles(DefaultStyles {
+            block_border_type: BorderType::Rounded,
+            block_style: Style::new().fg(Color::Blue),
+            text_style: Style::new().fg(Color::Green),
+            ..DefaultStyles::default()
+        });
+        let area = Rect::new(0, 0, 12, 5);
+        let root = ctx.spawn_ui(
+            ui(Block::bordered())
+                .with((Direction::Horizontal, Width::grow(), Height::grow()))
+                .children((
+                    ui(Block::bordered()).child(ui(Text::raw("a"))),
+                    ui(Block::bordered()
+                        .border_type(BorderType::Double)
+                        .style(Style::new().fg(Color::Red)))
+                    .child(ui(Text::raw("b").style(Style::new().fg(Color::Yellow)))),
+                )),
+        );
+        ctx.calculate_layout(root, area).unwrap();
+        let mut buf = Buffer::empty(area);
+        ctx.render(root, area, &mut buf);
+
+        assert_eq!(
+            buffer_to_string(&buf),
+            "╭──────────╮\n\
+             │╭─╮╔═╗    │\n\
+             ││a│║b║    │\n\
+             │╰─╯╚═╝    │\n\
+             ╰──────────╯\n"
+        );
+        let fg = |x, y| buf[(x, y)].fg;
+        assert_eq!(fg(0, 0), Color::Blue);
+        assert_eq!(fg(1, 1), Color::Blue);
+        assert_eq!(fg(2, 2), Color::Green);
+        // styles set on the widgets win.
+        assert_eq!(fg(4, 1), Color::Red);
+        assert_eq!(fg(5, 2), Color::Yellow);
+    }
+
+    #[test]
+    fn test_el_ref_wrong_context() {
+        use mana_tui::mana_tui_elemental::handle::ElRefError;
+
+        let area = Rect::new(0, 0, 10, 4);
+        let mut ctx = ElementCtx::new();
+        let mut other = ElementCtx::new();
+        let (root, el) = ctx.spawn_ui_checked(ui(Block::bordered()).with((Width::grow(),)));
+        let (other_root, other_el) = other.spawn_ui_checked(ui(Text::raw("other")));
+        // the raw ids collide, only the handles tell the contexts apart.
+        assert_eq!(root, other_root);
+        assert_ne!(el, other_el);
+
+        assert!(matches!(
+            other.calculate_layout_checked(el, area),
+            Err(ElRefError::WrongContext)
+        ));
+        let mut buf = Buffer::empty(area);
+        assert!(matches!(
+            other.render_checked(el, area, &mut buf),
+            Err(ElRefError::WrongContext)
+        ));
+        assert!(matches!(
+            other.despawn_ui_checked(el),
+            Err(ElRefError::WrongContext)
+        ));
+        // a context that never made a handle rejects them all.
+        assert!(matches!(
+            ElementCtx::new().check(el),
+            Err(ElRefError::WrongContext)
+        ));
+
+        ctx.calculate_layout_checked(el, area).unwrap();
+        ctx.render_checked(el, area, &mut buf).unwrap();
+        assert_eq!(buf[(0, 0)].symbol(), "┌");
+        ctx.despawn_ui_checked(el).unwrap();
+        assert!(matches!(
+            ctx.check(el),
+            Err(ElRefError::Component(hecs::ComponentError::NoSuchEntity))
+        ));
+        assert_eq!(other.check(other_el).unwrap(), other_root);
+    }
+
+    #[test]
+    fn test_axis_sizes_round_trip() {
+        use mana_tui::mana_tui_elemental::layout::{AxisSizes, axify};
+
+        let rect = Rect::new(3, 5, 40, 12);
+        let horizontal = AxisSizes::from((rect, Direction::Horizontal));
+        assert_eq!((horizontal.main_axis, horizontal.cross_axis), (40, 12));
+        let vertical = AxisSizes::from((rect, Direction::Vertical));
+        assert_eq!((vertical.main_axis, vertical.cross_axis), (12, 40));
+        for dir in [Direction::Horizontal, Direction::Vertical] {
+            let sizes = AxisSizes::from((rect, dir));
+            assert_eq!(sizes.to_u16vec2(dir), U16Vec2::new(40, 12));
+            assert_eq!(axify(sizes.to_u16vec2(dir), dir), sizes);
+            let padding = Padding::new(1, 2, 3, 4);
+            assert_eq!(sizes.pad(padding, dir).shrink(padding, dir), sizes);
+        }
+    }
+
+    #[test]
+    fn test_separator() {
+        let area = Rect::new(0, 0, 12, 5);
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Width::grow(), Height::grow(), Direction::Horizontal))
+                .children((
+                    ui(Block::new())
+                        .with((Width::fixed(5), Height::grow()))
+                        .children((
+                            ui(Text::raw("top")),
+                            ui(Separator::horizontal()),
+                            ui(Text::raw("end")),
+                        )),
+                    ui(Separator::vertical()),
+                )),
+        );
+        ctx.calculate_layout(root, area).unwrap();
+        let mut buf = Buffer::empty(area);
+        ctx.render(root, area, &mut buf);
+        assert_eq!(
+            buffer_to_string(&buf),
+            "top  │      \n─────│      \nend  │      \n     │      \n     │      \n"
+        );
+    }
+
+    #[test]
+    fn test_with_world_panic() {
+        let mut world = World::new();
+        let element = world.spawn((1i32,));
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            ElementCtx::with_world(&mut world, |ctx| {
+                ctx.spawn((2i32,));
+                panic!("handler panicked");
+            });
+        }));
+        assert!(result.is_err());
+        // the world is given back with what the closure did before it panicked.
+        assert_eq!(*world.get::<&i32>(element).unwrap(), 1);
+        assert_eq!(world.len(), 2);
+    }
+
     #[test]
     #[should_panic]
     fn test_hecs() {
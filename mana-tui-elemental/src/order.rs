@@ -0,0 +1,82 @@
+//! # Order
+//!
+//! hecs iterates entities in archetype order: adding or removing a component on one entity moves
+//! it to another archetype, and it is then visited before or after its siblings. views built
+//! from a query reorder on screen when that happens.
+//!
+//! spawn the entities a view is built from with [`OrderedExt::spawn_ordered`] and query them
+//! with [`OrderedExt::query_ordered`] to keep the order they were spawned in.
+//!
+//! # Example
+//!
+//! ```
+//! # use mana_tui_elemental::prelude::*;
+//! struct Todo(&'static str);
+//! struct Done;
+//!
+//! let mut ctx = ElementCtx::new();
+//! let first = ctx.spawn_ordered((Todo("buy milk"),));
+//! ctx.spawn_ordered((Todo("water plants"),));
+//! ctx.insert_one(first, Done).unwrap();
+//!
+//! let todos = ctx.query_ordered::<&Todo>();
+//! assert_eq!(todos[0].0, "buy milk");
+//! ```
+
+use hecs::{Component, DynamicBundle, Entity, EntityBuilder, Query, World};
+use mana_tui_utils::resource::Resources;
+
+/// the position of an entity in spawn order, added by [`OrderedExt::spawn_ordered`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SpawnOrder(pub u64);
+
+/// resource with the next [`SpawnOrder`].
+#[derive(Debug, Default)]
+struct SpawnCounter(u64);
+
+/// deterministic iteration order for entities, see the [module docs](self).
+pub trait OrderedExt {
+    /// spawns `bundle` with the next [`SpawnOrder`].
+    fn spawn_ordered(&mut self, bundle: impl DynamicBundle) -> Entity;
+
+    /// the results of `Q` sorted by [`SpawnOrder`]. entities spawned without one come first, in
+    /// the order of their ids.
+    fn query_ordered<Q: Query>(&mut self) -> Vec<Q::Item<'_>>;
+
+    /// the results of `Q` for entities with a `K`, sorted by it.
+    fn query_ordered_by<K: Component + Ord + Clone, Q: Query>(&mut self) -> Vec<Q::Item<'_>>;
+}
+
+impl OrderedExt for World {
+    fn spawn_ordered(&mut self, bundle: impl DynamicBundle) -> Entity {
+        let order = {
+            let mut counter =
+                self.get_or_insert_resource_with::<&mut SpawnCounter>(|_| SpawnCounter::default());
+            counter.0 += 1;
+            SpawnOrder(counter.0 - 1)
+        };
+        let mut builder = EntityBuilder::new();
+        builder.add_bundle(bundle).add(order);
+        self.spawn(builder.build())
+    }
+
+    fn query_ordered<Q: Query>(&mut self) -> Vec<Q::Item<'_>> {
+        let mut items = self
+            .query_mut::<(Entity, Option<&SpawnOrder>, Q)>()
+            .into_iter()
+            .map(|(entity, order, item)| ((order.copied(), entity.id()), item))
+            .collect::<Vec<_>>();
+        items.sort_by_key(|(key, _)| *key);
+        items.into_iter().map(|(_, item)| item).collect()
+    }
+
+    fn query_ordered_by<K: Component + Ord + Clone, Q: Query>(&mut self) -> Vec<Q::Item<'_>> {
+        let mut items = self
+            .query_mut::<(&K, Q)>()
+            .into_iter()
+            .map(|(key, item)| (key.clone(), item))
+            .collect::<Vec<_>>();
+        items.sort_by(|(a, _), (b, _)| a.cmp(b));
+        items.into_iter().map(|(_, item)| item).collect()
+    }
+}
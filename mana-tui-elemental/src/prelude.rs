@@ -1,4 +1,4 @@
-//! prelude module. contains public api for `ratatui-elemental`.
+//! prelude module. contains public api for `mana-tui-elemental`.
 //!
 //! # Usage
 //!
@@ -8,8 +8,26 @@
 
 pub use crate::layout::TuiElMarker;
 
+pub use crate::animation::{Animation, AnimationState, FadeIn};
+pub use crate::blit::Blit;
+pub use crate::color::ColorCaps;
+pub use crate::inspect::{ElId, Role};
+pub use crate::order::{OrderedExt, SpawnOrder};
+pub use crate::progress::ProgressBar;
+pub use crate::schedule::{AnimationHandle, AnimationScheduler};
+pub use crate::separator::Separator;
+pub use crate::spacer::Spacer;
+pub use crate::spinner::Spinner;
+pub use crate::text_hit::{SourceParagraph, TextHit, text_hit};
+pub use crate::theme::DefaultStyles;
+
 pub use crate::layout::{
-    Children, CrossJustify, ElWidget, Element, ElementCtx, Gap, Height, MainJustify, Size, Width,
+    AlignSelf, Children, ChildrenOffset, ClearBackground, ClipRegion, Container, ContentEditable,
+    ContentVersion, CrossJustify, Cursor, CursorShape, DynWidget, ElWidget, Element, ElementCtx,
+    FlexBasis, FlowOrder, Gap, Height, HeightFromWidth, HintPosition, KeyboardShortcutHint,
+    MainJustify, Margin, MaxChildren, Measure, NoTruncateTitles, NopWidget, Overflow,
+    OverflowIndicator, PreferredSize, Shadow, Size, SplitPane, Sticky, StickyTop, TerminalSize,
+    Viewport, Width, dyn_widget,
 };
 pub use ratatui::{
     layout::Direction,
@@ -24,7 +42,10 @@ pub use crate::ui::AsChildren;
 pub use bon;
 
 pub use crate::layout::Center;
-pub use crate::ui::{IntoUiBuilderList, IntoView, IteratorMarker, UiBuilder, View, ui};
+pub use crate::ui::{
+    AspectRatioBox, ConditionalBuilder, IntoUiBuilderList, IntoView, IteratorMarker, OnMount,
+    OnUnmount, RawDraw, Subview, UiBuilder, View, keyed_list, lazy_subview, raw, ui,
+};
 pub use strum;
 pub use tui_scrollview::*;
 
@@ -54,3 +75,32 @@ impl BlockExt<'static> for Block<'static> {
         self
     }
 }
+
+/// common [`Padding`] values. zero padding is already available as [`Padding::ZERO`].
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// ui(Block::bordered()).with((Padding::ONE, Width::grow()));
+/// assert_eq!(Padding::from_border(&Block::bordered()), Padding::ONE);
+/// ```
+pub trait PaddingExt {
+    /// one cell of padding on every side.
+    const ONE: Padding;
+    /// two cells of padding on every side.
+    const TWO: Padding;
+
+    /// the space the borders, titles and padding of `block` take, see
+    /// [`detect_block_padding`](crate::ui::detect_block_padding).
+    fn from_border(block: &Block) -> Padding;
+}
+
+impl PaddingExt for Padding {
+    const ONE: Padding = Padding::uniform(1);
+    const TWO: Padding = Padding::uniform(2);
+
+    fn from_border(block: &Block) -> Padding {
+        crate::ui::detect_block_padding(block)
+    }
+}
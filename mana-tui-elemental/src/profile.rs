@@ -0,0 +1,326 @@
+//! # Profile
+//!
+//! per-element timings of the last frame, to find the element that makes a frame slow.
+//!
+//! insert an enabled [`Profiler`] as a resource: every [`ElementCtx::calculate_layout`] then
+//! records the tree it lays out, and the layout and render of each element are timed until the
+//! next frame. [`Profiler::report`] prints them as a text flamegraph.
+//!
+//! # Example
+//!
+//! ```
+//! # use mana_tui_elemental::prelude::*;
+//! # use mana_tui_elemental::profile::Profiler;
+//! # use mana_tui_utils::resource::Resources;
+//! # use ratatui::{buffer::Buffer, layout::Rect};
+//! let mut ctx = ElementCtx::new();
+//! ctx.insert_resource(Profiler::enabled());
+//! let root = ctx.spawn_ui(ui(Block::new()).child(ui(Text::raw("hi"))));
+//! let area = Rect::new(0, 0, 10, 2);
+//! ctx.calculate_layout(root, area).unwrap();
+//! ctx.render(root, area, &mut Buffer::empty(area));
+//! let report = ctx.get_resource::<&Profiler>().unwrap().report(10);
+//! assert_eq!(report.lines().count(), 3);
+//! ```
+
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    fmt::Write,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
+
+use mana_tui_utils::resource::Resources;
+
+use crate::{
+    inspect::ElId,
+    layout::{Children, Element, ElementCtx, Props},
+};
+
+/// resource with the layout and render times of every element of the last frame.
+///
+/// a frame starts with [`Profiler::begin_frame`], or when a root of the current frame is laid
+/// out again. every root laid out in between, like overlays, is part of the same frame.
+///
+/// while disabled the profiler costs one branch per element.
+#[derive(Debug, Default)]
+pub struct Profiler {
+    enabled: bool,
+    /// reads the time, [`monotonic`] unless set with [`Profiler::with_clock`].
+    clock: Option<fn() -> Duration>,
+    nodes: Vec<ProfileNode>,
+    roots: Vec<usize>,
+    index: HashMap<Element, usize>,
+}
+
+#[derive(Debug)]
+struct ProfileNode {
+    name: String,
+    children: Vec<usize>,
+    /// time spent in the layout passes, including the children.
+    layout: Duration,
+    /// time spent drawing the element, without the children.
+    render: Duration,
+}
+
+/// the timings of one element in a [`Profiler`] frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ElementTimes {
+    /// layout and render time of the element and its subtree.
+    pub inclusive: Duration,
+    /// layout and render time of the element alone.
+    pub exclusive: Duration,
+}
+
+impl Profiler {
+    /// a profiler that records from the next layout on.
+    #[must_use]
+    pub fn enabled() -> Self {
+        Self {
+            enabled: true,
+            ..Self::default()
+        }
+    }
+
+    /// a profiler that records from the next layout on and reads the time from `clock`, like a
+    /// fake clock that tests move forward by hand. `clock` only has to be monotonic.
+    #[must_use]
+    pub fn with_clock(clock: fn() -> Duration) -> Self {
+        Self {
+            clock: Some(clock),
+            ..Self::enabled()
+        }
+    }
+
+    /// whether frames are recorded.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// starts or stops recording. the last frame is kept.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// forgets the last frame. the next layouts are recorded as a new one.
+    pub fn begin_frame(&mut self) {
+        self.nodes.clear();
+        self.roots.clear();
+        self.index.clear();
+    }
+
+    /// the timings of `element` in the last frame.
+    #[must_use]
+    pub fn times(&self, element: Element) -> Option<ElementTimes> {
+        let node = *self.index.get(&element)?;
+        Some(self.all_times()[node])
+    }
+
+    /// the total layout and render time of the last frame.
+    #[must_use]
+    pub fn total(&self) -> Duration {
+        let times = self.all_times();
+        self.roots.iter().map(|&root| times[root].inclusive).sum()
+    }
+
+    /// the last frame as an indented tree, with the inclusive time, its share of the frame and
+    /// the exclusive time of every element. siblings are sorted from the slowest, and only the
+    /// `top_n` slowest children of each element are listed.
+    #[must_use]
+    pub fn report(&self, top_n: usize) -> String {
+        let times = self.all_times();
+        let total = self.roots.iter().map(|&root| times[root].inclusive).sum();
+        let mut out = format!("{:>9} {:>6} {:>9}  element\n", "total", "%", "self");
+        let mut roots = self.roots.clone();
+        roots.sort_by_key(|&root| Reverse(times[root].inclusive));
+        for root in roots {
+            self.report_node(root, 0, (top_n, total, &times), &mut out);
+        }
+        out
+    }
+
+    fn report_node(
+        &self,
+        node: usize,
+        depth: usize,
+        (top_n, total, times): (usize, Duration, &[ElementTimes]),
+        out: &mut String,
+    ) {
+        let share = if total.is_zero() {
+            0.0
+        } else {
+            times[node].inclusive.as_secs_f64() / total.as_secs_f64() * 100.0
+        };
+        _ = writeln!(
+            out,
+            "{:>7.2}ms {share:>5.1}% {:>7.2}ms  {:indent$}{}",
+            millis(times[node].inclusive),
+            millis(times[node].exclusive),
+            "",
+            self.nodes[node].name,
+            indent = depth * 2,
+        );
+        let mut children = self.nodes[node].children.clone();
+        children.sort_by_key(|&child| Reverse(times[child].inclusive));
+        for &child in children.iter().take(top_n) {
+            self.report_node(child, depth + 1, (top_n, total, times), out);
+        }
+        if children.len() > top_n {
+            _ = writeln!(
+                out,
+                "{:28}{:indent$}… {} more",
+                "",
+                "",
+                children.len() - top_n,
+                indent = (depth + 1) * 2,
+            );
+        }
+    }
+
+    /// the timings of every node. layout times are recorded with the children and render times
+    /// without them, children always come after their parent.
+    fn all_times(&self) -> Vec<ElementTimes> {
+        let mut times = vec![ElementTimes::default(); self.nodes.len()];
+        let mut render = vec![Duration::ZERO; self.nodes.len()];
+        for (idx, node) in self.nodes.iter().enumerate().rev() {
+            let subtree_render = node
+                .children
+                .iter()
+                .map(|&child| render[child])
+                .sum::<Duration>();
+            render[idx] = node.render + subtree_render;
+            let inclusive = node.layout + render[idx];
+            let children = node
+                .children
+                .iter()
+                .map(|&child| times[child].inclusive)
+                .sum::<Duration>();
+            times[idx] = ElementTimes {
+                inclusive,
+                exclusive: inclusive.saturating_sub(children),
+            };
+        }
+        times
+    }
+
+    fn add_tree(&mut self, ctx: &ElementCtx, element: Element) -> usize {
+        let node = self.nodes.len();
+        self.index.insert(element, node);
+        self.nodes.push(ProfileNode {
+            name: element_name(ctx, element),
+            children: Vec::new(),
+            layout: Duration::ZERO,
+            render: Duration::ZERO,
+        });
+        let children = ctx
+            .world
+            .get::<&Children>(element)
+            .map(|children| children.clone())
+            .unwrap_or_default();
+        for child in children.iter() {
+            let child = self.add_tree(ctx, child);
+            self.nodes[node].children.push(child);
+        }
+        node
+    }
+}
+
+/// the time since the first call, the default clock of a [`Profiler`].
+fn monotonic() -> Duration {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    EPOCH.get_or_init(Instant::now).elapsed()
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// the [`ElId`] of the element, or the name of its widget without the module path.
+fn element_name(ctx: &ElementCtx, element: Element) -> String {
+    if let Ok(id) = ctx.world.get::<&ElId>(element) {
+        return format!("#{}", id.0);
+    }
+    let type_name = ctx
+        .world
+        .get::<&Props>(element)
+        .map_or("?", |props| props.type_name);
+    let path = type_name.split('<').next().unwrap_or(type_name);
+    path.rsplit("::").next().unwrap_or(path).to_string()
+}
+
+/// which part of the frame a [`ProfileTimer`] measures.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Phase {
+    Layout,
+    Render,
+}
+
+/// adds the time until it is dropped to the element in the [`Profiler`].
+pub(crate) struct ProfileTimer<'a> {
+    ctx: &'a ElementCtx,
+    element: Element,
+    phase: Phase,
+    clock: fn() -> Duration,
+    start: Duration,
+}
+
+impl Drop for ProfileTimer<'_> {
+    fn drop(&mut self) {
+        let elapsed = (self.clock)().saturating_sub(self.start);
+        let Ok(mut profiler) = self.ctx.world.get_resource::<&mut Profiler>() else {
+            return;
+        };
+        let Some(&node) = profiler.index.get(&self.element) else {
+            return;
+        };
+        let node = &mut profiler.nodes[node];
+        match self.phase {
+            Phase::Layout => node.layout += elapsed,
+            Phase::Render => node.render += elapsed,
+        }
+    }
+}
+
+impl ElementCtx {
+    /// records the tree under `root` if the [`Profiler`] is enabled, starting a new frame if
+    /// `root` was already recorded.
+    pub(crate) fn begin_profile(&mut self, root: Element) {
+        self.profiling = self
+            .world
+            .get_resource::<&Profiler>()
+            .is_ok_and(|profiler| profiler.enabled);
+        if !self.profiling {
+            return;
+        }
+        let Ok(mut profiler) = self.world.get_resource::<&mut Profiler>() else {
+            return;
+        };
+        if profiler.index.contains_key(&root) {
+            profiler.begin_frame();
+        }
+        let root = profiler.add_tree(self, root);
+        profiler.roots.push(root);
+    }
+
+    /// times `element` until the returned timer is dropped, if profiling.
+    pub(crate) fn profile(&self, element: Element, phase: Phase) -> Option<ProfileTimer<'_>> {
+        if !self.profiling {
+            return None;
+        }
+        let clock = self
+            .world
+            .get_resource::<&Profiler>()
+            .ok()
+            .and_then(|profiler| profiler.clock)
+            .unwrap_or(monotonic);
+        Some(ProfileTimer {
+            ctx: self,
+            element,
+            phase,
+            clock,
+            start: clock(),
+        })
+    }
+}
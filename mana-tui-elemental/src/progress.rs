@@ -0,0 +1,124 @@
+//! # Progress
+//!
+//! a horizontal [`ProgressBar`], drawn cell by cell.
+
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Style, Styled},
+    text::Span,
+    widgets::Widget,
+};
+
+/// a bar that fills `value / max` of its width, with an optional label centered over it.
+///
+/// every row of the area is filled with `█` in `filled_style` up to
+/// `floor(width * value / max)`, and with `░` in `empty_style` after that. the element is one
+/// cell high and grows to the width of its parent by default.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// # use ratatui::style::{Color, Style};
+/// ui(ProgressBar::new(3.0, 10.0)
+///     .filled_style(Style::new().fg(Color::Green))
+///     .label("3/10"));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgressBar {
+    /// the progress, from `0.0` to `max`.
+    pub value: f32,
+    /// the value of a full bar.
+    pub max: f32,
+    /// the style of the filled cells, also the style of the widget.
+    pub filled_style: Style,
+    /// the style of the empty cells.
+    pub empty_style: Style,
+    /// drawn centered over the middle row.
+    pub label: Option<Span<'static>>,
+}
+
+impl ProgressBar {
+    /// a bar at `value` out of `max`, without a label.
+    #[must_use]
+    pub fn new(value: f32, max: f32) -> Self {
+        Self {
+            value,
+            max,
+            filled_style: Style::new(),
+            empty_style: Style::new(),
+            label: None,
+        }
+    }
+
+    /// sets the style of the filled cells.
+    #[must_use]
+    pub fn filled_style(mut self, style: Style) -> Self {
+        self.filled_style = style;
+        self
+    }
+
+    /// sets the style of the empty cells.
+    #[must_use]
+    pub fn empty_style(mut self, style: Style) -> Self {
+        self.empty_style = style;
+        self
+    }
+
+    /// sets the label drawn centered over the bar.
+    #[must_use]
+    pub fn label(mut self, label: impl Into<Span<'static>>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// the number of filled cells in a bar `width` cells wide.
+    #[must_use]
+    pub fn filled_cells(&self, width: u16) -> u16 {
+        if self.max <= 0.0 {
+            return 0;
+        }
+        let ratio = (self.value / self.max).clamp(0.0, 1.0);
+        (f32::from(width) * ratio).floor() as u16
+    }
+}
+
+impl Widget for ProgressBar {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = area.intersection(buf.area);
+        if area.is_empty() {
+            return;
+        }
+        let filled = self.filled_cells(area.width);
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let (symbol, style) = if x - area.left() < filled {
+                    ("█", self.filled_style)
+                } else {
+                    ("░", self.empty_style)
+                };
+                buf[(x, y)].set_symbol(symbol).set_style(style);
+            }
+        }
+
+        if let Some(label) = self.label {
+            let width = (label.width() as u16).min(area.width);
+            let x = area.left() + (area.width - width) / 2;
+            let y = area.top() + area.height / 2;
+            buf.set_span(x, y, &label, width);
+        }
+    }
+}
+
+impl Styled for ProgressBar {
+    type Item = Self;
+
+    fn style(&self) -> Style {
+        self.filled_style
+    }
+
+    fn set_style<S: Into<Style>>(self, style: S) -> Self::Item {
+        self.filled_style(style.into())
+    }
+}
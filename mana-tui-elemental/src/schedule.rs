@@ -0,0 +1,172 @@
+//! # Schedule
+//!
+//! timers for everything that changes on its own, like spinners and blinking cursors, so the
+//! runtime only wakes up when one of them is due.
+//!
+//! register an [`AnimationHandle`] with [`ElementCtx::register_animation`]. the runtime sleeps
+//! until [`ElementCtx::next_animation_deadline`] and calls [`ElementCtx::tick_scheduled`], which
+//! runs the due callbacks and tells whether any of them changed something. without handles the
+//! runtime only wakes up for events.
+//!
+//! # Example
+//!
+//! ```
+//! # use std::time::{Duration, Instant};
+//! # use mana_tui_elemental::prelude::*;
+//! let mut ctx = ElementCtx::new();
+//! let start = Instant::now();
+//! let blink = ctx.register_animation(Duration::from_millis(500), start, |_, _| true);
+//! assert_eq!(ctx.next_animation_deadline(), Some(start + Duration::from_millis(500)));
+//! assert!(ctx.tick_scheduled(start + Duration::from_millis(500)));
+//!
+//! ctx.deregister_animation(blink);
+//! assert_eq!(ctx.next_animation_deadline(), None);
+//! ```
+
+use std::time::{Duration, Instant};
+
+use mana_tui_utils::resource::Resources;
+
+use crate::layout::ElementCtx;
+
+/// the callback of a scheduled animation, returns whether it changed anything that is drawn.
+pub type TickFn = Box<dyn FnMut(&mut ElementCtx, Tick) -> bool + Send + Sync>;
+
+/// the time passed to a [`TickFn`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tick {
+    /// when the tick happened.
+    pub now: Instant,
+    /// the time since the handle last ran, or was registered.
+    pub elapsed: Duration,
+}
+
+/// a registration in the [`AnimationScheduler`], see the [module docs](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AnimationHandle(u64);
+
+struct Scheduled {
+    handle: AnimationHandle,
+    interval: Duration,
+    last: Instant,
+    tick: TickFn,
+}
+
+/// resource with the registered animations, see the [module docs](self).
+#[derive(Default)]
+pub struct AnimationScheduler {
+    next_id: u64,
+    scheduled: Vec<Scheduled>,
+    /// handles deregistered while their callbacks ran.
+    removed: Vec<AnimationHandle>,
+}
+
+impl std::fmt::Debug for AnimationScheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnimationScheduler")
+            .field("active", &self.scheduled.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl AnimationScheduler {
+    /// whether any animation is registered.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        !self.scheduled.is_empty()
+    }
+
+    /// when the next animation is due.
+    #[must_use]
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.scheduled
+            .iter()
+            .map(|scheduled| scheduled.last + scheduled.interval)
+            .min()
+    }
+}
+
+impl ElementCtx {
+    /// calls `tick` every `interval` from `now` on, until the handle is deregistered.
+    pub fn register_animation(
+        &mut self,
+        interval: Duration,
+        now: Instant,
+        tick: impl FnMut(&mut ElementCtx, Tick) -> bool + Send + Sync + 'static,
+    ) -> AnimationHandle {
+        let mut scheduler = self
+            .world
+            .get_or_insert_resource_with::<&mut AnimationScheduler>(|_| {
+                AnimationScheduler::default()
+            });
+        let handle = AnimationHandle(scheduler.next_id);
+        scheduler.next_id += 1;
+        scheduler.scheduled.push(Scheduled {
+            handle,
+            // a zero interval would wake the runtime in a loop.
+            interval: interval.max(Duration::from_millis(1)),
+            last: now,
+            tick: Box::new(tick),
+        });
+        handle
+    }
+
+    /// stops the animation of `handle`. returns `false` if it wasn't registered.
+    pub fn deregister_animation(&mut self, handle: AnimationHandle) -> bool {
+        let Ok(mut scheduler) = self.world.get_resource::<&mut AnimationScheduler>() else {
+            return false;
+        };
+        let before = scheduler.scheduled.len();
+        scheduler
+            .scheduled
+            .retain(|scheduled| scheduled.handle != handle);
+        // the handle may be running right now, see `tick_scheduled`.
+        scheduler.removed.push(handle);
+        scheduler.scheduled.len() != before
+    }
+
+    /// when the next registered animation is due, [`None`] without any.
+    #[must_use]
+    pub fn next_animation_deadline(&self) -> Option<Instant> {
+        self.world
+            .get_resource::<&AnimationScheduler>()
+            .ok()?
+            .next_deadline()
+    }
+
+    /// runs the callbacks of the animations that are due at `now`.
+    ///
+    /// returns `true` if any of them changed something, the tree has to be drawn again then.
+    pub fn tick_scheduled(&mut self, now: Instant) -> bool {
+        let Ok(mut scheduler) = self.world.get_resource::<&mut AnimationScheduler>() else {
+            return false;
+        };
+        let (due, waiting) = std::mem::take(&mut scheduler.scheduled)
+            .into_iter()
+            .partition::<Vec<_>, _>(|scheduled| scheduled.last + scheduled.interval <= now);
+        scheduler.scheduled = waiting;
+        scheduler.removed.clear();
+        drop(scheduler);
+
+        let mut changed = false;
+        let mut ran = Vec::with_capacity(due.len());
+        for mut scheduled in due {
+            let elapsed = now.saturating_duration_since(scheduled.last);
+            changed |= (scheduled.tick)(self, Tick { now, elapsed });
+            scheduled.last = now;
+            ran.push(scheduled);
+        }
+
+        let mut scheduler = self
+            .world
+            .get_or_insert_resource_with::<&mut AnimationScheduler>(|_| {
+                AnimationScheduler::default()
+            });
+        let removed = std::mem::take(&mut scheduler.removed);
+        scheduler.scheduled.extend(
+            ran.into_iter()
+                .filter(|scheduled| !removed.contains(&scheduled.handle)),
+        );
+        changed
+    }
+}
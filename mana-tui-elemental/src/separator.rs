@@ -0,0 +1,103 @@
+//! # Separator
+//!
+//! a [`Separator`] draws a divider between elements.
+
+use ratatui::{
+    buffer::Buffer,
+    layout::{Direction, Rect},
+    style::{Style, Styled},
+    widgets::Widget,
+};
+
+/// a line across its area, `─` when horizontal and `│` when vertical.
+///
+/// a horizontal separator is one cell high and grows to the width of its parent by default, a
+/// vertical one is one cell wide and grows to the height of its parent.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// # use ratatui::style::{Color, Style};
+/// ui(Block::new()).children((
+///     ui(Text::raw("above")),
+///     ui(Separator::horizontal().style(Style::new().fg(Color::DarkGray))),
+///     ui(Text::raw("below")),
+/// ));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Separator {
+    /// the direction of the line.
+    pub direction: Direction,
+    /// the style of the line.
+    pub style: Style,
+}
+
+impl Default for Separator {
+    fn default() -> Self {
+        Self::horizontal()
+    }
+}
+
+impl Separator {
+    /// a horizontal line.
+    #[must_use]
+    pub const fn horizontal() -> Self {
+        Self {
+            direction: Direction::Horizontal,
+            style: Style::new(),
+        }
+    }
+
+    /// a vertical line.
+    #[must_use]
+    pub const fn vertical() -> Self {
+        Self {
+            direction: Direction::Vertical,
+            style: Style::new(),
+        }
+    }
+
+    /// sets the style of the line.
+    #[must_use]
+    pub const fn style(mut self, style: Style) -> Self {
+        self.style = style;
+        self
+    }
+}
+
+impl Widget for Separator {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let area = area.intersection(buf.area);
+        if area.is_empty() {
+            return;
+        }
+        match self.direction {
+            Direction::Horizontal => {
+                let line = "─".repeat(area.width.into());
+                for y in area.top()..area.bottom() {
+                    buf.set_string(area.x, y, &line, self.style);
+                }
+            }
+            Direction::Vertical => {
+                for y in area.top()..area.bottom() {
+                    for x in area.left()..area.right() {
+                        buf.set_string(x, y, "│", self.style);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Styled for Separator {
+    type Item = Self;
+
+    fn style(&self) -> Style {
+        self.style
+    }
+
+    fn set_style<S: Into<Style>>(self, style: S) -> Self::Item {
+        Separator::style(self, style.into())
+    }
+}
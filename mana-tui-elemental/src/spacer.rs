@@ -0,0 +1,92 @@
+//! # Spacer
+//!
+//! a [`Spacer`] is empty space that pushes its siblings apart.
+
+use ratatui::{buffer::Buffer, layout::Direction, layout::Rect, style::Style};
+
+use crate::layout::{ElWidget, Height, UnstyledMarker, Width};
+
+/// empty space between elements, it draws nothing.
+///
+/// a [`Spacer::default`] takes the direction of its parent when it's spawned as a child, so
+/// `<Spacer />` grows along the main axis of any container. spawned on its own it grows on both
+/// axes. use [`Spacer::horizontal`], [`Spacer::vertical`] or [`Spacer::fixed`] to pick the
+/// direction yourself, an explicit [`Width`] or [`Height`] wins over all of them.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// // "left" at the start of the row and "right" at the end.
+/// ui(Block::new())
+///     .with((Width::grow(), Direction::Horizontal))
+///     .children((
+///         ui(Text::raw("left")),
+///         ui(Spacer::default()),
+///         ui(Text::raw("right")),
+///     ));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Spacer {
+    /// the axis the spacer takes space on, [`None`] until it knows its parent.
+    pub direction: Option<Direction>,
+    /// the size along the axis, [`None`] to grow.
+    pub size: Option<u16>,
+}
+
+impl Spacer {
+    /// space that grows in a horizontal container.
+    #[must_use]
+    pub const fn horizontal() -> Self {
+        Self {
+            direction: Some(Direction::Horizontal),
+            size: None,
+        }
+    }
+
+    /// space that grows in a vertical container.
+    #[must_use]
+    pub const fn vertical() -> Self {
+        Self {
+            direction: Some(Direction::Vertical),
+            size: None,
+        }
+    }
+
+    /// a gap of `size` cells along `direction`.
+    #[must_use]
+    pub const fn fixed(size: u16, direction: Direction) -> Self {
+        Self {
+            direction: Some(direction),
+            size: Some(size),
+        }
+    }
+
+    /// the default size of the spacer.
+    pub(crate) fn sizes(&self) -> (Width, Height) {
+        match self.direction {
+            None => (Width::grow(), Height::grow()),
+            Some(Direction::Horizontal) => (
+                self.size.map_or_else(Width::grow, Width::fixed),
+                Height::fixed(0),
+            ),
+            Some(Direction::Vertical) => (
+                Width::fixed(0),
+                self.size.map_or_else(Height::grow, Height::fixed),
+            ),
+        }
+    }
+}
+
+impl ElWidget<UnstyledMarker> for Spacer {
+    fn render_element(&self, _: Rect, _: &mut Buffer) {}
+
+    fn set_style(&mut self, _: Style) {}
+
+    fn get_style(&self) -> Style {
+        Style::default()
+    }
+    fn fingerprint(&self) -> Option<u64> {
+        Some(0)
+    }
+}
@@ -0,0 +1,182 @@
+//! # Spinner
+//!
+//! animated loading indicators. a [`Spinner`] draws one of its frames, and
+//! [`ElementCtx::tick_spinners`] picks the frame of every spinner from the time since the first
+//! tick. [`ElementCtx::schedule_spinners`] keeps them turning through the
+//! [`AnimationScheduler`](crate::schedule::AnimationScheduler).
+
+use std::time::{Duration, Instant};
+
+use mana_tui_utils::resource::Resources;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Style, Styled},
+    text::Span,
+    widgets::Widget,
+};
+
+use crate::layout::ElementCtx;
+use crate::schedule::AnimationHandle;
+
+/// braille dots going around a full cell.
+pub const BRAILLE: &[&str] = &["⣾", "⣽", "⣻", "⢿", "⡿", "⣟", "⣯", "⣷"];
+/// the classic braille spinner.
+pub const CLASSIC: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+/// an ellipsis that fills up.
+pub const DOTS: &[&str] = &["   ", ".  ", ".. ", "..."];
+
+/// a widget that cycles through `frames`, showing each for `interval`. the frame is advanced by
+/// [`ElementCtx::tick_spinners`].
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// # use mana_tui_elemental::spinner;
+/// # use std::time::Duration;
+/// ui(Block::new())
+///     .with((Direction::Horizontal, Gap(1)))
+///     .children((
+///         ui(Spinner::new(spinner::CLASSIC, Duration::from_millis(80))),
+///         ui(Text::raw("loading")),
+///     ));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spinner {
+    /// the frames, drawn in order.
+    pub frames: &'static [&'static str],
+    /// how long each frame is shown.
+    pub interval: Duration,
+    /// the index of the current frame, wraps around.
+    pub current: usize,
+    /// the style of the frames.
+    pub style: Style,
+}
+
+impl Default for Spinner {
+    fn default() -> Self {
+        Self::new(CLASSIC, Duration::from_millis(80))
+    }
+}
+
+impl Spinner {
+    /// a spinner at its first frame.
+    #[must_use]
+    pub const fn new(frames: &'static [&'static str], interval: Duration) -> Self {
+        Self {
+            frames,
+            interval,
+            current: 0,
+            style: Style::new(),
+        }
+    }
+
+    /// the frame drawn by the spinner, empty if it has no frames.
+    #[must_use]
+    pub fn frame(&self) -> &'static str {
+        if self.frames.is_empty() {
+            return "";
+        }
+        self.frames[self.current % self.frames.len()]
+    }
+
+    /// the width of the widest frame.
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.frames
+            .iter()
+            .map(|frame| Span::raw(*frame).width())
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+impl Widget for Spinner {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        Span::styled(self.frame(), self.style).render(area, buf);
+    }
+}
+
+impl Styled for Spinner {
+    type Item = Self;
+
+    fn style(&self) -> Style {
+        self.style
+    }
+
+    fn set_style<S: Into<Style>>(mut self, style: S) -> Self::Item {
+        self.style = style.into();
+        self
+    }
+}
+
+/// resource with the time of the first [`ElementCtx::tick_spinners`]. every spinner shows the
+/// frame for the time since then, so spinners in a tree that is spawned again every frame keep
+/// turning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpinnerState(pub Instant);
+
+impl ElementCtx {
+    /// sets the current frame of every [`Spinner`] from the time between the first call and
+    /// `now`.
+    ///
+    /// returns `true` if there is any spinner, so the runtime knows to keep drawing frames.
+    pub fn tick_spinners(&mut self, now: Instant) -> bool {
+        let SpinnerState(start) = *self
+            .world
+            .get_or_insert_resource_with::<&SpinnerState>(|_| SpinnerState(now));
+        let elapsed = now.saturating_duration_since(start);
+
+        let mut any = false;
+        for spinner in self.query_mut::<&mut Spinner>() {
+            any = true;
+            if spinner.interval.is_zero() {
+                continue;
+            }
+            spinner.current = (elapsed.as_nanos() / spinner.interval.as_nanos()) as usize;
+        }
+        any
+    }
+
+    /// registers an animation that ticks the spinners at the interval of the fastest one, and
+    /// removes it once there are no spinners left. call it after spawning a tree.
+    pub fn schedule_spinners(&mut self, now: Instant) {
+        let fastest = self
+            .query_mut::<&Spinner>()
+            .into_iter()
+            .map(|spinner| spinner.interval)
+            .filter(|interval| !interval.is_zero())
+            .min();
+        let current = self.world.remove_resource::<SpinnerSchedule>().ok();
+        if let Some(current) = current {
+            if Some(current.interval) == fastest {
+                self.world.insert_resource(current);
+                return;
+            }
+            self.deregister_animation(current.handle);
+        }
+        let Some(interval) = fastest else {
+            return;
+        };
+        let handle = self.register_animation(interval, now, |ctx, tick| {
+            let frames = |ctx: &mut ElementCtx| {
+                ctx.query_mut::<&Spinner>()
+                    .into_iter()
+                    .map(|spinner| spinner.current)
+                    .collect::<Vec<_>>()
+            };
+            let before = frames(ctx);
+            ctx.tick_spinners(tick.now);
+            frames(ctx) != before
+        });
+        self.world
+            .insert_resource(SpinnerSchedule { handle, interval });
+    }
+}
+
+/// resource with the animation registered by [`ElementCtx::schedule_spinners`].
+struct SpinnerSchedule {
+    handle: AnimationHandle,
+    interval: Duration,
+}
@@ -0,0 +1,64 @@
+//! tree builders shared by the unit tests, benchmarks and the `stress` example.
+//!
+//! only available with the `testing` feature.
+
+use ratatui::{
+    layout::Direction,
+    text::Text,
+    widgets::{Block, Borders, Paragraph},
+};
+
+use crate::{
+    layout::{Height, Width},
+    ui::{View, ui},
+};
+
+/// a chain of `depth` nested elements, each with a single child.
+pub fn deep_chain(depth: usize) -> View {
+    let mut view = ui(Text::raw("leaf")).done();
+    for _ in 1..depth {
+        view = ui(Block::new()).child(view).done();
+    }
+    view
+}
+
+/// one parent with `count` text children.
+pub fn wide_fanout(count: usize) -> View {
+    ui(Block::new())
+        .with((Width::grow(), Height::grow()))
+        .children((0..count).map(|idx| ui(Text::raw(format!("{idx}"))).done()))
+        .done()
+}
+
+/// a mixed tree resembling a real application: a header, a sidebar with `rows` entries and
+/// a grid of `rows * cols` bordered panels. spawns `2 * rows * (cols + 1) + 6` elements,
+/// `dashboard(50, 50)` about 5.1k.
+pub fn dashboard(rows: usize, cols: usize) -> View {
+    let header = ui(Block::bordered().title_top("dashboard"))
+        .with((Width::grow(), Height::fixed(3)))
+        .child(ui(Text::raw("status: ok")));
+    let sidebar = ui(Block::new().borders(Borders::RIGHT))
+        .with((Width::fixed(20), Height::grow()))
+        .children((0..rows).map(|idx| ui(Text::raw(format!("item {idx:03}"))).done()));
+    let grid = ui(Block::new())
+        .with((Width::grow(), Height::grow()))
+        .children((0..rows).map(move |row| {
+            ui(Block::new())
+                .with((Width::grow(), Height::grow(), Direction::Horizontal))
+                .children((0..cols).map(move |col| {
+                    ui(Block::bordered())
+                        .with((Width::grow(), Height::grow()))
+                        .child(ui(Paragraph::new(format!("{row}:{col}"))))
+                        .done()
+                }))
+                .done()
+        }));
+    let body = ui(Block::new())
+        .with((Width::grow(), Height::grow(), Direction::Horizontal))
+        .children((sidebar, grid));
+
+    ui(Block::new())
+        .with((Width::grow(), Height::grow()))
+        .children((header, body))
+        .done()
+}
@@ -0,0 +1,244 @@
+//! # Text hits
+//!
+//! maps a cell of the terminal back to the character of the text drawn there, for links inside
+//! wrapped text like a file path in a log line.
+//!
+//! ratatui doesn't give back the text of a [`Paragraph`], draw text that is hit tested with a
+//! [`SourceParagraph`] instead, which keeps it. [`text_hit`] also works on [`Text`] elements.
+//!
+//! # Example
+//!
+//! ```
+//! # use mana_tui_elemental::prelude::*;
+//! # use mana_tui_elemental::text_hit::{SourceParagraph, text_hit};
+//! # use ratatui::layout::{Position, Rect};
+//! let mut ctx = ElementCtx::new();
+//! let root = ctx.spawn_ui(
+//!     ui(SourceParagraph::new("see src/main.rs").wrap(Wrap { trim: true }))
+//!         .with((Width::fixed(8), Height::fixed(3))),
+//! );
+//! ctx.calculate_layout(root, Rect::new(0, 0, 8, 3)).unwrap();
+//!
+//! // the second row starts with the path.
+//! let hit = text_hit(root, &ctx, Position::new(0, 1)).unwrap();
+//! assert_eq!(hit.byte_offset, 4);
+//! ```
+
+use ratatui::{
+    buffer::{Buffer, Cell},
+    layout::{Position, Rect},
+    style::{Color, Style, Styled},
+    text::{Line, Span, Text},
+    widgets::{Block, Paragraph, Widget, Wrap},
+};
+
+use crate::layout::{Element, ElementCtx, Props};
+
+/// where a cell falls in the source text, see [`text_hit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextHit {
+    /// the line of the text, before wrapping.
+    pub line: usize,
+    /// the column of the character in its line, in cells.
+    pub column: usize,
+    /// the span of the line the character is in.
+    pub span_index: usize,
+    /// the byte offset of the character in the content of its span.
+    pub byte_offset: usize,
+}
+
+/// a [`Paragraph`] that keeps its text, so [`text_hit`] can map cells back to it.
+#[derive(Debug, Clone, Default, PartialEq, Hash)]
+pub struct SourceParagraph {
+    text: Text<'static>,
+    block: Option<Block<'static>>,
+    wrap: Option<Wrap>,
+    scroll: (u16, u16),
+    style: Style,
+}
+
+impl SourceParagraph {
+    /// a paragraph of `text`, without wrapping.
+    #[must_use]
+    pub fn new(text: impl Into<Text<'static>>) -> Self {
+        Self {
+            text: text.into(),
+            ..Self::default()
+        }
+    }
+
+    /// see [`Paragraph::block`].
+    #[must_use]
+    pub fn block(mut self, block: Block<'static>) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    /// see [`Paragraph::wrap`].
+    #[must_use]
+    pub fn wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap = Some(wrap);
+        self
+    }
+
+    /// see [`Paragraph::scroll`], the offset is `(y, x)`.
+    #[must_use]
+    pub fn scroll(mut self, offset: (u16, u16)) -> Self {
+        self.scroll = offset;
+        self
+    }
+
+    /// the text of the paragraph.
+    #[must_use]
+    pub fn text(&self) -> &Text<'static> {
+        &self.text
+    }
+
+    /// the size of the paragraph without wrapping, including its block.
+    pub(crate) fn natural_size(&self) -> (usize, usize) {
+        let (width, height) = (self.text.width(), self.text.height());
+        let Some(block) = &self.block else {
+            return (width, height);
+        };
+        let outer = Rect::new(0, 0, 100, 100);
+        let inner = block.inner(outer);
+        (
+            width + usize::from(outer.width - inner.width),
+            height + usize::from(outer.height - inner.height),
+        )
+    }
+
+    /// the part of `area` inside the block, where the text is drawn.
+    pub(crate) fn inner(&self, area: Rect) -> Rect {
+        self.block.as_ref().map_or(area, |block| block.inner(area))
+    }
+
+    /// the paragraph drawn for the text.
+    #[must_use]
+    pub fn paragraph(&self) -> Paragraph<'static> {
+        self.paragraph_of(self.text.clone()).style(self.style)
+    }
+
+    /// `text` with the settings of the paragraph, without the block.
+    fn paragraph_of(&self, text: Text<'static>) -> Paragraph<'static> {
+        let paragraph = Paragraph::new(text).scroll(self.scroll);
+        let paragraph = match self.wrap {
+            Some(wrap) => paragraph.wrap(wrap),
+            None => paragraph,
+        };
+        match &self.block {
+            Some(block) => paragraph.block(block.clone()),
+            None => paragraph,
+        }
+    }
+
+    /// a copy of the paragraph with every grapheme colored by its index in the returned hits.
+    /// wrapping doesn't depend on styles, so the copy is laid out like the paragraph.
+    fn probe(&self) -> (Paragraph<'static>, Vec<TextHit>) {
+        let mut hits = Vec::new();
+        let mut lines = Vec::with_capacity(self.text.lines.len());
+        for (line_index, line) in self.text.lines.iter().enumerate() {
+            let mut spans = Vec::new();
+            let mut column = 0;
+            for (span_index, span) in line.spans.iter().enumerate() {
+                for grapheme in span.styled_graphemes(Style::default()) {
+                    let style = tag(hits.len()).map_or_else(Style::new, |tag| Style::new().fg(tag));
+                    // graphemes are slices of the content.
+                    let byte_offset =
+                        grapheme.symbol.as_ptr().addr() - span.content.as_ptr().addr();
+                    hits.push(TextHit {
+                        line: line_index,
+                        column,
+                        span_index,
+                        byte_offset,
+                    });
+                    column += Span::raw(grapheme.symbol).width();
+                    spans.push(Span::styled(grapheme.symbol.to_string(), style));
+                }
+            }
+            let mut probe = Line::from(spans);
+            probe.alignment = line.alignment;
+            lines.push(probe);
+        }
+        let mut text = Text::from(lines);
+        text.alignment = self.text.alignment;
+        // the block is left out, its styles could look like tags.
+        let mut probe = self.clone();
+        probe.block = None;
+        (probe.paragraph_of(text), hits)
+    }
+}
+
+impl Widget for SourceParagraph {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.paragraph().render(area, buf);
+    }
+}
+
+impl Styled for SourceParagraph {
+    type Item = Self;
+
+    fn style(&self) -> Style {
+        self.style
+    }
+
+    fn set_style<S: Into<Style>>(mut self, style: S) -> Self {
+        self.style = style.into();
+        self
+    }
+}
+
+/// the color marking the grapheme at `index` in a probe, if there are colors left.
+fn tag(index: usize) -> Option<Color> {
+    let index = u32::try_from(index).ok().filter(|index| *index < 1 << 24)?;
+    let [_, r, g, b] = index.to_be_bytes();
+    Some(Color::Rgb(r, g, b))
+}
+
+/// the index of the grapheme marked by the color of `cell`.
+fn untag(cell: &Cell) -> Option<usize> {
+    match cell.fg {
+        Color::Rgb(r, g, b) => Some(u32::from_be_bytes([0, r, g, b]) as usize),
+        _ => None,
+    }
+}
+
+/// the character `element` draws at `position`, in the coordinates of the layout. `None` outside
+/// of the text, like on the borders and padding of the block, or for elements that draw neither
+/// a [`SourceParagraph`] nor a [`Text`].
+///
+/// the paragraph is drawn again into a scratch buffer to find the character, call this for
+/// clicks rather than every frame.
+#[must_use]
+pub fn text_hit(element: Element, ctx: &ElementCtx, position: Position) -> Option<TextHit> {
+    let props = *ctx.get::<&Props>(element).ok()?;
+    let area = Rect::new(
+        props.position.x,
+        props.position.y,
+        props.size.x,
+        props.size.y,
+    );
+    let source = match ctx.get::<&SourceParagraph>(element) {
+        Ok(source) => source.clone(),
+        Err(_) => SourceParagraph::new(ctx.get::<&Text<'static>>(element).ok()?.clone()),
+    };
+    let inner = source.inner(area);
+    if !inner.contains(position) {
+        return None;
+    }
+    let (probe, hits) = source.probe();
+    let mut buf = Buffer::empty(inner);
+    probe.render(inner, &mut buf);
+    // the cells covered by a wide character are blank, it starts further left.
+    for x in (inner.x..=position.x).rev() {
+        let cell = &buf[Position::new(x, position.y)];
+        let Some(index) = untag(cell) else {
+            continue;
+        };
+        let width = Span::raw(cell.symbol()).width() as u16;
+        return (x.saturating_add(width) > position.x)
+            .then(|| hits.get(index).copied())
+            .flatten();
+    }
+    None
+}
@@ -0,0 +1,83 @@
+//! # Theme
+//!
+//! styles for the elements that don't set their own, so a whole tree can be restyled in one
+//! place.
+//!
+//! store [`DefaultStyles`] as a resource, with [`ElementCtx::with_default_styles`] or
+//! [`Resources::insert_or_update_resource`]. every [`Block`] and [`Text`] spawned afterwards with
+//! the default style of its widget gets the one of the theme, styles set on the widget win.
+//!
+//! # Example
+//!
+//! ```
+//! # use mana_tui_elemental::prelude::*;
+//! # use mana_tui_elemental::theme::DefaultStyles;
+//! # use ratatui::style::{Color, Style};
+//! let mut ctx = ElementCtx::new().with_default_styles(DefaultStyles {
+//!     block_border_type: BorderType::Rounded,
+//!     block_style: Style::new().fg(Color::Blue),
+//!     ..DefaultStyles::default()
+//! });
+//! // rounded and blue.
+//! ctx.spawn_ui(ui(Block::bordered()));
+//! // blue, but not rounded.
+//! ctx.spawn_ui(ui(Block::bordered().border_type(BorderType::Double)));
+//! ```
+
+use mana_tui_utils::resource::Resources;
+use ratatui::{
+    style::Style,
+    text::Text,
+    widgets::{Block, BorderType},
+};
+
+use crate::layout::ElementCtx;
+
+/// the styles of the elements that don't set their own, see the [module docs](self).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DefaultStyles {
+    /// the border type of blocks that keep the default [`BorderType::Plain`].
+    pub block_border_type: BorderType,
+    /// the style of blocks without a style.
+    pub block_style: Style,
+    /// the style of text without a style.
+    pub text_style: Style,
+    /// the style of focused elements without a focus style of their own, applied by the
+    /// runtimes.
+    pub focus_style: Style,
+    /// the style of [`OverflowIndicator`](crate::layout::OverflowIndicator)s.
+    pub overflow_style: Style,
+}
+
+impl ElementCtx {
+    /// stores `styles` as the theme of the context.
+    #[must_use]
+    pub fn with_default_styles(mut self, styles: DefaultStyles) -> Self {
+        self.world.insert_or_update_resource(styles);
+        self
+    }
+
+    /// gives the blocks and text without a style of their own the [`DefaultStyles`].
+    pub(crate) fn apply_default_styles(&mut self) {
+        let Ok(styles) = self
+            .world
+            .get_resource::<&DefaultStyles>()
+            .map(|styles| *styles)
+        else {
+            return;
+        };
+        for block in self.world.query_mut::<&mut Block<'static>>() {
+            if block.clone().border_type(BorderType::Plain) == *block {
+                *block = block.clone().border_type(styles.block_border_type);
+            }
+            if block.clone().style(Style::default()) == *block {
+                *block = block.clone().style(styles.block_style);
+            }
+        }
+        for text in self.world.query_mut::<&mut Text<'static>>() {
+            if text.style == Style::default() {
+                text.style = styles.text_style;
+            }
+        }
+    }
+}
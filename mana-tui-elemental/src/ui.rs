@@ -17,24 +17,49 @@
 //! ctx.spawn_ui(root);
 //!
 //! ```
+//!
+//! # Views from queries
+//!
+//! hecs doesn't keep entities in spawn order: adding a component to one of them moves it to the
+//! end of the query, so a list built from `ctx.query::<&Todo>()` reorders itself. spawn the
+//! entities with [`OrderedExt::spawn_ordered`][crate::order::OrderedExt::spawn_ordered] and
+//! build the list from [`OrderedExt::query_ordered`][crate::order::OrderedExt::query_ordered]
+//! instead.
 
-use std::{any::TypeId, borrow::Cow, collections::VecDeque, sync::Arc};
+use std::{
+    any::{Any, TypeId},
+    borrow::Cow,
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
 
 use glam::U16Vec2;
-use hecs::{CommandBuffer, DynamicBundle, Entity, EntityBuilder, Or, Query, World};
+use hecs::{CommandBuffer, DynamicBundle, Entity, EntityBuilder, Or, Query, Ref, RefMut, World};
+use mana_tui_utils::resource::Resources;
 use ratatui::{
     buffer::Buffer,
+    crossterm::event::KeyCode,
     layout::{Direction, Rect},
     style::Style,
     text::{Line, Span, Text},
-    widgets::{Block, Padding, Paragraph},
+    widgets::{Block, Clear, Padding, Paragraph},
 };
 use tracing::{Level, enabled, instrument};
 
+use crate::animation::{Animation, AnimationComponent};
+use crate::blit::Blit;
+use crate::inspect::ElId;
 use crate::layout::{
-    Center, Children, CrossJustify, ElWidget, Element, ElementCtx, Gap, Height, MainJustify,
-    ManaComponent, Props, Size, TuiElMarker, Width,
+    Center, Children, ClearBackground, CrossJustify, DynMarker, DynWidget, ElWidget, Element,
+    ElementCtx, FitText, Gap, Height, HeightFromWidth, HintPosition, KeyboardShortcutHint,
+    LayoutVisits, MainJustify, ManaComponent, Overflow, PreferredSize, Props, ScrollView,
+    ScrollViewState, Shadow, Size, SplitPane, TitleFit, TuiElMarker, UnstyledMarker, Width,
 };
+use crate::progress::ProgressBar;
+use crate::separator::Separator;
+use crate::spacer::Spacer;
+use crate::spinner::Spinner;
+use crate::text_hit::SourceParagraph;
 
 /// create a ui element.
 ///
@@ -117,6 +142,221 @@ pub fn ui<M>(w: impl IntoView<M>) -> UiBuilder<ui_builder::Empty> {
     __ui_internal(w.into_view())
 }
 
+/// create a leaf element that draws directly into the buffer. the closure receives the final
+/// laid out area of the element every time it is rendered.
+///
+/// the element has no size of its own: use [`Width`]/[`Height`] or a
+/// [`PreferredSize`][crate::layout::PreferredSize] to give it one.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// # use mana_tui_elemental::layout::PreferredSize;
+/// # use ratatui::style::Color;
+/// let gradient = raw(|area, buf| {
+///     for x in area.left()..area.right() {
+///         let shade = (x - area.left()) as u8 * 16;
+///         for y in area.top()..area.bottom() {
+///             buf[(x, y)].set_bg(Color::Rgb(shade, shade, shade));
+///         }
+///     }
+/// })
+/// .with((PreferredSize(glam::u16vec2(16, 2)),));
+/// ```
+pub fn raw(
+    draw: impl Fn(Rect, &mut Buffer) + Send + Sync + 'static,
+) -> UiBuilder<ui_builder::Empty> {
+    ui(RawDraw(Arc::new(draw)))
+}
+
+/// widget that draws with a closure, see [`raw`].
+#[derive(Clone)]
+pub struct RawDraw(pub Arc<dyn Fn(Rect, &mut Buffer) + Send + Sync>);
+
+impl std::fmt::Debug for RawDraw {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RawDraw").finish_non_exhaustive()
+    }
+}
+
+impl ElWidget<UnstyledMarker> for RawDraw {
+    fn render_element(&self, area: Rect, buf: &mut Buffer) {
+        (self.0)(area, buf);
+    }
+
+    fn set_style(&mut self, _: Style) {}
+
+    fn get_style(&self) -> Style {
+        Style::default()
+    }
+
+    /// the closure can draw anything, so it is never skipped.
+    fn fingerprint(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// a vertical list with one child per item. every child is tagged with an [`ElId`] made from
+/// its key, so callbacks can capture the key instead of the index and tree dumps follow items
+/// across reorders. the state kept with [`ElementCtx::retain_keyed`] follows the key too.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// let todos = vec![(3, "buy milk"), (7, "water plants")];
+/// keyed_list(todos, |id, todo| ui(Text::raw(format!("{id}: {todo}"))).done());
+/// ```
+pub fn keyed_list<K: ToString, T>(
+    items: impl IntoIterator<Item = (K, T)>,
+    child: impl Fn(&K, T) -> View,
+) -> View {
+    let children = items
+        .into_iter()
+        .map(|(key, item)| {
+            let mut view = child(&key, item);
+            view.add(ElId::new(key.to_string()));
+            view
+        })
+        .collect::<Vec<_>>();
+    ui(Block::new())
+        .with((Direction::Vertical,))
+        .children(children)
+        .done()
+}
+
+/// calls `f` with `view` and every child view added to it, parents first. the views of
+/// [subviews](Subview) are built when they are spawned, so they aren't visited.
+pub fn visit_views(view: &mut View, f: &mut impl FnMut(&mut View)) {
+    f(view);
+    if let Some(children) = view.get_mut::<&mut ChildrenBuilders>() {
+        for child in &mut children.0 {
+            visit_views(child, f);
+        }
+    }
+}
+
+type RetainedState = Box<dyn Any + Send + Sync>;
+
+/// resource with the components kept by [`ElementCtx::retain_keyed`] while their elements are
+/// respawned.
+#[derive(Default)]
+struct KeyedState {
+    kinds: Vec<RetainedKind>,
+    saved: HashMap<(ElId, TypeId), RetainedState>,
+}
+
+#[derive(Clone, Copy)]
+struct RetainedKind {
+    type_id: TypeId,
+    take: fn(&mut World, Element) -> Option<RetainedState>,
+    restore: fn(&mut World, Element, RetainedState),
+}
+
+impl RetainedKind {
+    fn of<T: hecs::Component>() -> Self {
+        Self {
+            type_id: TypeId::of::<T>(),
+            take: |world, element| {
+                let state = world.remove_one::<T>(element).ok()?;
+                Some(Box::new(state))
+            },
+            restore: |world, element, state| {
+                if let Ok(state) = state.downcast::<T>() {
+                    _ = world.insert_one(element, *state);
+                }
+            },
+        }
+    }
+}
+
+/// an empty element that fills the width of its parent and keeps its height at a fixed ratio of
+/// its width, through [`HeightFromWidth`]. children are laid out inside it like in any element.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// // 16:9 video placeholder
+/// AspectRatioBox::new(16, 9).child(ui(Text::raw("loading...")));
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AspectRatioBox;
+
+impl AspectRatioBox {
+    /// an element `height / width` times as high as it is wide.
+    #[must_use = "You can use the builder with ElementCtx::spawn_ui"]
+    pub fn new(width: u16, height: u16) -> UiBuilder<ui_builder::Empty> {
+        let mut builder = ui(AspectRatioBox);
+        builder.view.add_bundle((
+            Width::grow(),
+            HeightFromWidth {
+                ratio: f32::from(height) / f32::from(width.max(1)),
+            },
+        ));
+        builder
+    }
+}
+
+impl ElWidget<UnstyledMarker> for AspectRatioBox {
+    fn render_element(&self, _: Rect, _: &mut Buffer) {}
+
+    fn set_style(&mut self, _: Style) {}
+
+    fn get_style(&self) -> Style {
+        Style::default()
+    }
+    fn fingerprint(&self) -> Option<u64> {
+        Some(0)
+    }
+}
+
+/// a reusable component with typed props. derive the builder setters with
+/// `#[derive(Subview)]` from `mana-tui-macros` to use the struct as a tag in `ui!`.
+///
+/// the view is built when the subview is spawned, so it can read the context it is spawned
+/// into, like the theme. see [`lazy_subview`].
+///
+/// also see the `#[subview]` attribute for function subviews.
+pub trait Subview {
+    /// builds the view from the props.
+    fn view(&self, ctx: &mut ElementCtx) -> View;
+}
+
+/// the [`Subview`] of a view made with [`lazy_subview`], until it is spawned.
+struct LazySubview(Option<Box<dyn FnOnce(&mut ElementCtx) -> View + Send + Sync>>);
+
+/// a view that builds the view of `subview` once it is spawned. components and children set on
+/// the returned view replace the ones of the root of the subview.
+pub fn lazy_subview<S>(subview: S) -> View
+where
+    S: Subview + Send + Sync + 'static,
+{
+    let mut view = View::new();
+    view.add(LazySubview(Some(Box::new(move |ctx: &mut ElementCtx| {
+        subview.view(ctx)
+    }))));
+    view
+}
+
+/// replaces the [`LazySubview`] placeholder `view` with the view of its subview, keeping the
+/// components set on the placeholder. returns whether `view` was a placeholder, the spawned
+/// element then has to lose its empty [`LazySubview`].
+fn expand_subview(ctx: &mut ElementCtx, view: &mut View) -> bool {
+    let Some(build) = view
+        .get_mut::<&mut LazySubview>()
+        .and_then(|lazy| lazy.0.take())
+    else {
+        return false;
+    };
+    let mut expanded = build(ctx);
+    expand_subview(ctx, &mut expanded);
+    expanded.add_bundle(view.build());
+    *view = expanded;
+    true
+}
+
 /// trait that marks a type can be converted into a [`View`].
 ///
 /// automatically implementeed for widgets.
@@ -131,45 +371,52 @@ where
 {
     fn into_view(self) -> View {
         let mut builder = View::new();
-        fn render_system<M, W: ElWidget<M>>(
-            ctx: &ElementCtx,
-            entity: hecs::Entity,
-            area: Rect,
-            buf: &mut Buffer,
-        ) {
-            if let Ok(widget) = ctx.world.get::<&W>(entity) {
-                widget.render_element(area, buf);
-            }
+        builder.add(self);
+        builder.add_bundle((TuiElMarker, widget_props::<M, W>()));
+        builder
+    }
+}
+
+/// the [`Props`] of an element drawing a `W`, before the layout.
+fn widget_props<M, W: ElWidget<M>>() -> Props {
+    fn render_system<M, W: ElWidget<M>>(
+        ctx: &ElementCtx,
+        entity: hecs::Entity,
+        area: Rect,
+        buf: &mut Buffer,
+    ) {
+        if let Ok(widget) = ctx.world.get::<&W>(entity) {
+            widget.render_element(area, buf);
         }
-        fn set_style_system<M, W: ElWidget<M>>(
-            ctx: &mut World,
-            entity: hecs::Entity,
-            style: Style,
-        ) {
-            if let Ok(mut widget) = ctx.get::<&mut W>(entity) {
-                widget.set_style(style);
-            }
+    }
+    fn set_style_system<M, W: ElWidget<M>>(ctx: &mut World, entity: hecs::Entity, style: Style) {
+        if let Ok(mut widget) = ctx.get::<&mut W>(entity) {
+            widget.set_style(style);
         }
-        fn get_style_system<M, W: ElWidget<M>>(ctx: &World, entity: hecs::Entity) -> Option<Style> {
-            if let Ok(mut widget) = ctx.get::<&mut W>(entity) {
-                Some(widget.get_style())
-            } else {
-                None
-            }
+    }
+    fn get_style_system<M, W: ElWidget<M>>(ctx: &World, entity: hecs::Entity) -> Option<Style> {
+        if let Ok(mut widget) = ctx.get::<&mut W>(entity) {
+            Some(widget.get_style())
+        } else {
+            None
         }
-        builder.add(self);
-        builder.add_bundle((
-            TuiElMarker,
-            Props {
-                typeid: TypeId::of::<W>(),
-                size: U16Vec2::default(),
-                position: U16Vec2::default(),
-                render: render_system::<M, W>,
-                set_style: set_style_system::<M, W>,
-                get_style: get_style_system::<M, W>,
-            },
-        ));
-        builder
+    }
+    fn fingerprint_system<M, W: ElWidget<M>>(ctx: &World, entity: hecs::Entity) -> Option<u64> {
+        ctx.get::<&W>(entity).ok()?.fingerprint()
+    }
+    fn remove_system<M, W: ElWidget<M>>(ctx: &mut World, entity: hecs::Entity) {
+        _ = ctx.remove_one::<W>(entity);
+    }
+    Props {
+        typeid: TypeId::of::<W>(),
+        type_name: std::any::type_name::<W>(),
+        size: U16Vec2::default(),
+        position: U16Vec2::default(),
+        render: render_system::<M, W>,
+        set_style: set_style_system::<M, W>,
+        get_style: get_style_system::<M, W>,
+        fingerprint: fingerprint_system::<M, W>,
+        remove: remove_system::<M, W>,
     }
 }
 
@@ -257,6 +504,110 @@ where
         self.view.add_bundle(bundle);
         self
     }
+
+    /// adds the bundle like [`UiBuilder::with`], only if `condition` is true.
+    ///
+    /// # Example
+    /// ```
+    /// # use mana_tui_elemental::prelude::*;
+    /// # use ratatui::style::{Color, Style};
+    /// let selected = true;
+    /// ui(Block::bordered()).with_if(selected, (Shadow::new(Style::new().bg(Color::DarkGray)),));
+    /// ```
+    #[must_use = "You can use the builder with ElementCtx::spawn_ui"]
+    pub fn with_if(mut self, condition: bool, bundle: impl DynamicBundle) -> Self {
+        if condition {
+            self.view.add_bundle(bundle);
+        }
+        self
+    }
+
+    /// adds the bundle like [`UiBuilder::with`] if there is one.
+    ///
+    /// # Example
+    /// ```
+    /// # use mana_tui_elemental::prelude::*;
+    /// let width: Option<u16> = None;
+    /// ui(Block::bordered()).with_option(width.map(|width| (Width::fixed(width),)));
+    /// ```
+    #[must_use = "You can use the builder with ElementCtx::spawn_ui"]
+    pub fn with_option(mut self, bundle: Option<impl DynamicBundle>) -> Self {
+        if let Some(bundle) = bundle {
+            self.view.add_bundle(bundle);
+        }
+        self
+    }
+
+    /// clears the area behind the element before rendering it. use this for popups and other
+    /// elements that are drawn on top of existing content.
+    ///
+    /// a [`Clear`] child can't do this: children are drawn after their parent, so it would wipe
+    /// the element instead of what is behind it. use a `Clear` element to blank an area of its
+    /// own, like the backdrop of a modal.
+    ///
+    /// # Example
+    /// ```
+    /// # use mana_tui_elemental::prelude::*;
+    ///
+    /// ui(Block::bordered())
+    ///     .with((Width::fixed(20), Height::fixed(5)))
+    ///     .clear_background();
+    /// ```
+    #[must_use = "You can use the builder with ElementCtx::spawn_ui"]
+    pub fn clear_background(mut self) -> Self {
+        self.view.add(ClearBackground);
+        self
+    }
+
+    /// draws a [`Shadow`] of `style` behind the element, one cell right and down of it. insert a
+    /// [`Shadow`] with [`UiBuilder::with`] to pick another offset.
+    ///
+    /// # Example
+    /// ```
+    /// # use mana_tui_elemental::prelude::*;
+    /// # use ratatui::style::{Color, Style};
+    ///
+    /// ui(Block::bordered())
+    ///     .with((Width::fixed(20), Height::fixed(5)))
+    ///     .shadow(Style::new().bg(Color::DarkGray));
+    /// ```
+    #[must_use = "You can use the builder with ElementCtx::spawn_ui"]
+    pub fn shadow(mut self, style: Style) -> Self {
+        self.view.add(Shadow::new(style));
+        self
+    }
+
+    /// draws a [`KeyboardShortcutHint`] badge for `key` over a corner of the element.
+    ///
+    /// # Example
+    /// ```
+    /// # use mana_tui_elemental::prelude::*;
+    /// # use ratatui::crossterm::event::KeyCode;
+    /// ui(Block::bordered().title("quit"))
+    ///     .with((Width::fixed(12), Height::fixed(3)))
+    ///     .shortcut_hint(KeyCode::Char('q'), HintPosition::TopRight);
+    /// ```
+    #[must_use = "You can use the builder with ElementCtx::spawn_ui"]
+    pub fn shortcut_hint(mut self, key: KeyCode, position: HintPosition) -> Self {
+        self.view.add(KeyboardShortcutHint::new(key, position));
+        self
+    }
+
+    /// attaches an [`Animation`] to the element. the runtime advances it every frame until it
+    /// completes, see [`ElementCtx::tick_animations`].
+    ///
+    /// # Example
+    /// ```
+    /// # use std::time::Duration;
+    /// # use mana_tui_elemental::prelude::*;
+    /// ui(Block::bordered())
+    ///     .animate(FadeIn::new(Duration::from_millis(200)));
+    /// ```
+    #[must_use = "You can use the builder with ElementCtx::spawn_ui"]
+    pub fn animate<A: Animation>(mut self, animation: A) -> Self {
+        self.view.add(AnimationComponent(Box::new(animation)));
+        self
+    }
 }
 
 impl<S> From<UiBuilder<S>> for EntityBuilder
@@ -268,6 +619,76 @@ where
     }
 }
 
+impl<S> UiBuilder<S>
+where
+    S: ui_builder::State,
+{
+    /// only adds the element to the tree if `condition` returns true when the tree is built.
+    /// unlike wrapping the child in an [`Option`], the element can be placed in a tuple of
+    /// children next to unconditional ones.
+    ///
+    /// # Example
+    /// ```
+    /// # use mana_tui_elemental::prelude::*;
+    /// let show_hint = false;
+    /// ui(Block::new()).children((
+    ///     ui(Text::raw("always shown")),
+    ///     ui(Text::raw("hint")).conditional(|| show_hint),
+    /// ));
+    /// ```
+    #[must_use = "You can use the builder with ElementCtx::spawn_ui"]
+    pub fn conditional<F: Fn() -> bool>(self, condition: F) -> ConditionalBuilder<S, F> {
+        ConditionalBuilder {
+            builder: self,
+            condition,
+        }
+    }
+}
+
+/// an element that is only added to the tree if its condition holds. created with
+/// [`UiBuilder::conditional`].
+pub struct ConditionalBuilder<S: ui_builder::State, F> {
+    builder: UiBuilder<S>,
+    condition: F,
+}
+
+impl<S, F> ConditionalBuilder<S, F>
+where
+    S: ui_builder::IsComplete,
+    F: Fn() -> bool,
+{
+    /// finishes the element. if the condition is false, returns an empty builder that is
+    /// skipped when the children of an element are spawned.
+    #[must_use]
+    pub fn done(self) -> EntityBuilder {
+        if (self.condition)() {
+            self.builder.done()
+        } else {
+            EntityBuilder::new()
+        }
+    }
+}
+
+impl<S, F> From<ConditionalBuilder<S, F>> for EntityBuilder
+where
+    S: ui_builder::IsComplete,
+    F: Fn() -> bool,
+{
+    fn from(val: ConditionalBuilder<S, F>) -> Self {
+        val.done()
+    }
+}
+
+impl<S, F> IntoUiBuilderList<()> for ConditionalBuilder<S, F>
+where
+    S: ui_builder::IsComplete,
+    F: Fn() -> bool,
+{
+    fn into_list(self) -> impl Iterator<Item = EntityBuilder> {
+        (self.condition)().then(|| self.builder.done()).into_iter()
+    }
+}
+
 /// trait that marks a type can be converted into an iterator over [`hecs::EntityBuilder`].
 ///
 /// automatically implemented for N-tuples, vecs and arrays.
@@ -293,7 +714,7 @@ where
 impl IntoUiBuilderList<()> for &'static str {
     fn into_list(self) -> impl Iterator<Item = EntityBuilder> {
         [ui(Text::raw(self))
-            .with((Width::grow(), Height::grow()))
+            .with((Width::grow(), Height::fit(), FitText))
             .done()]
         .into_iter()
     }
@@ -302,7 +723,7 @@ impl IntoUiBuilderList<()> for &'static str {
 impl IntoUiBuilderList<()> for String {
     fn into_list(self) -> impl Iterator<Item = EntityBuilder> {
         [ui(Text::raw(self))
-            .with((Width::grow(), Height::grow()))
+            .with((Width::grow(), Height::fit(), FitText))
             .done()]
         .into_iter()
     }
@@ -311,7 +732,7 @@ impl IntoUiBuilderList<()> for String {
 impl<'a> IntoUiBuilderList<()> for Cow<'a, str> {
     fn into_list(self) -> impl Iterator<Item = EntityBuilder> {
         [ui(Text::raw(self.into_owned()))
-            .with((Width::grow(), Height::grow()))
+            .with((Width::grow(), Height::fit(), FitText))
             .done()]
         .into_iter()
     }
@@ -380,6 +801,137 @@ impl_into_ui_builder_list_for_tuples!(0 U0, 1 U1, 2 U2, 3 U3, 4 U4, 5 U5, 6 U6,
 
 pub(crate) struct ChildrenBuilders(pub(crate) Box<[EntityBuilder]>);
 
+#[derive(Query)]
+enum TextQuery<'a> {
+    Text(&'a Text<'a>),
+    Paragraph(&'a Paragraph<'a>),
+    SourceParagraph(&'a SourceParagraph),
+    Line(&'a Line<'a>),
+    Span(&'a Span<'a>),
+}
+
+impl TextQuery<'_> {
+    /// the size of the text, or `None` for paragraphs since they wrap.
+    fn content_size(&self) -> Option<(usize, usize)> {
+        match self {
+            TextQuery::Text(text) => Some((text.width(), text.height())),
+            TextQuery::Paragraph(_) | TextQuery::SourceParagraph(_) => None,
+            TextQuery::Line(line) => Some((line.width(), 1)),
+            TextQuery::Span(span) => Some((span.width(), 1)),
+        }
+    }
+}
+
+/// the size a widget gets on the axes the element has no [`Width`] or [`Height`] for.
+trait DefaultSize: hecs::Component {
+    fn default_size(&self) -> (Width, Height);
+}
+
+impl DefaultSize for ProgressBar {
+    fn default_size(&self) -> (Width, Height) {
+        (Width::grow(), Height::fixed(1))
+    }
+}
+
+impl DefaultSize for Separator {
+    fn default_size(&self) -> (Width, Height) {
+        match self.direction {
+            Direction::Horizontal => (Width::grow(), Height::fixed(1)),
+            Direction::Vertical => (Width::fixed(1), Height::grow()),
+        }
+    }
+}
+
+impl DefaultSize for Spacer {
+    fn default_size(&self) -> (Width, Height) {
+        self.sizes()
+    }
+}
+
+impl DefaultSize for Spinner {
+    fn default_size(&self) -> (Width, Height) {
+        (Width::fixed(self.width() as u16), Height::fixed(1))
+    }
+}
+
+impl DefaultSize for Blit {
+    fn default_size(&self) -> (Width, Height) {
+        let area = self.buffer.area;
+        (Width::fixed(area.width), Height::fixed(area.height))
+    }
+}
+
+impl DefaultSize for PreferredSize {
+    fn default_size(&self) -> (Width, Height) {
+        (Width::fixed(self.x), Height::fixed(self.y))
+    }
+}
+
+impl DefaultSize for SplitPane {
+    fn default_size(&self) -> (Width, Height) {
+        (Width::grow(), Height::grow())
+    }
+}
+
+/// `Clear` has no size of its own, it always covers its parent's area.
+impl DefaultSize for Clear {
+    fn default_size(&self) -> (Width, Height) {
+        (Width::grow(), Height::grow())
+    }
+}
+
+impl DefaultSize for DynWidget {
+    fn default_size(&self) -> (Width, Height) {
+        match self.content_size() {
+            Some((width, height)) => (Width::fixed(width as u16), Height::fixed(height as u16)),
+            None => (Width::fit(), Height::fit()),
+        }
+    }
+}
+
+/// marks the elements [`process_ui_system`] gave their default components, the next passes
+/// leave them alone.
+struct HasDefaults;
+
+/// the sizing components the element was given because it had none, see
+/// [`ElementCtx::swap_widget`].
+#[derive(Debug, Clone, Copy, Default)]
+struct DefaultedSize {
+    width: bool,
+    height: bool,
+    padding: bool,
+}
+
+impl DefaultedSize {
+    fn merge(&mut self, other: DefaultedSize) {
+        self.width |= other.width;
+        self.height |= other.height;
+        self.padding |= other.padding;
+    }
+}
+
+/// gives the elements holding a `T` their [`DefaultSize`] on the axes they have no size for.
+fn insert_default_size<T: DefaultSize>(
+    world: &mut World,
+    buffer: &mut CommandBuffer,
+    defaulted: &mut HashMap<Element, DefaultedSize>,
+) {
+    for (node, widget, width, height) in world
+        .query_mut::<(Entity, &T, Option<&Width>, Option<&Height>)>()
+        .without::<&HasDefaults>()
+    {
+        let (default_width, default_height) = widget.default_size();
+        if width.is_none() {
+            buffer.insert_one(node, default_width);
+            defaulted.entry(node).or_default().width = true;
+        }
+        if height.is_none() {
+            buffer.insert_one(node, default_height);
+            defaulted.entry(node).or_default().height = true;
+        }
+    }
+}
+
 #[instrument(skip(world))]
 fn process_ui_system(world: &mut ElementCtx) {
     let mut to_process: VecDeque<Element> = world
@@ -392,20 +944,33 @@ fn process_ui_system(world: &mut ElementCtx) {
         if let Ok(builders) = world.remove_one::<ChildrenBuilders>(node) {
             let mut builders = builders.0;
             let style = world.get::<&Style>(node).ok().map(|style| *style);
+            let direction = world
+                .get::<&Direction>(node)
+                .map_or(Direction::Vertical, |direction| *direction);
             // vvvvvvv you have caused me much pain
             // world.reserve_entities(builders.len() as u32);
             let children = builders
                 .iter_mut()
+                // empty builders come from a `conditional` element whose condition is false.
+                .filter(|builder| builder.component_types().next().is_some())
                 .map(|builder| {
+                    let lazy = expand_subview(world, builder);
                     let builder = builder.build();
                     let has_children = builder.has::<ChildrenBuilders>();
                     let entity = world.spawn(builder);
+                    if lazy {
+                        _ = world.remove_one::<LazySubview>(entity);
+                    }
                     if let Some(parent_style) = style {
                         let _ = world.insert_one(entity, parent_style);
                     }
                     if has_children {
                         to_process.push_back(entity);
                     }
+                    // a spacer grows along the main axis of its parent.
+                    if let Ok(mut spacer) = world.get::<&mut Spacer>(entity) {
+                        spacer.direction.get_or_insert(direction);
+                    }
                     entity
                 })
                 .collect();
@@ -415,60 +980,41 @@ fn process_ui_system(world: &mut ElementCtx) {
         }
     }
 
+    // the border type of the theme can change the padding of the block.
+    world.apply_default_styles();
+
     let mut buffer = CommandBuffer::new();
+    let mut defaulted = HashMap::<Element, DefaultedSize>::new();
 
-    for (node, block, padding) in world.query_mut::<(Entity, &mut Block, Option<&Padding>)>() {
+    for (node, block, padding, fit) in world
+        .query_mut::<(Entity, &Block, Option<&Padding>, Option<&TitleFit>)>()
+        .without::<&HasDefaults>()
+    {
         if padding.is_none() {
             tracing::trace!(?node, "processing default padding for block",);
-            let test_area = Rect {
-                x: 0,
-                y: 0,
-                width: 2,
-                height: 2,
-            };
-            let inner_area = block.inner(test_area);
-            let left = inner_area.left() - test_area.left();
-            let top = inner_area.top() - test_area.top();
-            let right = (test_area.width - inner_area.width).saturating_sub(1);
-            let bottom = (test_area.height - inner_area.height).saturating_sub(1);
-            buffer.insert_one(
-                node,
-                Padding {
-                    left,
-                    top,
-                    right,
-                    bottom,
-                },
-            );
+            buffer.insert_one(node, detect_block_padding(block));
+            defaulted.entry(node).or_default().padding = true;
+        }
+        if fit.is_none() {
+            buffer.insert_one(node, TitleFit::default());
         }
     }
 
-    #[derive(Query)]
-    enum TextQuery<'a> {
-        Text(&'a Text<'a>),
-        Paragraph(&'a Paragraph<'a>),
-        Line(&'a Line<'a>),
-        Span(&'a Span<'a>),
-    }
-
-    for (node, text_query, width, height) in
-        world.query_mut::<(Entity, TextQuery, Option<&Width>, Option<&Height>)>()
+    for (node, text_query, width, height) in world
+        .query_mut::<(Entity, TextQuery, Option<&Width>, Option<&Height>)>()
+        .without::<&HasDefaults>()
     {
         if enabled!(Level::TRACE) && (width.is_none() || height.is_none()) {
             tracing::trace!(?node, "processing default size for text",);
         }
-        let new_size = match text_query {
-            TextQuery::Text(text) => Some((text.width(), text.height())),
-            TextQuery::Paragraph(_) => None,
-            TextQuery::Line(line) => Some((line.width(), 1)),
-            TextQuery::Span(span) => Some((span.width(), 1)),
-        };
+        let new_size = text_query.content_size();
         if width.is_none() {
             if let Some((width, _)) = new_size {
                 buffer.insert_one(node, Width::fixed(width as u16));
             } else {
                 buffer.insert_one(node, Width::grow());
             }
+            defaulted.entry(node).or_default().width = true;
         }
         if height.is_none() {
             if let Some((_, height)) = new_size {
@@ -476,19 +1022,58 @@ fn process_ui_system(world: &mut ElementCtx) {
             } else {
                 buffer.insert_one(node, Height::grow());
             }
+            defaulted.entry(node).or_default().height = true;
+        }
+    }
+
+    insert_default_size::<ProgressBar>(world, &mut buffer, &mut defaulted);
+    insert_default_size::<Separator>(world, &mut buffer, &mut defaulted);
+    insert_default_size::<Spacer>(world, &mut buffer, &mut defaulted);
+    insert_default_size::<Spinner>(world, &mut buffer, &mut defaulted);
+    insert_default_size::<Blit>(world, &mut buffer, &mut defaulted);
+    insert_default_size::<PreferredSize>(world, &mut buffer, &mut defaulted);
+    insert_default_size::<SplitPane>(world, &mut buffer, &mut defaulted);
+    insert_default_size::<Clear>(world, &mut buffer, &mut defaulted);
+    insert_default_size::<DynWidget>(world, &mut buffer, &mut defaulted);
+
+    // the scroll view is sized after the content once the layout is known.
+    for (node, overflow, scrollview, state) in world.query_mut::<(
+        Entity,
+        &Overflow,
+        Option<&ScrollView>,
+        Option<&ScrollViewState>,
+    )>() {
+        if !overflow.scrolls() {
+            continue;
+        }
+        if scrollview.is_none() {
+            buffer.insert_one(node, ScrollView::default());
+        }
+        if state.is_none() {
+            buffer.insert_one(node, ScrollViewState::new());
         }
     }
 
+    // the divider of a split pane takes the place of the gap.
+    for (node, _) in world.query_mut::<(Entity, &SplitPane)>() {
+        buffer.insert_one(node, Gap(1));
+    }
+
     buffer.run_on(world);
 
-    let mut query = world.query::<(Entity, &TuiElMarker)>();
+    let mut query = world
+        .query::<(Entity, &TuiElMarker)>()
+        .without::<&HasDefaults>();
     for (node, _) in query.iter() {
+        buffer.insert_one(node, HasDefaults);
         let entity = world.entity(node).unwrap();
         if !entity.has::<Width>() {
             buffer.insert_one(node, Width(Size::Fit));
+            defaulted.entry(node).or_default().width = true;
         }
         if !entity.has::<Height>() {
             buffer.insert_one(node, Height(Size::Fit));
+            defaulted.entry(node).or_default().height = true;
         }
         if !entity.has::<Direction>() {
             buffer.insert_one(node, Direction::Vertical);
@@ -503,7 +1088,8 @@ fn process_ui_system(world: &mut ElementCtx) {
             buffer.insert_one(node, Gap::default());
         }
         if !entity.has::<Padding>() {
-            buffer.insert_one(node, Padding::default());
+            buffer.insert_one(node, Padding::ZERO);
+            defaulted.entry(node).or_default().padding = true;
         }
         if !entity.has::<Children>() {
             buffer.insert_one(node, Children::None);
@@ -513,10 +1099,83 @@ fn process_ui_system(world: &mut ElementCtx) {
 
     buffer.run_on(world);
 
+    for (node, sizes) in defaulted {
+        if let Ok(mut known) = world.get::<&mut DefaultedSize>(node) {
+            known.merge(sizes);
+        } else {
+            _ = world.insert_one(node, sizes);
+        }
+    }
+
     // post processing pass
     Center::run_postprocess(world, &mut buffer);
 }
 
+/// the space the borders, titles and padding of `block` take on each side. elements with a
+/// [`Block`] get it as their [`Padding`] unless they have one already, so children are laid out
+/// inside the borders.
+///
+/// ratatui doesn't expose the borders of a block, so this measures the inner area of a large
+/// rectangle instead.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// # use mana_tui_elemental::ui::detect_block_padding;
+/// let block = Block::bordered().padding(Padding::horizontal(1));
+/// assert_eq!(detect_block_padding(&block), Padding::new(2, 2, 1, 1));
+/// ```
+#[must_use]
+pub fn detect_block_padding(block: &Block) -> Padding {
+    let area = Rect::new(0, 0, 100, 100);
+    let inner = block.inner(area);
+    Padding {
+        left: inner.left() - area.left(),
+        right: area.right() - inner.right(),
+        top: inner.top() - area.top(),
+        bottom: area.bottom() - inner.bottom(),
+    }
+}
+
+/// called once when the element is spawned by [`ElementCtx::spawn_ui`], after the whole tree
+/// is spawned and has its default components. elements moved into a new tree without being
+/// despawned, like resolved suspense views, are not mounted again.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_elemental::prelude::*;
+/// ui(Block::new()).with((
+///     OnMount::new(|_, element| tracing::info!(?element, "start tailing the log")),
+///     OnUnmount::new(|_, element| tracing::info!(?element, "stop tailing the log")),
+/// ));
+/// ```
+#[derive(Clone, derive_more::Deref)]
+pub struct OnMount(pub Arc<dyn Fn(&mut World, Element) + Send + Sync + 'static>);
+
+impl OnMount {
+    /// creates a new mount hook.
+    pub fn new(f: impl Fn(&mut World, Element) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+}
+
+/// called when the element is despawned by [`ElementCtx::despawn_ui`], while it still exists.
+/// children are unmounted before their parents.
+#[derive(Clone, derive_more::Deref)]
+pub struct OnUnmount(pub Arc<dyn Fn(&mut World, Element) + Send + Sync + 'static>);
+
+impl OnUnmount {
+    /// creates a new unmount hook.
+    pub fn new(f: impl Fn(&mut World, Element) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(f))
+    }
+}
+
+/// marks an element whose [`OnMount`] already ran.
+struct Mounted;
+
 impl ElementCtx {
     /// spawns the root element along with its children.
     ///
@@ -526,21 +1185,311 @@ impl ElementCtx {
     /// also see [`ui`], [`Element`]
     pub fn spawn_ui(&mut self, ui: impl Into<EntityBuilder>) -> Element {
         let mut ui = ui.into();
-        let ui = ui.build();
-        let root = self.spawn(ui);
+        let lazy = expand_subview(self, &mut ui);
+        let root = self.spawn(ui.build());
+        if lazy {
+            _ = self.remove_one::<LazySubview>(root);
+        }
         process_ui_system(self);
+        self.restore_keyed(root);
+        self.adopt_layout(root);
+        self.mount(root);
+        // mount hooks can add elements, they get their defaults before the first layout.
+        self.ensure_defaults();
         root
     }
 
+    /// gives the elements that didn't go through [`ElementCtx::spawn_ui`], like children added
+    /// with [`hecs::World::spawn`], the default components the layout needs.
+    pub(crate) fn ensure_defaults(&mut self) {
+        let unprocessed = self
+            .world
+            .query::<&ChildrenBuilders>()
+            .iter()
+            .next()
+            .is_some()
+            || self
+                .world
+                .query::<&TuiElMarker>()
+                .without::<&HasDefaults>()
+                .iter()
+                .next()
+                .is_some();
+        if unprocessed {
+            tracing::debug!("applying the defaults of elements spawned outside of spawn_ui");
+            process_ui_system(self);
+        }
+    }
+
+    /// spawns `ui` like [`ElementCtx::spawn_ui`] and appends it to the children of `parent`, to
+    /// add an item to a tree that is already on screen. [`ElementCtx::despawn_ui`] removes one.
+    ///
+    /// the children are only cloned when another [`Children`] shares them.
+    pub fn spawn_ui_with_parent(
+        &mut self,
+        ui: impl Into<EntityBuilder>,
+        parent: Element,
+    ) -> Element {
+        let element = self.spawn_ui(ui);
+        if self.get::<&Children>(parent).is_err() {
+            _ = self.insert_one(parent, Children::None);
+        }
+        if let Ok(mut children) = self.get::<&mut Children>(parent) {
+            match &mut *children {
+                Children::Some(children) => Arc::make_mut(children).push(element),
+                children @ Children::None => *children = Children::Some(Arc::new(vec![element])),
+            }
+        }
+        element
+    }
+
+    /// replaces the widget of `element` with `widget`, which must have the type the element was
+    /// created with. text that was sized after its content is resized to the new content.
+    ///
+    /// returns whether the size of the element changed, the layout has to be calculated again
+    /// in that case.
+    pub fn replace_widget<M, W: ElWidget<M>>(&mut self, element: Element, widget: W) -> bool {
+        if !self.is_widget::<W>(element) {
+            tracing::warn!(
+                ?element,
+                widget = std::any::type_name::<W>(),
+                "can't replace a widget with one of another type"
+            );
+            return false;
+        }
+        let content_size = |world: &World| {
+            let mut query = world.query_one::<TextQuery>(element);
+            query.get().ok().and_then(|text| text.content_size())
+        };
+        let old = content_size(&self.world);
+        _ = self.world.insert_one(element, widget);
+        let (Some(old), Some(new)) = (old, content_size(&self.world)) else {
+            return false;
+        };
+
+        let mut resized = false;
+        if let Ok(mut width) = self.world.get::<&mut Width>(element)
+            && old.0 != new.0
+            && matches!(**width, Size::Fixed(size) if usize::from(size) == old.0)
+        {
+            *width = Width::fixed(new.0 as u16);
+            resized = true;
+        }
+        if let Ok(mut height) = self.world.get::<&mut Height>(element)
+            && old.1 != new.1
+            && matches!(**height, Size::Fixed(size) if usize::from(size) == old.1)
+        {
+            *height = Height::fixed(new.1 as u16);
+            resized = true;
+        }
+        resized
+    }
+
+    /// replaces the widget of `element` with `widget`, whatever type the element was created
+    /// with. the old widget is removed and the next frames draw the new one, the element keeps
+    /// its children and other components and is laid out again. the sizes and padding the
+    /// element got from the old widget are derived from the new one instead, the ones it was
+    /// spawned with are kept.
+    ///
+    /// returns `false` if `element` isn't an element.
+    pub fn swap_widget(&mut self, element: Element, widget: DynWidget) -> bool {
+        let Ok(old) = self.world.get::<&Props>(element).map(|props| *props) else {
+            return false;
+        };
+        if let Ok(defaulted) = self.world.remove_one::<DefaultedSize>(element) {
+            _ = self.world.remove_one::<HasDefaults>(element);
+            if defaulted.width {
+                _ = self.world.remove_one::<Width>(element);
+            }
+            if defaulted.height {
+                _ = self.world.remove_one::<Height>(element);
+            }
+            if defaulted.padding {
+                _ = self.world.remove_one::<Padding>(element);
+            }
+        }
+        (old.remove)(&mut self.world, element);
+        _ = self.world.insert_one(element, widget);
+        _ = self.world.insert_one(
+            element,
+            Props {
+                size: old.size,
+                position: old.position,
+                ..widget_props::<DynMarker, DynWidget>()
+            },
+        );
+        self.ensure_defaults();
+        self.invalidate_layout(element);
+        true
+    }
+
+    /// the widget of `element`, if it was created with a `W`. pass `_` for the marker:
+    /// `ctx.get_widget::<_, Block>(element)`.
+    ///
+    /// [`ElementCtx::replace_widget`] sets it.
+    pub fn get_widget<M, W: ElWidget<M>>(&self, element: Element) -> Option<Ref<'_, W>> {
+        if !self.is_widget::<W>(element) {
+            return None;
+        }
+        self.world.get::<&W>(element).ok()
+    }
+
+    /// the widget of `element` for changing it in place, if it was created with a `W`.
+    ///
+    /// unlike [`ElementCtx::replace_widget`] the size of the element is left alone.
+    pub fn get_widget_mut<M, W: ElWidget<M>>(&self, element: Element) -> Option<RefMut<'_, W>> {
+        if !self.is_widget::<W>(element) {
+            return None;
+        }
+        self.world.get::<&mut W>(element).ok()
+    }
+
+    /// whether `element` was created with a `W`, and not just given one as a component.
+    fn is_widget<W: 'static>(&self, element: Element) -> bool {
+        self.world
+            .get::<&Props>(element)
+            .is_ok_and(|props| props.typeid == TypeId::of::<W>())
+    }
+
+    /// lends `world` to `f` as an element context, for code that only has the world, like event
+    /// handlers spawning elements with [`ElementCtx::spawn_ui`].
+    pub fn with_world<R>(world: &mut World, f: impl FnOnce(&mut ElementCtx) -> R) -> R {
+        /// gives the world back even if `f` panics.
+        struct Lend<'w> {
+            world: &'w mut World,
+            ctx: ElementCtx,
+        }
+        impl Drop for Lend<'_> {
+            fn drop(&mut self) {
+                std::mem::swap(self.world, &mut self.ctx.world);
+            }
+        }
+
+        let mut lend = Lend {
+            ctx: ElementCtx {
+                world: std::mem::take(world),
+                profiling: false,
+                layout_visits: LayoutVisits::default(),
+            },
+            world,
+        };
+        f(&mut lend.ctx)
+    }
+
     /// despawns all entities starting from the root element
     pub fn despawn_ui(&mut self, root: Element) {
-        let children = self.query_one_mut::<&Children>(root).cloned();
-        _ = self.despawn(root);
+        let mut subtree = Vec::new();
+        self.collect_subtree(root, &mut subtree);
+        for element in &subtree {
+            let hook = self.get::<&OnUnmount>(*element).map(|hook| hook.0.clone());
+            if let Ok(hook) = hook {
+                hook(&mut self.world, *element);
+            }
+        }
+        self.take_keyed(&subtree);
+        self.retire_layout(root);
+        for element in subtree {
+            _ = self.despawn(element);
+        }
+    }
+
+    /// keeps the `T` of the elements with an [`ElId`] when they are despawned, and gives it to
+    /// the element with the same id in the next tree that is spawned, like the expanded state
+    /// of the items of a [`keyed_list`]. the kept components whose id isn't in that tree are
+    /// dropped. ids should be unique in the tree.
+    pub fn retain_keyed<T: hecs::Component>(&mut self) {
+        let kind = RetainedKind::of::<T>();
+        let mut keyed = self
+            .world
+            .get_or_insert_resource_with::<&mut KeyedState>(|_| KeyedState::default());
+        if !keyed
+            .kinds
+            .iter()
+            .any(|known| known.type_id == kind.type_id)
+        {
+            keyed.kinds.push(kind);
+        }
+    }
+
+    /// takes the components kept by [`ElementCtx::retain_keyed`] off `elements`.
+    fn take_keyed(&mut self, elements: &[Element]) {
+        let Ok(kinds) = self
+            .world
+            .get_resource::<&KeyedState>()
+            .map(|keyed| keyed.kinds.clone())
+        else {
+            return;
+        };
+        let mut taken = Vec::new();
+        for &element in elements {
+            let Ok(id) = self.world.get::<&ElId>(element).map(|id| id.clone()) else {
+                continue;
+            };
+            for kind in &kinds {
+                if let Some(state) = (kind.take)(&mut self.world, element) {
+                    taken.push(((id.clone(), kind.type_id), state));
+                }
+            }
+        }
+        if let Ok(mut keyed) = self.world.get_resource::<&mut KeyedState>() {
+            keyed.saved.extend(taken);
+        }
+    }
+
+    /// gives the components kept by [`ElementCtx::retain_keyed`] back to the tree of `root`.
+    fn restore_keyed(&mut self, root: Element) {
+        let Ok((kinds, mut saved)) = self
+            .world
+            .get_resource::<&mut KeyedState>()
+            .map(|mut keyed| (keyed.kinds.clone(), std::mem::take(&mut keyed.saved)))
+        else {
+            return;
+        };
+        if saved.is_empty() {
+            return;
+        }
+        let mut subtree = Vec::new();
+        self.collect_subtree(root, &mut subtree);
+        for element in subtree {
+            let Ok(id) = self.world.get::<&ElId>(element).map(|id| id.clone()) else {
+                continue;
+            };
+            for kind in &kinds {
+                if let Some(state) = saved.remove(&(id.clone(), kind.type_id)) {
+                    (kind.restore)(&mut self.world, element, state);
+                }
+            }
+        }
+    }
+
+    /// runs the [`OnMount`] hooks of the tree that didn't run yet.
+    fn mount(&mut self, root: Element) {
+        let mut subtree = Vec::new();
+        self.collect_subtree(root, &mut subtree);
+        for element in subtree {
+            if self.get::<&Mounted>(element).is_ok() {
+                continue;
+            }
+            let hook = self.get::<&OnMount>(element).map(|hook| hook.0.clone());
+            let Ok(hook) = hook else {
+                continue;
+            };
+            _ = self.insert_one(element, Mounted);
+            hook(&mut self.world, element);
+        }
+    }
+
+    /// pushes the elements of the tree, children before their parent.
+    fn collect_subtree(&self, element: Element, out: &mut Vec<Element>) {
+        let children = self
+            .get::<&Children>(element)
+            .map(|children| children.clone());
         if let Ok(children) = children {
             for child in children.iter() {
-                self.despawn_ui(*child);
+                self.collect_subtree(child, out);
             }
         }
+        out.push(element);
     }
 }
 
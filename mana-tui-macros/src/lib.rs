@@ -1,12 +1,15 @@
 use quote::quote;
 use syn::parse_macro_input;
 
+mod lint;
 mod manasx;
+mod submodel;
 mod subview;
 mod utils;
 
 use crate::manasx::ManaElement;
-use crate::subview::SubviewFn;
+use crate::submodel::SubModelStruct;
+use crate::subview::{SubviewFn, SubviewStruct};
 use crate::utils::mana_tui_elemental;
 
 /// # Example
@@ -38,6 +41,30 @@ pub fn ui(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     tokens.into()
 }
 
+/// turns a function that returns a `View` into a subview that can be used as a tag in [`ui!`].
+/// the arguments of the function become the attributes of the tag.
+///
+/// the function can be `async`. the view of an async subview is built with
+/// `into_view().await`, so its tag has to be awaited with `.await` after the tag name and the
+/// [`ui!`] invocation must be inside an async context.
+///
+/// # Example
+///
+/// ```ignore
+/// #[subview]
+/// async fn user_card(id: u32) -> View {
+///     let user = fetch_user(id).await;
+///     ui! { <Block .title_top={user.name} /> }
+/// }
+///
+/// async fn view(model: &Model) -> View {
+///     ui! {
+///         <Block>
+///             <UserCard.await .id={model.user_id} />
+///         </Block>
+///     }
+/// }
+/// ```
 #[proc_macro_attribute]
 pub fn subview(
     args: proc_macro::TokenStream,
@@ -47,3 +74,70 @@ pub fn subview(
     let tok = quote! { #subview };
     tok.into()
 }
+
+/// derives builder style setters and the conversion into a view for a struct that implements
+/// `Subview`. the struct can then be used as a tag in [`ui!`]. its view is built when it is
+/// spawned, with the context it is spawned into.
+///
+/// every field gets a setter of the same name. `Option<T>` fields take a `T` and also get a
+/// `maybe_` setter that takes the option directly. mark a field with `#[prop(into)]` to make
+/// its setter take `impl Into<T>`. the struct must implement [`Default`].
+///
+/// # Example
+///
+/// ```
+/// use mana_tui::prelude::*;
+/// use mana_tui_macros::{Subview, ui};
+///
+/// #[derive(Default, Subview)]
+/// struct Card {
+///     #[prop(into)]
+///     title: String,
+///     #[prop(into)]
+///     footer: Option<String>,
+/// }
+///
+/// impl Subview for Card {
+///     fn view(&self, _ctx: &mut ElementCtx) -> View {
+///         ui! {
+///             <Block
+///                 .title_top={self.title.clone()}
+///                 .title_bottom={self.footer.clone().unwrap_or_default()}
+///             />
+///         }
+///     }
+/// }
+///
+/// let root = ui! {
+///     <Card .title="card" .footer="footer" />
+/// };
+/// ```
+#[proc_macro_derive(Subview, attributes(prop))]
+pub fn derive_subview(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let subview = parse_macro_input!(item as SubviewStruct);
+    let tok = quote! { #subview };
+    tok.into()
+}
+
+/// implements `mana_tui_potion::SubModel` by forwarding to an update function. use it together
+/// with `mana_tui_potion::compose_models!` to split a large model into domains.
+///
+/// # Example
+///
+/// ```ignore
+/// #[derive(Default, SubModel)]
+/// #[submodel(msg = AuthMsg, update = auth_update)]
+/// struct AuthModel {
+///     user: Option<String>,
+/// }
+///
+/// async fn auth_update(model: AuthModel, msg: AuthMsg) -> (AuthModel, Effect<AuthMsg>) {
+///     // ...
+/// }
+/// ```
+#[proc_macro_derive(SubModel, attributes(submodel))]
+pub fn derive_submodel(item: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let submodel = parse_macro_input!(item as SubModelStruct);
+    let tok = quote! { #submodel };
+    tok.into()
+}
@@ -0,0 +1,198 @@
+//! warnings for attributes that are probably typos.
+//!
+//! stable proc macros can't emit warnings, so a warning is a use of a deprecated constant with
+//! the message as its note, spanned to the attribute.
+
+use proc_macro2::TokenStream;
+use quote::quote_spanned;
+
+/// the builder methods of the widgets the lint knows, from ratatui and the mana-tui prelude.
+const WIDGET_METHODS: &[(&str, &[&str])] = &[
+    (
+        "Block",
+        &[
+            "new",
+            "bordered",
+            "title",
+            "title_top",
+            "title_bottom",
+            "title_style",
+            "title_alignment",
+            "title_position",
+            "border_style",
+            "borders",
+            "border_type",
+            "border_set",
+            "padding",
+            "merge_borders",
+            "rounded",
+            "into_block",
+        ],
+    ),
+    (
+        "Paragraph",
+        &[
+            "new",
+            "block",
+            "wrap",
+            "scroll",
+            "alignment",
+            "left_aligned",
+            "centered",
+            "right_aligned",
+        ],
+    ),
+    (
+        "Text",
+        &[
+            "raw",
+            "styled",
+            "from",
+            "patch_style",
+            "reset_style",
+            "alignment",
+            "left_aligned",
+            "centered",
+            "right_aligned",
+            "push_line",
+            "push_span",
+        ],
+    ),
+];
+
+/// methods every styled widget has, from `Styled` and `Stylize`.
+const STYLE_METHODS: &[&str] = &[
+    "style",
+    "set_style",
+    "fg",
+    "bg",
+    "add_modifier",
+    "remove_modifier",
+    "reset",
+    "clone",
+    "into",
+];
+
+const COLORS: &[&str] = &[
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "gray",
+    "dark_gray",
+    "light_red",
+    "light_green",
+    "light_yellow",
+    "light_blue",
+    "light_magenta",
+    "light_cyan",
+    "white",
+];
+
+const MODIFIERS: &[&str] = &[
+    "bold",
+    "dim",
+    "italic",
+    "underlined",
+    "slow_blink",
+    "rapid_blink",
+    "reversed",
+    "hidden",
+    "crossed_out",
+];
+
+/// every method of `widget`, or [`None`] if the lint doesn't know the widget.
+fn methods(widget: &str) -> Option<Vec<String>> {
+    let (_, methods) = WIDGET_METHODS.iter().find(|(name, _)| *name == widget)?;
+    let colors = COLORS
+        .iter()
+        .flat_map(|color| [(*color).to_owned(), format!("on_{color}")]);
+    let modifiers = MODIFIERS
+        .iter()
+        .flat_map(|modifier| [(*modifier).to_owned(), format!("not_{modifier}")]);
+    Some(
+        methods
+            .iter()
+            .chain(STYLE_METHODS)
+            .map(|method| (*method).to_owned())
+            .chain(colors)
+            .chain(modifiers)
+            .collect(),
+    )
+}
+
+/// the number of edits between `a` and `b`.
+fn distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if a == *b {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// the warning for the attribute `attr` of a `widget` tag, if it looks wrong.
+pub(crate) fn check_attr(widget: &str, attr: &str) -> Option<String> {
+    if attr.chars().any(char::is_uppercase) {
+        return Some(format!(
+            "`.{attr}` is not a method name, builder methods are snake_case"
+        ));
+    }
+    let methods = methods(widget)?;
+    if methods.iter().any(|method| method == attr) {
+        return None;
+    }
+    let closest = methods
+        .iter()
+        .map(|method| (distance(method, attr), method))
+        .filter(|(distance, _)| *distance <= 2)
+        .min();
+    Some(match closest {
+        Some((_, method)) => {
+            format!("`{widget}` has no method `.{attr}`, did you mean `.{method}`?")
+        }
+        None => format!("`{widget}` has no method `.{attr}`"),
+    })
+}
+
+/// a statement that makes rustc warn with `message` at `span`.
+pub(crate) fn warning(span: proc_macro2::Span, message: &str) -> TokenStream {
+    quote_spanned! { span =>
+        {
+            #[deprecated(note = #message)]
+            #[allow(non_upper_case_globals)]
+            const ui_attribute: () = ();
+            let () = ui_attribute;
+        }
+    }
+}
+
+#[test]
+fn test_check_attr() {
+    assert_eq!(check_attr("Block", "title_top"), None);
+    assert_eq!(check_attr("Block", "on_dark_gray"), None);
+    assert_eq!(
+        check_attr("Block", "tittle_top").as_deref(),
+        Some("`Block` has no method `.tittle_top`, did you mean `.title_top`?")
+    );
+    assert_eq!(
+        check_attr("Paragraph", "sparkles").as_deref(),
+        Some("`Paragraph` has no method `.sparkles`")
+    );
+    // widgets the lint doesn't know can have any method.
+    assert_eq!(check_attr("Chart", "sparkles"), None);
+    assert!(check_attr("Chart", "TITLE").is_some());
+}
@@ -1,6 +1,6 @@
 use std::fmt::Display;
 
-use proc_macro2::{Span, TokenStream};
+use proc_macro2::{Group, Span, TokenStream, TokenTree};
 use quote::{quote, quote_spanned};
 use syn::{
     Token, parenthesized,
@@ -8,6 +8,8 @@ use syn::{
     spanned::Spanned,
 };
 
+use crate::lint;
+
 macro_rules! impl_parse_enum {
 ($enum_name:ident { $($variant:ident($inner:ty)),* $(,)? }) => {
     impl syn::parse::Parse for $enum_name {
@@ -85,8 +87,9 @@ impl Display for ManaName {
                 path.path
                     .segments
                     .iter()
-                    .map(|seg| seg.ident.to_string() + "::")
-                    .collect::<String>()
+                    .map(|seg| seg.ident.to_string())
+                    .collect::<Vec<_>>()
+                    .join("::")
             ),
         }
     }
@@ -99,28 +102,82 @@ impl ManaName {
             ManaName::Path(expr_path) => expr_path.span(),
         }
     }
+
+    /// whether the name is a path with generic arguments, like `Widget::<i32>`.
+    fn has_generics(&self) -> bool {
+        match self {
+            ManaName::Ident(_) => false,
+            ManaName::Path(expr_path) => expr_path
+                .path
+                .segments
+                .iter()
+                .any(|seg| !seg.arguments.is_none()),
+        }
+    }
 }
 
-impl_parse_enum!(ManaName {
-    Ident(syn::Ident),
-    Path(syn::ExprPath),
-});
+impl Parse for ManaName {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        // a type path, so generics don't need the turbofish: `<Widget<i32> />`.
+        let path = input.parse::<syn::Path>()?;
+        match path.get_ident() {
+            Some(ident) => Ok(ManaName::Ident(ident.clone())),
+            None => Ok(ManaName::Path(syn::ExprPath {
+                attrs: Vec::new(),
+                qself: None,
+                path,
+            })),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 struct ManaTagData {
     ident: ManaName,
+    /// `.await` after the tag name, set for async subviews.
+    awaited: Option<(Token![.], Token![await])>,
     attrs: ManaAttrVec,
     components: ComponentVec,
 }
 
 impl ManaTagData {
+    /// the name of the widget without its module, `Text` for `ratatui::text::Text`.
     pub fn name(&self) -> syn::Result<String> {
         let name = match self.ident {
             ManaName::Ident(ref ident) => ident.to_string(),
-            ManaName::Path(ref expr_path) => expr_path.path.require_ident()?.to_string(),
+            ManaName::Path(ref expr_path) => expr_path
+                .path
+                .segments
+                .last()
+                .ok_or_else(|| syn::Error::new(expr_path.span(), "expected a widget name"))?
+                .ident
+                .to_string(),
         };
         Ok(name)
     }
+
+    /// warnings for the attributes in `attrs` that are likely typos, see [`lint`].
+    fn lints(&self, attrs: &ManaAttrVec) -> TokenStream {
+        let Ok(name) = self.name() else {
+            return TokenStream::new();
+        };
+        attrs
+            .0
+            .iter()
+            .filter_map(|attr| {
+                let message = lint::check_attr(&name, &attr.fn_name.to_string())?;
+                Some(lint::warning(attr.fn_name.span(), &message))
+            })
+            .collect()
+    }
+}
+
+/// `tag`, after the statements in `lints`.
+fn with_lints(lints: TokenStream, tag: TokenStream) -> TokenStream {
+    if lints.is_empty() {
+        return tag;
+    }
+    quote! { { #lints #tag } }
 }
 
 #[derive(Debug, Clone)]
@@ -141,21 +198,54 @@ enum ManaAttrValue {
     Lit(syn::Lit),
     ExprTuple(syn::ExprTuple),
     ExprBlock(syn::ExprBlock),
+    ExprClosure(syn::ExprClosure),
 }
 
 impl_quote_enum!(ManaAttrValue {
     Lit,
     ExprTuple,
-    ExprBlock
+    ExprBlock,
+    ExprClosure
 });
 impl_parse_enum!(
     ManaAttrValue {
         Lit(syn::Lit),
         ExprTuple(syn::ExprTuple),
         ExprBlock(syn::ExprBlock),
+        ExprClosure(syn::ExprClosure),
     }
 );
 
+impl ManaAttrValue {
+    /// returns the closure for both `.attr=|..| ..` and `.attr={|..| ..}`
+    fn closure(&self) -> Option<&syn::ExprClosure> {
+        match self {
+            ManaAttrValue::ExprClosure(closure) => Some(closure),
+            ManaAttrValue::ExprBlock(block) => match block.block.stmts.as_slice() {
+                [syn::Stmt::Expr(syn::Expr::Closure(closure), None)] => Some(closure),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+impl ManaAttr {
+    /// event handler shorthands. `.on={|model, event| ..}` becomes an `On::new(..)` component
+    /// instead of a method call on the widget.
+    fn handler(&self) -> Option<TokenStream> {
+        let closure = self.assign.as_ref()?.value.closure()?;
+        let constructor = match self.fn_name.to_string().as_str() {
+            "on" => quote! { On::new },
+            "on_click" => quote! { OnClick::with_fn },
+            _ => return None,
+        };
+        let span = self.fn_name.span();
+        let constructor = respan(constructor, span);
+        Some(quote_spanned! { span => #constructor(#closure) })
+    }
+}
+
 #[derive(Debug, Clone)]
 struct ManaAttrVec(Vec<ManaAttr>);
 
@@ -296,7 +386,6 @@ impl_parse_enum!(Children {
     Block(ChildrenBlock),
     List(ChildrenList),
 });
-impl_quote_enum!(Children { Block, List });
 
 #[derive(Debug, Clone)]
 struct BraceBlock {
@@ -409,10 +498,16 @@ fn parse_any<T: Parse>(input: syn::parse::ParseStream) -> impl Iterator<Item = T
 impl Parse for ManaTagData {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let ident = input.parse()?;
+        let awaited = if input.peek(Token![.]) && input.peek2(Token![await]) {
+            Some((input.parse()?, input.parse()?))
+        } else {
+            None
+        };
         let attrs = input.parse()?;
         let components = input.parse()?;
         Ok(ManaTagData {
             ident,
+            awaited,
             attrs,
             components,
         })
@@ -438,6 +533,24 @@ impl Parse for ManaAttrAssign {
     }
 }
 
+impl ManaAttrVec {
+    /// splits the attributes into widget method calls and event handler components.
+    fn split_handlers(&self) -> (ManaAttrVec, Vec<TokenStream>) {
+        let mut attrs = Vec::new();
+        let mut handlers = Vec::new();
+        for attr in &self.0 {
+            match attr.handler() {
+                Some(handler) => {
+                    let span = attr.fn_name.span();
+                    handlers.push(quote_spanned! { span => .with((#handler,)) });
+                }
+                None => attrs.push(attr.clone()),
+            }
+        }
+        (ManaAttrVec(attrs), handlers)
+    }
+}
+
 impl Parse for ManaAttrVec {
     fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
         let attrs = parse_any::<ManaAttr>(input).collect::<Vec<_>>();
@@ -485,10 +598,12 @@ impl quote::ToTokens for ManaElement {
                 }
             }),
             ManaElement::Element(element) => {
-                tokens.extend(quote! { #element.done() });
+                let span = element.open.data.ident.span();
+                tokens.extend(quote_spanned! { span => #element.done() });
             }
             Self::TextElement(element) => {
-                tokens.extend(quote! { #element.done() });
+                let span = element.open.data.ident.span();
+                tokens.extend(quote_spanned! { span => #element.done() });
             }
             ManaElement::SelfClosing(open_tag) => {
                 let span = open_tag.span();
@@ -519,13 +634,25 @@ impl quote::ToTokens for ManaTagData {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let ManaTagData {
             ident,
+            awaited,
             attrs,
             components,
         } = self;
-        let out = quote! {
-            __ui_internal(#ident::default() #attrs .into_view())#components
+        let (attrs, handlers) = attrs.split_handlers();
+        let lints = self.lints(&attrs);
+        // errors in the generated calls point at the tag instead of the whole macro.
+        let span = ident.span();
+        let awaited = awaited.map(|_| quote_spanned! { span => .await });
+        // `Widget<i32>::default()` isn't an expression, generic widgets need the qualified call.
+        let default = if ident.has_generics() {
+            quote_spanned! { span => <#ident as ::core::default::Default>::default() }
+        } else {
+            quote_spanned! { span => #ident::default() }
+        };
+        let out = quote_spanned! { span =>
+            __ui_internal(#default #attrs .into_view() #awaited)#components #(#handlers)*
         };
-        tokens.extend(out);
+        tokens.extend(with_lints(lints, out));
     }
 }
 
@@ -550,14 +677,15 @@ impl quote::ToTokens for ComponentVec {
             .iter()
             .map(|component| {
                 let Component(c_expr) = component;
+                let span = c_expr.span();
                 match c_expr {
                     ComponentExpr::Tuple(c_expr) => {
-                        quote! {
+                        quote_spanned! { span =>
                             .with(#c_expr)
                         }
                     }
                     _ => {
-                        quote! {
+                        quote_spanned! { span =>
                             .with((#c_expr,))
                         }
                     }
@@ -586,7 +714,8 @@ impl quote::ToTokens for ManaAttr {
             fn_name,
             assign,
         } = self;
-        let tok = quote! {
+        let span = fn_name.span();
+        let tok = quote_spanned! { span =>
             .#fn_name(
                 #assign
             )
@@ -598,7 +727,8 @@ impl quote::ToTokens for ManaAttr {
 impl quote::ToTokens for ManaAttrAssign {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let ManaAttrAssign { _eq, value } = self;
-        let tok = quote! {
+        let span = value.span();
+        let tok = quote_spanned! { span =>
             #[allow(unused_braces)]
             {
                 #value
@@ -615,10 +745,20 @@ impl quote::ToTokens for Element {
             children,
             close: _close,
         } = self;
-        let init = quote! { #open };
-        let children = quote! { #children };
+        let span = open.data.ident.span();
+        let children = match children {
+            Children::List(ChildrenList(elements)) if elements.is_empty() => quote! {},
+            Children::List(ChildrenList(elements)) => quote_spanned! { span =>
+                .children((#(#elements,)*))
+            },
+            Children::Block(ChildrenBlock(block)) => quote_spanned! { span =>
+                .children(
+                    #block
+                )
+            },
+        };
         tokens.extend(quote! {
-            #init #children
+            #open #children
         });
     }
 }
@@ -630,37 +770,25 @@ impl quote::ToTokens for TextElement {
             text,
             close: _,
         } = self;
-        let constructor = match open.data.ident.to_string().as_str() {
+        let constructor = match open.data.name().unwrap_or_default().as_str() {
             "Text" | "Line" | "Span" => quote! { raw },
             "Paragraph" => quote! { new },
             _ => todo!(),
         };
+        let constructor = respan(constructor, open.data.ident.span());
         let ManaTagData {
             ref ident,
             ref attrs,
             ref components,
+            ..
         } = open.data;
-        let out = quote! {
-            __ui_internal(#ident::#constructor(format!(#text)) #attrs .into_view())#components
-        };
-        tokens.extend(out);
-    }
-}
-
-impl quote::ToTokens for ChildrenList {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
-        if self.0.is_empty() {
-            return;
-        }
-        let tok = self
-            .0
-            .iter()
-            .map(|el| quote! { #el })
-            .reduce(|acc, el| quote! {#acc, #el});
-        let fncall = quote! {
-            .children((#tok,))
+        let (attrs, handlers) = attrs.split_handlers();
+        let lints = open.data.lints(&attrs);
+        let span = ident.span();
+        let out = quote_spanned! { span =>
+            __ui_internal(#ident::#constructor(format!(#text)) #attrs .into_view())#components #(#handlers)*
         };
-        tokens.extend(fncall);
+        tokens.extend(with_lints(lints, out));
     }
 }
 
@@ -678,16 +806,23 @@ impl quote::ToTokens for BraceBlock {
     }
 }
 
-impl quote::ToTokens for ChildrenBlock {
-    fn to_tokens(&self, tokens: &mut TokenStream) {
-        let ChildrenBlock(block) = self;
-        let fncall = quote! {
-            .children(
-                #block
-            )
-        };
-        tokens.extend(fncall);
-    }
+/// `tokens` with `span` set on every token. `quote_spanned!` only spans the tokens written in
+/// it, not the ones interpolated into it.
+fn respan(tokens: TokenStream, span: Span) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|token| match token {
+            TokenTree::Group(group) => {
+                let mut respanned = Group::new(group.delimiter(), respan(group.stream(), span));
+                respanned.set_span(span);
+                TokenTree::Group(respanned)
+            }
+            mut token => {
+                token.set_span(span);
+                token
+            }
+        })
+        .collect()
 }
 
 #[test]
@@ -699,3 +834,112 @@ fn test() {
     let res = syn::parse2::<ManaElement>(input).unwrap();
     println!("{res:#?}");
 }
+
+#[test]
+fn test_handler_attr() {
+    use std::str::FromStr;
+
+    let input = TokenStream::from_str(r"<Block .rounded .on={|_, e| None} />").unwrap();
+    let ManaElement::SelfClosing(open) = syn::parse2::<ManaElement>(input).unwrap() else {
+        panic!("expected self closing tag");
+    };
+    let (attrs, handlers) = open.data.attrs.split_handlers();
+    assert_eq!(attrs.0.len(), 1);
+    assert_eq!(handlers.len(), 1);
+    assert!(handlers[0].to_string().contains("On :: new"));
+}
+
+#[test]
+fn test_tag_spans() {
+    use std::str::FromStr;
+
+    fn lines(tokens: TokenStream, name: &str, out: &mut Vec<usize>) {
+        for token in tokens {
+            match token {
+                TokenTree::Ident(ident) if ident == name => out.push(ident.span().start().line),
+                TokenTree::Group(group) => lines(group.stream(), name, out),
+                _ => {}
+            }
+        }
+    }
+
+    let input = TokenStream::from_str(
+        "<Block>
+            <Block Width::grow()>
+                <Nonexistent />
+            </Block>
+            <Text .on={|_, _| None}>\"hi\"</Text>
+        </Block>",
+    )
+    .unwrap();
+    let element = syn::parse2::<ManaElement>(input).unwrap();
+    let tokens = quote! { #element };
+
+    // every tag's calls point at the line of the tag.
+    let expected = [
+        ("__ui_internal", vec![1, 2, 3, 5]),
+        ("default", vec![1, 2, 3]),
+        ("done", vec![1, 2, 3, 5]),
+        ("children", vec![1, 2]),
+        ("with", vec![2, 5]),
+        // generated paths are spanned too, not only the tokens written in `quote_spanned!`.
+        ("On", vec![5]),
+        ("raw", vec![5]),
+    ];
+    for (name, expected) in expected {
+        let mut found = Vec::new();
+        lines(tokens.clone(), name, &mut found);
+        found.sort_unstable();
+        assert_eq!(found, expected, "{name}");
+    }
+}
+
+#[test]
+fn test_widget_paths() {
+    use std::str::FromStr;
+
+    let expand = |input: &str| {
+        let element = syn::parse2::<ManaElement>(TokenStream::from_str(input).unwrap()).unwrap();
+        quote! { #element }.to_string()
+    };
+
+    let tokens = expand("<widgets::MyWidget/>");
+    assert!(tokens.contains("__ui_internal (widgets :: MyWidget :: default ()"));
+    let tokens = expand("<widgets::MyWidget .rounded></widgets::MyWidget>");
+    assert!(tokens.contains("__ui_internal (widgets :: MyWidget :: default () . rounded"));
+    for input in ["<Widget<i32> />", "<Widget::<i32> />"] {
+        let tokens = expand(input);
+        assert!(tokens.contains(":: core :: default :: Default > :: default ()"));
+    }
+    assert!(
+        expand("<ratatui::text::Text>\"hi\"</ratatui::text::Text>")
+            .contains("ratatui :: text :: Text :: raw")
+    );
+
+    let input = TokenStream::from_str("<widgets::MyWidget></widgets::Other>").unwrap();
+    let err = syn::parse2::<ManaElement>(input).unwrap_err();
+    assert!(err.to_string().contains("widgets::Other"));
+}
+
+#[test]
+fn test_attr_lints() {
+    use std::str::FromStr;
+
+    let expand = |input: &str| {
+        let element = syn::parse2::<ManaElement>(TokenStream::from_str(input).unwrap()).unwrap();
+        quote! { #element }.to_string()
+    };
+
+    let tokens = expand(r#"<Block .tittle_top="typo" />"#);
+    assert!(tokens.contains("deprecated"));
+    assert!(tokens.contains("did you mean `.title_top`?"));
+    let tokens = expand(r#"<Paragraph .TITLE="x">"text"</Paragraph>"#);
+    assert!(tokens.contains("snake_case"));
+
+    for input in [
+        r#"<Block .rounded .title_top="ok" .red .on={|_, e| None} />"#,
+        r#"<MyWidget .anything="goes" />"#,
+    ] {
+        assert!(!expand(input).contains("deprecated"), "{input}");
+    }
+}
@@ -0,0 +1,71 @@
+use quote::quote_spanned;
+
+pub struct SubModelStruct {
+    ident: syn::Ident,
+    generics: syn::Generics,
+    msg: syn::Type,
+    update: syn::Path,
+}
+
+impl syn::parse::Parse for SubModelStruct {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let item: syn::DeriveInput = input.parse()?;
+        let mut msg = None;
+        let mut update = None;
+        for attr in item
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("submodel"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("msg") {
+                    msg = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else if meta.path.is_ident("update") {
+                    update = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unsupported submodel attribute, expected `msg` or `update`"))
+                }
+            })?;
+        }
+        let missing = |name: &str| {
+            syn::Error::new(
+                item.ident.span(),
+                format!("missing `#[submodel({name} = ...)]` attribute"),
+            )
+        };
+        Ok(Self {
+            msg: msg.ok_or_else(|| missing("msg"))?,
+            update: update.ok_or_else(|| missing("update"))?,
+            ident: item.ident,
+            generics: item.generics,
+        })
+    }
+}
+
+impl quote::ToTokens for SubModelStruct {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let SubModelStruct {
+            ident,
+            generics,
+            msg,
+            update,
+        } = self;
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let span = ident.span();
+        tokens.extend(quote_spanned! {
+            span =>
+            impl #impl_generics ::mana_tui_potion::SubModel for #ident #ty_generics #where_clause {
+                type Msg = #msg;
+
+                async fn update(
+                    self,
+                    msg: Self::Msg,
+                ) -> (Self, ::mana_tui_potion::Effect<Self::Msg>) {
+                    #update(self, msg).await
+                }
+            }
+        });
+    }
+}
@@ -96,15 +96,24 @@ impl quote::ToTokens for SubviewFn {
                 }
             }
 
-            impl #impl_generics From<#name #ty_generics> for #mana_crate::ui::View
-            #where_clause_is_complete
-            {
-                fn from(value: #name #ty_generics) -> Self {
-                    value.into_view()
-                }
-            }
         };
         tokens.extend(tok);
+
+        // an async subview builds its view with `into_view().await`, so it can't be converted
+        // into a view synchronously.
+        if func.sig.asyncness.is_none() {
+            tokens.extend(quote_spanned! {
+                span =>
+
+                impl #impl_generics From<#name #ty_generics> for #mana_crate::ui::View
+                #where_clause_is_complete
+                {
+                    fn from(value: #name #ty_generics) -> Self {
+                        value.into_view()
+                    }
+                }
+            });
+        }
     }
 }
 
@@ -176,3 +185,153 @@ impl quote::ToTokens for BuilderGenerics {
         self.full_generics.to_tokens(tokens);
     }
 }
+
+pub struct SubviewStruct {
+    item: syn::ItemStruct,
+}
+
+impl syn::parse::Parse for SubviewStruct {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            item: input.parse()?,
+        })
+    }
+}
+
+impl quote::ToTokens for SubviewStruct {
+    fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
+        let SubviewStruct { item } = self;
+        let name = &item.ident;
+        let (impl_generics, ty_generics, where_clause) = item.generics.split_for_impl();
+        let mana_crate = mana_tui_elemental();
+
+        let syn::Fields::Named(fields) = &item.fields else {
+            tokens.extend(
+                syn::Error::new(
+                    item.fields.span(),
+                    "#[derive(Subview)] only supports structs with named fields",
+                )
+                .to_compile_error(),
+            );
+            return;
+        };
+
+        let mut setters = Vec::new();
+        for field in &fields.named {
+            let ident = field.ident.as_ref().expect("named field");
+            let vis = &item.vis;
+            let span = ident.span();
+            let into = match has_into_attr(field) {
+                Ok(into) => into,
+                Err(err) => {
+                    tokens.extend(err.to_compile_error());
+                    return;
+                }
+            };
+            let inner = option_inner(&field.ty);
+            let ty = inner.unwrap_or(&field.ty);
+            let (arg_ty, mut value) = if into {
+                (
+                    quote::quote! { impl Into<#ty> },
+                    quote::quote! { value.into() },
+                )
+            } else {
+                (quote::quote! { #ty }, quote::quote! { value })
+            };
+            if inner.is_some() {
+                value = quote::quote! { Some(#value) };
+            }
+            setters.push(quote_spanned! {
+                span =>
+                #[must_use]
+                #vis fn #ident(mut self, value: #arg_ty) -> Self {
+                    self.#ident = #value;
+                    self
+                }
+            });
+            if inner.is_some() {
+                let maybe = format_ident!("maybe_{ident}");
+                setters.push(quote_spanned! {
+                    span =>
+                    #[must_use]
+                    #vis fn #maybe(mut self, value: Option<#ty>) -> Self {
+                        self.#ident = value;
+                        self
+                    }
+                });
+            }
+        }
+
+        // the view is built once the subview is spawned, so the props move into the view.
+        let mut view_where = where_clause
+            .cloned()
+            .unwrap_or_else(|| syn::parse_quote!(where));
+        view_where.predicates.push(syn::parse_quote! {
+            #name #ty_generics: #mana_crate::ui::Subview + Send + Sync + 'static
+        });
+
+        let span = name.span();
+        let tok = quote_spanned! {
+            span =>
+
+            impl #impl_generics #name #ty_generics #where_clause {
+                #(#setters)*
+
+                /// the view of this subview, built when it is spawned.
+                #[must_use]
+                pub fn into_view(self) -> #mana_crate::ui::View
+                #view_where
+                {
+                    #mana_crate::ui::lazy_subview(self)
+                }
+            }
+
+            impl #impl_generics From<#name #ty_generics> for #mana_crate::ui::View
+            #view_where
+            {
+                fn from(value: #name #ty_generics) -> Self {
+                    value.into_view()
+                }
+            }
+        };
+        tokens.extend(tok);
+    }
+}
+
+/// checks for `#[prop(into)]` on a field.
+fn has_into_attr(field: &syn::Field) -> syn::Result<bool> {
+    let mut into = false;
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("prop"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("into") {
+                into = true;
+                Ok(())
+            } else {
+                Err(meta.error("unsupported prop attribute, expected `into`"))
+            }
+        })?;
+    }
+    Ok(into)
+}
+
+/// returns `T` if the type is written as `Option<T>`.
+fn option_inner(ty: &syn::Type) -> Option<&syn::Type> {
+    let syn::Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        syn::GenericArgument::Type(inner) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
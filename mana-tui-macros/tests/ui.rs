@@ -1,5 +1,5 @@
 use mana_tui::prelude::{strum::IntoEnumIterator, *};
-use mana_tui_macros::{subview, ui};
+use mana_tui_macros::{Subview, subview, ui};
 
 #[subview]
 fn container(justify: MainJustify, children: impl AsChildren) -> View {
@@ -93,3 +93,108 @@ fn test() {
         </Block>
     };
 }
+
+mod widgets {
+    pub use mana_tui::prelude::Block as Panel;
+}
+
+#[test]
+fn test_widget_paths() {
+    let _ = ui! {
+        <widgets::Panel .rounded Width::fixed(10)>
+            <widgets::Panel .title_top="inner" />
+        </widgets::Panel>
+    };
+}
+
+#[derive(Debug, Default, Subview)]
+struct ProgressCard {
+    #[prop(into)]
+    title: String,
+    ratio: f32,
+    #[prop(into)]
+    label: Option<String>,
+}
+
+impl Subview for ProgressCard {
+    fn view(&self, _ctx: &mut ElementCtx) -> View {
+        ui! {
+            <Block .rounded .title_top={self.title.clone()} Width::fixed(24) Height::fixed(3)>
+                <Gauge .ratio={f64::from(self.ratio)} .label={self.label.clone().unwrap_or_default()} />
+            </Block>
+        }
+    }
+}
+
+#[test]
+fn test_derive_subview() {
+    let card = ProgressCard::default()
+        .title("cpu")
+        .ratio(0.5)
+        .maybe_label(None);
+    assert_eq!(card.title, "cpu");
+    assert!(card.label.is_none());
+    let card = card.label("50%");
+    assert_eq!(card.label.as_deref(), Some("50%"));
+
+    let _ = ui! {
+        <Block>
+            <ProgressCard .title="cpu" .ratio={0.5} />
+            <ProgressCard .title="memory" .ratio={0.25} .label="1/4" />
+        </Block>
+    };
+}
+
+#[derive(Debug, Default, Subview)]
+struct Greeting {
+    #[prop(into)]
+    name: String,
+}
+
+impl Subview for Greeting {
+    fn view(&self, ctx: &mut ElementCtx) -> View {
+        let style = ctx
+            .get_resource::<&DefaultStyles>()
+            .map(|styles| styles.text_style)
+            .unwrap_or_default();
+        ui(Text::styled(format!("hello {}", self.name), style)).done()
+    }
+}
+
+#[test]
+fn test_subview_ctx() {
+    let mut ctx = ElementCtx::new().with_default_styles(DefaultStyles {
+        text_style: Style::new().red(),
+        ..DefaultStyles::default()
+    });
+    let root = ctx.spawn_ui(ui! {
+        <Block>
+            <Greeting .name="you" Width::fixed(3) />
+        </Block>
+    });
+    let greeting = ctx.get::<&Children>(root).unwrap()[0];
+    // the view is built with the theme of the context.
+    let text = ctx.get::<&Text<'static>>(greeting).unwrap().clone();
+    assert_eq!(text, Text::styled("hello you", Style::new().red()));
+    // components on the tag replace the ones of the view.
+    assert_eq!(**ctx.get::<&Width>(greeting).unwrap(), Size::Fixed(3));
+}
+
+#[test]
+fn test_spacer() {
+    use mana_tui::__ratatui::{buffer::Buffer, layout::Rect};
+
+    let area = Rect::new(0, 0, 12, 1);
+    let mut ctx = ElementCtx::new();
+    let root = ctx.spawn_ui(ui! {
+        <Block Width::grow() Direction::Horizontal>
+            <Text>"left"</Text>
+            <Spacer />
+            <Text>"right"</Text>
+        </Block>
+    });
+    ctx.calculate_layout(root, area).unwrap();
+    let mut buf = Buffer::empty(area);
+    ctx.render(root, area, &mut buf);
+    assert_eq!(buf, Buffer::with_lines(["left   right"]));
+}
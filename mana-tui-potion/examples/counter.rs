@@ -1,7 +1,6 @@
-use std::io::stdout;
 use std::time::Duration;
 
-use crossterm::event::{EnableMouseCapture, Event, KeyEvent, KeyModifiers};
+use crossterm::event::{Event, KeyEvent, KeyModifiers};
 use mana_tui::key;
 use mana_tui_elemental::prelude::*;
 use mana_tui_elemental::ui::View;
@@ -9,7 +8,7 @@ use mana_tui_macros::ui;
 use mana_tui_potion::backends::{DefaultEvent, KeyEventExt};
 use mana_tui_potion::focus::handlers::{On, OnClickOrKey, OnKey};
 use mana_tui_potion::focus::{FocusStyle, FocusTarget};
-use mana_tui_potion::{Effect, Message, run};
+use mana_tui_potion::{Effect, Message, RuntimeOptions, run};
 use ratatui::style::Style;
 
 #[tokio::main(flavor = "current_thread")]
@@ -23,6 +22,7 @@ async fn main() {
         .view(view)
         .update(update)
         .quit_signal(should_quit)
+        .options(RuntimeOptions::builder().mouse_capture(true).build())
         .run()
         .await
         .unwrap();
@@ -47,7 +47,6 @@ impl Message for AppMsg {
 }
 
 async fn init() -> (Model, Effect<AppMsg>) {
-    _ = crossterm::execute!(stdout(), EnableMouseCapture);
     (
         Model::default(),
         Effect::new(async |tx| {
@@ -1,7 +1,43 @@
+use std::time::Duration;
+
+use flume::Receiver;
+use mana_tui_elemental::{layout::CursorShape, ui::View};
 use ratatui::prelude::Backend;
+use tokio::time::Instant;
 
 use crate::{Chan, RuntimeMsg};
 
+/// time between frames while animations are running.
+pub const FRAME_TIME: Duration = Duration::from_millis(16);
+
+/// a command executed by the runtime against the terminal between frames. send one with
+/// [`Effect::terminal`][crate::Effect::terminal].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TerminalCommand {
+    /// sets the title of the terminal window.
+    SetTitle(String),
+    /// begins a synchronized update (mode 2026). the terminal holds off rendering until
+    /// [`TerminalCommand::EndSync`].
+    BeginSync,
+    /// ends a synchronized update.
+    EndSync,
+    /// sets the shape of the terminal cursor.
+    SetCursorStyle(CursorShape),
+    /// starts reporting mouse events.
+    EnableMouseCapture,
+    /// stops reporting mouse events.
+    DisableMouseCapture,
+    /// starts reporting focus gained and lost events.
+    EnableFocusEvents,
+    /// stops reporting focus gained and lost events.
+    DisableFocusEvents,
+    /// enables the kitty keyboard protocol, reporting key event kinds and disambiguating escape
+    /// codes.
+    PushKeyboardEnhancement,
+    /// restores the keyboard protocol active before [`TerminalCommand::PushKeyboardEnhancement`].
+    PopKeyboardEnhancement,
+}
+
 pub trait ManaBackend: Backend {
     type Events: EventStream;
     type KeyEvent;
@@ -13,31 +49,71 @@ pub trait ManaBackend: Backend {
     fn default_cycle_event() -> Self::Event;
 
     fn event_as_key(ev: Self::Event) -> Option<Self::KeyEvent>;
+
+    /// # Errors
+    ///
+    /// errors if the backend fails to write the cursor style.
+    fn set_cursor_shape(&mut self, shape: CursorShape) -> std::io::Result<()>;
+
+    /// # Errors
+    ///
+    /// errors if the backend fails to write the command.
+    fn execute_command(&mut self, command: &TerminalCommand) -> std::io::Result<()>;
 }
 
 pub trait EventStream {
     type Out;
     type Err;
 
+    /// the next event, or `None` once the stream ended, for example because the terminal was
+    /// closed.
     #[allow(async_fn_in_trait)]
-    async fn read(&mut self) -> Result<Self::Out, Self::Err>;
+    async fn read(&mut self) -> Option<Result<Self::Out, Self::Err>>;
 }
 
-pub(crate) struct MsgStream<Msg> {
-    pub(crate) event_stream: <DefaultBackend<std::io::Stdout> as ManaBackend>::Events,
+pub(crate) struct MsgStream<Msg, E = <DefaultBackend<std::io::Stdout> as ManaBackend>::Events> {
+    pub(crate) event_stream: E,
     pub(crate) dispatch: Chan<Msg>,
+    pub(crate) resolved: Receiver<(u64, View)>,
+    /// time between frames while animations are running.
+    pub(crate) frame_time: Duration,
+    /// reload requests, see [`crate::reload`]. the runtime keeps the sender so it never closes.
+    pub(crate) reload: Chan<()>,
 }
 
-impl<Msg> MsgStream<Msg> {
-    pub(crate) async fn next(this: &mut Self) -> RuntimeMsg<Msg> {
+impl<Msg, E: EventStream<Out = DefaultEvent>> MsgStream<Msg, E> {
+    /// waits for the next terminal event or app message. while `animating` is set, a
+    /// [`RuntimeMsg::Frame`] is produced every `frame_time` so animations keep advancing. at
+    /// `deadline`, the next due scheduled animation, a [`RuntimeMsg::Tick`] is produced. once
+    /// the event stream ended, [`RuntimeMsg::Shutdown`] is produced.
+    pub(crate) async fn next(
+        this: &mut Self,
+        animating: bool,
+        deadline: Option<std::time::Instant>,
+    ) -> RuntimeMsg<Msg> {
+        let tick = deadline.map(Instant::from_std);
         loop {
             tokio::select! {
-                event = this.event_stream.read() => {
-                    if let Ok(event) = event { return RuntimeMsg::Term(event) }
+                () = tokio::time::sleep(this.frame_time), if animating => {
+                    return RuntimeMsg::Frame
                 }
+                () = tokio::time::sleep_until(tick.unwrap_or_else(Instant::now)), if tick.is_some() => {
+                    return RuntimeMsg::Tick
+                }
+                event = this.event_stream.read() => match event {
+                    Some(Ok(event)) => return RuntimeMsg::Term(event),
+                    Some(Err(_)) => {}
+                    None => return RuntimeMsg::Shutdown,
+                },
                 msg = this.dispatch.1.recv_async() => {
                     if let Ok(msg) = msg { return RuntimeMsg::App(msg) }
                 }
+                resolved = this.resolved.recv_async() => {
+                    if let Ok((key, view)) = resolved { return RuntimeMsg::Resolved(key, view) }
+                }
+                reload = this.reload.1.recv_async() => {
+                    if reload.is_ok() { return RuntimeMsg::Reload }
+                }
             }
         }
     }
@@ -45,11 +121,19 @@ impl<Msg> MsgStream<Msg> {
 
 #[cfg(feature = "crossterm")]
 pub(crate) mod crossterm_backend {
-    use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
+    use crossterm::cursor::SetCursorStyle;
+    use crossterm::event::{
+        DisableFocusChange, DisableMouseCapture, EnableFocusChange, EnableMouseCapture, Event,
+        KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers, KeyboardEnhancementFlags,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    };
+    use crossterm::terminal::{BeginSynchronizedUpdate, EndSynchronizedUpdate, SetTitle};
+
+    use mana_tui_elemental::layout::CursorShape;
     use ratatui::prelude::CrosstermBackend;
     use tokio_stream::StreamExt;
 
-    use crate::backends::{EventStream, ManaBackend};
+    use crate::backends::{EventStream, ManaBackend, TerminalCommand};
 
     impl<W: std::io::Write> ManaBackend for CrosstermBackend<W> {
         type Events = crossterm::event::EventStream;
@@ -72,19 +156,56 @@ pub(crate) mod crossterm_backend {
         fn event_as_key(ev: Self::Event) -> Option<Self::KeyEvent> {
             ev.as_key_event()
         }
+
+        fn set_cursor_shape(&mut self, shape: CursorShape) -> std::io::Result<()> {
+            let style = match shape {
+                CursorShape::DefaultUserShape => SetCursorStyle::DefaultUserShape,
+                CursorShape::BlinkingBlock => SetCursorStyle::BlinkingBlock,
+                CursorShape::SteadyBlock => SetCursorStyle::SteadyBlock,
+                CursorShape::BlinkingUnderScore => SetCursorStyle::BlinkingUnderScore,
+                CursorShape::SteadyUnderScore => SetCursorStyle::SteadyUnderScore,
+                CursorShape::BlinkingBar => SetCursorStyle::BlinkingBar,
+                CursorShape::SteadyBar => SetCursorStyle::SteadyBar,
+            };
+            crossterm::execute!(self, style)
+        }
+
+        fn execute_command(&mut self, command: &TerminalCommand) -> std::io::Result<()> {
+            match command {
+                TerminalCommand::SetTitle(title) => crossterm::execute!(self, SetTitle(title)),
+                TerminalCommand::BeginSync => crossterm::execute!(self, BeginSynchronizedUpdate),
+                TerminalCommand::EndSync => crossterm::execute!(self, EndSynchronizedUpdate),
+                TerminalCommand::SetCursorStyle(shape) => self.set_cursor_shape(*shape),
+                TerminalCommand::EnableMouseCapture => {
+                    crossterm::execute!(self, EnableMouseCapture)
+                }
+                TerminalCommand::DisableMouseCapture => {
+                    crossterm::execute!(self, DisableMouseCapture)
+                }
+                TerminalCommand::EnableFocusEvents => crossterm::execute!(self, EnableFocusChange),
+                TerminalCommand::DisableFocusEvents => {
+                    crossterm::execute!(self, DisableFocusChange)
+                }
+                TerminalCommand::PushKeyboardEnhancement => crossterm::execute!(
+                    self,
+                    PushKeyboardEnhancementFlags(
+                        KeyboardEnhancementFlags::REPORT_EVENT_TYPES
+                            | KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES
+                    )
+                ),
+                TerminalCommand::PopKeyboardEnhancement => {
+                    crossterm::execute!(self, PopKeyboardEnhancementFlags)
+                }
+            }
+        }
     }
 
     impl EventStream for crossterm::event::EventStream {
         type Out = crossterm::event::Event;
         type Err = std::io::Error;
 
-        async fn read(&mut self) -> Result<Self::Out, Self::Err> {
-            loop {
-                let res = self.next().await;
-                if let Some(event) = res {
-                    return event;
-                }
-            }
+        async fn read(&mut self) -> Option<Result<Self::Out, Self::Err>> {
+            self.next().await
         }
     }
 
@@ -0,0 +1,93 @@
+//! reactive bindings: elements that follow the model without running the view again.
+//!
+//! a [`Bind`] on an element computes its widget from the model. messages that only change
+//! bound values, like the tick of a clock, can skip the view: classify them as
+//! [`UpdateKind::BindingsOnly`] with `run().classify(..)` and the runtime only replaces the
+//! widgets of the bound elements before drawing the current tree again.
+//!
+//! # Example
+//!
+//! ```
+//! # use mana_tui_potion::bind::{UpdateKind, bound};
+//! # use mana_tui_elemental::prelude::*;
+//! struct Model {
+//!     clock: u64,
+//! }
+//!
+//! enum Msg {
+//!     Tick,
+//!     Quit,
+//! }
+//!
+//! fn status_bar(model: &Model) -> View {
+//!     bound(model, |model: &Model| Text::raw(format!("uptime {}s", model.clock)))
+//! }
+//!
+//! fn classify(msg: &Msg) -> UpdateKind {
+//!     match msg {
+//!         Msg::Tick => UpdateKind::BindingsOnly,
+//!         Msg::Quit => UpdateKind::Full,
+//!     }
+//! }
+//! ```
+
+use std::sync::Arc;
+
+use hecs::Entity;
+use mana_tui_elemental::{
+    layout::{ElWidget, Element, ElementCtx},
+    ui::{View, ui},
+};
+
+/// computes the widget of an element from the model. the widget must have the type the element
+/// was created with, see [`ElementCtx::replace_widget`].
+pub struct Bind<Model>(Arc<dyn Fn(&mut ElementCtx, Element, &Model) -> bool + Send + Sync>);
+
+impl<Model> Clone for Bind<Model> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<Model: 'static> Bind<Model> {
+    /// a binding that shows the widget returned by `f`.
+    pub fn new<M, W: ElWidget<M>>(f: impl Fn(&Model) -> W + Send + Sync + 'static) -> Self {
+        Self(Arc::new(move |ctx, element, model| {
+            ctx.replace_widget(element, f(model))
+        }))
+    }
+}
+
+/// an element showing the widget returned by `f`, kept up to date by [`Bind`].
+pub fn bound<Model: 'static, M, W: ElWidget<M>>(
+    model: &Model,
+    f: impl Fn(&Model) -> W + Send + Sync + 'static,
+) -> View {
+    let widget = f(model);
+    ui(widget).with((Bind::new(f),)).done()
+}
+
+/// how the runtime applies a message, see the [module docs](self).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UpdateKind {
+    /// spawns the tree returned by the view again.
+    #[default]
+    Full,
+    /// keeps the current tree and only applies the bindings.
+    BindingsOnly,
+}
+
+/// replaces the widgets of every element with a [`Bind`] with the ones computed from `model`.
+/// returns whether an element was resized, the layout has to be calculated again in that case.
+pub fn apply_bindings<Model: 'static>(ctx: &mut ElementCtx, model: &Model) -> bool {
+    let bindings = ctx
+        .query::<(Entity, &Bind<Model>)>()
+        .iter()
+        .map(|(element, bind)| (element, bind.clone()))
+        .collect::<Vec<_>>();
+    let mut resized = false;
+    for (element, bind) in bindings {
+        resized |= (bind.0)(ctx, element, model);
+    }
+    resized
+}
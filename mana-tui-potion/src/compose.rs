@@ -0,0 +1,110 @@
+use std::sync::Arc;
+
+use mana_tui_elemental::ui::View;
+
+use crate::focus::handlers::map_handlers;
+use crate::{Effect, Message};
+
+/// a domain specific part of the app model with its own message type and update function.
+///
+/// implement it with `#[derive(SubModel)]` from `mana-tui-macros` and combine sub models with
+/// [`compose_models!`][crate::compose_models].
+pub trait SubModel: Sized + Send + Sync + 'static {
+    type Msg: Clone + Send + Sync + 'static;
+
+    #[allow(async_fn_in_trait)]
+    async fn update(self, msg: Self::Msg) -> (Self, Effect<Self::Msg>);
+}
+
+/// embeds views that send their own messages, like the items of a
+/// [`keyed_list`](mana_tui_elemental::ui::keyed_list), in the view of the app.
+pub trait MapMsg {
+    /// wraps the messages the event handlers of the view and its children send with `f`. the
+    /// handlers still get the model of the app. subviews are built when they are spawned, so
+    /// their handlers aren't wrapped.
+    #[must_use]
+    fn map_msg<Item, App>(self, f: impl Fn(Item) -> App + Send + Sync + 'static) -> Self
+    where
+        Item: Message<Model = App::Model>,
+        App: Message;
+}
+
+impl MapMsg for View {
+    fn map_msg<Item, App>(mut self, f: impl Fn(Item) -> App + Send + Sync + 'static) -> Self
+    where
+        Item: Message<Model = App::Model>,
+        App: Message,
+    {
+        map_handlers(&mut self, &(Arc::new(f) as Arc<_>));
+        self
+    }
+}
+
+/// combines sub models into a single model, message enum and update function.
+///
+/// every field becomes a variant of the message enum that wraps the sub model's message.
+/// `update` routes each variant to the matching [`SubModel::update`] and maps the returned
+/// effect back into the combined message.
+///
+/// # Example
+///
+/// ```ignore
+/// compose_models! {
+///     #[derive(Debug, Default)]
+///     pub struct MainModel, MainMsg {
+///         auth: AuthModel => Auth,
+///         settings: SettingsModel => Settings,
+///     }
+/// }
+///
+/// run()
+///     .init(async || (MainModel::default(), Effect::none()))
+///     .update(MainModel::update)
+///     // ...
+/// ```
+#[macro_export]
+macro_rules! compose_models {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $model:ident, $msg:ident {
+            $($field:ident: $sub:ty => $variant:ident),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $model {
+            $(pub $field: $sub,)*
+        }
+
+        #[derive(Clone)]
+        $vis enum $msg {
+            $($variant(<$sub as $crate::SubModel>::Msg),)*
+        }
+
+        impl $crate::Message for $msg {
+            type Model = $model;
+        }
+
+        impl $model {
+            /// routes the message to the sub model it belongs to.
+            pub async fn update(self, msg: $msg) -> (Self, $crate::Effect<$msg>) {
+                match msg {
+                    $(
+                        $msg::$variant(msg) => {
+                            let (sub, effect) =
+                                <$sub as $crate::SubModel>::update(self.$field, msg).await;
+                            (Self { $field: sub, ..self }, effect.map($msg::$variant))
+                        }
+                    )*
+                }
+            }
+        }
+
+        impl $crate::SubModel for $model {
+            type Msg = $msg;
+
+            async fn update(self, msg: $msg) -> (Self, $crate::Effect<$msg>) {
+                $model::update(self, msg).await
+            }
+        }
+    };
+}
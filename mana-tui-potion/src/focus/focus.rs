@@ -1,17 +1,20 @@
 pub mod handlers;
+pub mod help;
 
 use std::any::TypeId;
 
 use hecs::{Entity, Or, World};
 use im::Vector;
-use mana_tui_elemental::layout::{Children, Props};
+use mana_tui_elemental::layout::{Children, ElementCtx, Props, Viewport};
+use mana_tui_elemental::text_hit::text_hit;
+use mana_tui_elemental::theme::DefaultStyles;
 use mana_tui_utils::resource::Resources;
 use ratatui::{layout::Rect, style::Style};
 
 use crate::{
     DefaultEvent, Effect, Message,
     backends::{DefaultBackend, DefaultKeyEvent, ManaBackend},
-    focus::handlers::{On, OnClick, OnKey},
+    focus::handlers::{On, OnClick, OnKey, OnTextClick},
 };
 
 #[derive(Debug, Clone, Copy)]
@@ -37,15 +40,41 @@ pub struct NavGroup {
 #[derive(Debug, Clone, Default)]
 pub struct UiStack {
     stack: Vector<NavGroup>,
+    /// when the root was drawn, roots drawn later are on top.
+    order: u64,
 }
 
+/// resource with the next [`UiStack::order`].
+#[derive(Debug, Default)]
+struct StackCounter(u64);
+
+/// the stack is stored on the root element, so every root rendered into its own
+/// [`Viewport`] keeps an independent stack.
 pub(crate) fn generate_ui_stack(world: &mut World, root: Entity) {
     let mut stack = Vector::new();
     let last_group = generate_ui_stack_impl(world, root, &mut stack, NavGroup::default());
     if !last_group.elements.is_empty() {
         stack.push_back(last_group);
     }
-    world.insert_or_update_resource(UiStack { stack });
+    let order = {
+        let mut counter =
+            world.get_or_insert_resource_with::<&mut StackCounter>(|_| StackCounter::default());
+        counter.0 += 1;
+        counter.0
+    };
+    _ = world.insert_one(root, UiStack { stack, order });
+}
+
+/// the stacks of the roots with their [`Viewport`], the topmost one first.
+fn stacks_from_top(world: &World) -> Vec<(UiStack, Option<Viewport>)> {
+    let mut stacks = world
+        .query::<(&UiStack, Option<&Viewport>)>()
+        .iter()
+        .map(|(uistack, viewport)| (uistack.clone(), viewport.copied()))
+        .collect::<Vec<_>>();
+    // hecs iterates by archetype, not in the order the roots are drawn.
+    stacks.sort_by_key(|(uistack, _)| std::cmp::Reverse(uistack.order));
+    stacks
 }
 
 #[tracing::instrument(skip(world))]
@@ -65,10 +94,12 @@ pub(crate) fn generate_ui_stack_impl(
         current_group = NavGroup::default();
     }
 
-    let children = world.get::<&Children>(root);
+    let children = world
+        .get::<&Children>(root)
+        .map(|children| children.visible(world, root));
     if let Ok(children) = children {
         for child in children.iter() {
-            current_group = generate_ui_stack_impl(world, *child, stack, current_group.clone());
+            current_group = generate_ui_stack_impl(world, child, stack, current_group.clone());
         }
     } else {
         stack.push_back(current_group.clone());
@@ -91,12 +122,21 @@ impl FocusTarget {
     }
 }
 
+/// the focus systems run after the layout of every frame.
+pub(crate) fn update_focus<Msg: Message>(world: &mut World, root: Entity) {
+    generate_ui_stack(world, root);
+    init_focus_system(world);
+    handlers::specialize_on_click_or_key_handlers::<Msg>(world);
+    handlers::infer_button_roles::<Msg>(world);
+    _ = set_focus_style(world);
+    help::update_help_bar::<Msg>(world);
+}
+
 pub(crate) fn init_focus_system(world: &mut World) {
     let _ = world.get_or_insert_resource_with::<&FocusContext>(|world| {
-        let ui_stack = world.get_resource::<&UiStack>();
-        let first_focus = ui_stack
-            .ok()
-            .and_then(|stack| stack.stack.iter().next().cloned())
+        let first_focus = stacks_from_top(world)
+            .first()
+            .and_then(|(stack, _)| stack.stack.iter().next().cloned())
             .and_then(|nav_group| nav_group.elements.iter().next().copied());
         let mut ctx = FocusContext { stack: Vec::new() };
         if let Some(entity) = first_focus {
@@ -134,10 +174,10 @@ pub(crate) fn propagate_key_event<Msg: Message>(
     model: &Msg::Model,
     msg: &DefaultEvent,
 ) -> Result<Option<(Msg, Effect<Msg>)>, anyhow::Error> {
-    let stack = world.get_resource::<&UiStack>()?;
+    let stacks = stacks_from_top(world);
     let mut query = world.query::<Or<&On<Msg>, &OnKey<Msg>>>();
     let query = query.view();
-    for group in &stack.stack {
+    for group in stacks.iter().flat_map(|(stack, _)| &stack.stack) {
         for entity in group.elements.iter().copied() {
             if let Some(value) = query.get(entity) {
                 match value {
@@ -158,10 +198,27 @@ pub(crate) fn propagate_key_event<Msg: Message>(
     Ok(None)
 }
 
-pub(crate) fn propagate_mouse_event<Msg: Message>(
+/// passes a resize to the [`On`] handlers in stack order, without moving the focus.
+fn propagate_resize_event<Msg: Message>(
     world: &World,
     model: &Msg::Model,
     msg: &DefaultEvent,
+) -> Option<(Msg, Effect<Msg>)> {
+    let stacks = stacks_from_top(world);
+    let mut query = world.query::<&On<Msg>>();
+    let query = query.view();
+    stacks
+        .iter()
+        .flat_map(|(stack, _)| &stack.stack)
+        .flat_map(|group| group.elements.iter().copied())
+        .filter_map(|entity| query.get(entity))
+        .find_map(|On(on)| on(model, msg))
+}
+
+pub(crate) fn propagate_mouse_event<Msg: Message>(
+    ctx: &ElementCtx,
+    model: &Msg::Model,
+    msg: &DefaultEvent,
     x_coord: u16,
     y_coord: u16,
 ) -> Result<Option<(Msg, Effect<Msg>)>, anyhow::Error> {
@@ -178,11 +235,28 @@ pub(crate) fn propagate_mouse_event<Msg: Message>(
             return Ok(None);
         }
     }
-    let stack = world.get_resource::<&UiStack>()?;
+    let world: &World = ctx;
+    let stacks = stacks_from_top(world);
     let mut query = world.query::<(&OnClick<Msg>, &Props)>();
     let query = query.view();
-    for group in &stack.stack {
+    let mut text_query = world.query::<&OnTextClick<Msg>>();
+    let text_query = text_query.view();
+    let position = ratatui::layout::Position {
+        x: x_coord,
+        y: y_coord,
+    };
+    let stacks = stacks
+        .iter()
+        .filter(|(_, viewport)| viewport.is_none_or(|viewport| viewport.contains(position)));
+    for group in stacks.flat_map(|(stack, _)| &stack.stack) {
         for entity in group.elements.iter().copied() {
+            if let Some(OnTextClick(on_text_click)) = text_query.get(entity)
+                && let Some(hit) = text_hit(entity, ctx, position)
+                && let Some(value) = on_text_click(model, hit)
+            {
+                _ = try_grab_focus(world, entity);
+                return Ok(Some(value));
+            }
             if let Some((OnClick(on_click), props)) = query.get(entity) {
                 let area = Rect {
                     x: props.position.x,
@@ -190,10 +264,7 @@ pub(crate) fn propagate_mouse_event<Msg: Message>(
                     width: props.size.x,
                     height: props.size.y,
                 };
-                if area.contains(ratatui::layout::Position {
-                    x: x_coord,
-                    y: y_coord,
-                }) {
+                if area.contains(position) {
                     try_handler!(world, entity, on_click, model, msg);
                 }
             }
@@ -203,17 +274,18 @@ pub(crate) fn propagate_mouse_event<Msg: Message>(
 }
 
 pub(crate) fn propagate_event<Msg: Message>(
-    world: &World,
+    ctx: &ElementCtx,
     model: &Msg::Model,
     msg: &DefaultEvent,
 ) -> Result<Option<(Msg, Effect<Msg>)>, anyhow::Error> {
     #[cfg(feature = "crossterm")]
     {
         match msg {
-            crossterm::event::Event::Key(_) => propagate_key_event(world, model, msg),
+            crossterm::event::Event::Key(_) => propagate_key_event(ctx, model, msg),
             crossterm::event::Event::Mouse(ev) => {
-                propagate_mouse_event(world, model, msg, ev.column, ev.row)
+                propagate_mouse_event(ctx, model, msg, ev.column, ev.row)
             }
+            crossterm::event::Event::Resize(..) => Ok(propagate_resize_event(ctx, model, msg)),
             _ => Ok(None),
         }
     }
@@ -241,7 +313,7 @@ pub(crate) struct FocusContext {
 }
 
 impl FocusContext {
-    fn top(&self) -> Option<TypeId> {
+    pub(crate) fn top(&self) -> Option<TypeId> {
         self.stack.last().copied()
     }
     fn push(&mut self, value: TypeId) {
@@ -271,18 +343,20 @@ pub(crate) fn set_focus_style(world: &mut World) -> anyhow::Result<()> {
     let current = focus_ctx.top();
     drop(focus_ctx);
 
+    // elements without a focus style of their own use the one of the theme, if there is one.
+    let default_style = world
+        .get_resource::<&DefaultStyles>()
+        .ok()
+        .map(|styles| styles.focus_style);
     let focused_on = world
-        .query_mut::<(Entity, &Props, &FocusTarget, &FocusStyle)>()
+        .query_mut::<(Entity, &Props, &FocusTarget, Option<&FocusStyle>)>()
         .into_iter()
         .find_map(|(entity, props, focus_target, focus_style)| {
-            if Some(focus_target.0) == current {
-                Some((entity, props, focus_style))
-            } else {
-                None
-            }
+            let style = focus_style.map(|style| style.0).or(default_style)?;
+            (Some(focus_target.0) == current).then_some((entity, *props, style))
         });
-    if let Some((entity, &props, &style)) = focused_on {
-        (props.set_style)(world, entity, style.0);
+    if let Some((entity, props, style)) = focused_on {
+        (props.set_style)(world, entity, style);
     }
 
     Ok(())
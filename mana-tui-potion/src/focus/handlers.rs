@@ -1,6 +1,11 @@
 use std::sync::Arc;
 
 use hecs::{CommandBuffer, Entity, World};
+use mana_tui_elemental::{
+    inspect::Role,
+    text_hit::TextHit,
+    ui::{View, visit_views},
+};
 
 use crate::{
     Effect, Message,
@@ -9,6 +14,7 @@ use crate::{
 
 type CallbackRes<Msg> = Option<(Msg, Effect<Msg>)>;
 type Callback<Msg, Model> = Arc<dyn Fn(&Model, &DefaultEvent) -> CallbackRes<Msg>>;
+type TextCallback<Msg, Model> = Arc<dyn Fn(&Model, TextHit) -> CallbackRes<Msg> + Send + Sync>;
 
 #[must_use]
 #[derive(Clone)]
@@ -94,6 +100,45 @@ impl<Msg: Message> OnClickOrKey<Msg> {
     }
 }
 
+/// a click on a character of a [`SourceParagraph`](mana_tui_elemental::text_hit::SourceParagraph)
+/// or [`Text`](ratatui::text::Text) element, gets the [`TextHit`] of the clicked cell. clicks
+/// outside of the text fall through to the elements below.
+#[must_use]
+#[derive(Clone)]
+pub struct OnTextClick<Msg: Message>(pub(crate) TextCallback<Msg, Msg::Model>);
+
+impl<Msg: Message> OnTextClick<Msg> {
+    pub fn new<F>(func: F) -> Self
+    where
+        F: Fn(&Msg::Model, TextHit) -> CallbackRes<Msg> + Send + Sync + 'static,
+    {
+        OnTextClick(Arc::new(func))
+    }
+}
+
+/// marks elements with click or key handlers as [`Role::Button`] for
+/// [`ElementCtx::reading_order`](mana_tui_elemental::layout::ElementCtx::reading_order), unless
+/// they already have a role.
+pub(crate) fn infer_button_roles<Msg: Message>(world: &mut World) {
+    let buttons = world
+        .query::<(
+            Entity,
+            Option<&OnClick<Msg>>,
+            Option<&OnKey<Msg>>,
+            Option<&OnClickOrKey<Msg>>,
+            Option<&Role>,
+        )>()
+        .iter()
+        .filter(|(_, click, key, click_or_key, role)| {
+            role.is_none() && (click.is_some() || key.is_some() || click_or_key.is_some())
+        })
+        .map(|(entity, ..)| entity)
+        .collect::<Vec<_>>();
+    for entity in buttons {
+        _ = world.insert_one(entity, Role::Button);
+    }
+}
+
 pub(crate) fn specialize_on_click_or_key_handlers<Msg: Message>(world: &mut World) {
     let mut cmd = CommandBuffer::new();
 
@@ -107,3 +152,57 @@ pub(crate) fn specialize_on_click_or_key_handlers<Msg: Message>(world: &mut Worl
 
     cmd.run_on(world);
 }
+
+type MsgMap<Item, App> = Arc<dyn Fn(Item) -> App + Send + Sync>;
+
+/// replaces the handlers of `view` and its children with ones that send `App` messages, see
+/// [`MapMsg`](crate::compose::MapMsg). the `Item` handlers stay on the elements, the runtime
+/// only runs the ones of the app message.
+pub(crate) fn map_handlers<Item, App>(view: &mut View, f: &MsgMap<Item, App>)
+where
+    Item: Message<Model = App::Model>,
+    App: Message,
+{
+    visit_views(view, &mut |view| {
+        if let Some(On(on)) = view.get::<&On<Item>>().cloned() {
+            view.add(On(lift(f, on)));
+        }
+        if let Some(OnKey(key, on)) = view.get::<&OnKey<Item>>().cloned() {
+            view.add(OnKey(key, lift(f, on)));
+        }
+        if let Some(OnClick(on)) = view.get::<&OnClick<Item>>().cloned() {
+            view.add(OnClick(lift(f, on)));
+        }
+        if let Some(OnClickOrKey(key, on)) = view.get::<&OnClickOrKey<Item>>().cloned() {
+            view.add(OnClickOrKey(key, lift(f, on)));
+        }
+        if let Some(OnTextClick(on)) = view.get::<&OnTextClick<Item>>().cloned() {
+            let f = f.clone();
+            view.add(OnTextClick::<App>(Arc::new(
+                move |model: &App::Model, hit| map_result(&f, on(model, hit)),
+            )));
+        }
+    });
+}
+
+fn lift<Item, App>(
+    f: &MsgMap<Item, App>,
+    on: Callback<Item, Item::Model>,
+) -> Callback<App, App::Model>
+where
+    Item: Message<Model = App::Model>,
+    App: Message,
+{
+    let f = f.clone();
+    Arc::new(move |model: &App::Model, event: &DefaultEvent| map_result(&f, on(model, event)))
+}
+
+fn map_result<Item, App>(f: &MsgMap<Item, App>, result: CallbackRes<Item>) -> CallbackRes<App>
+where
+    Item: Message,
+    App: Message,
+{
+    let (msg, effect) = result?;
+    let map = f.clone();
+    Some((f(msg), effect.map(move |msg| map(msg))))
+}
@@ -0,0 +1,174 @@
+use std::any::TypeId;
+
+use hecs::{Entity, World};
+use mana_tui_elemental::{
+    layout::{Children, Height, Props, Width},
+    ui::{View, ui},
+};
+use mana_tui_utils::resource::Resources;
+use ratatui::text::Line;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::{
+    Message,
+    backends::DefaultKeyEvent,
+    focus::{FocusContext, FocusPolicy, FocusPopup, FocusTarget, UiStack, handlers::OnKey},
+};
+
+/// describes what the key bound with [`OnKey`] on the same element does. shown by [`help_bar`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyHint(pub &'static str);
+
+/// marker for the element created by [`help_bar`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HelpBar;
+
+const HINT_GAP: &str = "  ";
+const ELLIPSIS: &str = "…";
+
+/// a single line listing the [`KeyHint`]s that are reachable with the current focus, like
+/// `a: add  q: quit  j/k: move`. hints that share a description are merged.
+///
+/// hints inside a [`FocusTarget`] are only listed while that target is focused. if the focused
+/// target is a [`FocusPopup`] or has [`FocusPolicy::Block`], only its own hints are listed.
+#[must_use]
+pub fn help_bar() -> View {
+    ui(Line::default())
+        .with((HelpBar, Width::grow(), Height::fixed(1)))
+        .done()
+}
+
+pub(crate) fn update_help_bar<Msg: Message>(world: &mut World) {
+    let bars = world
+        .query_mut::<(Entity, &HelpBar, &Props)>()
+        .into_iter()
+        .map(|(entity, _, props)| (entity, props.size.x))
+        .collect::<Vec<_>>();
+    if bars.is_empty() {
+        return;
+    }
+
+    let hints = collect_hints::<Msg>(world);
+    let text = hints
+        .iter()
+        .map(|(keys, hint)| format!("{}: {hint}", keys.join("/")))
+        .collect::<Vec<_>>()
+        .join(HINT_GAP);
+
+    for (entity, width) in bars {
+        let Ok(mut line) = world.get::<&mut Line<'static>>(entity) else {
+            continue;
+        };
+        let style = line.style;
+        *line = Line::raw(truncate(&text, width as usize)).style(style);
+    }
+}
+
+fn collect_hints<Msg: Message>(world: &World) -> Vec<(Vec<String>, &'static str)> {
+    let focused = world
+        .get_resource::<&FocusContext>()
+        .ok()
+        .and_then(|ctx| ctx.top());
+
+    let mut roots = world
+        .query::<(Entity, &UiStack)>()
+        .iter()
+        .map(|(entity, _)| entity)
+        .collect::<Vec<_>>();
+
+    // a blocking focus target hides every hint outside of it.
+    let blocking = world
+        .query::<(
+            Entity,
+            &FocusTarget,
+            Option<&FocusPopup>,
+            Option<&FocusPolicy>,
+        )>()
+        .iter()
+        .find(|(_, target, popup, policy)| {
+            Some(target.0) == focused
+                && (popup.is_some() || matches!(policy, Some(FocusPolicy::Block)))
+        })
+        .map(|(entity, ..)| entity);
+    if let Some(entity) = blocking {
+        roots = vec![entity];
+    }
+
+    let mut hints: Vec<(Vec<String>, &'static str)> = Vec::new();
+    for root in roots {
+        collect_hints_impl::<Msg>(world, root, None, focused, &mut hints);
+    }
+    hints
+}
+
+fn collect_hints_impl<Msg: Message>(
+    world: &World,
+    element: Entity,
+    scope: Option<TypeId>,
+    focused: Option<TypeId>,
+    hints: &mut Vec<(Vec<String>, &'static str)>,
+) {
+    let scope = world
+        .get::<&FocusTarget>(element)
+        .map_or(scope, |target| Some(target.0));
+    if scope.is_some() && scope != focused {
+        return;
+    }
+
+    let mut query = world.query_one::<(&KeyHint, &OnKey<Msg>)>(element);
+    if let Ok((KeyHint(hint), OnKey(key, _))) = query.get() {
+        let key = key_label(key);
+        match hints.iter_mut().find(|(_, other)| other == hint) {
+            Some((keys, _)) => keys.push(key),
+            None => hints.push((vec![key], *hint)),
+        }
+    }
+    drop(query);
+
+    let Ok(children) = world
+        .get::<&Children>(element)
+        .map(|children| children.clone())
+    else {
+        return;
+    };
+    for child in children.iter() {
+        collect_hints_impl::<Msg>(world, child, scope, focused, hints);
+    }
+}
+
+fn key_label(key: &DefaultKeyEvent) -> String {
+    use crossterm::event::{KeyCode, KeyModifiers};
+
+    let code = match key.code {
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Char(ch) => ch.to_string(),
+        code => code.to_string().to_lowercase(),
+    };
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        format!("ctrl+{code}")
+    } else if key.modifiers.contains(KeyModifiers::ALT) {
+        format!("alt+{code}")
+    } else {
+        code
+    }
+}
+
+/// cuts `text` to `width` columns, wide characters that don't fit anymore are left out.
+fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for ch in text.chars() {
+        used += ch.width().unwrap_or(0);
+        if used > width.saturating_sub(ELLIPSIS.width()) {
+            break;
+        }
+        out.push(ch);
+    }
+    if width > 0 {
+        out.push_str(ELLIPSIS);
+    }
+    out
+}
@@ -0,0 +1,404 @@
+//! golden frames, visual regression tests for whole sessions instead of single buffers.
+//!
+//! a [`FrameRecorder`] keeps every frame that is drawn. turn it on for a real session with
+//! [`RuntimeOptions::record_frames`](crate::RuntimeOptions::record_frames), or draw a scripted
+//! session without a terminal with [`replay_frames`]. [`FrameRecorder::compare`] checks the
+//! frames against a `.frames` file and reports the first one that changed, with a text diff.
+//!
+//! run the tests with [`BLESS_ENV`] set to write the files instead, once the changes are
+//! wanted: `MANA_BLESS=1 cargo test`.
+//!
+//! # Format
+//!
+//! a text file, so changed recordings show up in reviews:
+//!
+//! ```text
+//! mana-frames 1
+//! style <fg> <bg> <modifier bits>
+//! frame <width> <height>
+//! <cells> <style> <symbol>
+//! ```
+//!
+//! styles are numbered in the order they are listed. every frame is followed by runs of equal
+//! cells, row by row. only the symbol, colors and modifiers of a cell are kept.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use mana_tui_elemental::prelude::*;
+//! # use mana_tui_potion::{Effect, Message, frames::replay_frames};
+//! # use ratatui::layout::Size;
+//! #[derive(Debug, Clone)]
+//! enum Msg {
+//!     Inc,
+//! }
+//!
+//! impl Message for Msg {
+//!     type Model = u32;
+//! }
+//!
+//! # async fn test() {
+//! let frames = replay_frames(
+//!     Size::new(12, 1),
+//!     [Msg::Inc, Msg::Inc],
+//!     async || (0, Effect::none()),
+//!     async |count: &u32| ui(Text::raw(format!("count: {count}"))).done(),
+//!     async |count: u32, _| (count + 1, Effect::none()),
+//! )
+//! .await;
+//! frames.assert_frames("tests/frames/counter.frames");
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::io;
+use std::path::Path;
+
+use mana_tui_elemental::layout::{Element, ElementCtx};
+use ratatui::{
+    buffer::{Buffer, Cell},
+    layout::{Position, Rect, Size},
+    style::{Color, Modifier},
+};
+
+use crate::{
+    InitFn, Message, UpdateFn, focus,
+    view_ctx::{ViewCtx, ViewFn},
+};
+
+/// the environment variable that makes [`FrameRecorder::compare`] write the recording instead of
+/// checking it.
+pub const BLESS_ENV: &str = "MANA_BLESS";
+
+const HEADER: &str = "mana-frames 1";
+
+/// the frames of a session, see the [module docs](self).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrameRecorder {
+    frames: Vec<Buffer>,
+}
+
+/// the error of [`FrameRecorder::compare`].
+#[derive(thiserror::Error, Debug)]
+pub enum CompareError {
+    /// the recording is missing or broken.
+    #[error("failed to read the recording, run with MANA_BLESS=1 to write it: {0}")]
+    Io(#[from] io::Error),
+    /// a frame differs from the recording.
+    #[error("frame {frame} differs from the recording:\n{diff}")]
+    Mismatch {
+        /// the index of the frame.
+        frame: usize,
+        /// the rows that changed.
+        diff: String,
+    },
+    /// every frame matched, but there are more or fewer of them.
+    #[error("drew {drawn} frames, the recording has {recorded}")]
+    FrameCount {
+        /// the frames of the session.
+        drawn: usize,
+        /// the frames of the recording.
+        recorded: usize,
+    },
+}
+
+impl FrameRecorder {
+    /// a recorder without frames.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// keeps `buf` as the next frame, moved to the origin.
+    pub fn record(&mut self, buf: &Buffer) {
+        self.frames.push(Buffer {
+            area: Rect::new(0, 0, buf.area.width, buf.area.height),
+            content: buf.content.iter().map(normalize).collect(),
+        });
+    }
+
+    /// the recorded frames, in order.
+    #[must_use]
+    pub fn frames(&self) -> &[Buffer] {
+        &self.frames
+    }
+
+    /// the frames in the `.frames` format.
+    #[must_use]
+    pub fn encode(&self) -> String {
+        let mut styles = Vec::new();
+        let mut ids = HashMap::new();
+        let mut body = String::new();
+        for frame in &self.frames {
+            _ = writeln!(body, "frame {} {}", frame.area.width, frame.area.height);
+            for run in frame.content.chunk_by(|a, b| a == b) {
+                let cell = &run[0];
+                let key = (cell.fg, cell.bg, cell.modifier.bits());
+                let id = *ids.entry(key).or_insert_with(|| {
+                    styles.push(key);
+                    styles.len() - 1
+                });
+                _ = writeln!(body, "{} {id} {}", run.len(), escape(cell.symbol()));
+            }
+        }
+        let mut out = format!("{HEADER}\n");
+        for (fg, bg, modifier) in styles {
+            _ = writeln!(out, "style {fg} {bg} {modifier}");
+        }
+        out + &body
+    }
+
+    /// reads frames written by [`FrameRecorder::encode`].
+    ///
+    /// # Errors
+    ///
+    /// [`io::ErrorKind::InvalidData`] with the line that isn't part of the format.
+    pub fn decode(text: &str) -> io::Result<Self> {
+        let mut lines = text.lines().enumerate();
+        if lines.next().map(|(_, line)| line) != Some(HEADER) {
+            return Err(invalid_data(0, "not a frames file"));
+        }
+        let mut styles = Vec::new();
+        let mut frames = Vec::<Buffer>::new();
+        for (index, line) in lines {
+            let invalid = |message: &str| invalid_data(index, message);
+            if let Some(style) = line.strip_prefix("style ") {
+                let mut parts = style.split(' ');
+                let (Some(fg), Some(bg), Some(modifier)) =
+                    (parts.next(), parts.next(), parts.next())
+                else {
+                    return Err(invalid("expected a style"));
+                };
+                let color = |color: &str| color.parse::<Color>().map_err(|_| invalid("bad color"));
+                let modifier = modifier.parse().map_err(|_| invalid("bad modifier"))?;
+                styles.push((
+                    color(fg)?,
+                    color(bg)?,
+                    Modifier::from_bits_truncate(modifier),
+                ));
+            } else if let Some(size) = line.strip_prefix("frame ") {
+                let (width, height) = size
+                    .split_once(' ')
+                    .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+                    .ok_or_else(|| invalid("expected a size"))?;
+                frames.push(Buffer {
+                    area: Rect::new(0, 0, width, height),
+                    content: Vec::new(),
+                });
+            } else {
+                let frame = frames
+                    .last_mut()
+                    .ok_or_else(|| invalid("cells before the first frame"))?;
+                let mut parts = line.splitn(3, ' ');
+                let (Some(count), Some(style), Some(symbol)) =
+                    (parts.next(), parts.next(), parts.next())
+                else {
+                    return Err(invalid("expected a run of cells"));
+                };
+                let count = count.parse().map_err(|_| invalid("bad cell count"))?;
+                let (fg, bg, modifier) = style
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|style| styles.get(style).copied())
+                    .ok_or_else(|| invalid("unknown style"))?;
+                let mut cell = Cell::default();
+                cell.set_symbol(&unescape(symbol));
+                cell.fg = fg;
+                cell.bg = bg;
+                cell.modifier = modifier;
+                frame.content.extend(std::iter::repeat_n(cell, count));
+            }
+        }
+        if let Some(index) = frames
+            .iter()
+            .position(|frame| frame.content.len() != frame.area.area() as usize)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("frame {index} doesn't have a cell for every position"),
+            ));
+        }
+        Ok(Self { frames })
+    }
+
+    /// writes the frames to `path`.
+    ///
+    /// # Errors
+    ///
+    /// if the file can't be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, self.encode())
+    }
+
+    /// reads the frames saved at `path`.
+    ///
+    /// # Errors
+    ///
+    /// if the file can't be read or isn't a `.frames` file.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::decode(&std::fs::read_to_string(path)?)
+    }
+
+    /// checks the frames against the recording at `path`. with [`BLESS_ENV`] set, the frames are
+    /// saved as the new recording instead.
+    ///
+    /// # Errors
+    ///
+    /// the first frame that differs, see [`CompareError`].
+    pub fn compare(&self, path: impl AsRef<Path>) -> Result<(), CompareError> {
+        let bless = std::env::var_os(BLESS_ENV).is_some_and(|var| !var.is_empty() && var != "0");
+        self.compare_or_bless(path.as_ref(), bless)
+    }
+
+    /// [`FrameRecorder::compare`] for tests.
+    ///
+    /// # Panics
+    ///
+    /// with the diff of the first frame that changed.
+    #[track_caller]
+    pub fn assert_frames(&self, path: impl AsRef<Path>) {
+        if let Err(err) = self.compare(path) {
+            panic!("{err}");
+        }
+    }
+
+    pub(crate) fn compare_or_bless(&self, path: &Path, bless: bool) -> Result<(), CompareError> {
+        if bless {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            return Ok(self.save(path)?);
+        }
+        let recorded = Self::load(path)?;
+        let pairs = recorded.frames.iter().zip(&self.frames);
+        if let Some((frame, (expected, drawn))) = pairs
+            .enumerate()
+            .find(|(_, (expected, drawn))| expected != drawn)
+        {
+            return Err(CompareError::Mismatch {
+                frame,
+                diff: diff(expected, drawn),
+            });
+        }
+        if recorded.frames.len() != self.frames.len() {
+            return Err(CompareError::FrameCount {
+                drawn: self.frames.len(),
+                recorded: recorded.frames.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// draws the model after `init` and after each of the `recorded` messages, in a terminal of
+/// `size`, like [`replay`](crate::middleware::replay) does for the model.
+///
+/// effects are dropped, record the messages they send instead.
+pub async fn replay_frames<Msg: Message>(
+    size: Size,
+    recorded: impl IntoIterator<Item = Msg>,
+    init: impl InitFn<Msg, Msg::Model>,
+    view: impl ViewFn<Msg, Msg::Model>,
+    update: impl UpdateFn<Msg, Msg::Model>,
+) -> FrameRecorder {
+    let area = Rect::from((Position::ORIGIN, size));
+    let view_ctx = ViewCtx::new(size);
+    let mut recorder = FrameRecorder::new();
+    let mut ctx = ElementCtx::new();
+    let (mut model, _) = init().await;
+    let mut root = ctx.spawn_ui(view.view(&model, &view_ctx).await);
+    recorder.record(&draw::<Msg>(&mut ctx, root, area));
+    for msg in recorded {
+        (model, _) = update(model, msg).await;
+        let tree = view.view(&model, &view_ctx).await;
+        ctx.despawn_ui(root);
+        root = ctx.spawn_ui(tree);
+        recorder.record(&draw::<Msg>(&mut ctx, root, area));
+    }
+    recorder
+}
+
+/// a frame of `root`, drawn like the runtime does without a terminal.
+fn draw<Msg: Message>(ctx: &mut ElementCtx, root: Element, area: Rect) -> Buffer {
+    let mut buf = Buffer::empty(area);
+    if let Err(err) = ctx.calculate_layout(root, area) {
+        tracing::error!("failed to calculate layout: {err}");
+        return buf;
+    }
+    focus::update_focus::<Msg>(ctx, root);
+    ctx.render(root, area, &mut buf);
+    buf
+}
+
+/// the parts of `cell` that are recorded.
+fn normalize(cell: &Cell) -> Cell {
+    let mut normal = Cell::default();
+    normal.set_symbol(cell.symbol());
+    normal.fg = cell.fg;
+    normal.bg = cell.bg;
+    normal.modifier = cell.modifier;
+    normal
+}
+
+/// the rows of `drawn` that differ from `expected`, both at the origin.
+fn diff(expected: &Buffer, drawn: &Buffer) -> String {
+    if expected.area != drawn.area {
+        return format!(
+            "drew {}x{}, the recording is {}x{}\n",
+            drawn.area.width, drawn.area.height, expected.area.width, expected.area.height
+        );
+    }
+    let mut out = String::new();
+    for y in 0..drawn.area.height {
+        let row = |buf: &Buffer| {
+            (0..buf.area.width)
+                .map(|x| buf[Position::new(x, y)].symbol())
+                .collect::<String>()
+        };
+        let (recorded, row) = (row(expected), row(drawn));
+        if recorded != row {
+            _ = writeln!(out, "row {y}:\n  - |{recorded}|\n  + |{row}|");
+        } else if let Some(x) = (0..drawn.area.width)
+            .find(|&x| expected[Position::new(x, y)] != drawn[Position::new(x, y)])
+        {
+            _ = writeln!(
+                out,
+                "row {y}: the style changed from column {x}\n    |{row}|"
+            );
+        }
+    }
+    out
+}
+
+fn invalid_data(index: usize, message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("line {}: {message}", index + 1),
+    )
+}
+
+fn escape(symbol: &str) -> String {
+    symbol
+        .replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn unescape(symbol: &str) -> String {
+    let mut out = String::with_capacity(symbol.len());
+    let mut chars = symbol.chars();
+    while let Some(char) = chars.next() {
+        if char != '\\' {
+            out.push(char);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('r') => out.push('\r'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
@@ -1,26 +1,53 @@
 #![feature(trait_alias)]
 #![allow(clippy::collapsible_if)]
 
+extern crate self as mana_tui_potion;
+
 pub mod backends;
+pub mod bind;
+pub mod compose;
 #[path = "./focus/focus.rs"]
 pub mod focus;
+pub mod frames;
+pub mod lifecycle;
+pub mod metrics;
+pub mod middleware;
+pub mod options;
+pub mod reload;
+pub mod suspense;
+pub mod view_ctx;
+
+use std::{sync::Arc, time::Instant};
 
 use flume::{Receiver, Sender};
 use hecs::Component;
 use mana_tui_elemental::{
-    layout::{Element, ElementCtx},
+    color::ColorCaps,
+    layout::{CursorShape, Element, ElementCtx},
     ui::View,
 };
-use ratatui::{Terminal, prelude::Backend};
+use mana_tui_utils::resource::Resources;
+use ratatui::{Terminal, buffer::Buffer, layout::Position, prelude::Backend};
 use smallbox::SmallBox;
 use tailcall::tailcall;
 
-use crate::backends::{DefaultBackend, DefaultEvent, ManaBackend, MsgStream};
+pub use crate::backends::TerminalCommand;
+use crate::backends::{
+    DefaultBackend, DefaultEvent, DefaultKeyEvent, EventStream, ManaBackend, MsgStream,
+};
+use crate::bind::UpdateKind;
+pub use crate::compose::{MapMsg, SubModel};
+use crate::frames::FrameRecorder;
+pub use crate::lifecycle::QuitDecision;
+use crate::lifecycle::Shutdown;
+use crate::middleware::Middleware;
+pub use crate::options::RuntimeOptions;
+use crate::view_ctx::ViewCtx;
+pub use crate::view_ctx::ViewFn;
 
 pub type Chan<Msg> = (Sender<Msg>, Receiver<Msg>);
 pub trait UpdateFn<Msg, Model> = AsyncFn(Model, Msg) -> (Model, Effect<Msg>) + Component;
 pub trait InitFn<Msg, Model> = AsyncFn() -> (Model, Effect<Msg>) + Component;
-pub trait ViewFn<Msg, Model> = AsyncFn(&Model) -> View + Component;
 pub trait SignalFn<Msg, Model> = Fn(&Model, &Msg) -> bool;
 
 type PinnedFuture<R> = SmallBox<dyn Future<Output = R> + Send + Sync + 'static, [usize; 4]>;
@@ -39,7 +66,10 @@ where
         SmallBox::<Fut, [usize; 4]>::new(future as _)
     }
 }
-pub struct Effect<Msg>(SmallBox<dyn EffectFn<Msg>, [usize; 4]>);
+pub struct Effect<Msg>(
+    SmallBox<dyn EffectFn<Msg>, [usize; 4]>,
+    Vec<TerminalCommand>,
+);
 
 impl<Msg: Send + Sync + 'static> Effect<Msg> {
     #[must_use]
@@ -52,13 +82,90 @@ impl<Msg: Send + Sync + 'static> Effect<Msg> {
     >(
         f: F,
     ) -> Self {
-        Self(SmallBox::new(f) as _)
+        Self(SmallBox::new(f) as _, Vec::new())
+    }
+    /// an effect that runs `command` against the terminal before the next frame.
+    #[must_use]
+    pub fn terminal(command: TerminalCommand) -> Self {
+        let mut effect = Self::none();
+        effect.1.push(command);
+        effect
+    }
+    /// converts the messages sent by the effect, for example from a sub model's message to the
+    /// app message.
+    pub fn map<U, F>(mut self, f: F) -> Effect<U>
+    where
+        U: Send + Sync + 'static,
+        F: Fn(Msg) -> U + Send + Sync + 'static,
+    {
+        let commands = std::mem::take(&mut self.1);
+        let effect = Arc::new(self);
+        let f = Arc::new(f);
+        let mut mapped = Effect::new(move |tx: Sender<U>| {
+            let effect = effect.clone();
+            let f = f.clone();
+            async move {
+                let (sub_tx, sub_rx) = flume::unbounded();
+                let run = effect.0.run_effect(sub_tx);
+                let forward = async {
+                    while let Ok(msg) = sub_rx.recv_async().await {
+                        if tx.send_async(f(msg)).await.is_err() {
+                            break;
+                        }
+                    }
+                };
+                tokio::join!(run, forward);
+            }
+        });
+        mapped.1 = commands;
+        mapped
+    }
+    /// a copy of every message the effect sends also goes to the returned receiver, to observe
+    /// what an effect does in tests without a runtime.
+    #[must_use]
+    pub fn to_channel(mut self) -> (Self, Receiver<Msg>)
+    where
+        Msg: Clone,
+    {
+        let commands = std::mem::take(&mut self.1);
+        let effect = Arc::new(self);
+        let (observe_tx, observe_rx) = flume::unbounded();
+        let mut observed = Effect::new(move |tx: Sender<Msg>| {
+            let effect = effect.clone();
+            let observe_tx = observe_tx.clone();
+            async move {
+                let (sub_tx, sub_rx) = flume::unbounded();
+                let run = effect.0.run_effect(sub_tx);
+                let forward = async {
+                    while let Ok(msg) = sub_rx.recv_async().await {
+                        // nobody might be observing anymore.
+                        _ = observe_tx.send(msg.clone());
+                        if tx.send_async(msg).await.is_err() {
+                            break;
+                        }
+                    }
+                };
+                tokio::join!(run, forward);
+            }
+        });
+        observed.1 = commands;
+        (observed, observe_rx)
     }
 }
 
 enum RuntimeMsg<Msg> {
     App(Msg),
     Term(DefaultEvent),
+    /// redraw the current tree to advance running animations.
+    Frame,
+    /// a scheduled animation is due, see [`mana_tui_elemental::schedule`].
+    Tick,
+    /// a [`suspense::suspense`] loader finished.
+    Resolved(u64, View),
+    /// the terminal event stream ended.
+    Shutdown,
+    /// the dev server has a new build, see [`reload`].
+    Reload,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -69,6 +176,10 @@ pub enum RuntimeErr {
     PropagateEventError,
     #[error("error initializing runtine")]
     InitErr,
+    /// the model was saved for a rebuild of the `mx` dev server, exit with
+    /// [`reload::RELOAD_EXIT_CODE`] so it starts the new build.
+    #[error("the app is reloading")]
+    Reload,
 }
 
 #[derive(derive_more::Deref, derive_more::DerefMut)]
@@ -77,79 +188,300 @@ pub struct Ctx<B: Backend> {
     #[deref_mut]
     el_ctx: ElementCtx,
     terminal: Terminal<B>,
+    last_frame: Instant,
+    last_draw: Instant,
+    animating: bool,
+    metrics_key: Option<DefaultKeyEvent>,
+    profiler_key: Option<DefaultKeyEvent>,
+    synchronized_output: bool,
+    /// what the tree drew in the last frame, see [`ElementCtx::render_incremental`].
+    shadow: Buffer,
+    last_cursor: Option<(Position, CursorShape)>,
+    /// the runtime stopped for a [`RuntimeMsg::Reload`].
+    reloading: bool,
+    /// the drawn frames, see [`RuntimeOptions::record_frames`].
+    recorder: Option<FrameRecorder>,
+}
+
+/// what a run of the [`runtime`] is made of, it doesn't change until the runtime exits.
+struct Runtime<Msg: Message, V, U, S> {
+    view: V,
+    update: U,
+    quit_signal: S,
+    before_quit: lifecycle::BeforeQuitFn<Msg::Model, Msg>,
+    shutdown: Shutdown<Msg>,
+    middleware: Option<Middleware<Msg, Msg::Model>>,
+    classify: Option<fn(&Msg) -> UpdateKind>,
 }
 
 #[tailcall]
-async fn runtime<Msg: Message, B: 'static + ManaBackend>(
+async fn runtime<Msg, B, E, V, U, S>(
     model: Msg::Model,
-    view: impl ViewFn<Msg, Msg::Model>,
-    update: impl UpdateFn<Msg, Msg::Model>,
-    quit_signal: impl SignalFn<Msg, Msg::Model>,
-    mut msg_stream: MsgStream<Msg>,
+    rt: &Runtime<Msg, V, U, S>,
+    mut msg_stream: MsgStream<Msg, E>,
     ctx: &mut Ctx<B>,
     prev_root: Option<Element>,
-) -> Result<(), RuntimeErr> {
-    let msg = MsgStream::<Msg>::next(&mut msg_stream).await;
+) -> Result<Msg::Model, (Msg::Model, RuntimeErr)>
+where
+    Msg: Message,
+    B: 'static + ManaBackend<Event = DefaultEvent, KeyEvent = DefaultKeyEvent>,
+    E: EventStream<Out = DefaultEvent>,
+    V: ViewFn<Msg, Msg::Model>,
+    U: UpdateFn<Msg, Msg::Model>,
+    S: SignalFn<Msg, Msg::Model>,
+{
+    let Runtime {
+        view,
+        update,
+        quit_signal,
+        before_quit,
+        shutdown,
+        middleware,
+        classify,
+    } = rt;
+    let deadline = ctx.el_ctx.next_animation_deadline();
+    let msg = MsgStream::next(&mut msg_stream, ctx.animating, deadline).await;
+    metrics::record_queue_len(&mut ctx.el_ctx, msg_stream.dispatch.1.len());
     match msg {
-        RuntimeMsg::App(msg) if quit_signal(&model, &msg) => Ok(()),
+        // the escape hatch for apps that don't handle the quit key or can't see the terminal
+        // anymore, the quit signal and the before quit hook are skipped.
+        RuntimeMsg::Term(event) if shutdown.is_quit_key(&event) => {
+            Ok(shutdown.run(model, update, middleware.as_ref()).await)
+        }
+        RuntimeMsg::Shutdown => Ok(shutdown.run(model, update, middleware.as_ref()).await),
+        // `run` saves the model and exits, the dev server starts the new build.
+        RuntimeMsg::Reload => {
+            ctx.reloading = true;
+            Ok(model)
+        }
+        RuntimeMsg::App(msg) if quit_signal(&model, &msg) => {
+            if lifecycle::should_quit(before_quit(&model), &msg_stream.dispatch.0) {
+                Ok(model)
+            } else {
+                runtime(model, rt, msg_stream, ctx, prev_root)
+            }
+        }
         RuntimeMsg::App(msg) => {
-            let (model, effect) = update(model, msg).await;
-            tokio::spawn(effect.0.run_effect(msg_stream.dispatch.0.clone()));
-            let root = view(&model).await;
-            if let Some(prev) = prev_root {
-                ctx.despawn_ui(prev);
-            }
-            let root = render::<Msg, B>(ctx, root);
-
-            runtime(
-                model,
-                view,
-                update,
-                quit_signal,
-                msg_stream,
-                ctx,
-                Some(root),
-            )
+            let kind = classify.map_or(UpdateKind::Full, |classify| classify(&msg));
+            let (model, effect) =
+                crate::middleware::apply(model, msg, update, middleware.as_ref()).await;
+            spawn_effect(ctx, effect, &msg_stream.dispatch.0);
+            let root = match prev_root {
+                // the layout is calculated on every draw, so resized elements need nothing else.
+                Some(root) if kind == UpdateKind::BindingsOnly => {
+                    bind::apply_bindings(&mut ctx.el_ctx, &model);
+                    draw::<Msg, B>(ctx, root);
+                    root
+                }
+                _ => {
+                    let view_ctx = ViewCtx::capture(ctx);
+                    let root = view.view(&model, &view_ctx).await;
+                    if let Some(prev) = prev_root {
+                        suspense::detach_suspense(&mut ctx.el_ctx);
+                        ctx.despawn_ui(prev);
+                    }
+                    render::<Msg, B>(ctx, root)
+                }
+            };
+
+            runtime(model, rt, msg_stream, ctx, Some(root))
+        }
+        RuntimeMsg::Resolved(key, resolved) => {
+            let root = if suspense::resolve(&mut ctx.el_ctx, key, resolved) {
+                let view_ctx = ViewCtx::capture(ctx);
+                let root = view.view(&model, &view_ctx).await;
+                if let Some(prev) = prev_root {
+                    suspense::detach_suspense(&mut ctx.el_ctx);
+                    ctx.despawn_ui(prev);
+                }
+                Some(render::<Msg, B>(ctx, root))
+            } else {
+                prev_root
+            };
+            runtime(model, rt, msg_stream, ctx, root)
+        }
+        RuntimeMsg::Term(event @ crossterm::event::Event::Resize(width, height)) => {
+            ctx.el_ctx.on_resize(width, height);
+            // the shadow has the old size, redraw everything at the new one.
+            ctx.shadow = Buffer::default();
+            if let Some(root) = prev_root {
+                draw::<Msg, B>(ctx, root);
+            }
+            // apps see the resize too, after the redraw at the new size.
+            match propagate(ctx, &model, &event, &msg_stream.dispatch.0).await {
+                Ok(()) => runtime(model, rt, msg_stream, ctx, prev_root),
+                Err(err) => Err((model, err)),
+            }
+        }
+        RuntimeMsg::Term(event)
+            if ctx.metrics_key.is_some() && B::event_as_key(event.clone()) == ctx.metrics_key =>
+        {
+            metrics::toggle(&mut ctx.el_ctx);
+            // the overlay isn't part of the tree, force a full redraw to add or remove it.
+            ctx.shadow = Buffer::default();
+            if let Some(root) = prev_root {
+                draw::<Msg, B>(ctx, root);
+            }
+            runtime(model, rt, msg_stream, ctx, prev_root)
+        }
+        RuntimeMsg::Term(event)
+            if ctx.profiler_key.is_some() && B::event_as_key(event.clone()) == ctx.profiler_key =>
+        {
+            metrics::toggle_profiler(&mut ctx.el_ctx);
+            ctx.shadow = Buffer::default();
+            if let Some(root) = prev_root {
+                draw::<Msg, B>(ctx, root);
+            }
+            runtime(model, rt, msg_stream, ctx, prev_root)
         }
         RuntimeMsg::Term(event) => {
-            let result = focus::propagate_event::<Msg>(&ctx.el_ctx, &model, &event)
-                .map_err(|_| RuntimeErr::PropagateEventError)?;
-            if let Some((msg, effect)) = result {
-                tokio::spawn(effect.0.run_effect(msg_stream.dispatch.0.clone()));
-                msg_stream
-                    .dispatch
-                    .0
-                    .send_async(msg)
-                    .await
-                    .map_err(|_| RuntimeErr::ChannelClosed)?;
+            match propagate(ctx, &model, &event, &msg_stream.dispatch.0).await {
+                Ok(()) => runtime(model, rt, msg_stream, ctx, prev_root),
+                // the model goes back with the error, so `on_exit` still sees it.
+                Err(err) => Err((model, err)),
+            }
+        }
+        RuntimeMsg::Frame => {
+            if let Some(root) = prev_root {
+                draw::<Msg, B>(ctx, root);
+            } else {
+                ctx.animating = false;
+            }
+            runtime(model, rt, msg_stream, ctx, prev_root)
+        }
+        // only drawn when a callback changed something.
+        RuntimeMsg::Tick => {
+            if ctx.el_ctx.tick_scheduled(clock())
+                && let Some(root) = prev_root
+            {
+                draw::<Msg, B>(ctx, root);
             }
-            runtime(model, view, update, quit_signal, msg_stream, ctx, prev_root)
+            runtime(model, rt, msg_stream, ctx, prev_root)
+        }
+    }
+}
+
+/// runs the terminal commands of the effect right away and spawns the rest of it.
+/// passes a terminal event to the handlers of the tree, and sends the message they return.
+async fn propagate<Msg: Message, B: ManaBackend>(
+    ctx: &mut Ctx<B>,
+    model: &Msg::Model,
+    event: &DefaultEvent,
+    tx: &Sender<Msg>,
+) -> Result<(), RuntimeErr> {
+    match focus::propagate_event::<Msg>(&ctx.el_ctx, model, event) {
+        Ok(Some((msg, effect))) => {
+            spawn_effect(ctx, effect, tx);
+            tx.send_async(msg)
+                .await
+                .map_err(|_| RuntimeErr::ChannelClosed)
         }
+        Ok(None) => Ok(()),
+        Err(_) => Err(RuntimeErr::PropagateEventError),
+    }
+}
+
+fn spawn_effect<Msg: Message, B: ManaBackend>(
+    ctx: &mut Ctx<B>,
+    effect: Effect<Msg>,
+    tx: &Sender<Msg>,
+) {
+    let Effect(effect, commands) = effect;
+    for command in &commands {
+        execute_command(ctx, command);
+    }
+    tokio::spawn(effect.run_effect(tx.clone()));
+}
+
+fn execute_command<B: ManaBackend>(ctx: &mut Ctx<B>, command: &TerminalCommand) {
+    if let Err(err) = ctx.terminal.backend_mut().execute_command(command) {
+        tracing::error!("failed to execute {command:?}: {err}");
     }
 }
 
-fn render<Msg: Message, B: Backend>(ctx: &mut Ctx<B>, view: View) -> Element {
+fn render<Msg: Message, B: ManaBackend>(ctx: &mut Ctx<B>, view: View) -> Element {
     let root = ctx.spawn_ui(view);
-    let result = ctx.terminal.draw(|frame| {
-        let result = ctx.el_ctx.calculate_layout(root, frame.area());
-        focus::generate_ui_stack(&mut ctx.el_ctx, root);
-        focus::init_focus_system(&mut ctx.el_ctx);
-        focus::handlers::specialize_on_click_or_key_handlers::<Msg>(&mut ctx.el_ctx);
-        _ = focus::set_focus_style(&mut ctx.el_ctx);
+    suspense::update_suspense(&mut ctx.el_ctx);
+    // animations of a freshly spawned tree start from zero.
+    ctx.last_frame = Instant::now();
+    draw::<Msg, B>(ctx, root);
+    root
+}
 
-        if let Err(err) = result {
-            tracing::error!("failed to calculate layout: {err}");
-            return;
-        }
+/// the time of the scheduled animations, tokio's clock so paused tests can advance it.
+fn clock() -> Instant {
+    tokio::time::Instant::now().into_std()
+}
+
+fn draw<Msg: Message, B: ManaBackend>(ctx: &mut Ctx<B>, root: Element) {
+    let now = Instant::now();
+    let dt = now.duration_since(ctx.last_frame);
+    ctx.last_frame = now;
+    ctx.animating = ctx.el_ctx.tick_animations(dt);
+    // spinners wake the runtime at their own interval for as long as they are shown.
+    ctx.el_ctx.tick_spinners(now);
+    ctx.el_ctx.schedule_spinners(clock());
+    let frame_time = now.duration_since(ctx.last_draw);
+    ctx.last_draw = now;
 
-        ctx.el_ctx.render(root, frame.area(), frame.buffer_mut());
+    if let Err(err) = ctx.terminal.autoresize() {
+        tracing::error!("failed to resize terminal: {err}");
+    }
+    let area = ctx.terminal.get_frame().area();
+    metrics::begin_profile_frame(&mut ctx.el_ctx);
+    // a root without a layout is never drawn, the last frame stays on screen instead.
+    if let Err(err) = ctx.el_ctx.calculate_layout(root, area) {
+        tracing::error!("failed to calculate layout: {err}");
+        return;
+    }
+    focus::update_focus::<Msg>(&mut ctx.el_ctx, root);
+
+    // render into a fresh shadow buffer, only the subtrees that changed since the last frame
+    // are rendered again.
+    let mut next = Buffer::empty(area);
+    let damage = ctx
+        .el_ctx
+        .render_incremental(root, area, &mut next, &ctx.shadow);
+    ctx.shadow = next;
+    let cursor = ctx.el_ctx.cursor();
+    metrics::record_frame(&mut ctx.el_ctx, frame_time, &damage);
+    if damage.is_empty() && cursor == ctx.last_cursor && !metrics::is_shown(&ctx.el_ctx) {
+        metrics::record_skipped_frame(&mut ctx.el_ctx);
+        return;
+    }
+    ctx.last_cursor = cursor;
+
+    if ctx.synchronized_output {
+        execute_command(ctx, &TerminalCommand::BeginSync);
+    }
+    let result = ctx.terminal.draw(|frame| {
+        // ratatui diffs the frame against the last one, so only damaged cells are written.
+        frame.buffer_mut().merge(&ctx.shadow);
+        metrics::render_overlay(&mut ctx.el_ctx, frame.area(), frame.buffer_mut());
+        // the shadow keeps the colors of the tree, so both frames ratatui diffs are degraded.
+        ctx.el_ctx.degrade_colors(frame.buffer_mut());
+        if let Some((position, _)) = cursor {
+            frame.set_cursor_position(position);
+        }
     });
 
-    if let Err(err) = result {
-        tracing::error!("failed to draw: {err}");
+    match result {
+        Ok(frame) => {
+            if let Some(recorder) = &mut ctx.recorder {
+                recorder.record(frame.buffer);
+            }
+        }
+        Err(err) => tracing::error!("failed to draw: {err}"),
     }
 
-    root
+    if let Some((_, shape)) = cursor {
+        if let Err(err) = ctx.terminal.backend_mut().set_cursor_shape(shape) {
+            tracing::error!("failed to set cursor shape: {err}");
+        }
+    }
+    if ctx.synchronized_output {
+        execute_command(ctx, &TerminalCommand::EndSync);
+    }
 }
 
 /// # Errors
@@ -159,6 +491,9 @@ fn render<Msg: Message, B: Backend>(ctx: &mut Ctx<B>, view: View) -> Element {
 /// - if the app channel is closed somehow
 /// - if an error happens while propagating an event
 /// - if there is an error initializing the runtime
+///
+/// it also stops with [`RuntimeErr::Reload`] after saving the model for a [`reload`], which
+/// isn't an error: the caller exits with [`reload::RELOAD_EXIT_CODE`].
 #[bon::builder]
 #[builder(finish_fn = run)]
 pub async fn run<W, Msg>(
@@ -167,46 +502,1826 @@ pub async fn run<W, Msg>(
     view: impl ViewFn<Msg, Msg::Model>,
     update: impl UpdateFn<Msg, Msg::Model>,
     quit_signal: impl SignalFn<Msg, Msg::Model>,
+    /// toggles the [`metrics::metrics_overlay`], off if unset. see
+    /// [`metrics::default_metrics_key`].
+    metrics_key: Option<DefaultKeyEvent>,
+    /// toggles the [`Profiler`][mana_tui_elemental::profile::Profiler] and the
+    /// [`metrics::profile_overlay`], off if unset. see [`metrics::default_profiler_key`].
+    profiler_key: Option<DefaultKeyEvent>,
+    /// wraps every draw in a synchronized update so fast redraws don't tear, see
+    /// [`TerminalCommand::BeginSync`].
+    #[builder(default)]
+    synchronized_output: bool,
+    /// dispatched once after the first render, for example to focus an input.
+    on_ready: Option<Msg>,
+    /// called when the quit signal fires, see [`QuitDecision`]. quits right away if unset.
+    on_before_quit: Option<lifecycle::BeforeQuitFn<Msg::Model, Msg>>,
+    /// called with the final model after the terminal is restored, also when the runtime
+    /// stops with an error.
+    on_exit: Option<lifecycle::ExitFn<Msg::Model>>,
+    /// called after every update, see [`Middleware`].
+    middleware: Option<Middleware<Msg, Msg::Model>>,
+    /// picks the messages that only need the [bindings](bind) applied instead of running the
+    /// view again. every message is a full update if unset.
+    classify: Option<fn(&Msg) -> UpdateKind>,
+    /// shuts the runtime down even if the view doesn't handle it, like the terminal closing.
+    /// the quit signal and `on_before_quit` are skipped.
+    #[builder(default = lifecycle::default_quit_key())]
+    quit_key: DefaultKeyEvent,
+    /// the app handles the quit key itself, the runtime only shuts down on its own when the
+    /// terminal closes.
+    #[builder(default)]
+    own_quit_key: bool,
+    /// applied with `update` before the runtime shuts down on the quit key or because the
+    /// terminal closed, for example to save state.
+    on_shutdown: Option<Msg>,
+    /// terminal setup, see [`RuntimeOptions`].
+    #[builder(default)]
+    options: RuntimeOptions,
+    /// keeps the model across rebuilds of the `mx` dev server, see [`reload`].
+    hot_reload: Option<reload::HotReload<Msg::Model>>,
 ) -> Result<(), RuntimeErr>
 where
     Msg: Clone + Message + Component,
     W: std::io::Write + 'static,
 {
-    let dispatch = flume::unbounded::<Msg>();
     let mut backend = DefaultBackend::new(writer);
+    let mut el_ctx = ElementCtx::new();
+    el_ctx.insert_or_update_resource(options.color_caps.unwrap_or_else(ColorCaps::detect));
     let msg_stream = MsgStream {
         event_stream: backend.create_events().await,
-        dispatch: dispatch.clone(),
+        dispatch: flume::unbounded(),
+        resolved: suspense::init_suspense(&mut el_ctx),
+        frame_time: options.frame_time(),
+        reload: flume::unbounded(),
     };
-    let terminal = ratatui::Terminal::new(backend).map_err(|_| RuntimeErr::InitErr)?;
-
-    ratatui::init();
+    if hot_reload
+        .as_ref()
+        .is_some_and(|hot_reload| hot_reload.path().is_some())
+    {
+        reload::listen(msg_stream.reload.0.clone());
+    }
+    // the alternate screen is only entered for fullscreen apps, inline viewports draw below the
+    // shell prompt.
+    if options.viewport == ratatui::Viewport::Fullscreen {
+        ratatui::init();
+    } else {
+        ratatui::crossterm::terminal::enable_raw_mode().map_err(|_| RuntimeErr::InitErr)?;
+    }
+    let terminal = ratatui::Terminal::with_options(
+        backend,
+        ratatui::TerminalOptions {
+            viewport: options.viewport.clone(),
+        },
+    )
+    .map_err(|_| RuntimeErr::InitErr)?;
     let mut ctx = Ctx {
-        el_ctx: mana_tui_elemental::prelude::ElementCtx::new(),
+        el_ctx,
         terminal,
+        last_frame: Instant::now(),
+        last_draw: Instant::now(),
+        animating: false,
+        metrics_key,
+        profiler_key,
+        synchronized_output,
+        shadow: Buffer::default(),
+        last_cursor: None,
+        reloading: false,
+        recorder: options.record_frames.as_ref().map(|_| FrameRecorder::new()),
     };
+    for command in options.enter_commands() {
+        execute_command(&mut ctx, &command);
+    }
 
-    let (model, effect) = init().await;
-    tokio::spawn(effect.0.run_effect(dispatch.0.clone()));
-    let tree = view(&model).await;
-    let root = render::<Msg, DefaultBackend<W>>(&mut ctx, tree);
-
-    let result = runtime(
-        model,
+    let rt = Runtime {
         view,
         update,
         quit_signal,
+        before_quit: on_before_quit.unwrap_or_else(|| Box::new(|_| QuitDecision::Quit)),
+        shutdown: Shutdown {
+            key: (!own_quit_key).then_some(quit_key),
+            msg: on_shutdown,
+        },
+        middleware,
+        classify,
+    };
+    let hooks = lifecycle::Hooks { on_ready, on_exit };
+    let restore = |ctx: &mut Ctx<DefaultBackend<W>>| {
+        for command in options.leave_commands() {
+            execute_command(ctx, &command);
+        }
+        ratatui::restore();
+        if let (Some(recorder), Some(path)) = (&ctx.recorder, &options.record_frames)
+            && let Err(err) = recorder.save(path)
+        {
+            tracing::error!("failed to save the recorded frames: {err}");
+        }
+    };
+    session(
+        init,
+        rt,
+        hooks,
         msg_stream,
         &mut ctx,
-        Some(root),
+        hot_reload.as_ref(),
+        restore,
     )
-    .await;
+    .await
+}
+
+/// runs the app in a terminal that is set up: draws the first frame, dispatches `on_ready` and
+/// runs the [`runtime`]. `restore` leaves the terminal before `on_exit` is called, also when the
+/// runtime fails.
+async fn session<Msg, B, E, V, U, S>(
+    init: impl InitFn<Msg, Msg::Model>,
+    rt: Runtime<Msg, V, U, S>,
+    hooks: lifecycle::Hooks<Msg>,
+    msg_stream: MsgStream<Msg, E>,
+    ctx: &mut Ctx<B>,
+    hot_reload: Option<&reload::HotReload<Msg::Model>>,
+    restore: impl FnOnce(&mut Ctx<B>),
+) -> Result<(), RuntimeErr>
+where
+    Msg: Message,
+    B: 'static + ManaBackend<Event = DefaultEvent, KeyEvent = DefaultKeyEvent>,
+    E: EventStream<Out = DefaultEvent>,
+    V: ViewFn<Msg, Msg::Model>,
+    U: UpdateFn<Msg, Msg::Model>,
+    S: SignalFn<Msg, Msg::Model>,
+{
+    let restored = hot_reload.and_then(reload::HotReload::restore_model);
+    let (model, effect) = match restored {
+        Some(model) => (model, Effect::none()),
+        None => init().await,
+    };
+    let dispatch = msg_stream.dispatch.0.clone();
+    spawn_effect(ctx, effect, &dispatch);
+    let view_ctx = ViewCtx::capture(ctx);
+    let tree = rt.view.view(&model, &view_ctx).await;
+    let root = render::<Msg, B>(ctx, tree);
+    if let Some(msg) = hooks.on_ready {
+        // the receiver lives in `msg_stream`, so this can't fail.
+        _ = dispatch.send(msg);
+    }
 
-    ratatui::restore();
+    let result = runtime(model, &rt, msg_stream, ctx, Some(root)).await;
+    restore(ctx);
 
+    let (model, mut result) = match result {
+        Ok(model) => (model, Ok(())),
+        Err((model, err)) => (model, Err(err)),
+    };
+    if result.is_ok()
+        && ctx.reloading
+        && let Some(hot_reload) = hot_reload
+    {
+        match hot_reload.save_model(&model) {
+            Ok(()) => result = Err(RuntimeErr::Reload),
+            Err(err) => tracing::error!("failed to save the model for the reload: {err}"),
+        }
+    }
+    if let Some(on_exit) = hooks.on_exit {
+        on_exit(&model);
+    }
     result
 }
 
 pub trait Message: Clone + Component {
     type Model;
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use crossterm::event::{
+        Event, KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    };
+    use mana_tui_elemental::prelude::*;
+    use mana_tui_macros::ui;
+    use ratatui::{buffer::Buffer, layout::Rect};
+
+    use crate::focus::handlers::{On, OnClick};
+    use crate::{Effect, Message};
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Msg {
+        Quit,
+        Clicked(usize),
+    }
+
+    impl Message for Msg {
+        type Model = ();
+    }
+
+    /// terminal output shared with the test.
+    #[derive(Clone, Default)]
+    struct Output(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// a runtime context drawing to `backend` in a fixed viewport of `area`.
+    fn test_ctx<B: ratatui::backend::Backend>(backend: B, area: Rect) -> crate::Ctx<B> {
+        let terminal = ratatui::Terminal::with_options(
+            backend,
+            ratatui::TerminalOptions {
+                viewport: ratatui::Viewport::Fixed(area),
+            },
+        )
+        .unwrap();
+        crate::Ctx {
+            el_ctx: ElementCtx::new(),
+            terminal,
+            last_frame: std::time::Instant::now(),
+            last_draw: std::time::Instant::now(),
+            animating: false,
+            metrics_key: None,
+            profiler_key: None,
+            synchronized_output: false,
+            shadow: Buffer::default(),
+            last_cursor: None,
+            reloading: false,
+            recorder: None,
+        }
+    }
+
+    /// a terminal without input, the app quits on its own.
+    struct NoEvents;
+
+    impl crate::backends::EventStream for NoEvents {
+        type Out = Event;
+        type Err = std::io::Error;
+
+        async fn read(&mut self) -> Option<Result<Event, std::io::Error>> {
+            std::future::pending().await
+        }
+    }
+
+    #[test]
+    fn test_on_closure_attr() {
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(ui! {
+            <Block .on={|_, e| matches!(e, Event::Key(..)).then_some((Msg::Quit, Effect::none()))} />
+        });
+        crate::focus::generate_ui_stack(&mut ctx, root);
+
+        let event = Event::Key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE));
+        let result = crate::focus::propagate_event::<Msg>(&ctx, &(), &event).unwrap();
+        assert!(matches!(result, Some((Msg::Quit, _))));
+    }
+
+    #[test]
+    fn test_viewport_clicks() {
+        let mut ctx = ElementCtx::new();
+        let pane = |idx| {
+            ui(Block::bordered())
+                .with((Width::grow(), Height::grow()))
+                .child(ui(Block::new()).with((
+                    Width::grow(),
+                    Height::grow(),
+                    OnClick::new(Msg::Clicked(idx)),
+                )))
+        };
+        let left = ctx.spawn_ui(pane(0));
+        let right = ctx.spawn_ui(pane(1));
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 80, 10));
+        ctx.render_viewport(left, Rect::new(0, 0, 40, 10), &mut buf)
+            .unwrap();
+        ctx.render_viewport(right, Rect::new(40, 0, 40, 10), &mut buf)
+            .unwrap();
+        crate::focus::generate_ui_stack(&mut ctx, left);
+        crate::focus::generate_ui_stack(&mut ctx, right);
+
+        let click = |column| {
+            let event = Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column,
+                row: 5,
+                modifiers: KeyModifiers::NONE,
+            });
+            crate::focus::propagate_event::<Msg>(&ctx, &(), &event)
+                .unwrap()
+                .map(|(msg, _)| msg)
+        };
+        assert_eq!(click(5), Some(Msg::Clicked(0)));
+        assert_eq!(click(45), Some(Msg::Clicked(1)));
+        assert_eq!(click(40), None);
+    }
+
+    #[test]
+    fn test_overlapping_roots() {
+        let mut ctx = ElementCtx::new();
+        let area = Rect::new(0, 0, 8, 4);
+        // the popup is spawned first, and in another archetype than the main root.
+        let popup = ctx.spawn_ui(ui(Block::new()).with((
+            Width::grow(),
+            Height::grow(),
+            ClearBackground,
+            OnClick::new(Msg::Clicked(1)),
+        )));
+        let main = ctx.spawn_ui(ui(Block::new()).with((
+            Width::grow(),
+            Height::grow(),
+            OnClick::new(Msg::Clicked(0)),
+        )));
+        let mut click = |roots: [Element; 2]| {
+            for root in roots {
+                ctx.calculate_layout(root, area).unwrap();
+                crate::focus::generate_ui_stack(&mut ctx, root);
+            }
+            let event = Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column: 2,
+                row: 2,
+                modifiers: KeyModifiers::NONE,
+            });
+            crate::focus::propagate_event::<Msg>(&ctx, &(), &event)
+                .unwrap()
+                .map(|(msg, _)| msg)
+        };
+
+        // the root drawn last is on top.
+        assert_eq!(click([main, popup]), Some(Msg::Clicked(1)));
+        assert_eq!(click([popup, main]), Some(Msg::Clicked(0)));
+    }
+
+    #[test]
+    fn test_keyed_list_clicks() {
+        let row = |key: &usize, label: &str| {
+            ui(Text::raw(label.to_string()))
+                .with((
+                    Width::fixed(10),
+                    Height::fixed(1),
+                    OnClick::new(Msg::Clicked(*key)),
+                ))
+                .done()
+        };
+        let mut ctx = ElementCtx::new();
+        let items = [(0, "first"), (1, "second"), (2, "third")];
+        let reordered = [items[2], items[0], items[1]];
+        let list = ctx.spawn_ui(keyed_list(reordered, row));
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 3));
+        ctx.render_viewport(list, buf.area, &mut buf).unwrap();
+        crate::focus::generate_ui_stack(&mut ctx, list);
+
+        let click = |row| {
+            let event = Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column: 2,
+                row,
+                modifiers: KeyModifiers::NONE,
+            });
+            crate::focus::propagate_event::<Msg>(&ctx, &(), &event)
+                .unwrap()
+                .map(|(msg, _)| msg)
+        };
+        // the visually second row is the item keyed 0.
+        assert_eq!(click(1), Some(Msg::Clicked(0)));
+        assert_eq!(click(0), Some(Msg::Clicked(2)));
+        assert_eq!(click(2), Some(Msg::Clicked(1)));
+    }
+
+    #[test]
+    fn test_text_clicks() {
+        use crate::focus::handlers::OnTextClick;
+
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Width::fixed(10), Height::fixed(2)))
+                .children((
+                    ui(SourceParagraph::new("see a.rs").wrap(Wrap { trim: true })).with((
+                        Width::fixed(5),
+                        Height::fixed(2),
+                        OnTextClick::new(|_, hit: TextHit| {
+                            Some((Msg::Clicked(hit.byte_offset), Effect::none()))
+                        }),
+                    )),
+                    ui(Block::new()).with((Width::grow(), Height::grow(), OnClick::new(Msg::Quit))),
+                )),
+        );
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 2));
+        ctx.render_viewport(root, buf.area, &mut buf).unwrap();
+        crate::focus::generate_ui_stack(&mut ctx, root);
+
+        let click = |column, row| {
+            let event = Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            });
+            crate::focus::propagate_event::<Msg>(&ctx, &(), &event)
+                .unwrap()
+                .map(|(msg, _)| msg)
+        };
+        // "see " wraps, the second row starts with the path.
+        assert_eq!(click(0, 1), Some(Msg::Clicked(4)));
+        assert_eq!(click(2, 1), Some(Msg::Clicked(6)));
+        // past the end of the first row, outside of the text.
+        assert_eq!(click(4, 0), None);
+        assert_eq!(click(7, 0), Some(Msg::Quit));
+    }
+
+    #[test]
+    fn test_keyed_list_map_msg() {
+        use crate::MapMsg;
+
+        #[derive(Debug, Clone, PartialEq)]
+        enum RowMsg {
+            Remove(usize),
+        }
+
+        impl Message for RowMsg {
+            type Model = ();
+        }
+
+        let row = |key: &usize, label: &str| {
+            ui(Text::raw(label.to_string()))
+                .with((
+                    Width::fixed(10),
+                    Height::fixed(1),
+                    OnClick::new(RowMsg::Remove(*key)),
+                ))
+                .done()
+        };
+        let mut ctx = ElementCtx::new();
+        let items = [(4, "first"), (7, "second")];
+        let list = keyed_list(items, row).map_msg(|RowMsg::Remove(key)| Msg::Clicked(key));
+        let list = ctx.spawn_ui(list);
+
+        let mut buf = Buffer::empty(Rect::new(0, 0, 10, 2));
+        ctx.render_viewport(list, buf.area, &mut buf).unwrap();
+        crate::focus::generate_ui_stack(&mut ctx, list);
+
+        let event = Event::Mouse(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 2,
+            row: 1,
+            modifiers: KeyModifiers::NONE,
+        });
+        let msg = crate::focus::propagate_event::<Msg>(&ctx, &(), &event)
+            .unwrap()
+            .map(|(msg, _)| msg);
+        assert_eq!(msg, Some(Msg::Clicked(7)));
+    }
+
+    #[test]
+    fn test_help_bar() {
+        use crate::backends::KeyEventExt;
+        use crate::focus::help::{KeyHint, help_bar};
+        use crate::focus::{FocusTarget, handlers::OnKey};
+
+        struct Left;
+        struct Right;
+        struct Wide;
+
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(
+            ui(Block::new())
+                .with((Width::grow(), Height::fixed(1)))
+                .children((
+                    ui(Block::new()).with((
+                        FocusTarget::new::<Left>(),
+                        OnKey::new(KeyEvent::char('a'), Msg::Quit),
+                        KeyHint("add"),
+                    )),
+                    ui(Block::new()).with((
+                        FocusTarget::new::<Right>(),
+                        OnKey::new(KeyEvent::char('d'), Msg::Quit),
+                        KeyHint("delete"),
+                    )),
+                    ui(Block::new()).with((
+                        FocusTarget::new::<Wide>(),
+                        OnKey::new(KeyEvent::char('x'), Msg::Quit),
+                        KeyHint("削除する"),
+                    )),
+                    ui(Block::new())
+                        .with((OnKey::new(KeyEvent::char('q'), Msg::Quit), KeyHint("quit"))),
+                    help_bar(),
+                )),
+        );
+        let children = ctx.get::<&Children>(root).unwrap().clone();
+
+        let render = |ctx: &mut ElementCtx, focus: Element, area: Rect| {
+            ctx.calculate_layout(root, area).unwrap();
+            crate::focus::generate_ui_stack(ctx, root);
+            crate::focus::init_focus_system(ctx);
+            crate::focus::try_grab_focus(ctx, focus).unwrap();
+            crate::focus::help::update_help_bar::<Msg>(ctx);
+            let mut buf = Buffer::empty(area);
+            ctx.render(root, area, &mut buf);
+            buf
+        };
+
+        let area = Rect::new(0, 0, 30, 1);
+        let buf = render(&mut ctx, children[0], area);
+        assert_eq!(buf, Buffer::with_lines(["a: add  q: quit               "]));
+        let buf = render(&mut ctx, children[1], area);
+        assert_eq!(buf, Buffer::with_lines(["d: delete  q: quit            "]));
+        let buf = render(&mut ctx, children[1], Rect::new(0, 0, 10, 1));
+        assert_eq!(buf, Buffer::with_lines(["d: delete…"]));
+        // cut at the display width, the wide character that would overflow is left out.
+        let buf = render(&mut ctx, children[2], Rect::new(0, 0, 9, 1));
+        assert_eq!(buf, Buffer::with_lines(["x: 削除… "]));
+    }
+
+    #[test]
+    fn test_metrics_overlay() {
+        use std::time::Duration;
+
+        use mana_tui_utils::resource::Resources;
+
+        use mana_tui_elemental::damage::Damage;
+
+        use crate::metrics::{Metrics, ShowMetrics};
+
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(ui(Block::new()).with((Width::grow(), Height::grow())));
+        ctx.insert_resource(ShowMetrics(true));
+
+        let area = Rect::new(0, 0, 30, 8);
+        let mut buf = Buffer::empty(area);
+        for ms in [10, 20, 30] {
+            ctx.calculate_layout(root, area).unwrap();
+            ctx.render(root, area, &mut buf);
+            crate::metrics::record_frame(&mut ctx, Duration::from_millis(ms), &Damage::default());
+            crate::metrics::render_overlay(&mut ctx, area, &mut buf);
+        }
+
+        let lines = (0..area.height)
+            .map(|y| {
+                (0..area.width)
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+        // 10ms, 12ms and then 15.6ms with the newest frame weighted at 0.2.
+        assert_eq!(lines[1], "          │fps 64.1          │");
+        assert_eq!(lines[2], "          │frame 15.6ms      │");
+
+        let metrics = *ctx.get_resource::<&Metrics>().unwrap();
+        assert_eq!(metrics.entities, ctx.len());
+    }
+
+    #[tokio::test]
+    async fn test_effect_to_channel() {
+        use std::time::Duration;
+
+        #[derive(Debug, Clone, PartialEq)]
+        enum TimerMsg {
+            Tick,
+        }
+
+        let effect = Effect::new(async |tx| {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            _ = tx.send_async(TimerMsg::Tick).await;
+        });
+        let (effect, observed) = effect.to_channel();
+        let (tx, rx) = flume::unbounded();
+        effect.0.run_effect(tx).await;
+
+        assert_eq!(observed.drain().collect::<Vec<_>>(), [TimerMsg::Tick]);
+        // the dispatch channel still gets the message.
+        assert_eq!(rx.drain().collect::<Vec<_>>(), [TimerMsg::Tick]);
+    }
+
+    #[tokio::test]
+    async fn test_terminal_commands() {
+        use ratatui::prelude::CrosstermBackend;
+
+        use crate::TerminalCommand;
+
+        let output = Output::default();
+        let mut ctx = test_ctx(
+            CrosstermBackend::new(output.clone()),
+            Rect::new(0, 0, 10, 1),
+        );
+        ctx.synchronized_output = true;
+
+        let (tx, _rx) = flume::unbounded();
+        let effect = Effect::<Msg>::terminal(TerminalCommand::SetTitle("mana".into()));
+        crate::spawn_effect(&mut ctx, effect, &tx);
+        crate::render::<Msg, _>(&mut ctx, ui(Text::raw("hi")).done());
+
+        let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        let title = output.find("\x1b]0;mana\x07").unwrap();
+        let begin = output.find("\x1b[?2026h").unwrap();
+        let text = output.find("hi").unwrap();
+        let end = output.find("\x1b[?2026l").unwrap();
+        assert!(title < begin);
+        assert!(begin < text);
+        assert!(text < end);
+    }
+
+    #[test]
+    fn test_damage_regions() {
+        use std::sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        };
+
+        use ratatui::{prelude::CrosstermBackend, style::Styled};
+
+        use crate::metrics::Metrics;
+
+        /// text that counts how often it is rendered.
+        #[derive(Clone)]
+        struct Counted(&'static str, Arc<AtomicUsize>);
+
+        impl std::fmt::Debug for Counted {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.0)
+            }
+        }
+
+        impl Widget for Counted {
+            fn render(self, area: Rect, buf: &mut Buffer) {
+                self.1.fetch_add(1, Ordering::Relaxed);
+                Text::raw(self.0).render(area, buf);
+            }
+        }
+
+        impl Styled for Counted {
+            type Item = Self;
+
+            fn style(&self) -> ratatui::style::Style {
+                ratatui::style::Style::default()
+            }
+
+            fn set_style<S: Into<ratatui::style::Style>>(self, _: S) -> Self {
+                self
+            }
+        }
+
+        let top = Arc::new(AtomicUsize::new(0));
+        let bottom = Arc::new(AtomicUsize::new(0));
+        // the counted texts have no fingerprint, their version says when they change.
+        let view = |text, version| {
+            ui(Block::new())
+                .with((Width::grow(), Height::grow(), Direction::Vertical))
+                .children((
+                    ui(Counted("top", top.clone())).with((
+                        Width::fixed(6),
+                        Height::fixed(1),
+                        ContentVersion(0),
+                    )),
+                    ui(Counted(text, bottom.clone())).with((
+                        Width::fixed(6),
+                        Height::fixed(1),
+                        ContentVersion(version),
+                    )),
+                ))
+                .done()
+        };
+
+        let mut ctx = test_ctx(
+            CrosstermBackend::new(Output::default()),
+            Rect::new(0, 0, 10, 2),
+        );
+        let mut frame = |text, version| {
+            let root = crate::render::<Msg, _>(&mut ctx, view(text, version));
+            ctx.despawn_ui(root);
+            *ctx.get_resource::<&Metrics>().unwrap()
+        };
+
+        frame("one", 1);
+        assert_eq!(
+            (top.load(Ordering::Relaxed), bottom.load(Ordering::Relaxed)),
+            (1, 1)
+        );
+
+        let metrics = frame("two", 2);
+        // only the changed text is rendered again, next to its parent.
+        assert_eq!(
+            (top.load(Ordering::Relaxed), bottom.load(Ordering::Relaxed)),
+            (1, 2)
+        );
+        assert_eq!((metrics.rendered, metrics.reused), (2, 1));
+        assert_eq!(metrics.damage, Rect::new(0, 1, 3, 1));
+        assert_eq!(metrics.damaged_cells, 3);
+        assert_eq!(metrics.skipped_frames, 0);
+
+        let metrics = frame("two", 2);
+        assert_eq!(metrics.damaged_cells, 0);
+        assert_eq!(metrics.skipped_frames, 1);
+    }
+
+    #[test]
+    fn test_runtime_options() {
+        use std::time::Duration;
+
+        use ratatui::{Viewport, prelude::CrosstermBackend};
+
+        use crate::backends::{FRAME_TIME, ManaBackend};
+        use crate::{RuntimeOptions, TerminalCommand};
+
+        assert!(RuntimeOptions::default().enter_commands().is_empty());
+        assert_eq!(RuntimeOptions::default().viewport, Viewport::Fullscreen);
+        assert_eq!(RuntimeOptions::default().frame_time(), FRAME_TIME);
+
+        for mask in 0u32..8 {
+            let options = RuntimeOptions::builder()
+                .mouse_capture(mask & 1 != 0)
+                .focus_events(mask & 2 != 0)
+                .kitty_keyboard(mask & 4 != 0)
+                .viewport(Viewport::Inline(4))
+                .frame_rate_cap(30)
+                .build();
+            let enter = options.enter_commands();
+            assert_eq!(enter.len(), mask.count_ones() as usize);
+            assert_eq!(options.leave_commands().len(), enter.len());
+            assert_eq!(options.frame_time(), Duration::from_secs(1) / 30);
+        }
+
+        let options = RuntimeOptions::builder()
+            .mouse_capture(true)
+            .focus_events(true)
+            .kitty_keyboard(true)
+            .build();
+        assert_eq!(
+            options.enter_commands(),
+            [
+                TerminalCommand::EnableMouseCapture,
+                TerminalCommand::EnableFocusEvents,
+                TerminalCommand::PushKeyboardEnhancement,
+            ]
+        );
+        assert_eq!(
+            options.leave_commands(),
+            [
+                TerminalCommand::PopKeyboardEnhancement,
+                TerminalCommand::DisableFocusEvents,
+                TerminalCommand::DisableMouseCapture,
+            ]
+        );
+
+        let output = Output::default();
+        let mut backend = CrosstermBackend::new(output.clone());
+        for command in options.enter_commands() {
+            backend.execute_command(&command).unwrap();
+        }
+        let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        let mouse = output.find("\x1b[?1000h").unwrap();
+        let focus = output.find("\x1b[?1004h").unwrap();
+        let keyboard = output.find("\x1b[>3u").unwrap();
+        assert!(mouse < focus);
+        assert!(focus < keyboard);
+    }
+
+    #[test]
+    fn test_before_quit() {
+        use crate::QuitDecision;
+        use crate::lifecycle::should_quit;
+
+        #[derive(Debug, Clone, PartialEq)]
+        enum EditorMsg {
+            ShowConfirm,
+        }
+
+        struct Editor {
+            dirty: bool,
+            confirming: bool,
+        }
+
+        let before_quit = |model: &Editor| {
+            if model.dirty && !model.confirming {
+                QuitDecision::Confirm(EditorMsg::ShowConfirm)
+            } else {
+                QuitDecision::Quit
+            }
+        };
+        let (tx, rx) = flume::unbounded();
+
+        // the first quit attempt with unsaved changes shows the confirmation.
+        let model = Editor {
+            dirty: true,
+            confirming: false,
+        };
+        assert!(!should_quit(before_quit(&model), &tx));
+        assert_eq!(rx.try_recv(), Ok(EditorMsg::ShowConfirm));
+
+        // the second one, with the modal open, passes.
+        let model = Editor {
+            confirming: true,
+            ..model
+        };
+        assert!(should_quit(before_quit(&model), &tx));
+        assert!(rx.is_empty());
+
+        assert!(!should_quit(QuitDecision::<EditorMsg>::Cancel, &tx));
+        assert!(rx.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_shutdown() {
+        use std::collections::VecDeque;
+        use std::sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        };
+
+        use ratatui::prelude::CrosstermBackend;
+
+        use crate::backends::{EventStream, FRAME_TIME, MsgStream};
+        use crate::lifecycle::{Shutdown, default_quit_key};
+        use crate::{QuitDecision, Runtime};
+
+        #[derive(Debug, Clone, PartialEq)]
+        enum SessionMsg {
+            Save,
+        }
+
+        impl Message for SessionMsg {
+            type Model = Vec<SessionMsg>;
+        }
+
+        /// events of a terminal that is closed after the last one.
+        struct FakeEvents(VecDeque<Event>, Arc<AtomicUsize>);
+
+        impl EventStream for FakeEvents {
+            type Out = Event;
+            type Err = std::io::Error;
+
+            async fn read(&mut self) -> Option<Result<Event, std::io::Error>> {
+                self.1.fetch_add(1, Ordering::Relaxed);
+                self.0.pop_front().map(Ok)
+            }
+        }
+
+        let session = async |own_quit_key: bool| {
+            let mut ctx = test_ctx(
+                CrosstermBackend::new(Output::default()),
+                Rect::new(0, 0, 10, 1),
+            );
+            let reads = Arc::new(AtomicUsize::new(0));
+            let (_resolved_tx, resolved) = flume::unbounded();
+            let msg_stream = MsgStream {
+                event_stream: FakeEvents([Event::Key(default_quit_key())].into(), reads.clone()),
+                dispatch: flume::unbounded(),
+                resolved,
+                frame_time: FRAME_TIME,
+                reload: flume::unbounded(),
+            };
+            let rt = Runtime {
+                view: async |_: &Vec<SessionMsg>| ui(Block::new()).done(),
+                update: async |mut model: Vec<SessionMsg>, msg| {
+                    model.push(msg);
+                    (model, Effect::none())
+                },
+                quit_signal: |_: &Vec<SessionMsg>, _: &SessionMsg| false,
+                before_quit: Box::new(|_: &Vec<SessionMsg>| QuitDecision::Quit),
+                shutdown: Shutdown {
+                    key: (!own_quit_key).then(default_quit_key),
+                    msg: Some(SessionMsg::Save),
+                },
+                middleware: None,
+                classify: None,
+            };
+            let model = crate::runtime(Vec::new(), &rt, msg_stream, &mut ctx, None).await;
+            (model.unwrap(), reads.load(Ordering::Relaxed))
+        };
+
+        // ctrl + c quits even though nothing in the view handles it.
+        assert_eq!(session(false).await, (vec![SessionMsg::Save], 1));
+        // with the app owning the key, the runtime only stops once the terminal is gone.
+        assert_eq!(session(true).await, (vec![SessionMsg::Save], 2));
+    }
+
+    #[tokio::test]
+    async fn test_metrics_key() {
+        use std::collections::VecDeque;
+
+        use ratatui::prelude::CrosstermBackend;
+
+        use crate::backends::{EventStream, FRAME_TIME, MsgStream};
+        use crate::lifecycle::Shutdown;
+        use crate::metrics::{default_metrics_key, is_shown};
+        use crate::{QuitDecision, Runtime};
+
+        /// events of a terminal that is closed after the last one.
+        struct FakeEvents(VecDeque<Event>);
+
+        impl EventStream for FakeEvents {
+            type Out = Event;
+            type Err = std::io::Error;
+
+            async fn read(&mut self) -> Option<Result<Event, std::io::Error>> {
+                self.0.pop_front().map(Ok)
+            }
+        }
+
+        let shown = async |metrics_key| {
+            let mut ctx = test_ctx(
+                CrosstermBackend::new(Output::default()),
+                Rect::new(0, 0, 10, 1),
+            );
+            ctx.metrics_key = metrics_key;
+            let (_resolved_tx, resolved) = flume::unbounded();
+            let msg_stream = MsgStream {
+                event_stream: FakeEvents([Event::Key(default_metrics_key())].into()),
+                dispatch: flume::unbounded(),
+                resolved,
+                frame_time: FRAME_TIME,
+                reload: flume::unbounded(),
+            };
+            let rt = Runtime {
+                view: async |(): &()| ui(Block::new()).done(),
+                update: async |(): (), _: Msg| ((), Effect::none()),
+                quit_signal: |(): &(), _: &Msg| false,
+                before_quit: Box::new(|(): &()| QuitDecision::Quit),
+                shutdown: Shutdown {
+                    key: None,
+                    msg: None,
+                },
+                middleware: None,
+                classify: None,
+            };
+            crate::runtime((), &rt, msg_stream, &mut ctx, None)
+                .await
+                .unwrap();
+            is_shown(&ctx.el_ctx)
+        };
+
+        // the key reaches the app unless it is set.
+        assert!(!shown(None).await);
+        assert!(shown(Some(default_metrics_key())).await);
+    }
+
+    #[tokio::test]
+    async fn test_resize_event() {
+        use std::sync::{Arc, Mutex};
+
+        use ratatui::prelude::CrosstermBackend;
+
+        use crate::backends::{EventStream, FRAME_TIME, MsgStream};
+        use crate::lifecycle::{Hooks, Shutdown};
+        use crate::{Ctx, QuitDecision, Runtime};
+
+        #[derive(Debug, Clone, PartialEq)]
+        enum ResizeMsg {
+            Resized(u16, u16),
+            Quit,
+        }
+
+        impl Message for ResizeMsg {
+            type Model = Vec<ResizeMsg>;
+        }
+
+        /// a terminal that is resized once.
+        struct ResizeOnce(bool);
+
+        impl EventStream for ResizeOnce {
+            type Out = Event;
+            type Err = std::io::Error;
+
+            async fn read(&mut self) -> Option<Result<Event, std::io::Error>> {
+                if std::mem::replace(&mut self.0, true) {
+                    std::future::pending().await
+                } else {
+                    Some(Ok(Event::Resize(20, 5)))
+                }
+            }
+        }
+
+        let mut ctx = test_ctx(
+            CrosstermBackend::new(Output::default()),
+            Rect::new(0, 0, 10, 1),
+        );
+        let (_resolved_tx, resolved) = flume::unbounded();
+        let msg_stream = MsgStream {
+            event_stream: ResizeOnce(false),
+            dispatch: flume::unbounded(),
+            resolved,
+            frame_time: FRAME_TIME,
+            reload: flume::unbounded(),
+        };
+        let exited = Arc::new(Mutex::new(None));
+        let hooks = Hooks {
+            on_ready: None,
+            on_exit: Some(Box::new({
+                let exited = exited.clone();
+                move |model: &Vec<ResizeMsg>| *exited.lock().unwrap() = Some(model.clone())
+            })),
+        };
+        let rt = Runtime {
+            view: async |_: &Vec<ResizeMsg>| {
+                ui(Block::new())
+                    .with((On::new(|_, event: &Event| match *event {
+                        Event::Resize(width, height) => {
+                            Some((ResizeMsg::Resized(width, height), Effect::none()))
+                        }
+                        _ => None,
+                    }),))
+                    .done()
+            },
+            // the app quits once it has seen the resize.
+            update: async |mut model: Vec<ResizeMsg>, msg| {
+                model.push(msg);
+                let quit = Effect::new(async |tx| {
+                    _ = tx.send_async(ResizeMsg::Quit).await;
+                });
+                (model, quit)
+            },
+            quit_signal: |_: &Vec<ResizeMsg>, msg: &ResizeMsg| *msg == ResizeMsg::Quit,
+            before_quit: Box::new(|_: &Vec<ResizeMsg>| QuitDecision::Quit),
+            shutdown: Shutdown {
+                key: None,
+                msg: None,
+            },
+            middleware: None,
+            classify: None,
+        };
+        let result = crate::session(
+            async || (Vec::new(), Effect::none()),
+            rt,
+            hooks,
+            msg_stream,
+            &mut ctx,
+            None,
+            |_: &mut Ctx<_>| {},
+        )
+        .await;
+
+        assert!(result.is_ok());
+        assert_eq!(
+            *exited.lock().unwrap(),
+            Some(vec![ResizeMsg::Resized(20, 5)])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lifecycle_hooks() {
+        use std::sync::{
+            Arc, Mutex,
+            atomic::{AtomicBool, Ordering},
+        };
+
+        use ratatui::prelude::CrosstermBackend;
+
+        use crate::backends::{FRAME_TIME, MsgStream};
+        use crate::lifecycle::{Hooks, Shutdown};
+        use crate::{Ctx, QuitDecision, Runtime};
+
+        #[derive(Debug, Clone, PartialEq)]
+        enum EditorMsg {
+            Ready,
+            Quit,
+            ConfirmQuit,
+        }
+
+        impl Message for EditorMsg {
+            type Model = Vec<EditorMsg>;
+        }
+
+        let mut ctx = test_ctx(
+            CrosstermBackend::new(Output::default()),
+            Rect::new(0, 0, 10, 1),
+        );
+        let (_resolved_tx, resolved) = flume::unbounded();
+        let msg_stream = MsgStream {
+            event_stream: NoEvents,
+            dispatch: flume::unbounded(),
+            resolved,
+            frame_time: FRAME_TIME,
+            reload: flume::unbounded(),
+        };
+        let restored = Arc::new(AtomicBool::new(false));
+        let exited = Arc::new(Mutex::new(None));
+        let hooks = Hooks {
+            on_ready: Some(EditorMsg::Ready),
+            on_exit: Some(Box::new({
+                let restored = restored.clone();
+                let exited = exited.clone();
+                move |model: &Vec<EditorMsg>| {
+                    assert!(restored.load(Ordering::Relaxed));
+                    *exited.lock().unwrap() = Some(model.clone());
+                }
+            })),
+        };
+        let rt = Runtime {
+            view: async |_: &Vec<EditorMsg>| ui(Block::new()).done(),
+            // every message but the quit asks to quit.
+            update: async |mut model: Vec<EditorMsg>, msg| {
+                model.push(msg);
+                let quit = Effect::new(async |tx| {
+                    _ = tx.send_async(EditorMsg::Quit).await;
+                });
+                (model, quit)
+            },
+            quit_signal: |_: &Vec<EditorMsg>, msg: &EditorMsg| *msg == EditorMsg::Quit,
+            // the first quit asks for a confirmation, the second one quits.
+            before_quit: Box::new(|model: &Vec<EditorMsg>| {
+                if model.contains(&EditorMsg::ConfirmQuit) {
+                    QuitDecision::Quit
+                } else {
+                    QuitDecision::Confirm(EditorMsg::ConfirmQuit)
+                }
+            }),
+            shutdown: Shutdown {
+                key: None,
+                msg: None,
+            },
+            middleware: None,
+            classify: None,
+        };
+        let result = crate::session(
+            async || (Vec::new(), Effect::none()),
+            rt,
+            hooks,
+            msg_stream,
+            &mut ctx,
+            None,
+            |_: &mut Ctx<_>| restored.store(true, Ordering::Relaxed),
+        )
+        .await;
+
+        assert!(result.is_ok());
+        // the quit signal never reaches update.
+        assert_eq!(
+            *exited.lock().unwrap(),
+            Some(vec![EditorMsg::Ready, EditorMsg::ConfirmQuit])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bindings_only_update() {
+        use std::sync::{
+            Arc,
+            atomic::{AtomicUsize, Ordering},
+        };
+        use std::time::Duration;
+
+        use ratatui::prelude::CrosstermBackend;
+
+        use crate::backends::{EventStream, FRAME_TIME, MsgStream};
+        use crate::bind::{Bind, UpdateKind, bound};
+        use crate::lifecycle::Shutdown;
+        use crate::{QuitDecision, Runtime};
+
+        #[derive(Debug, Clone, PartialEq)]
+        enum ClockMsg {
+            Tick,
+        }
+
+        impl Message for ClockMsg {
+            type Model = u32;
+        }
+
+        /// a terminal that is closed after a while, once the queued messages are handled.
+        struct ClosingEvents;
+
+        impl EventStream for ClosingEvents {
+            type Out = Event;
+            type Err = std::io::Error;
+
+            async fn read(&mut self) -> Option<Result<Event, std::io::Error>> {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                None
+            }
+        }
+
+        let views = Arc::new(AtomicUsize::new(0));
+        let view = {
+            let views = views.clone();
+            async move |clock: &u32| {
+                views.fetch_add(1, Ordering::Relaxed);
+                ui(Block::new())
+                    .child(bound(clock, |clock: &u32| Text::raw(format!("{clock}s"))))
+                    .done()
+            }
+        };
+        let mut ctx = test_ctx(
+            CrosstermBackend::new(Output::default()),
+            Rect::new(0, 0, 10, 1),
+        );
+        let root = crate::render::<ClockMsg, _>(&mut ctx, view(&9).await);
+        let dispatch = flume::unbounded();
+        dispatch.0.send(ClockMsg::Tick).unwrap();
+        let (_resolved_tx, resolved) = flume::unbounded();
+        let msg_stream = MsgStream {
+            event_stream: ClosingEvents,
+            dispatch,
+            resolved,
+            frame_time: FRAME_TIME,
+            reload: flume::unbounded(),
+        };
+        let rt = Runtime {
+            view,
+            update: async |clock: u32, _| (clock + 1, Effect::none()),
+            quit_signal: |_: &u32, _: &ClockMsg| false,
+            before_quit: Box::new(|_: &u32| QuitDecision::Quit),
+            shutdown: Shutdown {
+                key: None,
+                msg: None,
+            },
+            middleware: None,
+            classify: Some(
+                (|_: &ClockMsg| UpdateKind::BindingsOnly) as fn(&ClockMsg) -> UpdateKind,
+            ),
+        };
+        let model = crate::runtime(9, &rt, msg_stream, &mut ctx, Some(root))
+            .await
+            .unwrap();
+
+        assert_eq!(model, 10);
+        assert_eq!(views.load(Ordering::Relaxed), 1);
+        let bound = ctx
+            .query::<(Element, &Bind<u32>)>()
+            .iter()
+            .map(|(element, _)| element)
+            .collect::<Vec<_>>();
+        let text = ctx.get::<&Text<'static>>(bound[0]).unwrap();
+        assert_eq!(text.to_string(), "10s");
+        // the text got wider, so the element did too.
+        let width = **ctx.get::<&Width>(bound[0]).unwrap();
+        assert!(matches!(width, Size::Fixed(3)));
+    }
+
+    #[tokio::test]
+    async fn test_record_replay() {
+        use std::sync::{Arc, Mutex};
+
+        use crate::middleware::{Middleware, Recorder, apply, replay};
+
+        #[derive(Debug, Clone, PartialEq)]
+        enum CounterMsg {
+            Add(i32),
+            Double,
+            Reset,
+        }
+
+        impl Message for CounterMsg {
+            type Model = Vec<i32>;
+        }
+
+        async fn init() -> (Vec<i32>, Effect<CounterMsg>) {
+            (vec![1], Effect::none())
+        }
+
+        async fn update(mut model: Vec<i32>, msg: CounterMsg) -> (Vec<i32>, Effect<CounterMsg>) {
+            let last = model.last().copied().unwrap_or_default();
+            match msg {
+                CounterMsg::Add(n) => model.push(last + n),
+                CounterMsg::Double => model.push(last * 2),
+                CounterMsg::Reset => model.clear(),
+            }
+            (model, Effect::none())
+        }
+
+        // the runtime applies every message through the middleware like this.
+        let recorder = Recorder::default();
+        let middleware = recorder.middleware();
+        let session = [
+            CounterMsg::Add(2),
+            CounterMsg::Double,
+            CounterMsg::Reset,
+            CounterMsg::Add(5),
+            CounterMsg::Double,
+        ];
+        let (mut model, _) = init().await;
+        for msg in session.clone() {
+            (model, _) = apply(model, msg, &update, Some(&middleware)).await;
+        }
+        assert_eq!(model, [5, 10]);
+        assert_eq!(recorder.messages(), session);
+
+        let replayed = replay(recorder.messages(), init, update).await;
+        assert_eq!(replayed, model);
+
+        // middlewares see the model before and after the update.
+        let seen = Arc::new(Mutex::new(None));
+        let middleware = Middleware::new({
+            let seen = seen.clone();
+            move |msg: &CounterMsg, prev: &Vec<i32>, next: &Vec<i32>| {
+                *seen.lock().unwrap() = Some((msg.clone(), prev.clone(), next.clone()));
+            }
+        });
+        _ = apply(model, CounterMsg::Add(1), &update, Some(&middleware)).await;
+        assert_eq!(
+            *seen.lock().unwrap(),
+            Some((CounterMsg::Add(1), vec![5, 10], vec![5, 10, 11]))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_frame_recording() {
+        use ratatui::layout::Size;
+
+        use crate::frames::{CompareError, FrameRecorder, replay_frames};
+
+        #[derive(Debug, Clone, PartialEq)]
+        enum CounterMsg {
+            Inc,
+        }
+
+        impl Message for CounterMsg {
+            type Model = u32;
+        }
+
+        async fn session(label: fn(u32) -> &'static str) -> FrameRecorder {
+            replay_frames(
+                Size::new(12, 3),
+                [CounterMsg::Inc, CounterMsg::Inc],
+                async || (0, Effect::none()),
+                async move |count: &u32| {
+                    ui(Block::bordered())
+                        .with((Width::grow(), Height::grow()))
+                        .child(ui(Text::raw(format!("{}: {count}", label(*count)))))
+                        .done()
+                },
+                async |count: u32, _| (count + 1, Effect::none()),
+            )
+            .await
+        }
+
+        let path = std::env::temp_dir().join(format!("mana-counter-{}.frames", std::process::id()));
+        let recorded = session(|_| "count").await;
+        assert_eq!(recorded.frames().len(), 3);
+        recorded.compare_or_bless(&path, true).unwrap();
+        assert_eq!(FrameRecorder::load(&path).unwrap(), recorded);
+        recorded.compare_or_bless(&path, false).unwrap();
+
+        // the label changes on the third frame only.
+        let changed = session(|count| if count < 2 { "count" } else { "total" }).await;
+        let err = changed.compare_or_bless(&path, false).unwrap_err();
+        assert!(matches!(err, CompareError::Mismatch { frame: 2, .. }));
+        assert_eq!(
+            err.to_string(),
+            "frame 2 differs from the recording:\n\
+             row 1:\n  - |│count: 2  │|\n  + |│total: 2  │|\n"
+        );
+        _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_scheduled_wakeups() {
+        use std::time::Duration;
+
+        use tokio::time::Instant;
+
+        use crate::RuntimeMsg;
+        use crate::backends::{DefaultEvent, EventStream, FRAME_TIME, MsgStream};
+
+        /// a terminal that stays quiet.
+        struct NoEvents;
+
+        impl EventStream for NoEvents {
+            type Out = DefaultEvent;
+            type Err = std::io::Error;
+
+            async fn read(&mut self) -> Option<Result<DefaultEvent, std::io::Error>> {
+                std::future::pending().await
+            }
+        }
+
+        let (_resolved_tx, resolved) = flume::unbounded();
+        let mut stream = MsgStream::<(), NoEvents> {
+            event_stream: NoEvents,
+            dispatch: flume::unbounded(),
+            resolved,
+            frame_time: FRAME_TIME,
+            reload: flume::unbounded(),
+        };
+        let mut ctx = ElementCtx::new();
+        let start = Instant::now();
+        let spinner =
+            ctx.register_animation(Duration::from_millis(100), start.into_std(), |_, _| true);
+
+        for i in 1..=3 {
+            let deadline = ctx.next_animation_deadline();
+            let msg = MsgStream::next(&mut stream, false, deadline).await;
+            assert!(matches!(msg, RuntimeMsg::Tick));
+            assert_eq!(start.elapsed().as_millis(), 100 * i);
+            assert!(ctx.tick_scheduled(Instant::now().into_std()));
+        }
+
+        // without handles the runtime only wakes up for events.
+        assert!(ctx.deregister_animation(spinner));
+        let deadline = ctx.next_animation_deadline();
+        assert_eq!(deadline, None);
+        let idle = tokio::time::timeout(
+            Duration::from_secs(60),
+            MsgStream::next(&mut stream, false, deadline),
+        )
+        .await;
+        assert!(idle.is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_async_subview() {
+        use std::time::Duration;
+
+        use mana_tui_macros::subview;
+
+        #[subview]
+        async fn user_name(id: u32) -> View {
+            // stands in for a request to a server.
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            ui! {
+                <Text>"user {id}"</Text>
+            }
+        }
+
+        async fn view(id: u32) -> View {
+            ui! {
+                <Block Width::fixed(10) Height::fixed(1)>
+                    <UserName.await .id={id} />
+                </Block>
+            }
+        }
+
+        let mut ctx = ElementCtx::new();
+        let root = ctx.spawn_ui(view(7).await);
+        let area = Rect::new(0, 0, 10, 1);
+        ctx.calculate_layout(root, area).unwrap();
+        let mut buf = Buffer::empty(area);
+        ctx.render(root, area, &mut buf);
+        assert_eq!(buf, Buffer::with_lines(["user 7    "]));
+    }
+
+    #[tokio::test]
+    async fn test_view_ctx() {
+        use ratatui::prelude::CrosstermBackend;
+
+        use crate::view_ctx::{ViewCtx, ViewFn, with_ctx};
+
+        // narrow terminals stack the panes.
+        let view = with_ctx(async |_: &(), ctx: &ViewCtx| {
+            let direction = if ctx.size().width < 60 {
+                Direction::Vertical
+            } else {
+                Direction::Horizontal
+            };
+            ui(Block::new())
+                .with((direction,))
+                .children((ui(Text::raw("left")), ui(Text::raw("right"))))
+                .done()
+        });
+
+        for (width, lines) in [
+            (80, [format!("{:80}", "leftright"), " ".repeat(80)]),
+            (40, [format!("{:40}", "left"), format!("{:40}", "right")]),
+        ] {
+            let mut ctx = test_ctx(
+                CrosstermBackend::new(Output::default()),
+                Rect::new(0, 0, width, 2),
+            );
+            let view_ctx = ViewCtx::capture(&mut ctx);
+            assert_eq!(view_ctx.size().width, width);
+            let tree = ViewFn::<Msg, ()>::view(&view, &(), &view_ctx).await;
+            crate::render::<Msg, _>(&mut ctx, tree);
+            assert_eq!(ctx.shadow, Buffer::with_lines(lines));
+        }
+    }
+
+    mod suspense {
+        use std::time::Duration;
+
+        use mana_tui_elemental::prelude::*;
+        use ratatui::{buffer::Buffer, layout::Rect};
+
+        use crate::suspense::{self, suspense};
+
+        fn app() -> View {
+            ui(Block::new())
+                .with((Width::grow(), Height::grow()))
+                .child(suspense(
+                    "user",
+                    async {
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                        ui(Text::raw("wyvern")).done()
+                    },
+                    ui(Text::raw("loading")).done(),
+                ))
+                .done()
+        }
+
+        fn render(ctx: &mut ElementCtx, root: Element) -> Buffer {
+            let area = Rect::new(0, 0, 10, 1);
+            ctx.calculate_layout(root, area).unwrap();
+            let mut buf = Buffer::empty(area);
+            ctx.render(root, area, &mut buf);
+            buf
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn test_suspense() {
+            let mut ctx = ElementCtx::new();
+            let resolved = suspense::init_suspense(&mut ctx);
+
+            let root = ctx.spawn_ui(app());
+            suspense::update_suspense(&mut ctx);
+            assert_eq!(render(&mut ctx, root), Buffer::with_lines(["loading   "]));
+
+            tokio::time::advance(Duration::from_secs(1)).await;
+            let (key, view) = resolved.recv_async().await.unwrap();
+            assert!(suspense::resolve(&mut ctx, key, view));
+
+            suspense::detach_suspense(&mut ctx);
+            ctx.despawn_ui(root);
+            let root = ctx.spawn_ui(app());
+            suspense::update_suspense(&mut ctx);
+            assert_eq!(render(&mut ctx, root), Buffer::with_lines(["wyvern    "]));
+
+            // the resolved view survives rerenders with the same key.
+            suspense::detach_suspense(&mut ctx);
+            ctx.despawn_ui(root);
+            let root = ctx.spawn_ui(app());
+            suspense::update_suspense(&mut ctx);
+            assert_eq!(render(&mut ctx, root), Buffer::with_lines(["wyvern    "]));
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn test_suspense_cancel() {
+            let mut ctx = ElementCtx::new();
+            let resolved = suspense::init_suspense(&mut ctx);
+
+            let root = ctx.spawn_ui(app());
+            suspense::update_suspense(&mut ctx);
+
+            // the key disappears before the loader is done.
+            ctx.despawn_ui(root);
+            let root = ctx.spawn_ui(ui(Block::new()));
+            suspense::update_suspense(&mut ctx);
+
+            tokio::time::advance(Duration::from_secs(2)).await;
+            tokio::task::yield_now().await;
+            assert!(resolved.try_recv().is_err());
+            ctx.despawn_ui(root);
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn test_suspense_mount_hooks() {
+            use std::sync::{Arc, Mutex};
+
+            use ratatui::prelude::CrosstermBackend;
+
+            use super::{Msg, NoEvents, Output, test_ctx};
+            use crate::backends::{FRAME_TIME, MsgStream};
+            use crate::lifecycle::{Hooks, Shutdown};
+            use crate::{Ctx, Effect, QuitDecision, Runtime};
+
+            #[derive(Default)]
+            struct Counts {
+                mounted: Vec<&'static str>,
+                unmounted: Vec<&'static str>,
+            }
+
+            let counts = Arc::new(Mutex::new(Counts::default()));
+            let hooked = {
+                let counts = counts.clone();
+                move |name: &'static str| {
+                    let (on_mount, on_unmount) = (counts.clone(), counts.clone());
+                    ui(Block::new()).with((
+                        OnMount::new(move |_, _| on_mount.lock().unwrap().mounted.push(name)),
+                        OnUnmount::new(move |_, _| on_unmount.lock().unwrap().unmounted.push(name)),
+                    ))
+                }
+            };
+
+            let mut ctx = test_ctx(
+                CrosstermBackend::new(Output::default()),
+                Rect::new(0, 0, 10, 1),
+            );
+            let msg_stream = MsgStream {
+                event_stream: NoEvents,
+                dispatch: flume::unbounded(),
+                resolved: suspense::init_suspense(&mut ctx.el_ctx),
+                frame_time: FRAME_TIME,
+                reload: flume::unbounded(),
+            };
+            let rt = Runtime {
+                view: async |_: &()| {
+                    let hooked = hooked.clone();
+                    hooked("root")
+                        .child(suspense(
+                            "pane",
+                            async move { hooked("pane").child(hooked("log")).done() },
+                            ui(Text::raw("loading")).done(),
+                        ))
+                        .done()
+                },
+                update: async |(): (), _: Msg| ((), Effect::none()),
+                quit_signal: |_: &(), msg: &Msg| *msg == Msg::Quit,
+                before_quit: Box::new(|_: &()| QuitDecision::Quit),
+                shutdown: Shutdown {
+                    key: None,
+                    msg: None,
+                },
+                middleware: None,
+                classify: None,
+            };
+            let hooks = Hooks {
+                on_ready: None,
+                on_exit: None,
+            };
+            let result = crate::session(
+                // the pane resolves right away, the app rerenders after a second and quits after
+                // two.
+                async || {
+                    let effect = Effect::new(async |tx| {
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                        _ = tx.send_async(Msg::Clicked(0)).await;
+                        tokio::time::sleep(Duration::from_secs(1)).await;
+                        _ = tx.send_async(Msg::Quit).await;
+                    });
+                    ((), effect)
+                },
+                rt,
+                hooks,
+                msg_stream,
+                &mut ctx,
+                None,
+                |_: &mut Ctx<_>| {},
+            )
+            .await;
+            assert!(result.is_ok());
+
+            // the resolved pane is moved from tree to tree, so it is mounted once and never
+            // unmounted while its key is rendered.
+            let counts = counts.lock().unwrap();
+            assert_eq!(counts.mounted, ["root", "log", "pane", "root", "root"]);
+            assert_eq!(counts.unmounted, ["root", "root"]);
+        }
+    }
+
+    mod compose {
+        use mana_tui_macros::SubModel;
+
+        use crate::{Effect, compose_models};
+
+        #[derive(Debug, Default, SubModel)]
+        #[submodel(msg = AuthMsg, update = auth_update)]
+        pub struct AuthModel {
+            user: Option<String>,
+        }
+
+        #[derive(Debug, Clone)]
+        pub enum AuthMsg {
+            Login(String),
+            Logout,
+        }
+
+        async fn auth_update(model: AuthModel, msg: AuthMsg) -> (AuthModel, Effect<AuthMsg>) {
+            let model = match msg {
+                AuthMsg::Login(user) => AuthModel { user: Some(user) },
+                AuthMsg::Logout => AuthModel { user: None },
+            };
+            (model, Effect::none())
+        }
+
+        #[derive(Debug, Default, SubModel)]
+        #[submodel(msg = SettingsMsg, update = settings_update)]
+        pub struct SettingsModel {
+            dark_mode: bool,
+        }
+
+        #[derive(Debug, Clone)]
+        pub enum SettingsMsg {
+            ToggleDarkMode,
+        }
+
+        async fn settings_update(
+            model: SettingsModel,
+            msg: SettingsMsg,
+        ) -> (SettingsModel, Effect<SettingsMsg>) {
+            match msg {
+                SettingsMsg::ToggleDarkMode => (
+                    SettingsModel {
+                        dark_mode: !model.dark_mode,
+                    },
+                    Effect::new(async |tx| {
+                        _ = tx.send_async(SettingsMsg::ToggleDarkMode).await;
+                    }),
+                ),
+            }
+        }
+
+        compose_models! {
+            #[derive(Debug, Default)]
+            pub struct MainModel, MainMsg {
+                auth: AuthModel => Auth,
+                settings: SettingsModel => Settings,
+            }
+        }
+
+        #[tokio::test]
+        async fn test_compose_models() {
+            let model = MainModel::default();
+            let (model, _) = model
+                .update(MainMsg::Auth(AuthMsg::Login("wyvern".into())))
+                .await;
+            assert_eq!(model.auth.user.as_deref(), Some("wyvern"));
+            assert!(!model.settings.dark_mode);
+
+            let (model, effect) = model
+                .update(MainMsg::Settings(SettingsMsg::ToggleDarkMode))
+                .await;
+            assert_eq!(model.auth.user.as_deref(), Some("wyvern"));
+            assert!(model.settings.dark_mode);
+
+            // effects of a sub model are mapped into the combined message.
+            let (tx, rx) = flume::unbounded();
+            effect.0.run_effect(tx).await;
+            assert!(matches!(
+                rx.try_recv(),
+                Ok(MainMsg::Settings(SettingsMsg::ToggleDarkMode))
+            ));
+
+            let (model, _) = model.update(MainMsg::Auth(AuthMsg::Logout)).await;
+            assert!(model.auth.user.is_none());
+            assert!(model.settings.dark_mode);
+        }
+    }
+
+    mod reload {
+        use std::ffi::OsString;
+        use std::path::PathBuf;
+
+        use crate::reload::{HotReload, state_path};
+
+        #[derive(Debug, Clone, PartialEq)]
+        #[cfg_attr(feature = "hot-reload", derive(serde::Serialize, serde::Deserialize))]
+        struct Model {
+            count: u32,
+            draft: String,
+        }
+
+        fn temp_file(name: &str) -> PathBuf {
+            std::env::temp_dir().join(format!("potion-{}-{name}", std::process::id()))
+        }
+
+        #[test]
+        fn test_state_path() {
+            assert_eq!(state_path(None), None);
+            // an empty variable is the same as none, `mx` clears it to start cold.
+            assert_eq!(state_path(Some(OsString::new())), None);
+            assert_eq!(
+                state_path(Some("/tmp/app.state".into())),
+                Some(PathBuf::from("/tmp/app.state"))
+            );
+        }
+
+        #[test]
+        fn test_restore_handshake() {
+            let path = temp_file("handshake");
+            let hot_reload = HotReload::<Model>::new(
+                |model| Ok(format!("{}:{}", model.count, model.draft).into_bytes()),
+                |bytes| {
+                    let text = String::from_utf8_lossy(bytes);
+                    let (count, draft) = text
+                        .split_once(':')
+                        .ok_or(std::io::ErrorKind::InvalidData)?;
+                    Ok(Model {
+                        count: count.parse().map_err(|_| std::io::ErrorKind::InvalidData)?,
+                        draft: draft.to_string(),
+                    })
+                },
+            )
+            .with_path(&path);
+
+            // a cold start has no state file and calls `init`.
+            assert_eq!(hot_reload.restore_model(), None);
+
+            let model = Model {
+                count: 3,
+                draft: "half a todo".into(),
+            };
+            hot_reload.save_model(&model).unwrap();
+            assert_eq!(hot_reload.restore_model(), Some(model));
+            // the state is used once, the next start is cold again.
+            assert!(!path.exists());
+            assert_eq!(hot_reload.restore_model(), None);
+
+            // a model that doesn't decode anymore starts over.
+            std::fs::write(&path, "garbage").unwrap();
+            assert_eq!(hot_reload.restore_model(), None);
+            assert!(!path.exists());
+        }
+
+        #[cfg(feature = "hot-reload")]
+        #[test]
+        fn test_persist_round_trip() {
+            let path = temp_file("persist");
+            let hot_reload = HotReload::<Model>::persist().with_path(&path);
+            let model = Model {
+                count: 7,
+                draft: "résumé".into(),
+            };
+            hot_reload.save_model(&model).unwrap();
+            assert_eq!(hot_reload.restore_model(), Some(model));
+        }
+    }
+}
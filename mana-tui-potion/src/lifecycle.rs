@@ -0,0 +1,89 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use flume::Sender;
+
+use crate::backends::{DefaultEvent, DefaultKeyEvent};
+use crate::middleware::{self, Middleware};
+use crate::{Message, UpdateFn};
+
+/// decides what happens when the quit signal fires, returned by the hook set with
+/// `run().on_before_quit(..)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QuitDecision<Msg> {
+    /// exits the runtime.
+    Quit,
+    /// ignores the quit signal and keeps running.
+    Cancel,
+    /// dispatches the message, for example to show an "unsaved changes" modal, and keeps
+    /// running. the runtime exits once the quit signal fires again and the hook returns
+    /// [`QuitDecision::Quit`].
+    Confirm(Msg),
+}
+
+/// hook called with the model when the quit signal fires.
+pub type BeforeQuitFn<Model, Msg> = Box<dyn Fn(&Model) -> QuitDecision<Msg>>;
+
+/// hook called with the final model after the terminal is restored, also when the runtime
+/// stops with an error.
+pub type ExitFn<Model> = Box<dyn FnOnce(&Model)>;
+
+/// the lifecycle hooks around a run, set with `run()`. the ones the runtime calls while it
+/// runs are part of its `Runtime`.
+pub(crate) struct Hooks<Msg: Message> {
+    /// dispatched once after the first render.
+    pub(crate) on_ready: Option<Msg>,
+    pub(crate) on_exit: Option<ExitFn<Msg::Model>>,
+}
+
+/// applies the decision of the before quit hook. returns `true` if the runtime should exit.
+pub(crate) fn should_quit<Msg>(decision: QuitDecision<Msg>, tx: &Sender<Msg>) -> bool {
+    match decision {
+        QuitDecision::Quit => true,
+        QuitDecision::Cancel => false,
+        QuitDecision::Confirm(msg) => {
+            if tx.send(msg).is_err() {
+                tracing::error!("failed to dispatch quit confirmation: app channel closed");
+            }
+            false
+        }
+    }
+}
+
+/// the key that shuts the runtime down even if the view doesn't handle it: ctrl + c.
+#[must_use]
+pub fn default_quit_key() -> DefaultKeyEvent {
+    KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL)
+}
+
+/// how the runtime shuts down without the quit signal: on the quit key, and when the event
+/// stream ends because the terminal was closed.
+pub(crate) struct Shutdown<Msg> {
+    /// `None` if the app handles the quit key itself.
+    pub(crate) key: Option<DefaultKeyEvent>,
+    /// applied before shutting down.
+    pub(crate) msg: Option<Msg>,
+}
+
+impl<Msg: Message> Shutdown<Msg> {
+    pub(crate) fn is_quit_key(&self, event: &DefaultEvent) -> bool {
+        self.key
+            .is_some_and(|key| event.as_key_press_event() == Some(key))
+    }
+
+    /// applies the shutdown message and returns the final model. the effect of the update is
+    /// dropped, the runtime exits right after.
+    pub(crate) async fn run(
+        &self,
+        model: Msg::Model,
+        update: &impl UpdateFn<Msg, Msg::Model>,
+        middleware: Option<&Middleware<Msg, Msg::Model>>,
+    ) -> Msg::Model {
+        match &self.msg {
+            Some(msg) => {
+                middleware::apply(model, msg.clone(), update, middleware)
+                    .await
+                    .0
+            }
+            None => model,
+        }
+    }
+}
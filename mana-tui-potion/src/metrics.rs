@@ -0,0 +1,226 @@
+use std::time::Duration;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use hecs::World;
+use mana_tui_elemental::{
+    damage::Damage,
+    layout::{ElementCtx, Height, LayoutStats, Width},
+    profile::Profiler,
+    ui::{View, ui},
+};
+use mana_tui_utils::resource::Resources;
+use ratatui::{buffer::Buffer, layout::Rect, widgets::Block};
+
+use crate::backends::DefaultKeyEvent;
+
+/// weight of the newest frame in the frame time moving average.
+const EMA_WEIGHT: f32 = 0.2;
+const OVERLAY_WIDTH: u16 = 20;
+const OVERLAY_HEIGHT: u16 = 8;
+const PROFILE_WIDTH: u16 = 56;
+/// children listed per element in the [`profile_overlay`].
+const PROFILE_TOP_N: usize = 3;
+
+/// runtime statistics, updated by the runtime on every draw. stored as a resource.
+///
+/// set [`ShowMetrics`] to render them with [`metrics_overlay`] on top of the app.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Metrics {
+    /// exponential moving average of the time between draws.
+    pub frame_time: Duration,
+    /// number of entities in the context, not counting the overlay.
+    pub entities: u32,
+    /// messages waiting in the dispatch channel.
+    pub queue_len: usize,
+    /// duration of the last layout calculation, see [`LayoutStats`].
+    pub layout_time: Duration,
+    /// cells that changed in the last draw, see [`Damage`].
+    pub damaged_cells: usize,
+    /// the area containing every cell that changed in the last draw.
+    pub damage: Rect,
+    /// elements rendered in the last draw.
+    pub rendered: usize,
+    /// unchanged subtrees copied from the previous draw instead of being rendered.
+    pub reused: usize,
+    /// draws skipped because nothing changed.
+    pub skipped_frames: u64,
+}
+
+impl Metrics {
+    /// feeds the time since the previous draw into the frame time average.
+    pub fn record_frame(&mut self, dt: Duration) {
+        self.frame_time = if self.frame_time.is_zero() {
+            dt
+        } else {
+            self.frame_time.mul_f32(1.0 - EMA_WEIGHT) + dt.mul_f32(EMA_WEIGHT)
+        };
+    }
+
+    /// draws per second, derived from [`Metrics::frame_time`].
+    #[must_use]
+    pub fn fps(&self) -> f32 {
+        if self.frame_time.is_zero() {
+            return 0.0;
+        }
+        1.0 / self.frame_time.as_secs_f32()
+    }
+}
+
+/// resource flag that shows the [`metrics_overlay`] in the top right corner of the terminal.
+/// toggled by the runtime with the key set through `run().metrics_key(..)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShowMetrics(pub bool);
+
+/// the usual key that toggles [`ShowMetrics`]: `F12`. the runtime only takes it if it is set
+/// with `run().metrics_key(..)`.
+#[must_use]
+pub fn default_metrics_key() -> DefaultKeyEvent {
+    KeyEvent::new(KeyCode::F(12), KeyModifiers::NONE)
+}
+
+/// the usual key that toggles the [`Profiler`] and its [`profile_overlay`]: `F11`. the runtime
+/// only takes it if it is set with `run().profiler_key(..)`.
+#[must_use]
+pub fn default_profiler_key() -> DefaultKeyEvent {
+    KeyEvent::new(KeyCode::F(11), KeyModifiers::NONE)
+}
+
+/// a box with the [`Profiler::report`] of the last frame.
+#[must_use]
+pub fn profile_overlay(report: &str, height: u16) -> View {
+    ui(Block::bordered().title("profile"))
+        .with((Width::fixed(PROFILE_WIDTH), Height::fixed(height)))
+        .clear_background()
+        .children(report.to_string())
+        .done()
+}
+
+/// a compact box listing the runtime [`Metrics`].
+#[must_use]
+pub fn metrics_overlay(metrics: &Metrics) -> View {
+    ui(Block::bordered().title("metrics"))
+        .with((Width::fixed(OVERLAY_WIDTH), Height::fixed(OVERLAY_HEIGHT)))
+        .clear_background()
+        .children(format!(
+            "fps {:.1}\nframe {:.1}ms\nlayout {:.1}ms\nentities {}\nqueue {}\ndamage {}",
+            metrics.fps(),
+            metrics.frame_time.as_secs_f32() * 1000.0,
+            metrics.layout_time.as_secs_f32() * 1000.0,
+            metrics.entities,
+            metrics.queue_len,
+            metrics.damaged_cells,
+        ))
+        .done()
+}
+
+pub(crate) fn record_frame(world: &mut World, dt: Duration, damage: &Damage) {
+    let layout_time = world
+        .get_resource::<&LayoutStats>()
+        .map(|stats| stats.duration)
+        .unwrap_or_default();
+    // counted before the overlay is spawned so it doesn't count itself.
+    let entities = world.len();
+    let mut metrics = world.get_or_insert_resource_with::<&mut Metrics>(|_| Metrics::default());
+    metrics.record_frame(dt);
+    metrics.layout_time = layout_time;
+    metrics.entities = entities;
+    metrics.damaged_cells = damage.cells;
+    metrics.damage = damage.bounds();
+    metrics.rendered = damage.rendered;
+    metrics.reused = damage.reused;
+}
+
+pub(crate) fn record_skipped_frame(world: &mut World) {
+    world
+        .get_or_insert_resource_with::<&mut Metrics>(|_| Metrics::default())
+        .skipped_frames += 1;
+}
+
+pub(crate) fn is_shown(world: &World) -> bool {
+    world
+        .get_resource::<&ShowMetrics>()
+        .is_ok_and(|show| show.0)
+        || is_profiling(world)
+}
+
+fn is_profiling(world: &World) -> bool {
+    world
+        .get_resource::<&Profiler>()
+        .is_ok_and(|profiler| profiler.is_enabled())
+}
+
+/// starts a new [`Profiler`] frame, so the tree and the overlays drawn after it are reported
+/// together.
+pub(crate) fn begin_profile_frame(world: &mut World) {
+    if let Ok(mut profiler) = world.get_resource::<&mut Profiler>() {
+        profiler.begin_frame();
+    }
+}
+
+pub(crate) fn toggle_profiler(world: &mut World) {
+    let mut profiler = world.get_or_insert_resource_with::<&mut Profiler>(|_| Profiler::default());
+    let enabled = profiler.is_enabled();
+    profiler.set_enabled(!enabled);
+}
+
+pub(crate) fn record_queue_len(world: &mut World, queue_len: usize) {
+    world
+        .get_or_insert_resource_with::<&mut Metrics>(|_| Metrics::default())
+        .queue_len = queue_len;
+}
+
+pub(crate) fn toggle(world: &mut World) {
+    let mut show = world.get_or_insert_resource_with::<&mut ShowMetrics>(|_| ShowMetrics(false));
+    show.0 = !show.0;
+}
+
+/// renders the [`metrics_overlay`] in the top right corner of `area`, on top of everything
+/// already in the buffer, if [`ShowMetrics`] is set. the [`profile_overlay`] goes in the bottom
+/// right corner while the [`Profiler`] is enabled.
+pub(crate) fn render_overlay(ctx: &mut ElementCtx, area: Rect, buf: &mut Buffer) {
+    let metrics = ctx
+        .get_resource::<&Metrics>()
+        .map(|metrics| *metrics)
+        .ok()
+        .filter(|_| ctx.get_resource::<&ShowMetrics>().is_ok_and(|show| show.0));
+    if let Some(metrics) = metrics {
+        let corner = Rect {
+            x: area.right().saturating_sub(OVERLAY_WIDTH),
+            y: area.y,
+            width: OVERLAY_WIDTH,
+            height: OVERLAY_HEIGHT,
+        }
+        .intersection(area);
+        render_box(ctx, metrics_overlay(&metrics), corner, buf);
+    }
+
+    let report = ctx
+        .get_resource::<&Profiler>()
+        .ok()
+        .filter(|profiler| profiler.is_enabled())
+        .map(|profiler| profiler.report(PROFILE_TOP_N));
+    if let Some(report) = report {
+        let height = u16::try_from(report.lines().count())
+            .unwrap_or(u16::MAX)
+            .saturating_add(2)
+            .min(area.height);
+        let corner = Rect {
+            x: area.right().saturating_sub(PROFILE_WIDTH),
+            y: area.bottom().saturating_sub(height),
+            width: PROFILE_WIDTH,
+            height,
+        }
+        .intersection(area);
+        render_box(ctx, profile_overlay(&report, height), corner, buf);
+    }
+}
+
+fn render_box(ctx: &mut ElementCtx, view: View, area: Rect, buf: &mut Buffer) {
+    let overlay = ctx.spawn_ui(view);
+    if let Err(err) = ctx.calculate_layout(overlay, area) {
+        tracing::error!("failed to calculate overlay layout: {err}");
+    } else {
+        ctx.render(overlay, area, buf);
+    }
+    ctx.despawn_ui(overlay);
+}
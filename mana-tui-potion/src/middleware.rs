@@ -0,0 +1,125 @@
+//! hooks that see every message the runtime applies, for logging, dev tools and replaying
+//! sessions.
+//!
+//! set one with `run().middleware(..)`. a [`Recorder`] keeps the messages of a session, and
+//! [`replay`] applies them again to a fresh model without a terminal.
+//!
+//! # Example
+//!
+//! ```
+//! # use mana_tui_potion::middleware::Middleware;
+//! # #[derive(Debug, Clone)] enum Msg { Inc }
+//! # #[derive(Debug, Clone)] struct Model(u32);
+//! let log = Middleware::new(|msg: &Msg, prev: &Model, next: &Model| {
+//!     tracing::debug!(?msg, ?prev, ?next, "update");
+//! });
+//! ```
+
+use std::sync::{Arc, Mutex};
+
+use crate::{Effect, InitFn, Message, UpdateFn};
+
+/// called after every update with the message, the previous model and the next model.
+///
+/// the middleware runs synchronously on the runtime task, between the update and the next
+/// render, so keep it cheap: send anything slow to another task.
+pub struct Middleware<Msg, Model> {
+    func: Box<dyn Fn(&Msg, &Model, &Model) + Send + Sync>,
+    snapshot: fn(&Model) -> Model,
+}
+
+impl<Msg, Model: Clone> Middleware<Msg, Model> {
+    /// a middleware calling `func`. the runtime clones the model before each update to pass it
+    /// as the previous model.
+    pub fn new(func: impl Fn(&Msg, &Model, &Model) + Send + Sync + 'static) -> Self {
+        Self {
+            func: Box::new(func),
+            snapshot: Model::clone,
+        }
+    }
+}
+
+/// a middleware that keeps every message, to [`replay`] the session later.
+///
+/// clones share the same messages, so keep one and pass [`Recorder::middleware`] to the
+/// runtime.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_potion::middleware::Recorder;
+/// # #[derive(Debug, Clone)] enum Msg { Inc }
+/// # #[derive(Debug, Clone)] struct Model(u32);
+/// let recorder = Recorder::<Msg>::default();
+/// let middleware = recorder.middleware::<Model>();
+/// // `run().middleware(middleware)`, then after the session:
+/// let session = recorder.messages();
+/// ```
+#[derive(Debug)]
+pub struct Recorder<Msg>(Arc<Mutex<Vec<Msg>>>);
+
+impl<Msg> Clone for Recorder<Msg> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<Msg> Default for Recorder<Msg> {
+    fn default() -> Self {
+        Self(Arc::default())
+    }
+}
+
+impl<Msg: Clone + Send + 'static> Recorder<Msg> {
+    /// a middleware that appends every message to the recorder.
+    #[must_use]
+    pub fn middleware<Model: Clone>(&self) -> Middleware<Msg, Model> {
+        let messages = self.0.clone();
+        Middleware::new(move |msg: &Msg, _: &Model, _: &Model| {
+            messages.lock().unwrap().push(msg.clone());
+        })
+    }
+
+    /// the recorded messages, in the order they were applied.
+    ///
+    /// # Panics
+    ///
+    /// panics if a thread panicked while recording.
+    #[must_use]
+    pub fn messages(&self) -> Vec<Msg> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// runs `init` and applies the `recorded` messages with `update`, without a terminal or a view.
+/// returns the final model.
+///
+/// effects are dropped instead of run: the messages they sent during the session were recorded
+/// too, and are replayed in their place. the replay is deterministic as long as `update` is.
+pub async fn replay<Msg: Message>(
+    recorded: impl IntoIterator<Item = Msg>,
+    init: impl InitFn<Msg, Msg::Model>,
+    update: impl UpdateFn<Msg, Msg::Model>,
+) -> Msg::Model {
+    let (mut model, _) = init().await;
+    for msg in recorded {
+        (model, _) = update(model, msg).await;
+    }
+    model
+}
+
+/// applies `msg` with `update` and calls the middleware with the result.
+pub(crate) async fn apply<Msg: Message>(
+    model: Msg::Model,
+    msg: Msg,
+    update: &impl UpdateFn<Msg, Msg::Model>,
+    middleware: Option<&Middleware<Msg, Msg::Model>>,
+) -> (Msg::Model, Effect<Msg>) {
+    let Some(middleware) = middleware else {
+        return update(model, msg).await;
+    };
+    let prev = (middleware.snapshot)(&model);
+    let (model, effect) = update(model, msg.clone()).await;
+    (middleware.func)(&msg, &prev, &model);
+    (model, effect)
+}
@@ -0,0 +1,90 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use mana_tui_elemental::color::ColorCaps;
+use ratatui::Viewport;
+
+use crate::backends::{FRAME_TIME, TerminalCommand};
+
+/// terminal setup done by [`run`](crate::run), passed with `run().options(..)`.
+///
+/// [`RuntimeOptions::default`] takes over the whole screen and enables nothing else.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui_potion::options::RuntimeOptions;
+/// let options = RuntimeOptions::builder()
+///     .mouse_capture(true)
+///     .kitty_keyboard(true)
+///     .frame_rate_cap(30)
+///     .build();
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, bon::Builder)]
+pub struct RuntimeOptions {
+    /// reports mouse events.
+    #[builder(default)]
+    pub mouse_capture: bool,
+    /// reports focus gained and lost events.
+    #[builder(default)]
+    pub focus_events: bool,
+    /// enables the kitty keyboard protocol, so release events and ambiguous keys like `Esc` are
+    /// reported.
+    #[builder(default)]
+    pub kitty_keyboard: bool,
+    /// where the app is drawn. the alternate screen is only entered for
+    /// [`Viewport::Fullscreen`].
+    #[builder(default)]
+    pub viewport: Viewport,
+    /// the maximum frames per second of animations. about 60 when unset, see [`FRAME_TIME`].
+    pub frame_rate_cap: Option<u32>,
+    /// the colors the terminal can show, RGB colors are degraded to the closest supported ones.
+    /// detected from `COLORTERM` and `TERM` when unset, see [`ColorCaps::detect`].
+    pub color_caps: Option<ColorCaps>,
+    /// writes every drawn frame to this `.frames` file when the runtime stops, see
+    /// [`frames`](crate::frames).
+    #[builder(into)]
+    pub record_frames: Option<PathBuf>,
+}
+
+impl RuntimeOptions {
+    /// the commands run after entering raw mode, in order.
+    #[must_use]
+    pub fn enter_commands(&self) -> Vec<TerminalCommand> {
+        let mut commands = Vec::new();
+        if self.mouse_capture {
+            commands.push(TerminalCommand::EnableMouseCapture);
+        }
+        if self.focus_events {
+            commands.push(TerminalCommand::EnableFocusEvents);
+        }
+        if self.kitty_keyboard {
+            commands.push(TerminalCommand::PushKeyboardEnhancement);
+        }
+        commands
+    }
+
+    /// the commands run before the terminal is restored, undoing [`Self::enter_commands`] in
+    /// reverse order.
+    #[must_use]
+    pub fn leave_commands(&self) -> Vec<TerminalCommand> {
+        let mut commands = Vec::new();
+        if self.kitty_keyboard {
+            commands.push(TerminalCommand::PopKeyboardEnhancement);
+        }
+        if self.focus_events {
+            commands.push(TerminalCommand::DisableFocusEvents);
+        }
+        if self.mouse_capture {
+            commands.push(TerminalCommand::DisableMouseCapture);
+        }
+        commands
+    }
+
+    /// time between animation frames.
+    #[must_use]
+    pub fn frame_time(&self) -> Duration {
+        self.frame_rate_cap
+            .map_or(FRAME_TIME, |fps| Duration::from_secs(1) / fps.max(1))
+    }
+}
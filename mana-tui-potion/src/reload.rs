@@ -0,0 +1,153 @@
+//! hot reloading under the `mx` dev server.
+//!
+//! `mx` starts the app with [`STATE_ENV`] set to the path of a state file. when a rebuild is
+//! ready it sends the app `SIGUSR1`, the runtime writes the model to the file, restores the
+//! terminal and returns [`RuntimeErr::Reload`](crate::RuntimeErr::Reload), the app then exits
+//! with [`RELOAD_EXIT_CODE`]. the new build finds the file on startup and continues from the
+//! saved model instead of calling `init`.
+//!
+//! pass a [`HotReload`] to [`run`](crate::run) to opt in. with the `hot-reload` feature any
+//! serde model can use [`HotReload::persist`].
+//!
+//! ```no_run
+//! # async fn app() -> Result<(), mana_tui_potion::RuntimeErr> { Ok(()) }
+//! # async fn exit() {
+//! // `app` runs the app with `run`.
+//! match app().await {
+//!     Err(mana_tui_potion::RuntimeErr::Reload) => {
+//!         std::process::exit(mana_tui_potion::reload::RELOAD_EXIT_CODE)
+//!     }
+//!     result => result.unwrap(),
+//! }
+//! # }
+//! ```
+
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use flume::Sender;
+
+pub use mana_tui_utils::reload::{RELOAD_EXIT_CODE, STATE_ENV};
+
+/// models that survive a reload, implemented for every serde model.
+#[cfg(feature = "hot-reload")]
+pub trait Persist: Sized {
+    /// encodes the model.
+    ///
+    /// # Errors
+    ///
+    /// if the model can't be serialized.
+    fn persist(&self) -> io::Result<Vec<u8>>;
+    /// decodes a model written by [`Persist::persist`].
+    ///
+    /// # Errors
+    ///
+    /// if the bytes aren't a model of this type, for example after its fields changed.
+    fn restore(bytes: &[u8]) -> io::Result<Self>;
+}
+
+#[cfg(feature = "hot-reload")]
+impl<T: serde::Serialize + serde::de::DeserializeOwned> Persist for T {
+    fn persist(&self) -> io::Result<Vec<u8>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    fn restore(bytes: &[u8]) -> io::Result<Self> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+/// how the runtime saves and restores the model for a reload.
+pub struct HotReload<Model> {
+    save: fn(&Model) -> io::Result<Vec<u8>>,
+    restore: fn(&[u8]) -> io::Result<Model>,
+    path: Option<PathBuf>,
+}
+
+impl<Model> HotReload<Model> {
+    /// reloads with the state file from [`STATE_ENV`]. does nothing outside of `mx`.
+    pub fn new(
+        save: fn(&Model) -> io::Result<Vec<u8>>,
+        restore: fn(&[u8]) -> io::Result<Model>,
+    ) -> Self {
+        Self {
+            save,
+            restore,
+            path: state_path(std::env::var_os(STATE_ENV)),
+        }
+    }
+
+    /// reloads with the [`Persist`] implementation of the model.
+    #[cfg(feature = "hot-reload")]
+    #[must_use]
+    pub fn persist() -> Self
+    where
+        Model: Persist,
+    {
+        Self::new(Model::persist, Model::restore)
+    }
+
+    /// uses `path` as the state file instead of the one from [`STATE_ENV`].
+    #[must_use]
+    pub fn with_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// the state file, if running under `mx`.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// writes `model` to the state file.
+    ///
+    /// # Errors
+    ///
+    /// if there's no state file or the model can't be written.
+    pub fn save_model(&self, model: &Model) -> io::Result<()> {
+        let path = self
+            .path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no state file"))?;
+        std::fs::write(path, (self.save)(model)?)
+    }
+
+    /// the model saved before the reload. the state file is removed, so the next cold start
+    /// calls `init` again.
+    pub fn restore_model(&self) -> Option<Model> {
+        let path = self.path()?;
+        let bytes = std::fs::read(path).ok()?;
+        _ = std::fs::remove_file(path);
+        (self.restore)(&bytes)
+            .inspect_err(|err| tracing::warn!("failed to restore the model, starting over: {err}"))
+            .ok()
+    }
+}
+
+/// the state file named by the value of [`STATE_ENV`].
+pub(crate) fn state_path(var: Option<OsString>) -> Option<PathBuf> {
+    var.filter(|var| !var.is_empty()).map(PathBuf::from)
+}
+
+/// sends to `tx` every time the process gets `SIGUSR1`.
+pub(crate) fn listen(tx: Sender<()>) {
+    #[cfg(unix)]
+    tokio::spawn(async move {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        let mut signals = match signal(SignalKind::user_defined1()) {
+            Ok(signals) => signals,
+            Err(err) => {
+                tracing::error!("failed to listen for reloads: {err}");
+                return;
+            }
+        };
+        while signals.recv().await.is_some() {
+            if tx.send_async(()).await.is_err() {
+                break;
+            }
+        }
+    });
+    #[cfg(not(unix))]
+    drop(tx);
+}
@@ -0,0 +1,186 @@
+use std::{
+    collections::{HashMap, HashSet},
+    hash::{DefaultHasher, Hash, Hasher},
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+use flume::{Receiver, Sender};
+use mana_tui_elemental::{
+    layout::{Children, Element, ElementCtx},
+    ui::{View, ui},
+};
+use mana_tui_utils::resource::Resources;
+use ratatui::widgets::Block;
+use tokio::task::AbortHandle;
+
+type Loader = Pin<Box<dyn Future<Output = View> + Send + 'static>>;
+
+/// placeholder created by [`suspense`]. holds the loader until the runtime starts it.
+pub struct SuspenseSlot {
+    key: u64,
+    loader: Mutex<Option<Loader>>,
+}
+
+enum SuspenseEntry {
+    Loading(AbortHandle),
+    Ready(Element),
+}
+
+/// resolved and in flight [`suspense`] views, keyed by the hash of their key.
+pub(crate) struct SuspenseStore {
+    entries: HashMap<u64, SuspenseEntry>,
+    tx: Sender<(u64, View)>,
+}
+
+/// shows `fallback` until `loader` resolves, then shows the view it resolved to.
+///
+/// the first time a key is rendered, the runtime spawns `loader` and redraws once it is done.
+/// the view function calls `suspense` again with the same key on every render and gets the
+/// resolved view from then on, so the loader of later calls is never polled. keys must be unique
+/// within the tree.
+///
+/// when a key is missing from a render, its resolved view is dropped and a loader that is still
+/// running is cancelled.
+///
+/// # Example
+///
+/// ```ignore
+/// suspense(
+///     ("user", model.user_id),
+///     async move { user_card(fetch_user(user_id).await) },
+///     ui(Text::raw("loading...")).done(),
+/// )
+/// ```
+pub fn suspense(
+    key: impl Hash,
+    loader: impl Future<Output = View> + Send + 'static,
+    fallback: View,
+) -> View {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    ui(Block::new())
+        .with((SuspenseSlot {
+            key: hasher.finish(),
+            loader: Mutex::new(Some(Box::pin(loader))),
+        },))
+        .child(fallback)
+        .done()
+}
+
+/// inserts the [`SuspenseStore`] resource. resolved views are sent through the returned channel
+/// and should be passed to [`resolve`].
+pub(crate) fn init_suspense(ctx: &mut ElementCtx) -> Receiver<(u64, View)> {
+    let (tx, rx) = flume::unbounded();
+    ctx.insert_or_update_resource(SuspenseStore {
+        entries: HashMap::new(),
+        tx,
+    });
+    rx
+}
+
+/// starts the loaders of new keys, swaps the fallback of resolved keys for their view and
+/// evicts the keys that are no longer in the tree. runs after the tree is spawned.
+pub(crate) fn update_suspense(ctx: &mut ElementCtx) {
+    let slots = ctx
+        .query_mut::<(Element, &mut SuspenseSlot)>()
+        .into_iter()
+        .map(|(slot, suspense)| {
+            let loader = suspense.loader.get_mut().ok().and_then(Option::take);
+            (slot, suspense.key, loader)
+        })
+        .collect::<Vec<_>>();
+
+    let mut ready = Vec::new();
+    let mut evicted = Vec::new();
+    {
+        let Ok(mut store) = ctx.get_resource::<&mut SuspenseStore>() else {
+            return;
+        };
+        let mut seen = HashSet::new();
+        for (slot, key, loader) in slots {
+            seen.insert(key);
+            match store.entries.get(&key) {
+                Some(SuspenseEntry::Ready(element)) => ready.push((slot, *element)),
+                Some(SuspenseEntry::Loading(_)) => {}
+                None => {
+                    let Some(loader) = loader else {
+                        continue;
+                    };
+                    let tx = store.tx.clone();
+                    let handle = tokio::spawn(async move {
+                        let view = loader.await;
+                        _ = tx.send_async((key, view)).await;
+                    });
+                    store
+                        .entries
+                        .insert(key, SuspenseEntry::Loading(handle.abort_handle()));
+                }
+            }
+        }
+        let missing = store
+            .entries
+            .keys()
+            .filter(|key| !seen.contains(*key))
+            .copied()
+            .collect::<Vec<_>>();
+        for key in missing {
+            evicted.extend(store.entries.remove(&key));
+        }
+    }
+
+    for (slot, element) in ready {
+        let fallback = ctx
+            .get::<&Children>(slot)
+            .map(|children| children.clone())
+            .unwrap_or_default();
+        _ = ctx.insert_one(slot, Children::Some(Arc::new(vec![element])));
+        for child in fallback.iter() {
+            ctx.despawn_ui(child);
+        }
+    }
+    for entry in evicted {
+        match entry {
+            SuspenseEntry::Loading(handle) => handle.abort(),
+            SuspenseEntry::Ready(element) => ctx.despawn_ui(element),
+        }
+    }
+}
+
+/// spawns a view sent by a loader. returns `true` if the key is still in the tree and the
+/// runtime should redraw.
+pub(crate) fn resolve(ctx: &mut ElementCtx, key: u64, view: View) -> bool {
+    let loading = ctx
+        .get_resource::<&SuspenseStore>()
+        .is_ok_and(|store| matches!(store.entries.get(&key), Some(SuspenseEntry::Loading(_))));
+    if !loading {
+        return false;
+    }
+    let element = ctx.spawn_ui(view);
+    if let Ok(mut store) = ctx.get_resource::<&mut SuspenseStore>() {
+        store.entries.insert(key, SuspenseEntry::Ready(element));
+    }
+    true
+}
+
+/// detaches the resolved views from their slots so despawning the old tree keeps them alive.
+pub(crate) fn detach_suspense(ctx: &mut ElementCtx) {
+    let Ok(store) = ctx.get_resource::<&SuspenseStore>() else {
+        return;
+    };
+    let resolved = store
+        .entries
+        .values()
+        .filter_map(|entry| match entry {
+            SuspenseEntry::Ready(element) => Some(*element),
+            SuspenseEntry::Loading(_) => None,
+        })
+        .collect::<HashSet<_>>();
+    drop(store);
+
+    for (_, children) in ctx.query_mut::<(&SuspenseSlot, &mut Children)>() {
+        if children.iter().any(|child| resolved.contains(&child)) {
+            *children = Children::None;
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! read only state for views.
+//!
+//! a view built with [`with_ctx`] also gets a [`ViewCtx`], a snapshot of the terminal size,
+//! the theme, the focus and the [`Metrics`] taken right before the view runs. views that only
+//! need the model keep the plain `async |model| ..` signature.
+//!
+//! # Example
+//!
+//! ```
+//! # use mana_tui_elemental::prelude::*;
+//! # use mana_tui_potion::view_ctx::{ViewCtx, with_ctx};
+//! let view = with_ctx(async |count: &u32, ctx: &ViewCtx| {
+//!     // narrow terminals stack the counter under its label.
+//!     let direction = if ctx.size().width < 60 {
+//!         Direction::Vertical
+//!     } else {
+//!         Direction::Horizontal
+//!     };
+//!     ui(Block::new())
+//!         .with((direction,))
+//!         .children((ui(Text::raw("count")), ui(Text::raw(count.to_string()))))
+//!         .done()
+//! });
+//! ```
+
+use std::any::TypeId;
+
+use hecs::Component;
+use mana_tui_elemental::{theme::DefaultStyles, ui::View};
+use mana_tui_utils::resource::Resources;
+use ratatui::{layout::Size, prelude::Backend};
+
+use crate::{Ctx, focus::FocusContext, metrics::Metrics};
+
+/// what a view can see of the runtime, see the [module docs](self).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ViewCtx {
+    size: Size,
+    theme: DefaultStyles,
+    focused: Option<TypeId>,
+    metrics: Metrics,
+}
+
+impl ViewCtx {
+    /// a context for a terminal of `size`, to call views outside of the runtime.
+    #[must_use]
+    pub fn new(size: Size) -> Self {
+        Self {
+            size,
+            ..Self::default()
+        }
+    }
+
+    /// the snapshot of `ctx` before the next view.
+    pub(crate) fn capture<B: Backend>(ctx: &mut Ctx<B>) -> Self {
+        let area = ctx.terminal.get_frame().area();
+        let world = &ctx.el_ctx;
+        Self {
+            size: area.as_size(),
+            theme: world
+                .get_resource::<&DefaultStyles>()
+                .map(|styles| *styles)
+                .unwrap_or_default(),
+            focused: world
+                .get_resource::<&FocusContext>()
+                .ok()
+                .and_then(|focus| focus.top()),
+            metrics: world
+                .get_resource::<&Metrics>()
+                .map(|metrics| *metrics)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// the size of the terminal, or of the viewport for inline apps.
+    #[must_use]
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// the theme of the context, the default one if none was set.
+    #[must_use]
+    pub fn theme(&self) -> DefaultStyles {
+        self.theme
+    }
+
+    /// the [`FocusTarget`](crate::focus::FocusTarget) that has the focus.
+    #[must_use]
+    pub fn focused(&self) -> Option<TypeId> {
+        self.focused
+    }
+
+    /// whether the focus is on the target `T`.
+    #[must_use]
+    pub fn is_focused<T: 'static>(&self) -> bool {
+        self.focused == Some(TypeId::of::<T>())
+    }
+
+    /// the statistics of the last draw.
+    #[must_use]
+    pub fn metrics(&self) -> Metrics {
+        self.metrics
+    }
+}
+
+/// a view of the model, passed to [`run`](crate::run).
+///
+/// implemented for every `async |model| ..` closure, wrap views that need the [`ViewCtx`] with
+/// [`with_ctx`].
+pub trait ViewFn<Msg, Model>: Component {
+    /// builds the view of `model`.
+    #[allow(async_fn_in_trait)]
+    async fn view(&self, model: &Model, ctx: &ViewCtx) -> View;
+}
+
+impl<Msg, Model, F> ViewFn<Msg, Model> for F
+where
+    F: AsyncFn(&Model) -> View + Component,
+{
+    async fn view(&self, model: &Model, _: &ViewCtx) -> View {
+        self(model).await
+    }
+}
+
+/// a view that gets the [`ViewCtx`], made with [`with_ctx`].
+#[derive(Debug, Clone, Copy)]
+pub struct WithCtx<F>(pub F);
+
+/// makes `f` a [`ViewFn`] that gets the [`ViewCtx`] next to the model.
+pub fn with_ctx<Model, F>(f: F) -> WithCtx<F>
+where
+    F: AsyncFn(&Model, &ViewCtx) -> View + Component,
+{
+    WithCtx(f)
+}
+
+impl<Msg, Model, F> ViewFn<Msg, Model> for WithCtx<F>
+where
+    F: AsyncFn(&Model, &ViewCtx) -> View + Component,
+{
+    async fn view(&self, model: &Model, ctx: &ViewCtx) -> View {
+        (self.0)(model, ctx).await
+    }
+}
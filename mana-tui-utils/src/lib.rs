@@ -3,6 +3,7 @@ use std::ops::{Deref, DerefMut};
 use hecs::World;
 
 pub mod ext;
+pub mod reload;
 pub mod resource;
 pub mod systems;
 
@@ -0,0 +1,9 @@
+//! the protocol between the `mx` dev server and the apps it reloads, shared by `mx` and
+//! `mana-tui-potion` so both sides agree on it.
+
+/// the environment variable with the path of the state file the app keeps its model in across
+/// reloads, set by `mx`.
+pub const STATE_ENV: &str = "MX_RELOAD_STATE";
+
+/// the exit code of an app that saved its model for a reload.
+pub const RELOAD_EXIT_CODE: i32 = 75;
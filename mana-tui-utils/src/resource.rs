@@ -47,6 +47,7 @@ pub trait Resources {
         });
     }
     fn insert_or_update_resource<T: Component>(&mut self, value: T);
+    fn remove_resource<T: Component>(&mut self) -> Result<T, hecs::ComponentError>;
     fn get_or_insert_resource_with<'w: 'a, 'a, T: ComponentRef<'a>>(
         &'w mut self,
         value: impl FnOnce(&mut Self) -> T::Component,
@@ -87,6 +88,14 @@ impl Resources for World {
         self.insert_resource(value);
     }
 
+    fn remove_resource<T: Component>(&mut self) -> Result<T, hecs::ComponentError> {
+        let (res_entity, entity) = Res::<&T>::get_res_entity(self)?;
+        let value = self.remove_one::<T>(entity)?;
+        _ = self.despawn(entity);
+        _ = self.despawn(res_entity);
+        Ok(value)
+    }
+
     fn get_or_insert_resource_with<'w: 'a, 'a, T: ComponentRef<'a>>(
         &'w mut self,
         value: impl FnOnce(&mut Self) -> T::Component,
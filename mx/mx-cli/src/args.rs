@@ -28,13 +28,35 @@ pub enum MxCommand {
 
 #[derive(clap::Args, Clone, Debug, Serialize, Deserialize)]
 pub struct RunnableArgs {
-    /// The height of the inline tui app view as a percentage of the screen.
+    /// The height of the inline tui app view, as a percentage of the screen (`30%`) or in
+    /// rows (`20`).
     #[arg(
         short = 'y', long,
         default_value = "60%",
-        value_parser = parse_percentage
+        value_parser = parse_height
     )]
-    pub height: u32,
+    pub height: ViewportHeight,
+}
+
+/// The height of the inline viewport, recomputed when the terminal is resized.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ViewportHeight {
+    /// A percentage of the terminal height.
+    Percent(u16),
+    /// A number of rows, at most the terminal height.
+    Rows(u16),
+}
+
+impl ViewportHeight {
+    /// The rows of the viewport in a terminal `terminal_height` rows tall.
+    pub fn rows(self, terminal_height: u16) -> u16 {
+        match self {
+            ViewportHeight::Percent(percent) => {
+                (u32::from(terminal_height) * u32::from(percent) / 100) as u16
+            }
+            ViewportHeight::Rows(rows) => rows.min(terminal_height),
+        }
+    }
 }
 
 impl MxArgs {
@@ -43,9 +65,21 @@ impl MxArgs {
     }
 }
 
-fn parse_percentage(val: &str) -> Result<u32> {
-    let val = val.trim_end_matches("%");
-    let num: u32 = val.parse()?;
+fn parse_height(val: &str) -> Result<ViewportHeight> {
+    match val.strip_suffix('%') {
+        Some(percent) => Ok(ViewportHeight::Percent(parse_percentage(percent)?)),
+        None => {
+            let rows: u16 = val.parse()?;
+            if rows == 0 {
+                bail!("Height must be at least one row");
+            }
+            Ok(ViewportHeight::Rows(rows))
+        }
+    }
+}
+
+fn parse_percentage(val: &str) -> Result<u16> {
+    let num: u16 = val.parse()?;
     if !(0..=100).contains(&num) {
         bail!("Percentage must be between 0 and 100");
     }
@@ -88,3 +122,40 @@ pub struct Features {
     /// Space-separated list of features to activate
     pub features: Vec<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_height() {
+        assert_eq!(parse_height("30%").unwrap(), ViewportHeight::Percent(30));
+        assert_eq!(parse_height("20").unwrap(), ViewportHeight::Rows(20));
+        assert!(parse_height("120%").is_err());
+        assert!(parse_height("0").is_err());
+        assert!(parse_height("tall").is_err());
+
+        let height = |args: &[&str]| {
+            let args = MxArgs::try_parse_from(["mx", "serve"].iter().chain(args)).unwrap();
+            let MxCommand::Serve(serve) = args.cmd else {
+                panic!("expected serve");
+            };
+            serve.args.height
+        };
+        assert_eq!(height(&[]), ViewportHeight::Percent(60));
+        assert_eq!(height(&["--height", "25"]), ViewportHeight::Rows(25));
+        assert_eq!(height(&["-y", "40%"]), ViewportHeight::Percent(40));
+    }
+
+    #[test]
+    fn test_viewport_rows() {
+        assert_eq!(ViewportHeight::Percent(60).rows(50), 30);
+        assert_eq!(ViewportHeight::Percent(30).rows(10), 3);
+        assert_eq!(ViewportHeight::Percent(100).rows(u16::MAX), u16::MAX);
+        assert_eq!(ViewportHeight::Rows(20).rows(50), 20);
+        // the viewport can't be taller than the terminal.
+        assert_eq!(ViewportHeight::Rows(30).rows(24), 24);
+    }
+}
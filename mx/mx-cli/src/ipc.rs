@@ -1,5 +1,6 @@
 use std::{
     net::{TcpListener, TcpStream},
+    path::PathBuf,
     process::Stdio,
     sync::atomic::Ordering,
 };
@@ -32,6 +33,11 @@ pub(crate) enum InnerProgressUpdate {
 
 pub(crate) struct IpcInner {
     running: Option<args::Serve>,
+    /// the app started by the last build.
+    child: Option<std::process::Child>,
+    /// where the app keeps its model across reloads, passed to it in
+    /// [`mx_core::RELOAD_STATE_ENV`].
+    state_path: PathBuf,
     stream: TcpStream,
 }
 
@@ -41,6 +47,8 @@ impl IpcInner {
         let stream = TcpStream::connect(format!("127.0.0.1:{port}"))?;
         Ok(Self {
             running: None,
+            child: None,
+            state_path: std::env::temp_dir().join(format!("mx-{}.state", std::process::id())),
             stream,
         })
     }
@@ -59,7 +67,11 @@ impl IpcInner {
             let msg = IpcMessage::deserialize(&mut deserializer)?;
             match msg {
                 IpcMessage::InnerProgressUpdate(_) => {}
-                IpcMessage::Run(serve) => self.build_and_run(&serve)?,
+                IpcMessage::Run(serve) => {
+                    // a fresh run starts cold.
+                    _ = std::fs::remove_file(&self.state_path);
+                    self.build_and_run(&serve)?;
+                }
                 IpcMessage::Reload => {
                     if let Some(serve) = self.running.clone() {
                         self.build_and_run(&serve)?;
@@ -139,13 +151,40 @@ impl IpcInner {
             }
         }
         let (_, run_cmd) = build_cmd();
-        run_cmd.run()?.command().spawn()?;
+        let mut command = run_cmd.run()?.command();
+        // the new build runs in the same pty, it picks up the model the old one saved.
+        if let Some(mut child) = self.child.take() {
+            stop_for_reload(&mut child)?;
+        }
+        command.env(mx_core::RELOAD_STATE_ENV, &self.state_path);
+        self.child = Some(command.spawn()?);
         self.running = Some(serve.clone());
 
         Ok(())
     }
 }
 
+/// asks the running app to save its model and waits for it to exit. apps without hot reloading
+/// just exit on the signal and the new build starts cold.
+fn stop_for_reload(child: &mut std::process::Child) -> Result<()> {
+    if child.try_wait()?.is_some() {
+        return Ok(());
+    }
+    let signaled = cfg!(unix)
+        && std::process::Command::new("kill")
+            .args(["-USR1", &child.id().to_string()])
+            .status()
+            .is_ok_and(|status| status.success());
+    if !signaled {
+        child.kill()?;
+    }
+    let status = child.wait()?;
+    if status.code() != Some(mx_core::RELOAD_EXIT_CODE) {
+        tracing::debug!("app exited with {status} instead of saving its model");
+    }
+    Ok(())
+}
+
 pub(crate) struct OuterIpc {
     listener: TcpListener,
 }
@@ -15,20 +15,21 @@ use crossterm::event::{Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
 use flume::Receiver;
 use flume::Sender;
 use mx_core::RenderMsg;
+use mx_core::fx::EffectSet;
+use mx_core::fx::TITLE_SHIMMER;
+use mx_core::fx::title_shimmer;
 use mx_core::logging::DevServerLogCollector;
 use notify::PollWatcher;
 use notify::Watcher;
 use portable_pty::{NativePtySystem, PtySize, PtySystem};
 use ratatui::DefaultTerminal;
+use ratatui::Terminal;
 use ratatui::TerminalOptions;
 use ratatui::Viewport;
 use ratatui::crossterm;
-use ratatui::layout::Rect;
+use ratatui::prelude::CrosstermBackend;
 use ratatui::widgets::Widget;
 use tachyonfx::Duration;
-use tachyonfx::fx;
-use tachyonfx::fx::RepeatMode;
-use tachyonfx::pattern::SweepPattern;
 use terminput::Encoding;
 use terminput::KittyFlags;
 use terminput_crossterm::to_terminput;
@@ -46,7 +47,6 @@ use mx_core::logging::RatatuiLayer;
 
 use crate::ipc::IpcEvent;
 use crate::ipc::OuterIpc;
-use crate::tui::AppFx;
 
 static SERVING: &str = r#" ----------------------------------------------------------
         💫 Serving your application!
@@ -125,7 +125,7 @@ impl AppBridge {
                     .try_init();
 
                 let mut terminal = ratatui::init_with_options(TerminalOptions {
-                    viewport: Viewport::Inline(run.args.height as u16 * self.aspect.1 / 100),
+                    viewport: Viewport::Inline(run.args.height.rows(self.aspect.1)),
                 });
                 // spawn the log collecter
                 let dev_server_port = DevServerLogCollector::start(self.render_chan.0.clone())?;
@@ -282,7 +282,7 @@ impl AppBridge {
                 std::thread::sleep(Duration::from_millis(100).into());
             }
             if crossterm::event::poll(Duration::from_millis(16).into())? {
-                self.handle_crossterm_events(&mut state)?;
+                self.handle_crossterm_events(&mut state, &mut terminal)?;
             }
             let dt = state.last_frame.elapsed();
             state.last_frame = Instant::now();
@@ -360,7 +360,11 @@ impl AppBridge {
     }
 
     /// Reads the crossterm events and updates the state of [`App`].
-    fn handle_crossterm_events(&self, state: &mut RendererState) -> Result<()> {
+    fn handle_crossterm_events(
+        &self,
+        state: &mut RendererState,
+        terminal: &mut DefaultTerminal,
+    ) -> Result<()> {
         let event = crossterm::event::read();
         if let Ok(evt) = &event {
             match evt.clone() {
@@ -372,17 +376,10 @@ impl AppBridge {
                 }
                 Event::Key(key) if key.kind == KeyEventKind::Press => self.on_key_event(state, key),
                 Event::Mouse(_) => {}
-                Event::Resize(w, h) => {
-                    let area = self.get_pty_area(Rect {
-                        x: 0,
-                        y: 0,
-                        width: w,
-                        height: h,
-                    });
-                    _ = self
-                        .parser_chan
-                        .0
-                        .send(ParserMsg::SetSize(area.width, area.height));
+                Event::Resize(_, height) => {
+                    if let Err(err) = self.resize_viewport(terminal, height) {
+                        tracing::warn!("failed to resize the viewport: {err}");
+                    }
                 }
                 _ => {}
             }
@@ -403,6 +400,29 @@ impl AppBridge {
         Ok(())
     }
 
+    /// Recreates the inline viewport with the height for a terminal `height` rows tall, and
+    /// resizes the pty to the area shown in it.
+    ///
+    /// ratatui keeps the height of an inline viewport for the lifetime of the terminal, so the
+    /// old viewport is cleared and a new terminal starts where it was. The logs above it stay.
+    fn resize_viewport(&self, terminal: &mut DefaultTerminal, height: u16) -> Result<()> {
+        let args::MxCommand::Serve(run) = &self.args.cmd else {
+            return Ok(());
+        };
+        terminal.clear()?;
+        *terminal = Terminal::with_options(
+            CrosstermBackend::new(std::io::stdout()),
+            TerminalOptions {
+                viewport: Viewport::Inline(run.args.height.rows(height)),
+            },
+        )?;
+        let area = self.get_pty_area(terminal.get_frame().area());
+        self.parser_chan
+            .0
+            .send(ParserMsg::SetSize(area.width, area.height))?;
+        Ok(())
+    }
+
     /// Handles the key events and updates the state of [`App`].
     fn on_key_event(&self, state: &mut RendererState, key: KeyEvent) {
         match (state.mx_menu_open, key.modifiers, key.code) {
@@ -428,7 +448,7 @@ impl AppBridge {
 }
 
 pub(crate) struct RendererState {
-    app_fx: AppFx,
+    fx: EffectSet,
     last_frame: Instant,
     screen: Option<Box<vt100::Screen>>,
     running_app: Option<String>,
@@ -454,25 +474,12 @@ pub(crate) enum RendererBuildState {
 
 impl RendererState {
     pub(crate) fn new() -> Self {
-        let app_fx = AppFx {
-            title_hsl_shift: Some(fx::repeat(
-                fx::parallel(&[
-                    fx::hsl_shift_fg([0.0, 0.0, 30.0], 1000)
-                        .with_pattern(SweepPattern::left_to_right(3)),
-                    fx::delay(
-                        200,
-                        fx::hsl_shift_fg([0.0, 0.0, -30.0], 800)
-                            .with_pattern(SweepPattern::left_to_right(3)),
-                    ),
-                ]),
-                RepeatMode::Forever,
-            )),
-            text_glitch_progress: 0.0,
-        };
+        let mut fx = EffectSet::new();
+        fx.insert(TITLE_SHIMMER, title_shimmer(), |area| area);
         Self {
             build_start: Instant::now(),
             build_duration: Duration::ZERO,
-            app_fx,
+            fx,
             last_frame: Instant::now(),
             screen: None,
             running_app: None,
@@ -1,3 +1,4 @@
+use mx_core::fx::TITLE_SHIMMER;
 use ratatui::Frame;
 use ratatui::layout::Constraint;
 use ratatui::layout::Direction;
@@ -18,8 +19,6 @@ use ratatui::widgets::Paragraph;
 use ratatui::widgets::Widget;
 use ratatui::widgets::Wrap;
 use tachyonfx::Duration;
-use tachyonfx::Effect;
-use tachyonfx::EffectRenderer;
 use tui_term::widget::PseudoTerminal;
 
 use crate::AppBridge;
@@ -27,17 +26,10 @@ use crate::AppStage;
 use crate::RendererBuildState;
 use crate::RendererState;
 
-pub struct AppFx {
-    pub(crate) title_hsl_shift: Option<Effect>,
-    pub(crate) text_glitch_progress: f32,
-}
-
 impl AppBridge {
+    /// The area of the viewport `area` the app is shown in, which is also the size of its pty.
     pub(crate) fn get_pty_area(&self, area: Rect) -> Rect {
-        let width = area.height * self.aspect.0 / self.aspect.1;
-        Layout::horizontal([Constraint::Max(width)])
-            .flex(Flex::Center)
-            .areas::<1>(area)[0]
+        pty_area(area, self.aspect)
     }
 
     /// Renders the user interface.
@@ -50,30 +42,15 @@ impl AppBridge {
         let title_text = format!("running {running_app}");
         let title_len = title_text.len();
         let title_text = format!(" 📺 {} ", title_text);
-        let block = Block::bordered()
-            .border_type(BorderType::Rounded)
-            .border_style(Style::new().dim())
-            .padding(Padding::uniform(1))
-            .title_top(title_text);
-        if let Some(fx) = &mut state.app_fx.title_hsl_shift {
-            let [title_area] =
-                Layout::new(Direction::Vertical, [Constraint::Length(1)]).areas(frame.area());
-            let [_, title_area] = Layout::new(
-                Direction::Horizontal,
-                [
-                    Constraint::Length(32 + 2),
-                    Constraint::Length(title_len as u16),
-                ],
-            )
-            .areas(title_area);
-            frame.render_effect(fx, title_area, dt);
-        }
-        let area = self.get_pty_area(frame.area()).outer(Margin {
-            horizontal: 1,
-            vertical: 1,
-        });
+        let block = app_block().title_top(title_text);
+        let title_len = title_len as u16;
+        state
+            .fx
+            .set_area(TITLE_SHIMMER, move |area| title_area(area, title_len));
+        state.fx.advance(dt, frame);
+        let area = frame_area(frame.area(), self.aspect);
         frame.render_widget(&block, area);
-        let screen_area = block.inner(area);
+        let screen_area = self.get_pty_area(frame.area());
 
         let [status_corner] = Layout::vertical([Constraint::Length(5)]).areas(frame.area());
         let [_, _, status_corner] = Layout::horizontal([
@@ -105,6 +82,47 @@ impl AppBridge {
     }
 }
 
+/// The block around the app, without its title.
+fn app_block<'a>() -> Block<'a> {
+    Block::bordered()
+        .border_type(BorderType::Rounded)
+        .border_style(Style::new().dim())
+        .padding(Padding::uniform(1))
+}
+
+/// The area of the title of the app block, `len` cells wide, in the viewport `area`.
+fn title_area(area: Rect, len: u16) -> Rect {
+    let [title_area] = Layout::new(Direction::Vertical, [Constraint::Length(1)]).areas(area);
+    let [_, title_area] = Layout::new(
+        Direction::Horizontal,
+        [Constraint::Length(32 + 2), Constraint::Length(len)],
+    )
+    .areas(title_area);
+    title_area
+}
+
+/// The area of the block around the app in the viewport `area`, as wide as the `aspect` ratio
+/// of the terminal allows for the height of the viewport.
+fn frame_area(area: Rect, aspect: (u16, u16)) -> Rect {
+    let width = u32::from(area.height) * u32::from(aspect.0) / u32::from(aspect.1.max(1));
+    let width = u16::try_from(width).unwrap_or(u16::MAX);
+    Layout::horizontal([Constraint::Max(width)])
+        .flex(Flex::Center)
+        .areas::<1>(area)[0]
+        .outer(Margin {
+            horizontal: 1,
+            vertical: 1,
+        })
+}
+
+/// The area inside the block around the app. Parts of the block can fall outside of the
+/// viewport, so the area is clipped to it: the pty has exactly the rows that are shown.
+pub(crate) fn pty_area(area: Rect, aspect: (u16, u16)) -> Rect {
+    app_block()
+        .inner(frame_area(area, aspect))
+        .intersection(area)
+}
+
 pub struct StatusCorner<'a> {
     state: &'a RendererState,
     dt: Duration,
@@ -163,3 +181,48 @@ impl<'a> Widget for StatusCorner<'a> {
         status.render(status_area, buf);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ratatui::layout::Rect;
+
+    use super::pty_area;
+    use crate::args::ViewportHeight;
+
+    /// the pty area of an inline viewport at the bottom of a terminal of `width` x `height`.
+    fn area(width: u16, height: u16, viewport: ViewportHeight, aspect: (u16, u16)) -> Rect {
+        let rows = viewport.rows(height);
+        pty_area(Rect::new(0, height - rows, width, rows), aspect)
+    }
+
+    #[test]
+    fn test_pty_area() {
+        let big = (200, 50);
+        assert_eq!(
+            area(200, 50, ViewportHeight::Percent(60), big),
+            Rect::new(41, 21, 118, 28)
+        );
+        let small = (80, 24);
+        assert_eq!(
+            area(80, 24, ViewportHeight::Rows(20), small),
+            Rect::new(8, 5, 64, 18)
+        );
+        // resized after startup, the aspect ratio stays the one of the start.
+        assert_eq!(
+            area(40, 10, ViewportHeight::Percent(30), small),
+            Rect::new(16, 8, 8, 1)
+        );
+        // the block can't reach above the top of the screen, so the bottom border is cut off
+        // instead. the pty still only gets the rows inside the viewport.
+        assert_eq!(
+            pty_area(Rect::new(0, 0, 80, 20), small),
+            Rect::new(8, 2, 64, 17)
+        );
+        for (width, height) in [(200, 50), (80, 24), (40, 10), (20, 4)] {
+            let rows = ViewportHeight::Percent(60).rows(height);
+            let viewport = Rect::new(0, height - rows, width, rows);
+            let pty = pty_area(viewport, small);
+            assert_eq!(pty.intersection(viewport), pty);
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! effects shared by the mx frontends.
+//!
+//! an [`EffectSet`] keeps effects under names, together with how to find their area in the
+//! frame. the areas are worked out again from the frame on every [`EffectSet::advance`], so
+//! effects follow the layout when the terminal is resized.
+
+use ratatui::{Frame, buffer::Buffer, layout::Rect};
+use tachyonfx::{Duration, Effect, fx, fx::RepeatMode, pattern::SweepPattern};
+
+/// the name of the [`title_shimmer`] in an [`EffectSet`].
+pub const TITLE_SHIMMER: &str = "title_shimmer";
+
+struct Slot {
+    name: &'static str,
+    effect: Effect,
+    area: Box<dyn Fn(Rect) -> Rect>,
+}
+
+/// named effects and their areas, see the [module docs](self).
+#[derive(Default)]
+pub struct EffectSet {
+    slots: Vec<Slot>,
+}
+
+impl EffectSet {
+    /// a set without effects.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// adds `effect` under `name`, replacing the effect that had the name. `area` gets the area
+    /// of the frame and returns the one the effect is drawn in.
+    pub fn insert(
+        &mut self,
+        name: &'static str,
+        effect: Effect,
+        area: impl Fn(Rect) -> Rect + 'static,
+    ) {
+        let slot = Slot {
+            name,
+            effect,
+            area: Box::new(area),
+        };
+        match self.slots.iter_mut().find(|slot| slot.name == name) {
+            Some(old) => *old = slot,
+            None => self.slots.push(slot),
+        }
+    }
+
+    /// changes how the area of the effect `name` is found, for areas that depend on more than
+    /// the frame. does nothing if there's no such effect.
+    pub fn set_area(&mut self, name: &str, area: impl Fn(Rect) -> Rect + 'static) {
+        if let Some(slot) = self.slots.iter_mut().find(|slot| slot.name == name) {
+            slot.area = Box::new(area);
+        }
+    }
+
+    /// removes the effect `name`.
+    pub fn remove(&mut self, name: &str) -> Option<Effect> {
+        let index = self.slots.iter().position(|slot| slot.name == name)?;
+        Some(self.slots.remove(index).effect)
+    }
+
+    /// whether the effect `name` is still running. finished effects are dropped, so this is
+    /// `false` for them.
+    pub fn is_running(&self, name: &str) -> bool {
+        self.slots
+            .iter()
+            .any(|slot| slot.name == name && slot.effect.running())
+    }
+
+    /// whether any effect is running, the frontends keep drawing while it is.
+    pub fn running(&self) -> bool {
+        self.slots.iter().any(|slot| slot.effect.running())
+    }
+
+    /// advances every effect by `dt` and draws it over `frame`.
+    pub fn advance(&mut self, dt: Duration, frame: &mut Frame) {
+        let area = frame.area();
+        self.process(dt, frame.buffer_mut(), area);
+    }
+
+    /// advances every effect by `dt` and draws it over the `area` of `buf`, then drops the
+    /// finished ones.
+    pub fn process(&mut self, dt: Duration, buf: &mut Buffer, area: Rect) {
+        for slot in &mut self.slots {
+            let effect_area = (slot.area)(area).intersection(area);
+            slot.effect.set_area(effect_area);
+            slot.effect.process(dt, buf, effect_area);
+        }
+        self.slots.retain(|slot| !slot.effect.done());
+    }
+}
+
+/// the shimmer that sweeps over the title of the app frame, forever.
+pub fn title_shimmer() -> Effect {
+    fx::repeat(
+        fx::parallel(&[
+            fx::hsl_shift_fg([0.0, 0.0, 30.0], 1000).with_pattern(SweepPattern::left_to_right(3)),
+            fx::delay(
+                200,
+                fx::hsl_shift_fg([0.0, 0.0, -30.0], 800)
+                    .with_pattern(SweepPattern::left_to_right(3)),
+            ),
+        ]),
+        RepeatMode::Forever,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effect_set_drops_finished_effects() {
+        let area = Rect::new(0, 0, 10, 2);
+        let mut buf = Buffer::empty(area);
+        let mut set = EffectSet::new();
+        set.insert("sleep", fx::sleep(100), |area| area);
+        set.insert(TITLE_SHIMMER, title_shimmer(), |area| area);
+
+        set.process(Duration::from_millis(50), &mut buf, area);
+        assert!(set.is_running("sleep"));
+
+        set.process(Duration::from_millis(60), &mut buf, area);
+        assert!(!set.is_running("sleep"));
+        assert!(set.remove("sleep").is_none());
+        // the shimmer repeats forever.
+        assert!(set.is_running(TITLE_SHIMMER));
+        assert!(set.running());
+
+        set.remove(TITLE_SHIMMER);
+        assert!(!set.running());
+    }
+
+    #[test]
+    fn test_effect_set_follows_the_area() {
+        let mut set = EffectSet::new();
+        set.insert("sleep", fx::sleep(100), |area| Rect { height: 1, ..area });
+        let small = Rect::new(0, 0, 10, 2);
+        set.process(Duration::from_millis(10), &mut Buffer::empty(small), small);
+        let large = Rect::new(0, 0, 40, 8);
+        set.process(Duration::from_millis(10), &mut Buffer::empty(large), large);
+        let effect = set.remove("sleep").unwrap();
+        assert_eq!(effect.area(), Some(Rect::new(0, 0, 40, 1)));
+    }
+}
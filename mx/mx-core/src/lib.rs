@@ -1,6 +1,8 @@
+pub mod fx;
 pub mod logging;
 
 pub use logging::layer;
+pub use mana_tui_utils::reload::{RELOAD_EXIT_CODE, STATE_ENV as RELOAD_STATE_ENV};
 
 use crate::logging::Trace;
 
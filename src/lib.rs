@@ -16,44 +16,87 @@ pub mod prelude {
 
     pub use ratatui::style::palette;
     pub use ratatui::style::*;
+
+    pub use crate::key;
 }
 
 pub use mx_core as mx;
 
+#[doc(hidden)]
+pub use ratatui as __ratatui;
+
+/// [`KeyModifiers`](ratatui::crossterm::event::KeyModifiers) combinations usable as patterns in
+/// [`key!`]. or-patterns like `CONTROL | SHIFT` match either modifier, not both.
+pub mod modifiers {
+    use ratatui::crossterm::event::KeyModifiers;
+
+    /// ctrl + shift.
+    pub const CTRL_SHIFT: KeyModifiers = KeyModifiers::CONTROL.union(KeyModifiers::SHIFT);
+    /// ctrl + alt.
+    pub const CTRL_ALT: KeyModifiers = KeyModifiers::CONTROL.union(KeyModifiers::ALT);
+    /// alt + shift.
+    pub const ALT_SHIFT: KeyModifiers = KeyModifiers::ALT.union(KeyModifiers::SHIFT);
+    /// ctrl + alt + shift.
+    pub const CTRL_ALT_SHIFT: KeyModifiers = CTRL_ALT.union(KeyModifiers::SHIFT);
+}
+
+/// builds a [`KeyEvent`](ratatui::crossterm::event::KeyEvent) pattern.
+///
+/// - `key!(Char('q'))` matches a press of `q`.
+/// - `key!(Char('q'), Release)` matches the given kind, `key!(Char('q'), Any)` matches every kind.
+/// - `key!(Char('j') | Down, Press)` matches any of the codes.
+/// - `key!(Char(digit @ '0'..='9'), Press)` matches a range and binds the char.
+/// - `key!(Char('c'), Press, KeyModifiers::CONTROL)` matches exactly the given modifiers, see
+///   [`modifiers`] for combinations. `_` ignores the modifiers.
+///
+/// the forms without modifiers only match when no modifier is held, so `ctrl+q` does not match
+/// `key!(Char('q'))`.
+///
+/// # Example
+///
+/// ```
+/// # use mana_tui::key;
+/// # use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+/// let event = KeyEvent::new(KeyCode::Char('7'), KeyModifiers::NONE);
+/// let digit = match event {
+///     key!(Char(digit @ '0'..='9')) => digit.to_digit(10),
+///     _ => None,
+/// };
+/// assert_eq!(digit, Some(7));
+/// ```
+///
+/// codes must be `KeyCode` variants:
+///
+/// ```compile_fail
+/// # use mana_tui::key;
+/// # use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+/// let event = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+/// let _ = matches!(event, key!('q', Press));
+/// ```
 // TODO: lock behind crossterm feature
 #[macro_export]
 macro_rules! key {
-    // Variant with no arguments, e.g. Enter, Esc
-    ($code:ident, $kind:ident) => {
-        ratatui::crossterm::event::KeyEvent {
-            code: ratatui::crossterm::event::KeyCode::$code,
-            kind: ratatui::crossterm::event::KeyEventKind::$kind,
-            modifiers: ratatui::crossterm::event::KeyModifiers::NONE,
-            ..
-        }
+    (@kind Any) => {
+        _
     };
-    // Variant with arguments, e.g. Char('x'), Char(_)
-    ($code:ident ( $($arg:tt)* ), $kind:ident) => {
-        ratatui::crossterm::event::KeyEvent {
-            code: ratatui::crossterm::event::KeyCode::$code($($arg)*),
-            kind: ratatui::crossterm::event::KeyEventKind::$kind,
-            modifiers: ratatui::crossterm::event::KeyModifiers::NONE,
-            ..
-        }
+    (@kind $kind:ident) => {
+        $crate::__ratatui::crossterm::event::KeyEventKind::$kind
     };
-    // Variant with no arguments and optional modifiers
-    ($code:ident, $kind:ident, $mods:expr ) => {
-        ratatui::crossterm::event::KeyEvent {
-            code: ratatui::crossterm::event::KeyCode::$code,
-            kind: ratatui::crossterm::event::KeyEventKind::$kind,
-            modifiers: $mods,
-            ..
-        }
+    // e.g. Enter, Char('x') | Char('y'), Char(c @ '0'..='9')
+    ($($code:ident $(( $($arg:tt)* ))?)|+ $(,)?) => {
+        $crate::key!($($code $(( $($arg)* ))?)|+, Press)
+    };
+    ($($code:ident $(( $($arg:tt)* ))?)|+, $kind:ident $(,)?) => {
+        $crate::key!(
+            $($code $(( $($arg)* ))?)|+,
+            $kind,
+            $crate::__ratatui::crossterm::event::KeyModifiers::NONE
+        )
     };
-    ($code:ident ( $($arg:tt)* ), $kind:ident, $mods:pat) => {
-        ratatui::crossterm::event::KeyEvent {
-            code: ratatui::crossterm::event::KeyCode::$code($($arg)*),
-            kind: ratatui::crossterm::event::KeyEventKind::$kind,
+    ($($code:ident $(( $($arg:tt)* ))?)|+, $kind:ident, $mods:pat $(,)?) => {
+        $crate::__ratatui::crossterm::event::KeyEvent {
+            code: $($crate::__ratatui::crossterm::event::KeyCode::$code $(( $($arg)* ))?)|+,
+            kind: $crate::key!(@kind $kind),
             modifiers: $mods,
             ..
         }
@@ -0,0 +1,64 @@
+use mana_tui::key;
+use mana_tui::modifiers::CTRL_SHIFT;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+
+fn press(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::NONE)
+}
+
+#[test]
+fn test_key_default_kind() {
+    let release = KeyEvent::new_with_kind(
+        KeyCode::Char('q'),
+        KeyModifiers::NONE,
+        KeyEventKind::Release,
+    );
+    assert!(matches!(press(KeyCode::Char('q')), key!(Char('q'))));
+    assert!(!matches!(release, key!(Char('q'))));
+    assert!(matches!(release, key!(Char('q'), Any)));
+    assert!(matches!(release, key!(Char('q'), Release)));
+}
+
+#[test]
+fn test_key_alternatives() {
+    assert!(matches!(
+        press(KeyCode::Char('j')),
+        key!(Char('j') | Down, Press)
+    ));
+    assert!(matches!(
+        press(KeyCode::Down),
+        key!(Char('j') | Down, Press)
+    ));
+    assert!(!matches!(press(KeyCode::Up), key!(Char('j') | Down, Press)));
+}
+
+#[test]
+fn test_key_char_range() {
+    let digit = match press(KeyCode::Char('7')) {
+        key!(Char(digit @ '0'..='9'), Press) => Some(digit),
+        _ => None,
+    };
+    assert_eq!(digit, Some('7'));
+    assert!(!matches!(
+        press(KeyCode::Char('a')),
+        key!(Char('0'..='9'), Press)
+    ));
+}
+
+#[test]
+fn test_key_exact_modifiers() {
+    let ctrl_q = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL);
+    assert!(!matches!(ctrl_q, key!(Char('q'), Press)));
+    assert!(matches!(
+        ctrl_q,
+        key!(Char('q'), Press, KeyModifiers::CONTROL)
+    ));
+    assert!(matches!(ctrl_q, key!(Char('q'), Press, _)));
+
+    let ctrl_shift_s = KeyEvent::new(KeyCode::Char('s'), CTRL_SHIFT);
+    assert!(!matches!(
+        ctrl_shift_s,
+        key!(Char('s'), Press, KeyModifiers::CONTROL)
+    ));
+    assert!(matches!(ctrl_shift_s, key!(Char('s'), Press, CTRL_SHIFT)));
+}
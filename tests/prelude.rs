@@ -0,0 +1,69 @@
+//! the same ui through the `mana_tui` and the `mana_tui_elemental` prelude, so examples work
+//! with either import.
+
+use ratatui::buffer::Buffer;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::Rect;
+
+macro_rules! prelude_ui {
+    () => {
+        fn view() -> View {
+            ui(Block::new().rounded())
+                .with((
+                    Width::grow(),
+                    Height::fixed(3),
+                    Direction::Horizontal,
+                    MainJustify::Center,
+                    CrossJustify::Center,
+                    Padding::ONE,
+                ))
+                .child(ui(Text::raw("hi")).with((Width(Size::Fit), ClearBackground)))
+                .into()
+        }
+    };
+}
+
+mod mana_tui_style {
+    use mana_tui::prelude::*;
+
+    prelude_ui!();
+
+    pub(super) fn spawn(ctx: &mut ElementCtx) -> Element {
+        ctx.spawn_ui(view())
+    }
+
+    pub(super) fn is_quit(event: ratatui::crossterm::event::KeyEvent) -> bool {
+        matches!(event, key!(Char('q')))
+    }
+}
+
+mod elemental_style {
+    use mana_tui_elemental::prelude::*;
+
+    prelude_ui!();
+
+    pub(super) fn spawn(ctx: &mut ElementCtx) -> Element {
+        ctx.spawn_ui(view())
+    }
+}
+
+#[test]
+fn test_both_preludes() {
+    let area = Rect::new(0, 0, 10, 3);
+    let render = |spawn: fn(&mut mana_tui_elemental::layout::ElementCtx) -> _| {
+        let mut ctx = mana_tui_elemental::layout::ElementCtx::new();
+        let root = spawn(&mut ctx);
+        ctx.calculate_layout(root, area).unwrap();
+        let mut buf = Buffer::empty(area);
+        ctx.render(root, area, &mut buf);
+        buf
+    };
+    assert_eq!(
+        render(mana_tui_style::spawn),
+        render(elemental_style::spawn)
+    );
+    assert!(mana_tui_style::is_quit(KeyEvent::new(
+        KeyCode::Char('q'),
+        KeyModifiers::NONE
+    )));
+}